@@ -14,6 +14,15 @@ pub trait Backend {
 
     fn next_event(&mut self, timeout: Duration) -> Option<Event>;
 
+    /// A receiver for the backend events.
+    ///
+    /// This lets the runtime sleep until there is an event (or a message) to handle,
+    /// rather than polling the backend once per frame.
+    /// Events sent on this receiver should also be available through [`Backend::next_event`].
+    fn event_receiver(&mut self) -> Option<flume::Receiver<Event>> {
+        None
+    }
+
     fn resize(&mut self, new_size: Size);
 
     /// Paint the widgets
@@ -0,0 +1,217 @@
+use std::fmt::{self, Display, Write};
+
+use anathema_geometry::{Pos, Region, Size};
+
+use crate::tui::Style;
+
+/// A rendered frame: the glyph and the style of every cell.
+///
+/// The `Display` implementation writes the glyphs, one line per row.
+/// Use [`Frame::styled`] to include the style runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    size: Size,
+    glyphs: Vec<char>,
+    styles: Vec<Style>,
+}
+
+impl Frame {
+    pub(crate) fn new(size: Size) -> Self {
+        let len = size.width * size.height;
+        Self {
+            size,
+            glyphs: vec![' '; len],
+            styles: vec![Style::new(); len],
+        }
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// The glyph of every cell, row by row
+    pub fn glyphs(&self) -> &[char] {
+        &self.glyphs
+    }
+
+    /// The style of every cell, row by row
+    pub fn styles(&self) -> &[Style] {
+        &self.styles
+    }
+
+    /// The glyph at the given position, or `None` if the position is outside of the frame
+    pub fn glyph_at(&self, pos: impl Into<Pos>) -> Option<char> {
+        self.index(pos.into()).map(|index| self.glyphs[index])
+    }
+
+    /// The style at the given position, or `None` if the position is outside of the frame
+    pub fn style_at(&self, pos: impl Into<Pos>) -> Option<Style> {
+        self.index(pos.into()).map(|index| self.styles[index])
+    }
+
+    /// The position of every cell with a style matching the predicate, row by row
+    pub fn positions_where(&self, mut f: impl FnMut(&Style) -> bool) -> Vec<Pos> {
+        self.styles
+            .iter()
+            .enumerate()
+            .filter(|(_, style)| f(style))
+            .map(|(index, _)| self.pos(index))
+            .collect()
+    }
+
+    /// The smallest region containing every cell with a style matching the predicate.
+    ///
+    /// ```
+    /// # use anathema_backend::test::TestBackend;
+    /// # use anathema_state::Color;
+    /// let backend = TestBackend::new((10, 2));
+    /// let frame = backend.surface.frame();
+    /// let region = frame.region_where(|style| style.fg == Some(Color::Red));
+    /// assert!(region.is_none());
+    /// ```
+    pub fn region_where(&self, f: impl FnMut(&Style) -> bool) -> Option<Region> {
+        let positions = self.positions_where(f);
+        let first = positions.first()?;
+        let (mut from, mut to) = (*first, *first);
+
+        for pos in &positions {
+            from.x = from.x.min(pos.x);
+            from.y = from.y.min(pos.y);
+            to.x = to.x.max(pos.x);
+            to.y = to.y.max(pos.y);
+        }
+
+        // The end of a region is exclusive
+        Some(Region::new(from, to + Pos::new(1, 1)))
+    }
+
+    /// Display the glyphs along with the style runs of each row.
+    ///
+    /// Every row is followed by one line per run of cells sharing the same style,
+    /// with the columns of the run and the style, e.g `  [0..5] fg:Red bold`.
+    /// Cells without a style are left out.
+    pub fn styled(&self) -> Styled<'_> {
+        Styled(self)
+    }
+
+    pub(crate) fn set_glyph(&mut self, c: char, pos: Pos) {
+        let Some(index) = self.index(pos) else { return };
+        self.glyphs[index] = c;
+    }
+
+    pub(crate) fn style_mut(&mut self, pos: Pos) -> Option<&mut Style> {
+        let index = self.index(pos)?;
+        Some(&mut self.styles[index])
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.glyphs.fill(' ');
+        self.styles.fill(Style::new());
+    }
+
+    fn index(&self, pos: Pos) -> Option<usize> {
+        let (x, y) = (usize::try_from(pos.x).ok()?, usize::try_from(pos.y).ok()?);
+        if x >= self.size.width || y >= self.size.height {
+            return None;
+        }
+        Some(y * self.size.width + x)
+    }
+
+    fn pos(&self, index: usize) -> Pos {
+        Pos::from((index % self.size.width, index / self.size.width))
+    }
+
+    fn rows(&self) -> impl Iterator<Item = (&[char], &[Style])> {
+        // `chunks` panics on zero, and a frame without width has no rows anyway
+        let width = self.size.width.max(1);
+        self.glyphs.chunks(width).zip(self.styles.chunks(width))
+    }
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (glyphs, _) in self.rows() {
+            glyphs.iter().try_for_each(|c| f.write_char(*c))?;
+            f.write_char('\n')?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Glyphs and style runs of a [`Frame`], see [`Frame::styled`].
+pub struct Styled<'a>(&'a Frame);
+
+impl Display for Styled<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (glyphs, styles) in self.0.rows() {
+            glyphs.iter().try_for_each(|c| f.write_char(*c))?;
+            f.write_char('\n')?;
+
+            let mut start = 0;
+            for end in 1..=styles.len() {
+                if end < styles.len() && styles[end] == styles[start] {
+                    continue;
+                }
+
+                if styles[start] != Style::new() {
+                    writeln!(f, "  [{start}..{end}] {}", styles[start])?;
+                }
+                start = end;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anathema_state::Color;
+
+    use super::*;
+
+    fn frame() -> Frame {
+        let mut red = Style::new();
+        red.set_fg(Color::Red);
+
+        let mut frame = Frame::new(Size::new(4, 2));
+        "abcd"
+            .chars()
+            .enumerate()
+            .for_each(|(x, c)| frame.set_glyph(c, Pos::new(x as i32, 0)));
+        *frame.style_mut(Pos::new(1, 0)).unwrap() = red;
+        *frame.style_mut(Pos::new(2, 0)).unwrap() = red;
+        *frame.style_mut(Pos::new(1, 1)).unwrap() = red;
+        frame.style_mut(Pos::new(3, 1)).unwrap().set_bold(true);
+        frame
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(frame().to_string(), "abcd\n    \n");
+    }
+
+    #[test]
+    fn style_runs() {
+        let expected = "abcd\n  [1..3] fg:Red\n    \n  [1..2] fg:Red\n  [3..4] bold\n";
+        assert_eq!(frame().styled().to_string(), expected);
+    }
+
+    #[test]
+    fn query_cells() {
+        let frame = frame();
+        assert_eq!(frame.glyph_at((1, 0)), Some('b'));
+        assert_eq!(frame.glyph_at((4, 0)), None);
+        assert_eq!(frame.style_at((1, 1)).unwrap().fg, Some(Color::Red));
+        assert_eq!(frame.style_at((0, 2)), None);
+
+        let red = |style: &Style| style.fg == Some(Color::Red);
+        let positions = frame.positions_where(red);
+        assert_eq!(positions, vec![Pos::new(1, 0), Pos::new(2, 0), Pos::new(1, 1)]);
+
+        let region = frame.region_where(red).unwrap();
+        assert_eq!((region.from, region.to), (Pos::new(1, 0), Pos::new(3, 2)));
+        assert!(frame.region_where(|style| style.bg.is_some()).is_none());
+    }
+}
@@ -1,16 +1,39 @@
+//! Headless backend for testing.
+//!
+//! The [`TestBackend`] replays scripted events, one batch of events per frame,
+//! and captures every rendered frame as text along with the style of each cell.
+//!
+//! ```
+//! # use anathema_backend::test::TestBackend;
+//! # use anathema_widgets::components::events::KeyCode;
+//! let mut backend = TestBackend::new((10, 2));
+//! backend.type_str("hello").next_frame().press(KeyCode::Enter);
+//! ```
+use std::collections::VecDeque;
 use std::fmt::Display;
 
 use anathema_geometry::{Pos, Size};
 use anathema_store::tree::{Node, TreeValues};
-use anathema_widgets::components::events::Event;
+use anathema_widgets::components::events::{
+    Event, KeyCode, KeyEvent, KeyState, Modifiers, MouseEvent, MouseState, Paste,
+};
 use anathema_widgets::paint::CellAttributes;
 use anathema_widgets::{AttributeStorage, Element, WidgetKind, WidgetRenderer};
 
+pub use self::frame::{Frame, Styled};
+pub use self::snapshot::{assert_snapshot, UPDATE_SNAPSHOTS};
+use crate::tui::Style;
 use crate::Backend;
 
+mod frame;
+mod snapshot;
+
 pub struct TestBackend {
     pub surface: TestSurface,
     pub output: String,
+    // Each batch of events is delivered during one frame
+    events: VecDeque<VecDeque<Event>>,
+    frames: Vec<Frame>,
 }
 
 impl TestBackend {
@@ -19,21 +42,112 @@ impl TestBackend {
         Self {
             surface: TestSurface::new(size),
             output: String::new(),
+            events: VecDeque::new(),
+            frames: vec![],
+        }
+    }
+
+    /// Queue an event.
+    /// All events queued before calling [`TestBackend::next_frame`]
+    /// are delivered during the same frame.
+    pub fn push_event(&mut self, event: Event) -> &mut Self {
+        match self.events.back_mut() {
+            Some(batch) => batch.push_back(event),
+            None => self.events.push_back(VecDeque::from([event])),
         }
+        self
+    }
+
+    /// Deliver the events queued after this call in the next frame.
+    pub fn next_frame(&mut self) -> &mut Self {
+        self.events.push_back(VecDeque::new());
+        self
+    }
+
+    /// Queue a key press
+    pub fn press(&mut self, code: impl Into<KeyCode>) -> &mut Self {
+        self.press_with(code, Modifiers::empty())
+    }
+
+    /// Queue a key press with modifiers held down
+    pub fn press_with(&mut self, code: impl Into<KeyCode>, modifiers: Modifiers) -> &mut Self {
+        self.push_event(Event::Key(KeyEvent {
+            code: code.into(),
+            modifiers,
+            state: KeyState::Press,
+        }))
+    }
+
+    /// Queue a key press for every character in the string
+    pub fn type_str(&mut self, s: &str) -> &mut Self {
+        s.chars().for_each(|c| {
+            let modifiers = match c.is_uppercase() {
+                true => Modifiers::SHIFT,
+                false => Modifiers::empty(),
+            };
+            self.press_with(c, modifiers);
+        });
+        self
+    }
+
+    /// Queue a mouse event
+    pub fn mouse(&mut self, state: MouseState, pos: impl Into<Pos>) -> &mut Self {
+        let pos = pos.into();
+        self.push_event(Event::Mouse(MouseEvent {
+            x: pos.x as u16,
+            y: pos.y as u16,
+            state,
+            modifiers: Modifiers::empty(),
+        }))
+    }
+
+    /// Queue a paste event
+    pub fn paste(&mut self, text: &str) -> &mut Self {
+        self.push_event(Event::Paste(Paste::new(text)))
+    }
+
+    /// Queue a resize event
+    pub fn resize_to(&mut self, size: impl Into<Size>) -> &mut Self {
+        let size = size.into();
+        self.push_event(Event::Resize(size.width as u16, size.height as u16))
+    }
+
+    /// All frames rendered so far, oldest first
+    pub fn frames(&self) -> &[Frame] {
+        &self.frames
+    }
+
+    /// The last rendered frame
+    pub fn last_frame(&self) -> Option<&Frame> {
+        self.frames.last()
+    }
+
+    /// Remove and return all rendered frames
+    pub fn take_frames(&mut self) -> Vec<Frame> {
+        std::mem::take(&mut self.frames)
     }
 }
 
 impl Backend for TestBackend {
     fn size(&self) -> Size {
-        self.surface.size
+        self.surface.size()
     }
 
+    // Returns `None` once the events for the current frame are exhausted,
+    // so the remaining events are delivered in the following frames.
     fn next_event(&mut self, _timeout: std::time::Duration) -> Option<Event> {
-        None
+        let batch = self.events.front_mut()?;
+        match batch.pop_front() {
+            Some(event) => Some(event),
+            None => {
+                self.events.pop_front();
+                None
+            }
+        }
     }
 
-    fn resize(&mut self, _new_size: Size) {
-        todo!()
+    fn resize(&mut self, new_size: Size) {
+        self.surface = TestSurface::new(new_size);
     }
 
     fn paint<'bp>(
@@ -60,56 +174,131 @@ impl Backend for TestBackend {
 
     fn render(&mut self) {
         self.output = format!("{}", self.surface);
+        self.frames.push(self.surface.frame.clone());
     }
 }
 
 pub struct TestSurface {
-    size: Size,
-    buffer: Vec<char>,
+    frame: Frame,
+    cursor: Option<Pos>,
 }
 
 impl TestSurface {
     pub fn new(size: impl Into<Size>) -> Self {
-        let size = size.into();
-        let buffer_size = size.width * size.height;
         Self {
-            buffer: vec![' '; buffer_size],
-            size,
+            frame: Frame::new(size.into()),
+            cursor: None,
         }
     }
 
+    /// The cursor position set by a widget during the last paint, if any.
+    pub fn cursor(&self) -> Option<Pos> {
+        self.cursor
+    }
+
+    /// The glyphs and styles painted so far
+    pub fn frame(&self) -> &Frame {
+        &self.frame
+    }
+
+    /// The style at the given position, or `None` if the position is outside of the surface
+    pub fn style_at(&self, pos: impl Into<Pos>) -> Option<Style> {
+        self.frame.style_at(pos)
+    }
+
     fn clear(&mut self) {
-        self.buffer.fill_with(|| ' ');
+        self.frame.clear();
+        self.cursor = None;
     }
 }
 
 impl WidgetRenderer for TestSurface {
     fn draw_glyph(&mut self, c: char, local_pos: Pos) {
-        let y_offset = local_pos.y as usize * self.size.width;
-        let x_offset = local_pos.x as usize;
-        let index = y_offset + x_offset;
-        self.buffer[index] = c;
+        self.frame.set_glyph(c, local_pos);
     }
 
     fn size(&self) -> Size {
-        self.size
+        self.frame.size()
+    }
+
+    // Styles are applied the same way as the tui buffer applies them:
+    // colours replace the existing colours and attributes are added.
+    fn set_attributes(&mut self, attribs: &dyn CellAttributes, local_pos: Pos) {
+        let Some(cell) = self.frame.style_mut(local_pos) else { return };
+        let style = Style::from_cell_attribs(attribs);
+
+        if let fg @ Some(_) = style.fg {
+            cell.fg = fg;
+        }
+
+        if let bg @ Some(_) = style.bg {
+            cell.bg = bg;
+        }
+
+        cell.attributes |= style.attributes;
     }
 
-    fn set_attributes(&mut self, _attribs: &dyn CellAttributes, _local_pos: Pos) {
-        // NOTE: currently no attributes are stored on the test surface
+    fn set_cursor(&mut self, pos: Pos) {
+        self.cursor = Some(pos);
     }
 }
 
 impl Display for TestSurface {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for y in 0..self.size.height {
-            for x in 0..self.size.width {
-                let idx = y * self.size.width + x;
-                write!(f, "{}", self.buffer[idx])?;
-            }
-            writeln!(f)?;
-        }
+        self.frame.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn next_event(backend: &mut TestBackend) -> Option<Event> {
+        backend.next_event(Duration::ZERO)
+    }
+
+    #[test]
+    fn events_per_frame() {
+        let mut backend = TestBackend::new((1, 1));
+        backend.type_str("ab").next_frame().resize_to((2, 2));
+
+        // First frame
+        assert_eq!(next_event(&mut backend).and_then(|e| e.get_char()), Some('a'));
+        assert_eq!(next_event(&mut backend).and_then(|e| e.get_char()), Some('b'));
+        assert!(next_event(&mut backend).is_none());
+
+        // Second frame
+        assert!(matches!(next_event(&mut backend), Some(Event::Resize(2, 2))));
+        assert!(next_event(&mut backend).is_none());
+        assert!(next_event(&mut backend).is_none());
+    }
+
+    #[test]
+    fn resize() {
+        let mut backend = TestBackend::new((1, 1));
+        backend.resize((3, 2).into());
+        backend.render();
+        assert_eq!(backend.last_frame().unwrap().to_string(), "   \n   \n");
+    }
+
+    #[test]
+    fn capture_styles() {
+        let mut style = Style::new();
+        style.set_bold(true);
+
+        let mut backend = TestBackend::new((2, 1));
+        backend.surface.draw_glyph('a', Pos::new(0, 0));
+        backend.surface.set_attributes(&style, Pos::new(0, 0));
+        backend.render();
+        backend.clear();
+        backend.render();
 
-        Ok(())
+        let frames = backend.take_frames();
+        assert_eq!(frames[0].to_string(), "a \n");
+        assert_eq!(frames[0].styles()[0], style);
+        assert_eq!(frames[1].styles()[0], Style::new());
+        assert!(backend.frames().is_empty());
     }
 }
@@ -0,0 +1,115 @@
+use std::fmt::{Display, Write};
+use std::path::Path;
+
+/// Set this environment variable to create missing snapshots and overwrite
+/// existing snapshots with the current output, e.g `ANATHEMA_UPDATE_SNAPSHOTS=1 cargo test`.
+pub const UPDATE_SNAPSHOTS: &str = "ANATHEMA_UPDATE_SNAPSHOTS";
+
+/// Assert that the output matches the snapshot file at the given path.
+///
+/// To create or update snapshots set the [`UPDATE_SNAPSHOTS`] environment variable.
+///
+/// # Panics
+///
+/// Panics with a line by line diff if the output does not match the snapshot,
+/// if the snapshot file does not exist, or if the snapshot can't be read or written.
+pub fn assert_snapshot(path: impl AsRef<Path>, output: impl Display) {
+    let path = path.as_ref();
+    let actual = output.to_string();
+
+    if std::env::var_os(UPDATE_SNAPSHOTS).is_some() {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).unwrap_or_else(|e| panic!("failed to create {}: {e}", parent.display()));
+        }
+        std::fs::write(path, &actual).unwrap_or_else(|e| panic!("failed to write {}: {e}", path.display()));
+        return;
+    }
+
+    // A missing snapshot is a failure, otherwise a test that lost its snapshot would always pass
+    if !path.exists() {
+        panic!(
+            "missing snapshot: {}\n(set {UPDATE_SNAPSHOTS}=1 to create it)",
+            path.display()
+        );
+    }
+
+    let expected = std::fs::read_to_string(path).unwrap_or_else(|e| panic!("failed to read {}: {e}", path.display()));
+    let expected = expected.replace("\r\n", "\n");
+
+    if expected != actual {
+        panic!(
+            "snapshot mismatch: {}\n(- snapshot, + output, set {UPDATE_SNAPSHOTS}=1 to update)\n{}",
+            path.display(),
+            diff(&expected, &actual)
+        );
+    }
+}
+
+// Compare line by line.
+// Frames are grids of the same size, so there is no need to find
+// inserted or removed lines.
+// The lines are wrapped in `|` to show trailing whitespace.
+fn diff(expected: &str, actual: &str) -> String {
+    let expected = expected.lines().collect::<Vec<_>>();
+    let actual = actual.lines().collect::<Vec<_>>();
+    let mut output = String::new();
+
+    for i in 0..expected.len().max(actual.len()) {
+        let _ = match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => writeln!(output, "  |{e}|"),
+            (e, a) => {
+                if let Some(e) = e {
+                    let _ = writeln!(output, "- |{e}|");
+                }
+                match a {
+                    Some(a) => writeln!(output, "+ |{a}|"),
+                    None => Ok(()),
+                }
+            }
+        };
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn line_diff() {
+        let expected = "abc\ndef\nghi";
+        let actual = "abc\ndxf\nghi\njkl";
+        let output = diff(expected, actual);
+        assert_eq!(output, "  |abc|\n- |def|\n+ |dxf|\n  |ghi|\n+ |jkl|\n");
+    }
+
+    #[test]
+    fn compare_snapshot() {
+        // Snapshots are never compared while updating them
+        if std::env::var_os(UPDATE_SNAPSHOTS).is_some() {
+            return;
+        }
+
+        let dir = std::env::temp_dir().join(format!("anathema-snapshot-{}", std::process::id()));
+        let path = dir.join("frame.snap");
+
+        let missing = std::panic::catch_unwind(|| assert_snapshot(&path, "a\nb\n"));
+        assert!(!path.exists());
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(&path, "a\nb\n").unwrap();
+        assert_snapshot(&path, "a\nb\n");
+
+        let mismatch = std::panic::catch_unwind(|| assert_snapshot(&path, "a\nc\n"));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let panic = missing.unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.starts_with("missing snapshot"));
+
+        let panic = mismatch.unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        assert!(message.contains("- |b|\n+ |c|"));
+    }
+}
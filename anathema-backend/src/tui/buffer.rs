@@ -240,6 +240,8 @@ impl Buffer {
         self.inner[index]
     }
 
+    /// Get the character at the given position.
+    /// This will panic if the cell is empty.
     pub fn char_at(&self, x: usize, y: usize) -> char {
         let cell = self.cell_at(x, y);
         match cell.state {
@@ -1,49 +1,135 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
 use std::time::Duration;
 
-use anathema_widgets::components::events::{Event, KeyCode, KeyEvent, KeyState, MouseButton, MouseEvent, MouseState};
+use anathema_widgets::components::events::{
+    Event, KeyCode, KeyEvent, KeyState, Modifiers, MouseButton, MouseEvent, MouseState, Paste,
+};
 use crossterm::event::{read, Event as CTEvent, KeyEventKind};
 pub use crossterm::event::{
     KeyCode as CTKeyCode, KeyEvent as CTKeyEvent, KeyEventState, KeyModifiers, MouseButton as CTMouseButton,
     MouseEvent as CTMouseEvent, MouseEventKind,
 };
 
+/// How often the event thread checks if it should stop
+const STOP_CHECK: Duration = Duration::from_millis(50);
+
 /// Event listener
-pub struct Events;
+pub struct Events {
+    receiver: Option<flume::Receiver<Event>>,
+    thread: Option<JoinHandle<()>>,
+    stop: Arc<AtomicBool>,
+}
 
 impl Events {
+    /// Create a new event listener.
+    pub fn new() -> Self {
+        Self {
+            receiver: None,
+            thread: None,
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
     /// Poll events given a duration.
     /// If no event is available within the duration
     /// the function will return `None`.
     pub fn poll(&self, timeout: Duration) -> Option<Event> {
+        if let Some(receiver) = &self.receiver {
+            return match timeout.is_zero() {
+                true => receiver.try_recv().ok(),
+                false => receiver.recv_timeout(timeout).ok(),
+            };
+        }
+
         match crossterm::event::poll(timeout).ok()? {
-            true => {
-                let event = read().map(Into::into).ok()?;
-
-                let event = match event {
-                    CTEvent::Paste(_) => Event::Noop,
-                    CTEvent::FocusGained => Event::Focus,
-                    CTEvent::FocusLost => Event::Blur,
-                    CTEvent::Key(CTKeyEvent {
-                        kind: KeyEventKind::Press,
-                        code: CTKeyCode::Char('c'),
-                        modifiers: KeyModifiers::CONTROL,
-                        ..
-                    }) => Event::Stop,
-                    CTEvent::Key(key_ev) => Event::Key(key_code_to_key_code(key_ev)),
-                    CTEvent::Mouse(mouse_ev) => Event::Mouse(mouse_to_mouse(mouse_ev)),
-                    CTEvent::Resize(width, height) => Event::Resize(width, height),
-                };
-
-                Some(event)
-            }
+            true => read().ok().map(event_to_event),
             false => None,
         }
     }
+
+    /// Read events on a separate thread and send them to the returned receiver.
+    ///
+    /// Once this is called all events are read by the thread,
+    /// and [`Events::poll`] reads from the receiver.
+    /// The thread runs until [`Events::stop`] is called or the event listener is dropped.
+    pub fn receiver(&mut self) -> flume::Receiver<Event> {
+        if let Some(receiver) = &self.receiver {
+            return receiver.clone();
+        }
+
+        let (tx, rx) = flume::unbounded();
+        let stop = self.stop.clone();
+        stop.store(false, Ordering::Relaxed);
+        let thread = std::thread::Builder::new()
+            .name("anathema-events".into())
+            .spawn(move || {
+                // Only read an event once one is available,
+                // so the thread is not holding on to the next event once it's stopped
+                while !stop.load(Ordering::Relaxed) {
+                    match crossterm::event::poll(STOP_CHECK) {
+                        Ok(true) => (),
+                        Ok(false) => continue,
+                        Err(_) => break,
+                    }
+
+                    let Ok(event) = read() else { break };
+                    if tx.send(event_to_event(event)).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("failed to spawn event thread");
+
+        self.thread = Some(thread);
+        self.receiver = Some(rx.clone());
+        rx
+    }
+
+    /// Stop the thread reading the events, if there is one, and wait for it to finish.
+    /// After this [`Events::poll`] reads the events directly.
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        self.receiver = None;
+    }
+}
+
+impl Drop for Events {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+impl Default for Events {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn event_to_event(event: CTEvent) -> Event {
+    match event {
+        CTEvent::Paste(text) => Event::Paste(Paste::new(text)),
+        CTEvent::FocusGained => Event::Focus,
+        CTEvent::FocusLost => Event::Blur,
+        CTEvent::Key(CTKeyEvent {
+            kind: KeyEventKind::Press,
+            code: CTKeyCode::Char('c'),
+            modifiers: KeyModifiers::CONTROL,
+            ..
+        }) => Event::Stop,
+        CTEvent::Key(key_ev) => Event::Key(key_code_to_key_code(key_ev)),
+        CTEvent::Mouse(mouse_ev) => Event::Mouse(mouse_to_mouse(mouse_ev)),
+        CTEvent::Resize(width, height) => Event::Resize(width, height),
+    }
 }
 
 fn key_code_to_key_code(from: CTKeyEvent) -> KeyEvent {
     KeyEvent {
-        ctrl: from.modifiers.contains(KeyModifiers::CONTROL),
+        modifiers: modifiers_to_modifiers(from.modifiers),
         code: match from.code {
             CTKeyCode::Backspace => KeyCode::Backspace,
             CTKeyCode::Enter => KeyCode::Enter,
@@ -85,6 +171,7 @@ fn mouse_to_mouse(from: CTMouseEvent) -> MouseEvent {
     MouseEvent {
         x: from.column,
         y: from.row,
+        modifiers: modifiers_to_modifiers(from.modifiers),
         state: match from.kind {
             MouseEventKind::Down(button) => MouseState::Down(button_to_button(button)),
             MouseEventKind::Up(button) => MouseState::Up(button_to_button(button)),
@@ -105,3 +192,60 @@ fn button_to_button(button: CTMouseButton) -> MouseButton {
         CTMouseButton::Right => MouseButton::Right,
     }
 }
+
+fn modifiers_to_modifiers(from: KeyModifiers) -> Modifiers {
+    let mut modifiers = Modifiers::empty();
+    let pairs = [
+        (KeyModifiers::SHIFT, Modifiers::SHIFT),
+        (KeyModifiers::CONTROL, Modifiers::CTRL),
+        (KeyModifiers::ALT, Modifiers::ALT),
+        (KeyModifiers::SUPER, Modifiers::SUPER),
+        (KeyModifiers::HYPER, Modifiers::HYPER),
+        (KeyModifiers::META, Modifiers::META),
+    ];
+
+    for (ct, modifier) in pairs {
+        if from.contains(ct) {
+            modifiers |= modifier;
+        }
+    }
+
+    modifiers
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn translate_modifiers() {
+        let event = CTKeyEvent::new(CTKeyCode::Enter, KeyModifiers::ALT | KeyModifiers::SHIFT);
+        let event = key_code_to_key_code(event);
+        assert!(event.matches(KeyCode::Enter, Modifiers::ALT | Modifiers::SHIFT));
+        assert!(!event.ctrl());
+
+        let event = CTKeyEvent::new(CTKeyCode::Char('s'), KeyModifiers::CONTROL);
+        let event = key_code_to_key_code(event);
+        assert!(event.is_ctrl('s'));
+
+        let modifiers = KeyModifiers::SUPER | KeyModifiers::HYPER | KeyModifiers::META;
+        assert_eq!(
+            modifiers_to_modifiers(modifiers),
+            Modifiers::SUPER | Modifiers::HYPER | Modifiers::META
+        );
+        assert_eq!(modifiers_to_modifiers(KeyModifiers::NONE), Modifiers::empty());
+    }
+
+    #[test]
+    fn translate_mouse_modifiers() {
+        let event = CTMouseEvent {
+            kind: MouseEventKind::Moved,
+            column: 1,
+            row: 2,
+            modifiers: KeyModifiers::CONTROL,
+        };
+        let event = mouse_to_mouse(event);
+        assert!(event.ctrl());
+        assert!(!event.shift());
+    }
+}
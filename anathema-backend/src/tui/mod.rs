@@ -34,6 +34,7 @@ pub struct TuiBackendBuilder {
     enable_raw_mode: bool,
     enable_alt_screen: bool,
     enable_mouse: bool,
+    enable_bracketed_paste: bool,
 }
 
 impl TuiBackendBuilder {
@@ -51,6 +52,14 @@ impl TuiBackendBuilder {
         self
     }
 
+    /// Enable bracketed paste.
+    /// Pasted text is received as a single paste event
+    /// rather than a key event per character.
+    pub fn enable_bracketed_paste(mut self) -> Self {
+        self.enable_bracketed_paste = true;
+        self
+    }
+
     /// When raw mode is enabled, every key press is sent to the terminal.
     /// If raw mode is not enabled, the return key has to be pressed to
     /// send characters to the terminal.
@@ -74,12 +83,13 @@ impl TuiBackendBuilder {
             quit_on_ctrl_c: self.quit_on_ctrl_c,
             screen,
             output: self.output,
-            events: Events,
+            events: Events::new(),
 
             hide_cursor: self.hide_cursor,
             enable_raw_mode: self.enable_raw_mode,
             enable_alt_screen: self.enable_alt_screen,
             enable_mouse: self.enable_mouse,
+            enable_bracketed_paste: self.enable_bracketed_paste,
         };
 
         Ok(backend)
@@ -99,6 +109,7 @@ pub struct TuiBackend {
     enable_raw_mode: bool,
     enable_alt_screen: bool,
     enable_mouse: bool,
+    enable_bracketed_paste: bool,
 }
 
 impl TuiBackend {
@@ -114,6 +125,7 @@ impl TuiBackend {
             enable_raw_mode: false,
             enable_alt_screen: false,
             enable_mouse: false,
+            enable_bracketed_paste: false,
         }
     }
 
@@ -133,6 +145,10 @@ impl Backend for TuiBackend {
         self.events.poll(timeout)
     }
 
+    fn event_receiver(&mut self) -> Option<flume::Receiver<Event>> {
+        Some(self.events.receiver())
+    }
+
     fn resize(&mut self, new_size: Size) {
         self.screen.resize(new_size);
     }
@@ -183,12 +199,19 @@ impl Backend for TuiBackend {
             let _ = Screen::enable_mouse(&mut self.output);
         }
 
+        if self.enable_bracketed_paste {
+            let _ = Screen::enable_bracketed_paste(&mut self.output);
+        }
+
         let _ = self.output.flush();
     }
 }
 
 impl Drop for TuiBackend {
     fn drop(&mut self) {
+        // Stop reading events before the terminal is restored,
+        // so the next key press goes to whatever runs after this
+        self.events.stop();
         let _ = self.screen.restore(&mut self.output);
     }
 }
@@ -3,7 +3,7 @@ use std::io::{Result, Write};
 use anathema_geometry::{Pos, Size};
 use anathema_widgets::paint::CellAttributes;
 use anathema_widgets::WidgetRenderer;
-use crossterm::event::EnableMouseCapture;
+use crossterm::event::{EnableBracketedPaste, EnableMouseCapture};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
 use crossterm::{cursor, ExecutableCommand, QueueableCommand};
 
@@ -16,6 +16,10 @@ pub struct Screen {
     pub(crate) new_buffer: Buffer,
     old_buffer: Buffer,
     changes: Vec<(LocalPos, Option<Style>, Change)>,
+    // The cursor position set while painting the current frame
+    cursor: Option<LocalPos>,
+    // The cursor position drawn by the last render
+    rendered_cursor: Option<LocalPos>,
 }
 
 impl Screen {
@@ -37,6 +41,12 @@ impl Screen {
         Ok(())
     }
 
+    /// Enable bracketed paste
+    pub(super) fn enable_bracketed_paste(mut output: impl Write) -> Result<()> {
+        output.queue(EnableBracketedPaste)?;
+        Ok(())
+    }
+
     /// Create a new instance of a screen.
     /// The `output` should be a mutable reference to whatever this screen renders to.
     /// The `output` is used initially to move the cursor and hide it.
@@ -47,6 +57,8 @@ impl Screen {
             old_buffer: Buffer::new(size),
             new_buffer: Buffer::new(size),
             changes: vec![],
+            cursor: None,
+            rendered_cursor: None,
         }
     }
 
@@ -60,6 +72,7 @@ impl Screen {
 
     /// Erase the entire buffer by writing empty cells
     pub(crate) fn erase(&mut self) {
+        self.cursor = None;
         self.erase_region(LocalPos::ZERO, self.size());
     }
 
@@ -87,22 +100,48 @@ impl Screen {
         self.new_buffer.update_cell(style, pos);
     }
 
+    /// Place the cursor at the given screen position for the current frame.
+    pub(crate) fn set_cursor(&mut self, pos: LocalPos) {
+        self.cursor = Some(pos);
+    }
+
     /// Draw the changes to the screen
     pub(crate) fn render(&mut self, mut output: impl Write) -> Result<()> {
         diff(&self.old_buffer, &self.new_buffer, &mut self.changes)?;
 
-        if self.changes.is_empty() {
+        if self.changes.is_empty() && self.cursor == self.rendered_cursor {
             return Ok(());
         }
 
-        draw_changes(&mut output, &self.changes)?;
+        if !self.changes.is_empty() {
+            draw_changes(&mut output, &self.changes)?;
+            self.changes.clear();
+            self.old_buffer = self.new_buffer.clone();
+        }
 
-        self.changes.clear();
+        self.render_cursor(&mut output)?;
 
         output.flush()?;
 
-        self.old_buffer = self.new_buffer.clone();
+        Ok(())
+    }
+
+    // Drawing the changes moves the cursor, so it has to be
+    // placed after every render if a widget has set a position.
+    // The cursor is only hidden if it was previously shown by a widget.
+    fn render_cursor(&mut self, mut output: impl Write) -> Result<()> {
+        match self.cursor {
+            Some(pos) => {
+                output.queue(cursor::MoveTo(pos.x, pos.y))?;
+                output.queue(cursor::Show)?;
+            }
+            None if self.rendered_cursor.is_some() => {
+                output.queue(cursor::Hide)?;
+            }
+            None => {}
+        }
 
+        self.rendered_cursor = self.cursor;
         Ok(())
     }
 
@@ -125,13 +164,15 @@ impl Screen {
         Ok(())
     }
 
-    /// Restore the terminal by setting the cursor to show, disable raw mode, disable mouse capture
-    /// and leave any alternative screens
+    /// Restore the terminal by setting the cursor to show, disable raw mode, disable mouse capture,
+    /// disable bracketed paste and leave any alternative screens
     pub fn restore(&mut self, mut output: impl Write) -> Result<()> {
         disable_raw_mode()?;
         output.execute(LeaveAlternateScreen)?;
         #[cfg(not(target_os = "windows"))]
         output.execute(crossterm::event::DisableMouseCapture)?;
+        #[cfg(not(target_os = "windows"))]
+        output.execute(crossterm::event::DisableBracketedPaste)?;
         output.execute(cursor::Show)?;
         Ok(())
     }
@@ -152,6 +193,11 @@ impl WidgetRenderer for Screen {
     fn size(&self) -> Size {
         self.new_buffer.size()
     }
+
+    fn set_cursor(&mut self, pos: Pos) {
+        let Ok(screen_pos) = pos.try_into() else { return };
+        Screen::set_cursor(self, screen_pos);
+    }
 }
 
 #[cfg(test)]
@@ -184,6 +230,29 @@ mod test {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn render_cursor() {
+        let mut screen = make_screen(Size::new(2, 2));
+        screen.render(&mut vec![]).unwrap();
+
+        // Show the cursor at the position set while painting
+        let mut render_output = vec![];
+        screen.set_cursor(LocalPos::new(1, 0));
+        screen.render(&mut render_output).unwrap();
+        let mut expected = vec![];
+        expected.queue(cursor::MoveTo(1, 0)).unwrap();
+        expected.queue(cursor::Show).unwrap();
+        assert_eq!(expected, render_output);
+
+        // Hide the cursor once no widget sets a position
+        let mut render_output = vec![];
+        screen.erase();
+        screen.render(&mut render_output).unwrap();
+        let mut expected = vec![];
+        expected.queue(cursor::Hide).unwrap();
+        assert!(render_output.ends_with(&expected));
+    }
+
     #[test]
     fn erase_region() {
         // Erase a whole region, leaving all cells `empty`
@@ -1,3 +1,4 @@
+use std::fmt;
 use std::io::{Result, Write};
 use std::str::FromStr;
 
@@ -328,6 +329,40 @@ impl Style {
     }
 }
 
+/// Write the colours and attributes separated by spaces, e.g `fg:Red bg:Blue bold italic`.
+/// The attributes use the same names as the template attributes.
+impl fmt::Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = vec![];
+
+        if let Some(fg) = self.fg {
+            parts.push(format!("fg:{fg}"));
+        }
+
+        if let Some(bg) = self.bg {
+            parts.push(format!("bg:{bg}"));
+        }
+
+        let attributes = [
+            (Attributes::BOLD, "bold"),
+            (Attributes::DIM, "dim"),
+            (Attributes::ITALIC, "italic"),
+            (Attributes::UNDERLINED, "underline"),
+            (Attributes::CROSSED_OUT, "crossed-out"),
+            (Attributes::OVERLINED, "overline"),
+            (Attributes::INVERSE, "inverse"),
+        ];
+
+        for (attribute, name) in attributes {
+            if self.attributes.contains(attribute) {
+                parts.push(name.to_string());
+            }
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
 bitflags::bitflags! {
     /// Style attributes
     #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -367,4 +402,16 @@ mod tests {
         assert_eq!(left.fg.unwrap(), Color::Red);
         assert_eq!(left.bg.unwrap(), Color::Blue);
     }
+
+    #[test]
+    fn display_style() {
+        let mut style = Style::new();
+        assert_eq!(style.to_string(), "");
+
+        style.set_fg(Color::Red);
+        style.set_bg(Color::Rgb(0, 0, 255));
+        style.set_bold(true);
+        style.set_crossed_out(true);
+        assert_eq!(style.to_string(), "fg:Red bg:#0000FF bold crossed-out");
+    }
 }
@@ -0,0 +1,132 @@
+//! Check a template, and the components and partials it uses,
+//! for problems without running the application.
+//!
+//! ```text
+//! anathema-check templates/index.aml --component main=templates/main.aml --partial row=templates/row.aml
+//! ```
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use anathema_default_widgets::register_default_widgets;
+use anathema_templates::Document;
+use anathema_widgets::Factory;
+
+const USAGE: &str = "\
+usage: anathema-check <template> [options]
+
+options:
+    -c, --component <name>=<path>   register a component template
+    -p, --partial <name>=<path>     register a partial template
+    -h, --help                      show this message";
+
+struct Args {
+    template: PathBuf,
+    components: Vec<(String, PathBuf)>,
+    partials: Vec<(String, PathBuf)>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut template = None;
+    let mut components = vec![];
+    let mut partials = vec![];
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-c" | "--component" | "-p" | "--partial" => {
+                let value = args.next().ok_or(format!("missing value for `{arg}`"))?;
+                let (name, path) = value
+                    .split_once('=')
+                    .ok_or(format!("expected <name>=<path>, found `{value}`"))?;
+                let entry = (name.to_string(), PathBuf::from(path));
+                match arg.as_str() {
+                    "-c" | "--component" => components.push(entry),
+                    _ => partials.push(entry),
+                }
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ if template.is_some() => return Err(format!("unexpected argument `{arg}`")),
+            _ => template = Some(PathBuf::from(arg)),
+        }
+    }
+
+    let template = template.ok_or("missing template")?;
+    Ok(Some(Args {
+        template,
+        components,
+        partials,
+    }))
+}
+
+// Returns the number of problems found
+fn check(args: Args) -> Result<usize, String> {
+    let template = read_to_string(&args.template).map_err(|e| format!("{}: {e}", args.template.display()))?;
+    let mut doc = Document::new(template);
+
+    for (name, path) in args.components {
+        doc.add_component(name, path.clone().into())
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+    }
+
+    for (name, path) in args.partials {
+        doc.add_partial(name, path.clone().into())
+            .map_err(|e| format!("{}: {e}", path.display()))?;
+    }
+
+    let mut factory = Factory::new();
+    register_default_widgets(&mut factory);
+
+    // The document template is in memory, so any location
+    // without a path belongs to the template passed in.
+    let with_path = |path: &mut Option<Arc<Path>>| {
+        path.get_or_insert_with(|| args.template.as_path().into());
+    };
+
+    let diagnostics = match doc.check(&factory) {
+        Ok(diagnostics) => diagnostics,
+        Err(mut err) => {
+            if let Some(location) = &mut err.location {
+                with_path(&mut location.path);
+            }
+            println!("{err}");
+            return Ok(1);
+        }
+    };
+
+    for mut diagnostic in diagnostics.iter().cloned() {
+        with_path(&mut diagnostic.location.path);
+        println!("{diagnostic}\n");
+    }
+
+    if !diagnostics.is_empty() {
+        let s = if diagnostics.len() == 1 { "" } else { "s" };
+        println!("{}: {} problem{s} found", args.template.display(), diagnostics.len());
+    }
+
+    Ok(diagnostics.len())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(msg) => {
+            eprintln!("error: {msg}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match check(args) {
+        Ok(0) => ExitCode::SUCCESS,
+        Ok(_) => ExitCode::FAILURE,
+        Err(msg) => {
+            eprintln!("error: {msg}");
+            ExitCode::from(2)
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Format templates.
+//!
+//! ```text
+//! anathema-fmt templates/index.aml templates/main.aml
+//! anathema-fmt --check templates/*.aml
+//! ```
+use std::fs::{read_to_string, write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use anathema_templates::format;
+
+const USAGE: &str = "\
+usage: anathema-fmt [options] <template>...
+
+Formats the templates in place.
+
+options:
+    --check       don't write anything, only list the templates that are not formatted
+    -h, --help    show this message";
+
+struct Args {
+    check: bool,
+    templates: Vec<PathBuf>,
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut check = false;
+    let mut templates = vec![];
+
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--check" => check = true,
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ => templates.push(PathBuf::from(arg)),
+        }
+    }
+
+    if templates.is_empty() {
+        return Err("missing template".into());
+    }
+
+    Ok(Some(Args { check, templates }))
+}
+
+// Returns true if the template was already formatted
+fn format_template(path: &PathBuf, check: bool) -> Result<bool, String> {
+    let template = read_to_string(path).map_err(|e| format!("error: {}: {e}", path.display()))?;
+
+    let output = match format(&template) {
+        Ok(output) => output,
+        Err(mut err) => {
+            if let Some(location) = &mut err.location {
+                location.path = Some(path.as_path().into());
+            }
+            return Err(err.to_string());
+        }
+    };
+
+    if output == template {
+        return Ok(true);
+    }
+
+    match check {
+        true => println!("{} is not formatted", path.display()),
+        false => write(path, output).map_err(|e| format!("error: {}: {e}", path.display()))?,
+    }
+
+    Ok(false)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(msg) => {
+            eprintln!("error: {msg}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut success = true;
+
+    for path in &args.templates {
+        match format_template(path, args.check) {
+            Ok(formatted) => success &= formatted || !args.check,
+            Err(msg) => {
+                eprintln!("{msg}");
+                success = false;
+            }
+        }
+    }
+
+    match success {
+        true => ExitCode::SUCCESS,
+        false => ExitCode::FAILURE,
+    }
+}
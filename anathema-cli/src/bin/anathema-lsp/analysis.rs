@@ -0,0 +1,223 @@
+// Text analysis for the language server.
+//
+// Editors want to complete half written templates that won't parse,
+// so this works on the text of a single line rather than on statements.
+use anathema_templates::error::Location;
+use lsp_types::{Position, Range};
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum CompletionContext<'a> {
+    /// The start of a line
+    Widget,
+    /// An attribute key of the widget
+    Attribute(&'a str),
+    /// After `@`
+    Component,
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// What to complete, given the text of the line before the cursor
+pub(crate) fn completion_context(before_cursor: &str) -> Option<CompletionContext<'_>> {
+    let word_start = before_cursor.trim_end_matches(is_ident_char).len();
+    let before_word = &before_cursor[..word_start];
+
+    if before_word.ends_with('@') {
+        return Some(CompletionContext::Component);
+    }
+
+    if before_word.trim().is_empty() {
+        return Some(CompletionContext::Widget);
+    }
+
+    let line = before_cursor.trim_start();
+    let widget_end = line.find(|c| !is_ident_char(c)).unwrap_or(line.len());
+    let (widget, rest) = line.split_at(widget_end);
+    if widget.is_empty() {
+        return None;
+    }
+
+    // The cursor has to be inside the attribute list, where a key goes
+    let attributes = rest.trim_start().strip_prefix('[')?;
+    let mut depth = 0;
+    let mut quote = None;
+    let mut is_key = true;
+
+    for c in attributes.chars() {
+        match (c, quote) {
+            (c, Some(q)) if c == q => quote = None,
+            (_, Some(_)) => {}
+            ('"' | '\'', None) => quote = Some(c),
+            ('[' | '(' | '{', None) => depth += 1,
+            (']', None) if depth == 0 => return None,
+            (']' | ')' | '}', None) => depth -= 1,
+            (',', None) if depth == 0 => is_key = true,
+            (':', None) if depth == 0 => is_key = false,
+            _ => {}
+        }
+    }
+
+    match is_key && quote.is_none() {
+        true => Some(CompletionContext::Attribute(widget)),
+        false => None,
+    }
+}
+
+/// The word at the byte index, along with the byte index the word starts at
+pub(crate) fn word_at(line: &str, index: usize) -> Option<(usize, &str)> {
+    let index = index.min(line.len());
+    let start = line[..index].trim_end_matches(is_ident_char).len();
+    let end = line[index..]
+        .find(|c| !is_ident_char(c))
+        .map(|i| index + i)
+        .unwrap_or(line.len());
+
+    match start < end {
+        true => Some((start, &line[start..end])),
+        false => None,
+    }
+}
+
+/// True if the word starting at `word_start` is the first word on the line
+pub(crate) fn is_head(line: &str, word_start: usize) -> bool {
+    line[..word_start].trim().is_empty()
+}
+
+pub(crate) fn line(text: &str, line: u32) -> Option<&str> {
+    text.lines().nth(line as usize)
+}
+
+// Positions sent by the client count UTF-16 code units
+pub(crate) fn byte_index(line: &str, character: u32) -> usize {
+    let mut utf16 = 0;
+    for (index, c) in line.char_indices() {
+        if utf16 >= character as usize {
+            return index;
+        }
+        utf16 += c.len_utf16();
+    }
+    line.len()
+}
+
+fn utf16_len(s: &str) -> u32 {
+    s.chars().map(char::len_utf16).sum::<usize>() as u32
+}
+
+/// The range of the word the location points at
+pub(crate) fn location_range(location: &Location) -> Range {
+    let line = location.line().saturating_sub(1) as u32;
+    let src_line = location.src_line();
+    let start = src_line
+        .char_indices()
+        .nth(location.col().saturating_sub(1))
+        .map(|(index, _)| index)
+        .unwrap_or(src_line.len());
+
+    // Components (`@name`) and slots (`$name`) include the sigil
+    let sigil = src_line[start..].starts_with(['@', '$']) as usize;
+    let len = match word_at(src_line, start + sigil) {
+        Some((word_start, word)) if word_start == start + sigil => sigil as u32 + utf16_len(word),
+        _ => src_line[start..]
+            .chars()
+            .next()
+            .map(|c| c.len_utf16() as u32)
+            .unwrap_or(0),
+    };
+
+    let start = utf16_len(&src_line[..start]);
+    Range::new(Position::new(line, start), Position::new(line, start + len))
+}
+
+/// The range of the first statement of a template, skipping blank lines and comments
+pub(crate) fn root_range(text: &str) -> Range {
+    let root = text.lines().enumerate().find_map(|(index, line)| {
+        let statement = line.trim();
+        match statement.is_empty() || statement.starts_with("//") {
+            true => None,
+            false => Some((index as u32, line, statement)),
+        }
+    });
+
+    let Some((line, text, statement)) = root else { return Range::default() };
+    let start = utf16_len(&text[..text.len() - text.trim_start().len()]);
+    Range::new(
+        Position::new(line, start),
+        Position::new(line, start + utf16_len(statement)),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn widget_context() {
+        assert_eq!(completion_context(""), Some(CompletionContext::Widget));
+        assert_eq!(completion_context("    vst"), Some(CompletionContext::Widget));
+        assert_eq!(completion_context("    text "), None);
+    }
+
+    #[test]
+    fn component_context() {
+        assert_eq!(completion_context("@"), Some(CompletionContext::Component));
+        assert_eq!(completion_context("    @but"), Some(CompletionContext::Component));
+    }
+
+    #[test]
+    fn attribute_context() {
+        use CompletionContext::Attribute;
+
+        assert_eq!(completion_context("text ["), Some(Attribute("text")));
+        assert_eq!(completion_context("  text [fore"), Some(Attribute("text")));
+        assert_eq!(completion_context("text [bold: true, "), Some(Attribute("text")));
+        assert_eq!(completion_context("text [a: [1, 2], b"), Some(Attribute("text")));
+
+        // Values
+        assert_eq!(completion_context("text [bold: "), None);
+        assert_eq!(completion_context("text [bold: f(a, "), None);
+        assert_eq!(completion_context("text [foreground: 'a, "), None);
+
+        // After the attributes
+        assert_eq!(completion_context("text [bold: true] "), None);
+        assert_eq!(completion_context("text 'a' ["), None);
+    }
+
+    #[test]
+    fn words() {
+        let line = "    @button [a: 1]";
+        assert_eq!(word_at(line, 7), Some((5, "button")));
+        assert_eq!(word_at(line, 5), Some((5, "button")));
+        assert_eq!(word_at(line, 11), Some((5, "button")));
+        assert_eq!(word_at(line, 12), None);
+        assert_eq!(word_at(line, 0), None);
+        assert!(is_head("    text", 4));
+        assert!(!is_head("    text [a: 1]", 10));
+    }
+
+    #[test]
+    fn utf16_positions() {
+        let line = "text 'å😀' x";
+        assert_eq!(byte_index(line, 6), 6);
+        assert_eq!(byte_index(line, 7), 8);
+        assert_eq!(byte_index(line, 9), 12);
+        assert_eq!(byte_index(line, 100), line.len());
+    }
+
+    #[test]
+    fn range_of_root() {
+        let range = root_range("// a comment\n\n  border [a: 1]\n    text");
+        assert_eq!(range, Range::new(Position::new(2, 2), Position::new(2, 15)));
+        assert_eq!(root_range(""), Range::default());
+    }
+
+    #[test]
+    fn range_of_location() {
+        let src = "vstack\n    😀 textbox [a: 1]";
+        let location = Location::new(src.find("textbox").unwrap(), src);
+        let range = location_range(&location);
+        assert_eq!(range.start, Position::new(1, 7));
+        assert_eq!(range.end, Position::new(1, 14));
+    }
+}
@@ -0,0 +1,681 @@
+//! A language server for templates, speaking JSON-RPC over stdio.
+//!
+//! Provides diagnostics (the same as `anathema-check`), completion of widget names,
+//! attribute keys and component names, hover documentation for widgets and
+//! go-to-definition from `@component` to the template of the component.
+//!
+//! Components and partials are registered the same way as with `anathema-check`,
+//! relative paths are relative to the directory the editor starts the server in:
+//! ```text
+//! anathema-lsp --component main=templates/main.aml --partial row=templates/row.aml
+//! ```
+//!
+//! Go-to-definition also finds components that are not registered, by looking for
+//! `<name>.aml` next to the open template and then under the directory of the server.
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Write;
+use std::fs::{canonicalize, metadata, read_dir, read_to_string};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::SystemTime;
+
+use analysis::CompletionContext;
+use anathema_default_widgets::register_default_widgets;
+use anathema_templates::diagnostics::{DiagnosticKind, WidgetSchema};
+use anathema_templates::error::Location;
+use anathema_templates::{Document, SourceKind};
+use anathema_widgets::Factory;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, DidSaveTextDocument, Notification as _,
+    PublishDiagnostics,
+};
+use lsp_types::request::{Completion, GotoDefinition, HoverRequest, Request as _};
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionOptions, CompletionParams, CompletionResponse, Diagnostic,
+    DiagnosticSeverity, Documentation, GotoDefinitionParams, GotoDefinitionResponse, Hover, HoverContents, HoverParams,
+    HoverProviderCapability, MarkupContent, MarkupKind, OneOf, Position, PublishDiagnosticsParams, Range,
+    ServerCapabilities, TextDocumentPositionParams, TextDocumentSyncCapability, TextDocumentSyncKind, Uri,
+};
+use serde_json::Value;
+
+mod analysis;
+
+const USAGE: &str = "\
+usage: anathema-lsp [options]
+
+Runs a language server over stdio.
+
+options:
+    -c, --component <name>=<path>   register a component template
+    -p, --partial <name>=<path>     register a partial template
+    -h, --help                      show this message";
+
+// How many directories deep to look for a component that is not registered
+const SEARCH_DEPTH: usize = 8;
+
+type BoxError = Box<dyn Error + Send + Sync>;
+
+struct Args {
+    components: Vec<(String, PathBuf)>,
+    partials: Vec<(String, PathBuf)>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut components = vec![];
+    let mut partials = vec![];
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-c" | "--component" | "-p" | "--partial" => {
+                let value = args.next().ok_or(format!("missing value for `{arg}`"))?;
+                let (name, path) = value
+                    .split_once('=')
+                    .ok_or(format!("expected <name>=<path>, found `{value}`"))?;
+                let entry = (name.to_string(), PathBuf::from(path));
+                match arg.as_str() {
+                    "-c" | "--component" => components.push(entry),
+                    _ => partials.push(entry),
+                }
+            }
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+
+    Ok(Some(Args { components, partials }))
+}
+
+// -----------------------------------------------------------------------------
+//     - Templates -
+// -----------------------------------------------------------------------------
+// Component and partial templates, only read again when the file is modified
+#[derive(Default)]
+struct Templates(RefCell<HashMap<PathBuf, (SystemTime, String)>>);
+
+impl Templates {
+    fn get(&self, path: &Path) -> Option<String> {
+        let modified = metadata(path).and_then(|metadata| metadata.modified()).ok()?;
+        let mut files = self.0.borrow_mut();
+        if let Some((_, template)) = files.get(path).filter(|(time, _)| *time == modified) {
+            return Some(template.clone());
+        }
+
+        let template = read_to_string(path).ok()?;
+        files.insert(path.to_path_buf(), (modified, template.clone()));
+        Some(template)
+    }
+}
+
+// -----------------------------------------------------------------------------
+//     - Server -
+// -----------------------------------------------------------------------------
+struct Server {
+    factory: Factory,
+    components: Vec<(String, PathBuf)>,
+    partials: Vec<(String, PathBuf)>,
+    templates: Templates,
+    // Open documents and their current text
+    documents: HashMap<Uri, String>,
+}
+
+impl Server {
+    fn new(args: Args) -> Self {
+        let mut factory = Factory::new();
+        register_default_widgets(&mut factory);
+
+        Self {
+            factory,
+            components: args.components,
+            partials: args.partials,
+            templates: Templates::default(),
+            documents: HashMap::new(),
+        }
+    }
+
+    fn handle_request(&self, request: Request) -> Response {
+        let result = match request.method.as_str() {
+            Completion::METHOD => handle::<Completion>(request.params, |params| self.completion(params)),
+            HoverRequest::METHOD => handle::<HoverRequest>(request.params, |params| self.hover(params)),
+            GotoDefinition::METHOD => handle::<GotoDefinition>(request.params, |params| self.definition(params)),
+            method => {
+                let message = format!("unsupported request `{method}`");
+                return Response::new_err(request.id, ErrorCode::MethodNotFound as i32, message);
+            }
+        };
+
+        match result {
+            Ok(value) => Response::new_ok(request.id, value),
+            Err(err) => Response::new_err(request.id, ErrorCode::InvalidParams as i32, err.to_string()),
+        }
+    }
+
+    // Returns the diagnostics to publish
+    fn handle_notification(&mut self, notification: Notification) -> Result<Vec<PublishDiagnosticsParams>, BoxError> {
+        let uris = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params =
+                    notification.extract::<lsp_types::DidOpenTextDocumentParams>(DidOpenTextDocument::METHOD)?;
+                let document = params.text_document;
+                self.documents.insert(document.uri.clone(), document.text);
+                vec![document.uri]
+            }
+            DidChangeTextDocument::METHOD => {
+                let params =
+                    notification.extract::<lsp_types::DidChangeTextDocumentParams>(DidChangeTextDocument::METHOD)?;
+                // The server asks for full text sync, so the last change is the whole document
+                if let Some(change) = params.content_changes.into_iter().last() {
+                    self.documents.insert(params.text_document.uri.clone(), change.text);
+                }
+                vec![params.text_document.uri]
+            }
+            // A saved template could be a component used by any other open template
+            DidSaveTextDocument::METHOD => self.documents.keys().cloned().collect(),
+            DidCloseTextDocument::METHOD => {
+                let params =
+                    notification.extract::<lsp_types::DidCloseTextDocumentParams>(DidCloseTextDocument::METHOD)?;
+                self.documents.remove(&params.text_document.uri);
+                let clear = PublishDiagnosticsParams::new(params.text_document.uri, vec![], None);
+                return Ok(vec![clear]);
+            }
+            _ => return Ok(vec![]),
+        };
+
+        let diagnostics = uris
+            .into_iter()
+            .filter_map(|uri| {
+                let diagnostics = self.diagnostics(self.documents.get(&uri)?);
+                Some(PublishDiagnosticsParams::new(uri, diagnostics, None))
+            })
+            .collect();
+
+        Ok(diagnostics)
+    }
+
+    fn diagnostics(&self, text: &str) -> Vec<Diagnostic> {
+        let mut doc = Document::new(text);
+
+        // A component or partial that can't be read is reported
+        // where it's used, as it's not registered
+        for (name, path) in &self.components {
+            if let Some(template) = self.templates.get(path) {
+                let _ = doc.add_component(
+                    name,
+                    SourceKind::Loaded {
+                        path: path.clone(),
+                        template,
+                    },
+                );
+            }
+        }
+
+        for (name, path) in &self.partials {
+            if let Some(template) = self.templates.get(path) {
+                let _ = doc.add_partial(
+                    name,
+                    SourceKind::Loaded {
+                        path: path.clone(),
+                        template,
+                    },
+                );
+            }
+        }
+
+        let err = match doc.check(&self.factory) {
+            Ok(diagnostics) => {
+                return diagnostics
+                    .into_iter()
+                    // Problems in components and partials are reported when they are opened.
+                    // Any template could be the template of a component, where associations
+                    // are handled by the parent, so these are not reported either.
+                    .filter(|diagnostic| diagnostic.location.path.is_none())
+                    .filter(|diagnostic| !matches!(diagnostic.kind, DiagnosticKind::UnhandledAssociation { .. }))
+                    .map(|diagnostic| {
+                        let range = analysis::location_range(&diagnostic.location);
+                        to_diagnostic(range, DiagnosticSeverity::WARNING, diagnostic.kind.to_string())
+                    })
+                    .collect();
+            }
+            Err(err) => err,
+        };
+
+        let diagnostic = match err.location {
+            // The error is in a component or a partial
+            Some(Location { path: Some(path), .. }) => {
+                let message = format!("{}: {}", path.display(), err.kind);
+                to_diagnostic(Range::default(), DiagnosticSeverity::ERROR, message)
+            }
+            Some(location) => {
+                let range = analysis::location_range(&location);
+                to_diagnostic(range, DiagnosticSeverity::ERROR, err.kind.to_string())
+            }
+            None => to_diagnostic(Range::default(), DiagnosticSeverity::ERROR, err.kind.to_string()),
+        };
+
+        vec![diagnostic]
+    }
+
+    // The line of the document and the byte index in the line of the position
+    fn line_at(&self, position: &TextDocumentPositionParams) -> Option<(&str, usize)> {
+        let text = self.documents.get(&position.text_document.uri)?;
+        let line = analysis::line(text, position.position.line).unwrap_or("");
+        let index = analysis::byte_index(line, position.position.character);
+        Some((line, index))
+    }
+
+    fn completion(&self, params: CompletionParams) -> Option<CompletionResponse> {
+        let (line, index) = self.line_at(&params.text_document_position)?;
+
+        let items = match analysis::completion_context(&line[..index])? {
+            CompletionContext::Widget => {
+                let mut widgets = self.factory.widgets().collect::<Vec<_>>();
+                widgets.sort();
+                widgets
+                    .into_iter()
+                    .map(|ident| CompletionItem {
+                        label: ident.into(),
+                        kind: Some(CompletionItemKind::CLASS),
+                        documentation: self.factory.docs(ident).map(markdown_docs),
+                        ..Default::default()
+                    })
+                    .collect()
+            }
+            CompletionContext::Attribute(widget) => self
+                .factory
+                .attributes(widget)?
+                .iter()
+                .map(|attribute| CompletionItem {
+                    label: attribute.name.to_string(),
+                    kind: Some(CompletionItemKind::PROPERTY),
+                    detail: Some(attribute.ty.to_string()),
+                    ..Default::default()
+                })
+                .collect(),
+            CompletionContext::Component => self
+                .components
+                .iter()
+                .map(|(name, path)| CompletionItem {
+                    label: name.clone(),
+                    kind: Some(CompletionItemKind::MODULE),
+                    detail: Some(path.display().to_string()),
+                    ..Default::default()
+                })
+                .collect(),
+        };
+
+        Some(CompletionResponse::Array(items))
+    }
+
+    fn hover(&self, params: HoverParams) -> Option<Hover> {
+        let (line, index) = self.line_at(&params.text_document_position_params)?;
+        let (start, word) = analysis::word_at(line, index)?;
+
+        if !analysis::is_head(line, start) {
+            return None;
+        }
+
+        let docs = self.factory.docs(word)?;
+        let position = params.text_document_position_params.position;
+        let start = position.character - line[start..index].encode_utf16().count() as u32;
+        let end = start + word.encode_utf16().count() as u32;
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: format!("**{word}**\n\n{docs}"),
+            }),
+            range: Some(Range::new(
+                Position::new(position.line, start),
+                Position::new(position.line, end),
+            )),
+        })
+    }
+
+    fn definition(&self, params: GotoDefinitionParams) -> Option<GotoDefinitionResponse> {
+        let position = &params.text_document_position_params;
+        let (line, index) = self.line_at(position)?;
+        let (start, word) = analysis::word_at(line, index)?;
+
+        if !line[..start].ends_with('@') {
+            return None;
+        }
+
+        let path = match self.components.iter().find(|(name, _)| name == word) {
+            Some((_, path)) => path.clone(),
+            None => find_component(word, &position.text_document.uri)?,
+        };
+
+        // Point at the root of the template
+        let range = analysis::root_range(&self.templates.get(&path)?);
+        let location = lsp_types::Location::new(path_to_uri(&path)?, range);
+        Some(GotoDefinitionResponse::Scalar(location))
+    }
+}
+
+fn handle<R: lsp_types::request::Request>(
+    params: Value,
+    f: impl FnOnce(R::Params) -> R::Result,
+) -> Result<Value, serde_json::Error> {
+    let params = serde_json::from_value(params)?;
+    serde_json::to_value(f(params))
+}
+
+fn to_diagnostic(range: Range, severity: DiagnosticSeverity, message: String) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        source: Some("anathema".into()),
+        message,
+        ..Default::default()
+    }
+}
+
+fn markdown_docs(docs: &str) -> Documentation {
+    Documentation::MarkupContent(MarkupContent {
+        kind: MarkupKind::Markdown,
+        value: docs.into(),
+    })
+}
+
+fn path_to_uri(path: &Path) -> Option<Uri> {
+    let path = canonicalize(path).ok()?;
+    let path = path.to_str()?.replace('\\', "/");
+
+    let mut uri = String::from("file://");
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+
+    for byte in path.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b'-' | b'.' | b'_' | b'~' | b':' => uri.push(byte as char),
+            _ => {
+                let _ = write!(uri, "%{byte:02X}");
+            }
+        }
+    }
+
+    uri.parse().ok()
+}
+
+fn uri_to_path(uri: &Uri) -> Option<PathBuf> {
+    let mut bytes = uri.as_str().strip_prefix("file://")?.bytes();
+    let mut path = vec![];
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'%' => {
+                let hex = [bytes.next()?, bytes.next()?];
+                path.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            _ => path.push(byte),
+        }
+    }
+
+    let path = String::from_utf8(path).ok()?;
+    // Windows paths are written as `/C:/...`
+    match path.get(2..3) {
+        Some(":") => Some(PathBuf::from(&path[1..])),
+        _ => Some(PathBuf::from(path)),
+    }
+}
+
+// A component that is not registered is looked for as `<name>.aml`,
+// first next to the template using it, then under the current directory
+fn find_component(name: &str, uri: &Uri) -> Option<PathBuf> {
+    let file_name = format!("{name}.aml");
+    let sibling = uri_to_path(uri).and_then(|path| Some(path.parent()?.join(&file_name)));
+    match sibling {
+        Some(path) if path.is_file() => Some(path),
+        _ => find_file(Path::new("."), &file_name, SEARCH_DEPTH),
+    }
+}
+
+fn find_file(dir: &Path, file_name: &str, depth: usize) -> Option<PathBuf> {
+    let mut dirs = vec![];
+    for entry in read_dir(dir).ok()?.flatten() {
+        let name = entry.file_name();
+        if name.to_string_lossy().starts_with('.') || name == "target" {
+            continue;
+        }
+
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_file() && name == file_name => return Some(entry.path()),
+            Ok(file_type) if file_type.is_dir() => dirs.push(entry.path()),
+            _ => {}
+        }
+    }
+
+    if depth == 0 {
+        return None;
+    }
+
+    // Sorted so the same file is found every time
+    dirs.sort();
+    dirs.into_iter().find_map(|dir| find_file(&dir, file_name, depth - 1))
+}
+
+// -----------------------------------------------------------------------------
+//     - Main loop -
+// -----------------------------------------------------------------------------
+fn run(args: Args) -> Result<(), BoxError> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["@".into(), "[".into(), ",".into()]),
+            ..Default::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    main_loop(connection, Server::new(args))?;
+    io_threads.join()?;
+    Ok(())
+}
+
+fn main_loop(connection: Connection, mut server: Server) -> Result<(), BoxError> {
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                let response = server.handle_request(request);
+                connection.sender.send(response.into())?;
+            }
+            Message::Notification(notification) => {
+                // A notification the server can't read is logged, as there is no response to send
+                let diagnostics = match server.handle_notification(notification) {
+                    Ok(diagnostics) => diagnostics,
+                    Err(err) => {
+                        eprintln!("error: {err}");
+                        continue;
+                    }
+                };
+
+                for params in diagnostics {
+                    let notification = Notification::new(PublishDiagnostics::METHOD.into(), params);
+                    connection.sender.send(notification.into())?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(msg) => {
+            eprintln!("error: {msg}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use lsp_types::{PartialResultParams, TextDocumentIdentifier, WorkDoneProgressParams};
+
+    use super::*;
+
+    fn server(template: &str) -> (Server, Uri) {
+        let args = Args {
+            components: vec![(
+                "button".into(),
+                PathBuf::from("../examples/templates/buttons/button.aml"),
+            )],
+            partials: vec![],
+        };
+        let mut server = Server::new(args);
+        let uri = Uri::from_str("file:///tmp/index.aml").unwrap();
+        server.documents.insert(uri.clone(), template.into());
+        (server, uri)
+    }
+
+    fn position(uri: &Uri, line: u32, character: u32) -> TextDocumentPositionParams {
+        TextDocumentPositionParams::new(TextDocumentIdentifier::new(uri.clone()), Position::new(line, character))
+    }
+
+    fn complete(template: &str, line: u32, character: u32) -> Vec<String> {
+        let (server, uri) = server(template);
+        let params = CompletionParams {
+            text_document_position: position(&uri, line, character),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+            context: None,
+        };
+        match server.completion(params) {
+            Some(CompletionResponse::Array(items)) => items.into_iter().map(|item| item.label).collect(),
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn diagnostics() {
+        let (server, _) = server("");
+        let diagnostics = server.diagnostics("vstack\n    text [foregound: 'red'] 'a'\n    @missing\n");
+
+        let ranges = diagnostics.iter().map(|d| d.range).collect::<Vec<_>>();
+        let expected = vec![
+            Range::new(Position::new(1, 10), Position::new(1, 19)),
+            Range::new(Position::new(2, 4), Position::new(2, 12)),
+        ];
+        assert_eq!(ranges, expected);
+        assert!(diagnostics[0].message.contains("did you mean `foreground`"));
+    }
+
+    #[test]
+    fn parse_error() {
+        let (server, _) = server("");
+        let diagnostics = server.diagnostics("vstack\n    text 'a\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(diagnostics[0].range.start, Position::new(1, 9));
+    }
+
+    #[test]
+    fn complete_widgets_attributes_and_components() {
+        let widgets = complete("vstack\n    te", 1, 6);
+        assert!(widgets.contains(&"text".to_string()));
+        assert!(widgets.contains(&"textarea".to_string()));
+
+        let attributes = complete("border [bo", 0, 10);
+        assert!(attributes.contains(&"border_style".to_string()));
+        assert!(attributes.contains(&"bold".to_string()));
+
+        let components = complete("vstack\n    @", 1, 5);
+        assert_eq!(components, vec!["button".to_string()]);
+    }
+
+    #[test]
+    fn hover_widget() {
+        let (server, uri) = server("vstack\n    text 'a'");
+        let params = HoverParams {
+            text_document_position_params: position(&uri, 1, 6),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+        };
+        let hover = server.hover(params).unwrap();
+        assert_eq!(hover.range, Some(Range::new(Position::new(1, 4), Position::new(1, 8))));
+    }
+
+    #[test]
+    fn goto_component() {
+        let (server, uri) = server("vstack\n    @button");
+        let params = GotoDefinitionParams {
+            text_document_position_params: position(&uri, 1, 7),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let Some(GotoDefinitionResponse::Scalar(location)) = server.definition(params) else {
+            panic!("no definition")
+        };
+        assert!(location.uri.as_str().starts_with("file:///"));
+        assert!(location
+            .uri
+            .as_str()
+            .ends_with("/examples/templates/buttons/button.aml"));
+        assert_eq!(location.range, Range::new(Position::new(0, 0), Position::new(0, 6)));
+    }
+
+    #[test]
+    fn goto_unregistered_component() {
+        let mut server = Server::new(Args {
+            components: vec![],
+            partials: vec![],
+        });
+        let uri = path_to_uri(Path::new("../examples/templates/buttons/buttons.aml")).unwrap();
+        server.documents.insert(uri.clone(), "vstack\n    @button".into());
+
+        let params = GotoDefinitionParams {
+            text_document_position_params: position(&uri, 1, 7),
+            work_done_progress_params: WorkDoneProgressParams::default(),
+            partial_result_params: PartialResultParams::default(),
+        };
+        let Some(GotoDefinitionResponse::Scalar(location)) = server.definition(params) else {
+            panic!("no definition")
+        };
+        assert!(location
+            .uri
+            .as_str()
+            .ends_with("/examples/templates/buttons/button.aml"));
+    }
+
+    #[test]
+    fn uri_round_trip() {
+        let uri = path_to_uri(Path::new("../examples/templates/buttons/button.aml")).unwrap();
+        let path = uri_to_path(&uri).unwrap();
+        assert_eq!(path, canonicalize("../examples/templates/buttons/button.aml").unwrap());
+    }
+
+    #[test]
+    fn templates_are_cached() {
+        let (server, _) = server("");
+        let path = Path::new("../examples/templates/buttons/button.aml");
+        let template = server.templates.get(path).unwrap();
+        assert!(template.starts_with("border"));
+        assert!(server.templates.0.borrow().contains_key(path));
+    }
+}
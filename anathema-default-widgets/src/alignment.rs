@@ -1,14 +1,17 @@
 use std::ops::ControlFlow;
 
 use anathema_geometry::{Pos, Size};
+use anathema_templates::diagnostics::{Attribute, AttributeType};
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::{AttributeStorage, LayoutChildren, PositionChildren, Widget, WidgetId};
 
-use crate::layout::alignment::{Alignment, ALIGNMENT};
+use crate::layout::alignment::{Alignment, ALIGNMENT, ALIGNMENTS};
 
 #[derive(Default)]
 pub struct Align;
 
+const ATTRIBUTES: &[Attribute] = &[Attribute::new(ALIGNMENT, AttributeType::OneOf(ALIGNMENTS))];
+
 impl Widget for Align {
     fn layout<'bp>(
         &mut self,
@@ -57,6 +60,10 @@ impl Widget for Align {
             ControlFlow::Break(())
         });
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(ATTRIBUTES)
+    }
 }
 
 #[cfg(test)]
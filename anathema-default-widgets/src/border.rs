@@ -3,6 +3,7 @@ use std::fmt::Display;
 use std::ops::{ControlFlow, Deref};
 
 use anathema_geometry::{LocalPos, Pos, Rect, Size};
+use anathema_templates::diagnostics::{Attribute, AttributeType};
 use anathema_widgets::expressions::EvalValue;
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::paint::{PaintCtx, SizePos};
@@ -16,6 +17,7 @@ use crate::layout::Axis;
 use crate::{HEIGHT, MAX_HEIGHT, MAX_WIDTH, MIN_HEIGHT, MIN_WIDTH, WIDTH};
 
 pub const BORDER_STYLE: &str = "border_style";
+pub(crate) const SIDES: &str = "sides";
 
 // -----------------------------------------------------------------------------
 //     - Indices -
@@ -140,6 +142,7 @@ impl Display for BorderStyle {
     }
 }
 
+#[allow(clippy::infallible_try_from)]
 impl TryFrom<&EvalValue<'_>> for BorderStyle {
     type Error = Infallible;
 
@@ -400,6 +403,17 @@ impl Border {
     }
 }
 
+pub(crate) const ATTRIBUTES: &[Attribute] = &[
+    Attribute::new(BORDER_STYLE, AttributeType::Str),
+    Attribute::new(SIDES, AttributeType::Flags(&["all", "top", "right", "bottom", "left"])),
+    Attribute::new(WIDTH, AttributeType::Unsigned),
+    Attribute::new(HEIGHT, AttributeType::Unsigned),
+    Attribute::new(MIN_WIDTH, AttributeType::Unsigned),
+    Attribute::new(MIN_HEIGHT, AttributeType::Unsigned),
+    Attribute::new(MAX_WIDTH, AttributeType::Unsigned),
+    Attribute::new(MAX_HEIGHT, AttributeType::Unsigned),
+];
+
 impl Widget for Border {
     fn layout<'bp>(
         &mut self,
@@ -410,7 +424,7 @@ impl Widget for Border {
     ) -> Size {
         let attributes = ctx.attribs.get(id);
         self.sides = attributes
-            .get_val("sides")
+            .get_val(SIDES)
             .and_then(|s| Sides::try_from(s.deref()).ok())
             .unwrap_or_default();
 
@@ -495,13 +509,17 @@ impl Widget for Border {
         size.height = size.height.saturating_sub(bs.bottom as usize);
         Rect::from((pos, size))
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(ATTRIBUTES)
+    }
 }
 
 pub(crate) fn make(attributes: &Attributes<'_>) -> Box<dyn AnyWidget> {
     let border_style: BorderStyle = attributes.get_ref(BORDER_STYLE).unwrap_or_default();
 
     let sides = attributes
-        .get_val("sides")
+        .get_val(SIDES)
         .and_then(|s| Sides::try_from(s.deref()).ok())
         .unwrap_or_default();
 
@@ -1,5 +1,6 @@
 use anathema_backend::tui::Style;
 use anathema_geometry::{LocalPos, Pos, Size};
+use anathema_templates::diagnostics::{Attribute, AttributeType};
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::paint::{PaintCtx, SizePos};
 use anathema_widgets::{AttributeStorage, LayoutChildren, PaintChildren, PositionChildren, Widget, WidgetId};
@@ -158,6 +159,11 @@ impl Default for Canvas {
     }
 }
 
+const ATTRIBUTES: &[Attribute] = &[
+    Attribute::new(WIDTH, AttributeType::Unsigned),
+    Attribute::new(HEIGHT, AttributeType::Unsigned),
+];
+
 impl Widget for Canvas {
     fn layout<'bp>(
         &mut self,
@@ -211,6 +217,10 @@ impl Widget for Canvas {
     fn needs_reflow(&self) -> bool {
         self.is_dirty
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(ATTRIBUTES)
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
 use std::ops::ControlFlow;
 
 use anathema_geometry::Size;
+use anathema_templates::diagnostics::{Attribute, AttributeType};
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::{AttributeStorage, LayoutChildren, PositionChildren, Widget, WidgetId};
 
@@ -9,6 +10,15 @@ use crate::{HEIGHT, MAX_HEIGHT, MAX_WIDTH, MIN_HEIGHT, MIN_WIDTH, WIDTH};
 #[derive(Debug, Default)]
 pub struct Container;
 
+const ATTRIBUTES: &[Attribute] = &[
+    Attribute::new(WIDTH, AttributeType::Unsigned),
+    Attribute::new(HEIGHT, AttributeType::Unsigned),
+    Attribute::new(MIN_WIDTH, AttributeType::Unsigned),
+    Attribute::new(MIN_HEIGHT, AttributeType::Unsigned),
+    Attribute::new(MAX_WIDTH, AttributeType::Unsigned),
+    Attribute::new(MAX_HEIGHT, AttributeType::Unsigned),
+];
+
 impl Widget for Container {
     fn layout<'bp>(
         &mut self,
@@ -68,6 +78,10 @@ impl Widget for Container {
             ControlFlow::Break(())
         });
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(ATTRIBUTES)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,40 @@
+// Cursor movement shared by the text input and the text editor.
+// Cursors are char indices into the text.
+
+fn is_word(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+// The start of the word before the cursor
+pub(crate) fn prev_word(text: &str, from: usize) -> usize {
+    let chars = text.chars().take(from).collect::<Vec<_>>();
+    let mut index = from;
+    while index > 0 && !is_word(chars[index - 1]) {
+        index -= 1;
+    }
+    while index > 0 && is_word(chars[index - 1]) {
+        index -= 1;
+    }
+    index
+}
+
+// The end of the word after the cursor
+pub(crate) fn next_word(text: &str, from: usize) -> usize {
+    let mut chars = text.chars().skip(from).peekable();
+    let mut index = from;
+    while chars.next_if(|c| !is_word(*c)).is_some() {
+        index += 1;
+    }
+    while chars.next_if(|c| is_word(*c)).is_some() {
+        index += 1;
+    }
+    index
+}
+
+// The byte index of a char index, or the length of the text if the index is past the end
+pub(crate) fn byte_index(text: &str, char_index: usize) -> usize {
+    text.char_indices()
+        .nth(char_index)
+        .map(|(i, _)| i)
+        .unwrap_or(text.len())
+}
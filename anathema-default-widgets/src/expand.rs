@@ -1,15 +1,25 @@
 use std::ops::ControlFlow;
 
 use anathema_geometry::{LocalPos, Size};
+use anathema_templates::diagnostics::{Attribute, AttributeType};
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::paint::{PaintCtx, SizePos};
 use anathema_widgets::{AttributeStorage, LayoutChildren, PaintChildren, PositionChildren, Widget, WidgetId};
 
-use crate::layout::{single_layout, Axis};
+use crate::layout::expand::FACTOR;
+use crate::layout::{single_layout, Axis, AXES, AXIS};
+
+pub(crate) const FILL: &str = "fill";
 
 #[derive(Debug, Default)]
 pub struct Expand;
 
+const ATTRIBUTES: &[Attribute] = &[
+    Attribute::new(AXIS, AttributeType::OneOf(AXES)),
+    Attribute::new(FILL, AttributeType::Any),
+    Attribute::new(FACTOR, AttributeType::Unsigned),
+];
+
 impl Widget for Expand {
     fn layout<'bp>(
         &mut self,
@@ -21,7 +31,7 @@ impl Widget for Expand {
         let mut size = single_layout(children, constraints, ctx);
 
         let attributes = ctx.attribs.get(id);
-        match attributes.get("axis") {
+        match attributes.get(AXIS) {
             Some(Axis::Horizontal) => size.width = constraints.max_width(),
             Some(Axis::Vertical) => size.height = constraints.max_height(),
             None => {
@@ -54,7 +64,7 @@ impl Widget for Expand {
         mut ctx: PaintCtx<'_, SizePos>,
     ) {
         let attributes = attribute_storage.get(id);
-        if let Some(fill) = attributes.get_val("fill") {
+        if let Some(fill) = attributes.get_val(FILL) {
             for y in 0..ctx.local_size.height as u16 {
                 let mut used_width = 0;
                 loop {
@@ -83,6 +93,10 @@ impl Widget for Expand {
             ControlFlow::Break(())
         });
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(ATTRIBUTES)
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,907 @@
+use std::ops::ControlFlow;
+
+use anathema_geometry::{LocalPos, Size};
+use anathema_state::{Color, Hex, State, Value};
+use anathema_templates::diagnostics::{Attribute, AttributeType};
+use anathema_widgets::components::events::{KeyCode, KeyEvent, KeyState};
+use anathema_widgets::components::{Component, Context};
+use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
+use anathema_widgets::paint::{CellAttributes, PaintCtx, SizePos};
+use anathema_widgets::{AttributeStorage, Elements, LayoutChildren, PaintChildren, PositionChildren, Widget, WidgetId};
+use unicode_width::UnicodeWidthChar;
+
+use crate::editing::{byte_index, next_word, prev_word};
+use crate::WIDTH;
+
+pub(crate) const CURSOR: &str = "cursor";
+pub(crate) const SELECTION_START: &str = "selection_start";
+pub(crate) const SELECTION_END: &str = "selection_end";
+pub(crate) const FOCUSED: &str = "focused";
+pub(crate) const PLACEHOLDER: &str = "placeholder";
+pub(crate) const PASSWORD: &str = "password";
+pub(crate) const MASK: &str = "mask";
+pub(crate) const MAX_LENGTH: &str = "max_length";
+pub(crate) const VALUE: &str = "value";
+
+const DEFAULT_MASK: char = '*';
+
+/// The result of passing a key event to an [`InputBuffer`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InputEvent {
+    /// The text was changed
+    Changed,
+    /// The cursor or the selection was moved
+    Moved,
+    /// Enter was pressed
+    Submit,
+    /// The key event was not used
+    Ignored,
+}
+
+/// A single line of editable text.
+///
+/// The cursor and the selection are char indices into the text.
+/// If a max length is set, no more than that number of chars can be inserted.
+///
+/// ```
+/// # use anathema_default_widgets::InputBuffer;
+/// let mut buffer = InputBuffer::new();
+/// buffer.insert_str("hello world");
+/// buffer.word_left(true);
+/// buffer.insert_str("there");
+/// assert_eq!(buffer.text(), "hello there");
+/// ```
+#[derive(Debug, Default)]
+pub struct InputBuffer {
+    text: String,
+    cursor: usize,
+    anchor: Option<usize>,
+    max_length: Option<usize>,
+}
+
+impl InputBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The number of chars in the text
+    pub fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// The start and end of the selection, if anything is selected.
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        let anchor = self.anchor?;
+        match anchor.cmp(&self.cursor) {
+            std::cmp::Ordering::Less => Some((anchor, self.cursor)),
+            std::cmp::Ordering::Greater => Some((self.cursor, anchor)),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// Set the max number of chars.
+    /// This does not truncate the existing text.
+    pub fn set_max_length(&mut self, max_length: Option<usize>) {
+        self.max_length = max_length;
+    }
+
+    /// Replace the text, placing the cursor at the end.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.cursor = self.len();
+        self.anchor = None;
+    }
+
+    /// Insert a string at the cursor, replacing the selection.
+    /// Returns false if nothing was inserted or deleted.
+    pub fn insert_str(&mut self, s: &str) -> bool {
+        let deleted = self.delete_selection();
+
+        let available = match self.max_length {
+            Some(max) => max.saturating_sub(self.len()),
+            None => usize::MAX,
+        };
+
+        let inserted = s
+            .chars()
+            .filter(|c| !c.is_control())
+            .take(available)
+            .collect::<String>();
+        if inserted.is_empty() {
+            return deleted;
+        }
+
+        let index = byte_index(&self.text, self.cursor);
+        self.text.insert_str(index, &inserted);
+        self.cursor += inserted.chars().count();
+        true
+    }
+
+    pub fn insert_char(&mut self, c: char) -> bool {
+        self.insert_str(c.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Delete the selection or the char before the cursor.
+    pub fn delete_back(&mut self) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+
+        if self.cursor == 0 {
+            return false;
+        }
+
+        self.cursor -= 1;
+        let index = byte_index(&self.text, self.cursor);
+        self.text.remove(index);
+        true
+    }
+
+    /// Delete the selection or the char after the cursor.
+    pub fn delete_forward(&mut self) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+
+        if self.cursor == self.len() {
+            return false;
+        }
+
+        let index = byte_index(&self.text, self.cursor);
+        self.text.remove(index);
+        true
+    }
+
+    /// Delete the selection or the word before the cursor.
+    pub fn delete_word_back(&mut self) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+
+        let start = prev_word(&self.text, self.cursor);
+        self.delete_range(start, self.cursor)
+    }
+
+    /// Delete the selection or the word after the cursor.
+    pub fn delete_word_forward(&mut self) -> bool {
+        if self.delete_selection() {
+            return true;
+        }
+
+        let end = next_word(&self.text, self.cursor);
+        self.delete_range(self.cursor, end)
+    }
+
+    /// Delete the selected text.
+    /// Returns false if nothing was selected.
+    pub fn delete_selection(&mut self) -> bool {
+        let Some((start, end)) = self.selection() else {
+            self.anchor = None;
+            return false;
+        };
+        self.delete_range(start, end)
+    }
+
+    /// Clear the text and the selection.
+    pub fn clear(&mut self) -> bool {
+        self.anchor = None;
+        self.cursor = 0;
+        !std::mem::take(&mut self.text).is_empty()
+    }
+
+    pub fn left(&mut self, select: bool) -> bool {
+        match self.selection() {
+            Some((start, _)) if !select => self.move_to(start, false),
+            _ => self.move_to(self.cursor.saturating_sub(1), select),
+        }
+    }
+
+    pub fn right(&mut self, select: bool) -> bool {
+        match self.selection() {
+            Some((_, end)) if !select => self.move_to(end, false),
+            _ => self.move_to(self.cursor + 1, select),
+        }
+    }
+
+    /// Move the cursor to the start of the current or previous word.
+    pub fn word_left(&mut self, select: bool) -> bool {
+        self.move_to(prev_word(&self.text, self.cursor), select)
+    }
+
+    /// Move the cursor to the end of the current or next word.
+    pub fn word_right(&mut self, select: bool) -> bool {
+        self.move_to(next_word(&self.text, self.cursor), select)
+    }
+
+    pub fn home(&mut self, select: bool) -> bool {
+        self.move_to(0, select)
+    }
+
+    pub fn end(&mut self, select: bool) -> bool {
+        self.move_to(self.len(), select)
+    }
+
+    pub fn select_all(&mut self) -> bool {
+        let before = (self.anchor, self.cursor);
+        self.anchor = Some(0);
+        self.cursor = self.len();
+        before != (self.anchor, self.cursor)
+    }
+
+    /// Apply a key event.
+    ///
+    /// * Arrow keys, `Home` and `End` move the cursor
+    /// * Holding shift extends the selection
+    /// * Holding ctrl or alt moves and deletes by word
+    /// * `ctrl+a` selects everything and `ctrl+w` deletes the previous word
+    pub fn on_key(&mut self, key: &KeyEvent) -> InputEvent {
+        if let KeyState::Release = key.state {
+            return InputEvent::Ignored;
+        }
+
+        let select = key.shift();
+        let word = key.ctrl() || key.alt();
+
+        let changed = match key.code {
+            KeyCode::Enter => return InputEvent::Submit,
+            KeyCode::Char('a') if key.ctrl() => return moved(self.select_all()),
+            KeyCode::Char('w') if key.ctrl() => self.delete_word_back(),
+            KeyCode::Char(_) if word => return InputEvent::Ignored,
+            KeyCode::Char(c) => self.insert_char(c),
+            KeyCode::Backspace if word => self.delete_word_back(),
+            KeyCode::Backspace => self.delete_back(),
+            KeyCode::Delete if word => self.delete_word_forward(),
+            KeyCode::Delete => self.delete_forward(),
+            KeyCode::Left if word => return moved(self.word_left(select)),
+            KeyCode::Left => return moved(self.left(select)),
+            KeyCode::Right if word => return moved(self.word_right(select)),
+            KeyCode::Right => return moved(self.right(select)),
+            KeyCode::Home => return moved(self.home(select)),
+            KeyCode::End => return moved(self.end(select)),
+            _ => return InputEvent::Ignored,
+        };
+
+        match changed {
+            true => InputEvent::Changed,
+            false => InputEvent::Ignored,
+        }
+    }
+
+    fn move_to(&mut self, cursor: usize, select: bool) -> bool {
+        let before = (self.anchor, self.cursor);
+        self.anchor = match select {
+            true => self.anchor.or(Some(self.cursor)),
+            false => None,
+        };
+        self.cursor = cursor.min(self.len());
+        before != (self.anchor, self.cursor)
+    }
+
+    fn delete_range(&mut self, start: usize, end: usize) -> bool {
+        self.anchor = None;
+        if start == end {
+            return false;
+        }
+
+        let range = byte_index(&self.text, start)..byte_index(&self.text, end);
+        self.text.replace_range(range, "");
+        self.cursor = start;
+        true
+    }
+}
+
+pub(crate) fn moved(moved: bool) -> InputEvent {
+    match moved {
+        true => InputEvent::Moved,
+        false => InputEvent::Ignored,
+    }
+}
+
+// Paint selected text with the colours inverted
+struct Selected<'a>(&'a dyn CellAttributes);
+
+impl CellAttributes for Selected<'_> {
+    fn with_str(&self, key: &str, f: &mut dyn FnMut(&str)) {
+        self.0.with_str(key, f)
+    }
+
+    fn get_i64(&self, key: &str) -> Option<i64> {
+        self.0.get_i64(key)
+    }
+
+    fn get_u8(&self, key: &str) -> Option<u8> {
+        self.0.get_u8(key)
+    }
+
+    fn get_hex(&self, key: &str) -> Option<Hex> {
+        self.0.get_hex(key)
+    }
+
+    fn get_color(&self, key: &str) -> Option<Color> {
+        self.0.get_color(key)
+    }
+
+    fn get_bool(&self, key: &str) -> bool {
+        key == "inverse" || self.0.get_bool(key)
+    }
+}
+
+/// Input widget
+/// ```ignore
+/// Attributes:
+/// * cursor
+/// * selection_start
+/// * selection_end
+/// * focused
+/// * placeholder
+/// * password
+/// * mask
+/// * width
+/// ```
+///
+/// A single line of text that scrolls horizontally to keep the cursor visible.
+/// The terminal cursor is placed at the `cursor` position while `focused` is true.
+///
+/// The widget only displays the text, see [`TextInput`] for the component
+/// that handles the editing.
+#[derive(Debug, Default)]
+pub struct Input {
+    text: String,
+    // Index of the first visible char
+    offset: usize,
+}
+
+const ATTRIBUTES: &[Attribute] = &[
+    Attribute::new(WIDTH, AttributeType::Unsigned),
+    Attribute::new(CURSOR, AttributeType::Unsigned),
+    Attribute::new(SELECTION_START, AttributeType::Unsigned),
+    Attribute::new(SELECTION_END, AttributeType::Unsigned),
+    Attribute::new(FOCUSED, AttributeType::Bool),
+    Attribute::new(PLACEHOLDER, AttributeType::Str),
+    Attribute::new(PASSWORD, AttributeType::Bool),
+    Attribute::new(MASK, AttributeType::Str),
+];
+
+impl Widget for Input {
+    fn layout<'bp>(
+        &mut self,
+        _: LayoutChildren<'_, '_, 'bp>,
+        mut constraints: Constraints,
+        id: WidgetId,
+        ctx: &mut LayoutCtx<'_, 'bp>,
+    ) -> Size {
+        let attributes = ctx.attribs.get(id);
+
+        if let Some(width) = attributes.get_usize(WIDTH) {
+            constraints.make_width_tight(width);
+        }
+
+        self.text.clear();
+        attributes.value().map(|value| {
+            value.str_iter(|s| {
+                self.text.push_str(s);
+                ControlFlow::Continue(())
+            })
+        });
+
+        // Fill the available width, unless the width is unbounded
+        let width = match constraints.max_width() {
+            usize::MAX => {
+                let mut width = self.text.chars().map(|c| c.width().unwrap_or(0)).sum::<usize>();
+                attributes.with_str(PLACEHOLDER, &mut |s| {
+                    width = width.max(s.chars().map(|c| c.width().unwrap_or(0)).sum());
+                });
+                // Leave room for the cursor
+                width + 1
+            }
+            max => max,
+        };
+
+        Size::new(width.max(constraints.min_width), constraints.max_height().min(1))
+    }
+
+    fn position<'bp>(
+        &mut self,
+        _: PositionChildren<'_, '_, 'bp>,
+        _: WidgetId,
+        _: &AttributeStorage<'bp>,
+        _: PositionCtx,
+    ) {
+        // NOTE
+        // No positioning is done in here, it's all done when painting
+    }
+
+    fn paint<'bp>(
+        &mut self,
+        _: PaintChildren<'_, '_, 'bp>,
+        id: WidgetId,
+        attribute_storage: &AttributeStorage<'bp>,
+        mut ctx: PaintCtx<'_, SizePos>,
+    ) {
+        let attributes = attribute_storage.get(id);
+        let width = ctx.local_size.width;
+
+        // Style the entire input so the background covers the empty space
+        for x in 0..width {
+            ctx.set_attributes(attributes, LocalPos::new(x as u16, 0));
+        }
+
+        let focused = attributes.get_bool(FOCUSED);
+
+        if self.text.is_empty() {
+            self.offset = 0;
+            attributes.with_str(PLACEHOLDER, &mut |s| {
+                ctx.place_glyphs(s, LocalPos::ZERO);
+            });
+            if focused {
+                ctx.set_cursor(LocalPos::ZERO);
+            }
+            return;
+        }
+
+        let mask = match attributes.get_bool(PASSWORD) {
+            true => {
+                let mut mask = DEFAULT_MASK;
+                attributes.with_str(MASK, &mut |s| mask = s.chars().next().unwrap_or(DEFAULT_MASK));
+                Some(mask)
+            }
+            false => None,
+        };
+
+        let glyphs = self.text.chars().map(|c| mask.unwrap_or(c)).collect::<Vec<_>>();
+        let glyph_width = |glyphs: &[char]| glyphs.iter().map(|c| c.width().unwrap_or(0)).sum::<usize>();
+
+        let cursor = attributes.get_usize(CURSOR).unwrap_or(glyphs.len()).min(glyphs.len());
+        let selection = match (
+            attributes.get_usize(SELECTION_START),
+            attributes.get_usize(SELECTION_END),
+        ) {
+            (Some(start), Some(end)) => start.min(end)..start.max(end),
+            _ => 0..0,
+        };
+
+        // Scroll the text so the cursor, and the cell it occupies, is visible.
+        // If there is room to spare, scroll back to show as much text as possible.
+        self.offset = self.offset.min(cursor);
+        while self.offset < cursor && glyph_width(&glyphs[self.offset..cursor]) + 1 > width {
+            self.offset += 1;
+        }
+        while self.offset > 0 && glyph_width(&glyphs[self.offset - 1..]) < width {
+            self.offset -= 1;
+        }
+
+        let mut x = 0;
+        let mut cursor_x = None;
+        for (index, c) in glyphs.iter().enumerate().skip(self.offset) {
+            if index == cursor {
+                cursor_x = Some(x);
+            }
+
+            let glyph_width = c.width().unwrap_or(0);
+            if x + glyph_width > width {
+                break;
+            }
+
+            let pos = LocalPos::new(x as u16, 0);
+            ctx.place_glyph(*c, pos);
+            if selection.contains(&index) {
+                for x in x..x + glyph_width {
+                    ctx.set_attributes(&Selected(attributes), LocalPos::new(x as u16, 0));
+                }
+            }
+
+            x += glyph_width;
+        }
+
+        if focused {
+            let x = cursor_x.unwrap_or(x);
+            ctx.set_cursor(LocalPos::new(x as u16, 0));
+        }
+    }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(ATTRIBUTES)
+    }
+}
+
+/// The state of a [`TextInput`].
+#[derive(Debug, State)]
+pub struct TextInputState {
+    pub text: Value<String>,
+    pub cursor: Value<usize>,
+    pub selection_start: Value<usize>,
+    pub selection_end: Value<usize>,
+    pub focused: Value<bool>,
+}
+
+impl TextInputState {
+    pub fn new() -> Self {
+        Self {
+            text: String::new().into(),
+            cursor: 0.into(),
+            selection_start: 0.into(),
+            selection_end: 0.into(),
+            focused: false.into(),
+        }
+    }
+}
+
+impl Default for TextInputState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An editable text field.
+///
+/// The text input publishes `change` when the text changes
+/// and `submit` when enter is pressed.
+///
+/// The parent can set or reset the text with the external `value`.
+/// When the value changes the text is replaced and the cursor is moved to the end.
+///
+/// External state:
+/// * value
+/// * placeholder
+/// * password
+/// * mask
+/// * max_length
+/// * width
+///
+/// ```ignore
+/// builder.register_prototype("input", TextInput::TEMPLATE.to_template(), TextInput::new, TextInputState::new)?;
+/// ```
+///
+/// ```ignore
+/// @input (change->on_change, submit->on_submit) { "value": name, "placeholder": "Name", "max_length": 20 }
+/// ```
+#[derive(Debug, Default)]
+pub struct TextInput {
+    buffer: InputBuffer,
+}
+
+impl TextInput {
+    /// The template for the text input
+    pub const TEMPLATE: &'static str = "input [cursor: cursor, selection_start: selection_start, selection_end: selection_end, focused: focused, placeholder: placeholder, password: password, mask: mask, width: width, foreground: foreground, background: background] text";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn update_state(&self, state: &mut TextInputState) {
+        if *state.text.to_ref() != self.buffer.text() {
+            state.text.set(self.buffer.text().to_string());
+        }
+
+        let (start, end) = self.buffer.selection().unwrap_or((0, 0));
+        state.cursor.set(self.buffer.cursor());
+        state.selection_start.set(start);
+        state.selection_end.set(end);
+    }
+
+    // The max length is external state, so it's read before every edit
+    fn read_max_length(&mut self, context: &Context<'_, TextInput>) {
+        let max_length = context
+            .get_external(MAX_LENGTH)
+            .and_then(|val| val.load_number())
+            .map(|n| n.as_uint());
+        self.buffer.set_max_length(max_length);
+    }
+
+    fn apply(&self, event: InputEvent, state: &mut TextInputState, context: &mut Context<'_, TextInput>) {
+        match event {
+            InputEvent::Changed => {
+                self.update_state(state);
+                context.publish("change", |state| &state.text);
+            }
+            InputEvent::Moved => self.update_state(state),
+            InputEvent::Submit => context.publish("submit", |state| &state.text),
+            InputEvent::Ignored => (),
+        }
+    }
+}
+
+impl Component for TextInput {
+    type Message = ();
+    type State = TextInputState;
+
+    fn on_focus(&mut self, state: &mut Self::State, _: Elements<'_, '_>, _: Context<'_, Self>) {
+        state.focused.set(true);
+    }
+
+    fn on_blur(&mut self, state: &mut Self::State, _: Elements<'_, '_>, _: Context<'_, Self>) {
+        state.focused.set(false);
+    }
+
+    fn on_key(&mut self, key: KeyEvent, state: &mut Self::State, _: Elements<'_, '_>, mut context: Context<'_, Self>) {
+        self.read_max_length(&context);
+        let event = self.buffer.on_key(&key);
+        self.apply(event, state, &mut context);
+    }
+
+    fn on_paste(&mut self, text: &str, state: &mut Self::State, _: Elements<'_, '_>, mut context: Context<'_, Self>) {
+        self.read_max_length(&context);
+        let event = match self.buffer.insert_str(text) {
+            true => InputEvent::Changed,
+            false => InputEvent::Ignored,
+        };
+        self.apply(event, state, &mut context);
+    }
+
+    fn on_external_change(
+        &mut self,
+        key: &str,
+        state: &mut Self::State,
+        _: Elements<'_, '_>,
+        context: Context<'_, Self>,
+    ) {
+        if key != VALUE {
+            return;
+        }
+
+        let Some(value) = context.get_external(VALUE) else { return };
+        let Some(value) = value.to_common().map(|value| value.to_string()) else { return };
+
+        // The parent echoing back a published change is not a new value
+        if value != self.buffer.text() {
+            self.buffer.set_text(value);
+            self.update_state(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anathema_widgets::components::events::Modifiers;
+
+    use super::*;
+    use crate::testing::TestRunner;
+
+    fn key(code: impl Into<KeyCode>, modifiers: Modifiers) -> KeyEvent {
+        KeyEvent {
+            code: code.into(),
+            modifiers,
+            state: KeyState::Press,
+        }
+    }
+
+    fn buffer(text: &str) -> InputBuffer {
+        let mut buffer = InputBuffer::new();
+        buffer.set_text(text);
+        buffer
+    }
+
+    #[test]
+    fn insert_and_delete() {
+        let mut buffer = buffer("");
+        buffer.insert_str("hllo");
+        buffer.home(false);
+        buffer.right(false);
+        buffer.insert_char('e');
+        assert_eq!(buffer.text(), "hello");
+        assert_eq!(buffer.cursor(), 2);
+
+        buffer.delete_back();
+        buffer.delete_forward();
+        assert_eq!(buffer.text(), "hlo");
+        assert_eq!(buffer.cursor(), 1);
+
+        buffer.home(false);
+        assert!(!buffer.delete_back());
+        buffer.end(false);
+        assert!(!buffer.delete_forward());
+    }
+
+    #[test]
+    fn multi_byte_chars() {
+        let mut buffer = buffer("åäö");
+        buffer.left(false);
+        buffer.insert_char('x');
+        buffer.delete_back();
+        buffer.delete_back();
+        assert_eq!(buffer.text(), "åö");
+        assert_eq!(buffer.cursor(), 1);
+    }
+
+    #[test]
+    fn word_jumps() {
+        let mut buffer = buffer("one two  three");
+        buffer.word_left(false);
+        assert_eq!(buffer.cursor(), 9);
+        buffer.word_left(false);
+        assert_eq!(buffer.cursor(), 4);
+        buffer.word_right(false);
+        assert_eq!(buffer.cursor(), 7);
+        buffer.word_right(false);
+        assert_eq!(buffer.cursor(), 14);
+
+        buffer.delete_word_back();
+        assert_eq!(buffer.text(), "one two  ");
+        buffer.home(false);
+        buffer.delete_word_forward();
+        assert_eq!(buffer.text(), " two  ");
+    }
+
+    #[test]
+    fn selection() {
+        let mut buffer = buffer("hello world");
+        buffer.word_left(true);
+        assert_eq!(buffer.selection(), Some((6, 11)));
+
+        // Moving without shift collapses the selection
+        buffer.right(false);
+        assert_eq!(buffer.selection(), None);
+        assert_eq!(buffer.cursor(), 11);
+
+        // Typing replaces the selection
+        buffer.home(true);
+        buffer.insert_str("bye");
+        assert_eq!(buffer.text(), "bye");
+
+        buffer.select_all();
+        buffer.delete_back();
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn max_length() {
+        let mut buffer = buffer("");
+        buffer.set_max_length(Some(3));
+        buffer.insert_str("abcdef");
+        assert_eq!(buffer.text(), "abc");
+        assert!(!buffer.insert_char('d'));
+
+        // Replacing a selection makes room for new chars
+        buffer.left(true);
+        assert!(buffer.insert_char('d'));
+        assert_eq!(buffer.text(), "abd");
+    }
+
+    #[test]
+    fn key_events() {
+        let mut buffer = buffer("");
+        assert_eq!(buffer.on_key(&key('a', Modifiers::empty())), InputEvent::Changed);
+        assert_eq!(buffer.on_key(&key('B', Modifiers::SHIFT)), InputEvent::Changed);
+        assert_eq!(buffer.on_key(&key('x', Modifiers::CTRL)), InputEvent::Ignored);
+        assert_eq!(buffer.on_key(&key(KeyCode::Left, Modifiers::SHIFT)), InputEvent::Moved);
+        assert_eq!(buffer.selection(), Some((1, 2)));
+        assert_eq!(
+            buffer.on_key(&key(KeyCode::Backspace, Modifiers::empty())),
+            InputEvent::Changed
+        );
+        assert_eq!(buffer.text(), "a");
+        assert_eq!(
+            buffer.on_key(&key(KeyCode::Right, Modifiers::empty())),
+            InputEvent::Ignored
+        );
+        assert_eq!(
+            buffer.on_key(&key(KeyCode::Enter, Modifiers::empty())),
+            InputEvent::Submit
+        );
+
+        let mut release = key('c', Modifiers::empty());
+        release.state = KeyState::Release;
+        assert_eq!(buffer.on_key(&release), InputEvent::Ignored);
+    }
+
+    #[test]
+    fn render_input() {
+        let src = "input [cursor: 2, focused: true] 'hello'";
+        let expected = "
+            ╔══════════╗
+            ║hello     ║
+            ╚══════════╝
+        ";
+
+        let mut runner = TestRunner::new(src, (10, 1));
+        let mut instance = runner.instance();
+        instance.render_assert(expected).cursor_assert(Some((3, 1)));
+    }
+
+    #[test]
+    fn render_placeholder() {
+        let src = "input [placeholder: 'name', focused: true] ''";
+        let expected = "
+            ╔══════════╗
+            ║name      ║
+            ╚══════════╝
+        ";
+
+        let mut runner = TestRunner::new(src, (10, 1));
+        let mut instance = runner.instance();
+        instance.render_assert(expected).cursor_assert(Some((1, 1)));
+    }
+
+    #[test]
+    fn text_input_template() {
+        let src = "@input { placeholder: 'name', width: 6 }";
+        let expected = "
+            ╔══════════╗
+            ║name      ║
+            ╚══════════╝
+        ";
+
+        TestRunner::with_components(src, &[("input", TextInput::TEMPLATE)], (10, 1))
+            .instance()
+            .render_assert(expected);
+    }
+
+    #[test]
+    fn render_selection() {
+        let src = "input [selection_start: 1, selection_end: 4, foreground: 'green'] 'hello'";
+        let expected = "
+            ╔══════════╗
+            ║hello     ║
+              [1..2] fg:Green
+              [2..5] fg:Green inverse
+              [5..11] fg:Green
+            ╚══════════╝
+        ";
+
+        let mut runner = TestRunner::new(src, (10, 1));
+        runner.instance().style_assert(expected);
+    }
+
+    #[test]
+    fn render_password() {
+        let src = "input [password: true] 'secret'";
+        let expected = "
+            ╔══════════╗
+            ║******    ║
+            ╚══════════╝
+        ";
+
+        let mut runner = TestRunner::new(src, (10, 1));
+        let mut instance = runner.instance();
+        instance.render_assert(expected).cursor_assert(None);
+    }
+
+    #[test]
+    fn scroll_to_cursor() {
+        let src = "input [focused: true] 'hello world'";
+        let expected = "
+            ╔══════╗
+            ║world ║
+            ╚══════╝
+        ";
+
+        let mut runner = TestRunner::new(src, (6, 1));
+        let mut instance = runner.instance();
+        instance.render_assert(expected).cursor_assert(Some((6, 1)));
+    }
+
+    #[test]
+    fn scroll_back_to_cursor() {
+        let src = "input [focused: true, cursor: value] 'hello world'";
+        let expected = "
+            ╔══════╗
+            ║lo wor║
+            ╚══════╝
+        ";
+
+        let mut runner = TestRunner::new(src, (6, 1));
+        let mut instance = runner.instance();
+        instance.with_state(|state| state.value.set(11));
+        instance.render_assert(
+            "
+            ╔══════╗
+            ║world ║
+            ╚══════╝
+        ",
+        );
+        instance.with_state(|state| state.value.set(3));
+        instance.render_assert(expected).cursor_assert(Some((1, 1)));
+    }
+}
@@ -1,6 +1,18 @@
 use anathema::CommonVal;
 
 pub const ALIGNMENT: &str = "alignment";
+pub(crate) const ALIGNMENTS: &[&str] = &[
+    "top_left",
+    "top",
+    "top_right",
+    "right",
+    "bottom_right",
+    "bottom",
+    "bottom_left",
+    "left",
+    "centre",
+    "center",
+];
 
 /// Word wrapping strategy
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
@@ -7,6 +7,7 @@ use anathema_widgets::LayoutChildren;
 use super::Axis;
 
 const DEFAULT_FACTOR: usize = 1;
+pub(crate) const FACTOR: &str = "factor";
 
 /// Distributes the total size over a list of weights
 ///
@@ -55,7 +56,7 @@ pub fn layout_all_expansions<'bp>(
     nodes.for_each(|node, _children| {
         if node.ident == "expand" {
             let attributes = ctx.attribs.get(node.id());
-            let factor = attributes.get("factor").unwrap_or(DEFAULT_FACTOR);
+            let factor = attributes.get(FACTOR).unwrap_or(DEFAULT_FACTOR);
             factors.push(factor);
         }
 
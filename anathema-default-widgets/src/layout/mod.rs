@@ -7,6 +7,8 @@ use anathema_widgets::LayoutChildren;
 
 pub static DIRECTION: &str = "direction";
 pub static AXIS: &str = "axis";
+pub(crate) const DIRECTIONS: &[&str] = &["fwd", "forward", "forwards", "back", "backward", "backwards"];
+pub(crate) const AXES: &[&str] = &["horz", "horizontal", "vert", "vertical"];
 
 pub(crate) mod alignment;
 pub(crate) mod border;
@@ -6,7 +6,9 @@ mod alignment;
 mod border;
 mod canvas;
 mod container;
+mod editing;
 mod expand;
+mod input;
 mod layout;
 mod overflow;
 mod padding;
@@ -14,6 +16,7 @@ mod position;
 mod spacer;
 mod stacks;
 mod text;
+mod textarea;
 
 #[cfg(test)]
 mod testing;
@@ -33,17 +36,20 @@ pub use alignment::Align;
 pub use border::Border;
 pub use canvas::Canvas;
 pub use expand::Expand;
+pub use input::{Input, InputBuffer, InputEvent, TextInput, TextInputState};
 pub use overflow::Overflow;
 pub use padding::Padding;
 pub use position::Position;
 pub use stacks::{Column, HStack, Row, VStack};
 pub use text::Text;
+pub use textarea::{EditorBuffer, TextArea, TextEditor, TextEditorState};
 
 pub fn register_default_widgets(factory: &mut Factory) {
     factory.register_default::<alignment::Align>("align");
     factory.register_default::<expand::Expand>("expand");
     factory.register_default::<canvas::Canvas>("canvas");
     factory.register_default::<container::Container>("container");
+    factory.register_default::<input::Input>("input");
     factory.register_default::<padding::Padding>("padding");
     factory.register_default::<position::Position>("position");
     factory.register_default::<stacks::Column>("column");
@@ -54,6 +60,151 @@ pub fn register_default_widgets(factory: &mut Factory) {
     factory.register_default::<stacks::ZStack>("zstack");
     factory.register_default::<text::Span>("span");
     factory.register_default::<text::Text>("text");
+    factory.register_default::<textarea::TextArea>("textarea");
     factory.register_default::<overflow::Overflow>("overflow");
     factory.register_widget("border", border::make);
+    // The border is made from its attributes rather than `Default`, so its attributes are registered separately
+    factory.register_attributes("border", border::ATTRIBUTES);
+
+    register_default_docs(factory);
+}
+
+// Documentation shown by editors, see `anathema-lsp`
+fn register_default_docs(factory: &mut Factory) {
+    let widgets: [(&str, &'static str); 18] = [
+        (
+            "align",
+            "Align the child inside the available space.\n\n\
+             `alignment`: `top_left`, `top`, `top_right`, `left`, `centre`, `right`, \
+             `bottom_left`, `bottom` or `bottom_right`",
+        ),
+        (
+            "border",
+            "Draw a border around the child.\n\n\
+             Without a child the border needs a `width` and a `height` to be drawn.\n\n\
+             `border_style`: `thin`, `thick` or a string of eight characters\n\n\
+             `sides`: `all`, `top`, `right`, `bottom`, `left` or a list of sides",
+        ),
+        (
+            "canvas",
+            "An area that is drawn on from Rust, one character at a time.\n\n\
+             The canvas uses all the available space, unless it has a `width` and / or a `height`.",
+        ),
+        (
+            "column",
+            "Lay out children vertically, centred horizontally.\n\n\
+             `direction`: `forward` or `backward`",
+        ),
+        ("container", "A single child with a fixed, minimum or maximum size."),
+        (
+            "expand",
+            "Fill the available space along the `axis` (`horizontal` or `vertical`), or both.\n\n\
+             Inside a stack the remaining space is shared between expands based on their `factor`.",
+        ),
+        (
+            "hstack",
+            "Lay out children horizontally.\n\n\
+             `direction`: `forward` or `backward`",
+        ),
+        (
+            "input",
+            "A single line of text input.\n\n\
+             The text, cursor and selection are managed from Rust, \
+             the `placeholder` is shown while the input is empty.",
+        ),
+        (
+            "overflow",
+            "Lay out children along the `axis` without constraining their size, \
+             so they can extend beyond the widget.\n\n\
+             The children can be scrolled from Rust.",
+        ),
+        (
+            "padding",
+            "Add padding around the child.\n\n\
+             `padding` sets all sides, `top`, `right`, `bottom` and `left` set a single side.",
+        ),
+        (
+            "position",
+            "Position the child at an offset from the `top` / `bottom` and `left` / `right` edge.\n\n\
+             `placement`: `relative` (to the parent, default) or `absolute` (to the screen)",
+        ),
+        (
+            "row",
+            "Lay out children horizontally, centred vertically.\n\n\
+             `direction`: `forward` or `backward`",
+        ),
+        ("span", "A piece of text inside a `text` widget, with its own style."),
+        ("spacer", "Take up the remaining space inside a stack."),
+        (
+            "text",
+            "Display text.\n\n\
+             The text can be made up of multiple values and `span` children.\n\n\
+             `wrap`: `normal` (wrap on words) or `break` (wrap anywhere)\n\n\
+             `text_align`: `left`, `centre` or `right`",
+        ),
+        (
+            "textarea",
+            "Multiple lines of text input.\n\n\
+             The text, cursor and selection are managed from Rust, \
+             the `placeholder` is shown while the text area is empty.",
+        ),
+        (
+            "vstack",
+            "Lay out children vertically.\n\n\
+             `direction`: `forward` or `backward`",
+        ),
+        (
+            "zstack",
+            "Lay out children on top of each other, the last child on top.",
+        ),
+    ];
+
+    for (ident, docs) in widgets {
+        factory.register_docs(ident, docs);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anathema_templates::diagnostics::{AttributeType, DiagnosticKind};
+    use anathema_templates::Document;
+
+    use super::*;
+
+    #[test]
+    fn check_default_attributes() {
+        let src = "
+            border [border_style: 'thick', width: 10, foreground: 'red']
+                vstack [direction: 'backward']
+                    text [wrap: 'word', text_align: 'centre', bold: true] 'hello'
+                    text [foregound: 'red'] 'typo'
+                    textbox
+        ";
+
+        let mut factory = Factory::new();
+        register_default_widgets(&mut factory);
+
+        let mut doc = Document::new(src);
+        let diagnostics = doc
+            .check(&factory)
+            .unwrap()
+            .into_iter()
+            .map(|d| d.kind)
+            .collect::<Vec<_>>();
+
+        let expected = vec![
+            DiagnosticKind::InvalidAttributeValue {
+                widget: "text".into(),
+                attribute: "wrap".into(),
+                expected: AttributeType::OneOf(text::WRAPS),
+            },
+            DiagnosticKind::UnknownAttribute {
+                widget: "text".into(),
+                attribute: "foregound".into(),
+                suggestion: Some("foreground"),
+            },
+            DiagnosticKind::UnknownWidget("textbox".into()),
+        ];
+        assert_eq!(diagnostics, expected);
+    }
 }
@@ -1,16 +1,17 @@
 use std::ops::ControlFlow;
 
 use anathema_geometry::{Pos, Size};
+use anathema_templates::diagnostics::{Attribute, AttributeType};
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::paint::{PaintCtx, SizePos};
 use anathema_widgets::{AttributeStorage, LayoutChildren, PositionChildren, Widget, WidgetId};
 
 use crate::layout::many::Many;
-use crate::layout::{Axis, Direction, AXIS, DIRECTION};
+use crate::layout::{Axis, Direction, AXES, AXIS, DIRECTION, DIRECTIONS};
 use crate::{HEIGHT, WIDTH};
 
-const UNCONSTRAINED: &str = "unconstrained";
-const CLAMP: &str = "clamp";
+pub(crate) const UNCONSTRAINED: &str = "unconstrained";
+pub(crate) const CLAMP: &str = "clamp";
 
 #[derive(Debug, Default)]
 pub struct Overflow {
@@ -103,6 +104,15 @@ impl Overflow {
     }
 }
 
+const ATTRIBUTES: &[Attribute] = &[
+    Attribute::new(WIDTH, AttributeType::Unsigned),
+    Attribute::new(HEIGHT, AttributeType::Unsigned),
+    Attribute::new(AXIS, AttributeType::OneOf(AXES)),
+    Attribute::new(DIRECTION, AttributeType::OneOf(DIRECTIONS)),
+    Attribute::new(UNCONSTRAINED, AttributeType::Bool),
+    Attribute::new(CLAMP, AttributeType::Bool),
+];
+
 impl Widget for Overflow {
     fn layout<'bp>(
         &mut self,
@@ -218,6 +228,10 @@ impl Widget for Overflow {
     fn needs_reflow(&self) -> bool {
         self.is_dirty
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(ATTRIBUTES)
+    }
 }
 
 #[cfg(test)]
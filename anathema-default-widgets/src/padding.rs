@@ -1,12 +1,13 @@
 use std::ops::ControlFlow;
 
 use anathema_geometry::{Pos, Rect, Size};
+use anathema_templates::diagnostics::{Attribute, AttributeType};
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::{AttributeStorage, LayoutChildren, PositionChildren, Widget, WidgetId};
 
 use crate::{BOTTOM, LEFT, RIGHT, TOP};
 
-const PADDING: &str = "padding";
+pub(crate) const PADDING: &str = "padding";
 
 #[derive(Default)]
 struct PaddingValues {
@@ -28,6 +29,14 @@ impl PaddingValues {
 #[derive(Default)]
 pub struct Padding(PaddingValues);
 
+const ATTRIBUTES: &[Attribute] = &[
+    Attribute::new(PADDING, AttributeType::Unsigned),
+    Attribute::new(TOP, AttributeType::Unsigned),
+    Attribute::new(RIGHT, AttributeType::Unsigned),
+    Attribute::new(BOTTOM, AttributeType::Unsigned),
+    Attribute::new(LEFT, AttributeType::Unsigned),
+];
+
 impl Widget for Padding {
     fn layout<'bp>(
         &mut self,
@@ -120,6 +129,10 @@ impl Widget for Padding {
         size.height = size.height.saturating_sub(self.0.bottom as usize);
         Rect::from((pos, size))
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(ATTRIBUTES)
+    }
 }
 
 #[cfg(test)]
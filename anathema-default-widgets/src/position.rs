@@ -2,6 +2,7 @@ use std::ops::ControlFlow;
 
 use anathema::CommonVal;
 use anathema_geometry::{Pos, Size};
+use anathema_templates::diagnostics::{Attribute, AttributeType};
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::paint::{PaintCtx, SizePos};
 use anathema_widgets::{AttributeStorage, LayoutChildren, PaintChildren, PositionChildren, Widget, WidgetId};
@@ -10,7 +11,7 @@ use crate::{BOTTOM, LEFT, RIGHT, TOP};
 
 const RELATIVE: &str = "relative";
 const ABSOLUTE: &str = "absolute";
-const PLACEMENT: &str = "placement";
+pub(crate) const PLACEMENT: &str = "placement";
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum HorzEdge {
@@ -65,6 +66,14 @@ impl Default for Position {
     }
 }
 
+const ATTRIBUTES: &[Attribute] = &[
+    Attribute::new(PLACEMENT, AttributeType::OneOf(&[RELATIVE, ABSOLUTE])),
+    Attribute::new(TOP, AttributeType::Number),
+    Attribute::new(RIGHT, AttributeType::Number),
+    Attribute::new(BOTTOM, AttributeType::Number),
+    Attribute::new(LEFT, AttributeType::Number),
+];
+
 impl Widget for Position {
     fn floats(&self) -> bool {
         true
@@ -183,6 +192,10 @@ impl Widget for Position {
             ControlFlow::Continue(())
         });
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(ATTRIBUTES)
+    }
 }
 
 #[cfg(test)]
@@ -307,4 +320,55 @@ mod test {
 
         TestRunner::new(tpl, (4, 2)).instance().render_assert(expected);
     }
+
+    #[test]
+    fn mouse_over_floating_component() {
+        let tpl = "
+            vstack
+                @list
+                position [top: 0, left: 0]
+                    @popup
+            ";
+
+        let list = "
+            vstack
+                text 'abcd'
+                text 'efgh'
+            ";
+
+        let popup = "text 'x'";
+
+        let mut runner = TestRunner::with_components(tpl, &[("list", list), ("popup", popup)], (4, 2));
+        let mut instance = runner.instance();
+
+        // The popup floats on top of the list
+        assert_eq!(instance.component_at((1, 1)), Some(2));
+        assert_eq!(instance.component_at((2, 1)), Some(1));
+        assert_eq!(instance.component_at((1, 2)), Some(1));
+
+        // The border is outside of any component
+        assert_eq!(instance.component_at((0, 0)), None);
+    }
+
+    #[test]
+    fn mouse_over_overlapping_floating_components() {
+        let tpl = "
+            vstack
+                @list
+                position [top: 0, left: 0]
+                    @first
+                position [top: 0, left: 1]
+                    @second
+            ";
+
+        let components = [("list", "text 'abcd'"), ("first", "text 'xx'"), ("second", "text 'yy'")];
+        let mut runner = TestRunner::with_components(tpl, &components, (4, 1));
+        let mut instance = runner.instance();
+
+        // The second popup is painted last and covers the first one
+        assert_eq!(instance.component_at((1, 1)), Some(2));
+        assert_eq!(instance.component_at((2, 1)), Some(3));
+        assert_eq!(instance.component_at((3, 1)), Some(3));
+        assert_eq!(instance.component_at((4, 1)), Some(1));
+    }
 }
@@ -1,4 +1,5 @@
 use anathema_geometry::Size;
+use anathema_templates::diagnostics::Attribute;
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::paint::{PaintCtx, SizePos};
 use anathema_widgets::{AttributeStorage, LayoutChildren, PaintChildren, PositionChildren, Widget, WidgetId};
@@ -36,4 +37,8 @@ impl Widget for Spacer {
     ) {
         // The spacer widget has no children
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(&[])
+    }
 }
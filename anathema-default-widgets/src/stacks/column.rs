@@ -1,6 +1,7 @@
 use std::ops::ControlFlow;
 
 use anathema_geometry::Size;
+use anathema_templates::diagnostics::Attribute;
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::{AttributeStorage, LayoutChildren, PositionChildren, Widget, WidgetId};
 
@@ -48,6 +49,10 @@ impl Widget for Column {
             ControlFlow::Continue(())
         });
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(super::ATTRIBUTES)
+    }
 }
 
 #[cfg(test)]
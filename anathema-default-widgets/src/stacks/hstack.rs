@@ -1,4 +1,5 @@
 use anathema_geometry::Size;
+use anathema_templates::diagnostics::Attribute;
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::{AttributeStorage, LayoutChildren, PositionChildren, Widget, WidgetId};
 
@@ -33,4 +34,8 @@ impl Widget for HStack {
     ) {
         self.0.position(children, attributes, attribute_storage, ctx)
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(super::ATTRIBUTES)
+    }
 }
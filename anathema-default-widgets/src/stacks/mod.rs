@@ -1,6 +1,7 @@
 use std::ops::ControlFlow;
 
 use anathema_geometry::Size;
+use anathema_templates::diagnostics::{Attribute, AttributeType};
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::{AttributeStorage, LayoutChildren, PositionChildren, WidgetId};
 
@@ -10,7 +11,7 @@ pub use self::row::Row;
 pub use self::vstack::VStack;
 pub use self::zstack::ZStack;
 use crate::layout::many::Many;
-use crate::layout::{Axis, Direction, DIRECTION};
+use crate::layout::{Axis, Direction, DIRECTION, DIRECTIONS};
 use crate::{HEIGHT, MIN_HEIGHT, MIN_WIDTH, WIDTH};
 
 mod column;
@@ -19,6 +20,15 @@ mod row;
 mod vstack;
 mod zstack;
 
+// The attributes of every stack, except the `zstack`
+const ATTRIBUTES: &[Attribute] = &[
+    Attribute::new(WIDTH, AttributeType::Unsigned),
+    Attribute::new(HEIGHT, AttributeType::Unsigned),
+    Attribute::new(MIN_WIDTH, AttributeType::Unsigned),
+    Attribute::new(MIN_HEIGHT, AttributeType::Unsigned),
+    Attribute::new(DIRECTION, AttributeType::OneOf(DIRECTIONS)),
+];
+
 pub struct Stack(Axis);
 
 impl Stack {
@@ -1,4 +1,5 @@
 use anathema_geometry::Size;
+use anathema_templates::diagnostics::Attribute;
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::{AttributeStorage, LayoutChildren, PositionChildren, Widget, WidgetId};
 
@@ -33,6 +34,10 @@ impl Widget for VStack {
     ) {
         self.0.position(children, attributes, attribute_storage, ctx)
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(super::ATTRIBUTES)
+    }
 }
 
 #[cfg(test)]
@@ -80,6 +85,46 @@ mod test {
             .render_assert(expected_second);
     }
 
+    #[test]
+    fn switch_case() {
+        let tpl = "
+            vstack
+                switch value
+                    case 0
+                        text \"zero\"
+                    case 1
+                        text \"one \"
+                    default
+                        text \"many\"
+        ";
+
+        let expected_zero = "
+            ╔════╗
+            ║zero║
+            ╚════╝
+        ";
+
+        let expected_one = "
+            ╔════╗
+            ║one ║
+            ╚════╝
+        ";
+
+        let expected_many = "
+            ╔════╗
+            ║many║
+            ╚════╝
+        ";
+
+        TestRunner::new(tpl, (4, 1))
+            .instance()
+            .render_assert(expected_zero)
+            .with_state(|state| *state.value.to_mut() = 1)
+            .render_assert(expected_one)
+            .with_state(|state| *state.value.to_mut() = 5)
+            .render_assert(expected_many);
+    }
+
     #[test]
     fn fixed_height() {
         let tpl = "
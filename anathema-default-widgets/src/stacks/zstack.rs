@@ -1,6 +1,7 @@
 use std::ops::ControlFlow;
 
 use anathema_geometry::Size;
+use anathema_templates::diagnostics::Attribute;
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::{AttributeStorage, LayoutChildren, PositionChildren, Widget, WidgetId};
 
@@ -37,6 +38,10 @@ impl Widget for ZStack {
             ControlFlow::Continue(())
         });
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(&[])
+    }
 }
 
 #[cfg(test)]
@@ -61,4 +66,20 @@ mod test {
 
         TestRunner::new(tpl, (3, 1)).instance().render_assert(expected);
     }
+
+    #[test]
+    fn mouse_over_topmost_component() {
+        let tpl = "
+            zstack
+                @back
+                @front
+        ";
+
+        let mut runner = TestRunner::with_components(tpl, &[("back", "text '333'"), ("front", "text '1'")], (3, 1));
+        let mut instance = runner.instance();
+
+        assert_eq!(instance.component_at((1, 1)), Some(2));
+        assert_eq!(instance.component_at((2, 1)), Some(1));
+        assert_eq!(instance.component_at((3, 1)), Some(1));
+    }
 }
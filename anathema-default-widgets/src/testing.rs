@@ -1,15 +1,15 @@
 use anathema::{drain_changes, Changes};
-use anathema_backend::test::TestBackend;
+use anathema_backend::test::{Frame, TestBackend};
 use anathema_backend::{Backend, WidgetCycle};
-use anathema_geometry::Size;
+use anathema_geometry::{Pos, Size};
 use anathema_state::{State, StateId, States, Value};
 use anathema_templates::blueprints::Blueprint;
 use anathema_templates::{Document, Globals, ToSourceKind};
 use anathema_widgets::components::ComponentRegistry;
 use anathema_widgets::layout::{Constraints, Viewport};
 use anathema_widgets::{
-    eval_blueprint, update_tree, AttributeStorage, Components, DirtyWidgets, Elements, EvalContext, Factory,
-    FloatingWidgets, Scope, WidgetRenderer as _, WidgetTree,
+    component_at, eval_blueprint, update_tree, AttributeStorage, Components, DirtyWidgets, Elements, EvalContext,
+    Factory, FloatingWidgets, Scope, WidgetKind, WidgetRenderer as _, WidgetTree,
 };
 
 use crate::register_default_widgets;
@@ -26,6 +26,13 @@ pub struct TestRunner {
 
 impl TestRunner {
     pub fn new(src: &str, size: impl Into<Size>) -> Self {
+        Self::with_components(src, &[], size)
+    }
+
+    /// Create a test runner with additional components.
+    /// The main component has the component id zero, and the
+    /// additional components are numbered in order, starting at one.
+    pub fn with_components(src: &str, components: &[(&str, &str)], size: impl Into<Size>) -> Self {
         let mut factory = Factory::new();
         register_default_widgets(&mut factory);
 
@@ -48,6 +55,11 @@ impl TestRunner {
         let main = doc.add_component("main", src.to_template()).unwrap();
         component_registry.add_component(main.into(), (), ());
 
+        for (name, src) in components {
+            let id = doc.add_component(*name, src.to_template()).unwrap();
+            component_registry.add_component(id.into(), (), ());
+        }
+
         let (blueprint, globals) = doc.compile().unwrap();
 
         Self {
@@ -148,9 +160,7 @@ impl TestInstance<'_> {
         self
     }
 
-    pub fn render_assert(&mut self, expected: &str) -> &mut Self {
-        let expected = expected.trim().lines().map(str::trim).collect::<Vec<_>>().join("\n");
-
+    fn cycle(&mut self) {
         let (width, height) = self.backend.surface.size().into();
         let constraints = Constraints::new(width as usize, height as usize);
 
@@ -165,7 +175,12 @@ impl TestInstance<'_> {
             self.viewport,
         )
         .run();
+    }
 
+    pub fn render_assert(&mut self, expected: &str) -> &mut Self {
+        let expected = expected.trim().lines().map(str::trim).collect::<Vec<_>>().join("\n");
+
+        self.cycle();
         self.backend.render();
 
         let actual = std::mem::take(&mut self.backend.output);
@@ -177,6 +192,64 @@ impl TestInstance<'_> {
         self
     }
 
+    /// Assert the glyphs and the style runs of every row, see [`Frame::styled`].
+    /// The columns of the style runs include the border injected by the test runner.
+    pub(crate) fn style_assert(&mut self, expected: &str) -> &mut Self {
+        let expected = expected.trim().lines().map(str::trim).collect::<Vec<_>>().join("\n");
+
+        let actual = self.with_frame(|frame| frame.styled().to_string());
+        let actual = actual.trim().lines().map(str::trim).collect::<Vec<_>>().join("\n");
+
+        eprintln!("{actual}");
+
+        assert_eq!(actual, expected);
+        self
+    }
+
+    /// Paint the widgets and query the glyphs and styles of the frame.
+    /// Positions include the border injected by the test runner.
+    pub(crate) fn with_frame<F, T>(&mut self, f: F) -> T
+    where
+        F: FnOnce(&Frame) -> T,
+    {
+        self.backend.clear();
+        self.cycle();
+
+        let output = f(self.backend.surface.frame());
+        self.backend.clear();
+        output
+    }
+
+    /// Assert the position of the terminal cursor set while painting.
+    /// The position includes the border injected by the test runner.
+    pub(crate) fn cursor_assert(&mut self, expected: Option<(i32, i32)>) -> &mut Self {
+        self.backend.clear();
+        self.cycle();
+
+        let actual = self.backend.surface.cursor();
+        assert_eq!(actual, expected.map(Pos::from));
+        self
+    }
+
+    /// The id of the topmost component under the given position, if any.
+    /// The position includes the border injected by the test runner.
+    pub(crate) fn component_at(&mut self, pos: impl Into<Pos>) -> Option<usize> {
+        self.cycle();
+        self.backend.clear();
+
+        let widget_id = component_at(
+            pos.into(),
+            &mut self.tree,
+            &self.attribute_storage,
+            &self.floating_widgets,
+        )?;
+
+        match self.tree.get_ref_by_id(widget_id)? {
+            WidgetKind::Component(component) => Some(component.component_id.into()),
+            _ => None,
+        }
+    }
+
     pub(crate) fn with_widget<F>(&mut self, mut f: F) -> &mut Self
     where
         F: FnMut(Elements<'_, '_>),
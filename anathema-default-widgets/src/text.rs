@@ -2,6 +2,7 @@ use std::ops::ControlFlow;
 
 use anathema_geometry::{LocalPos, Size};
 use anathema_state::CommonVal;
+use anathema_templates::diagnostics::{Attribute, AttributeType};
 use anathema_widgets::layout::text::{ProcessResult, Segment, Strings};
 use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
 use anathema_widgets::paint::{PaintCtx, SizePos};
@@ -11,6 +12,8 @@ use crate::{LEFT, RIGHT};
 
 pub(crate) const WRAP: &str = "wrap";
 pub(crate) const TEXT_ALIGN: &str = "text_align";
+// The values of `wrap`, see `Wrap`
+pub(crate) const WRAPS: &[&str] = &["normal", "break"];
 
 /// Text alignment aligns the text inside its parent.
 ///
@@ -71,6 +74,11 @@ pub struct Text {
     strings: Strings,
 }
 
+const ATTRIBUTES: &[Attribute] = &[
+    Attribute::new(WRAP, AttributeType::OneOf(WRAPS)),
+    Attribute::new(TEXT_ALIGN, AttributeType::OneOf(&[LEFT, RIGHT, "centre", "center"])),
+];
+
 impl Widget for Text {
     fn layout<'bp>(
         &mut self,
@@ -170,6 +178,10 @@ impl Widget for Text {
         // NOTE
         // No positioning is done in here, it's all done when painting
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(ATTRIBUTES)
+    }
 }
 
 #[derive(Default, Copy, Clone)]
@@ -197,12 +209,57 @@ impl Widget for Span {
         // Everything is handled by the parent text
         panic!("this should never be called");
     }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(&[])
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use anathema_backend::tui::Style;
+    use anathema_geometry::Pos;
+    use anathema_state::Color;
+
     use crate::testing::TestRunner;
 
+    #[test]
+    fn span_styles() {
+        let src = r#"
+            text [foreground: 'red'] 'one'
+                span [bold: true] 'two'
+                span [background: 'blue'] 'six'
+        "#;
+
+        let expected = "
+           ╔═════════════╗
+           ║onetwosix    ║
+             [1..4] fg:Red
+             [4..7] fg:Red bold
+             [7..10] fg:Red bg:Blue
+           ╚═════════════╝
+        ";
+
+        TestRunner::new(src, (13, 1)).instance().style_assert(expected);
+    }
+
+    #[test]
+    fn query_span_styles() {
+        let src = r#"
+            text 'one '
+                span [foreground: 'red'] 'two'
+                span ' three'
+        "#;
+
+        TestRunner::new(src, (14, 2)).instance().with_frame(|frame| {
+            let red = |style: &Style| style.fg == Some(Color::Red);
+            let region = frame.region_where(red).unwrap();
+            assert_eq!((region.from, region.to), (Pos::new(5, 1), Pos::new(8, 2)));
+            assert_eq!(frame.glyph_at((5, 1)), Some('t'));
+            assert_eq!(frame.style_at((1, 2)), Some(Style::new()));
+        });
+    }
+
     #[test]
     fn word_wrap_excessive_space() {
         let src = "text 'hello      how are     you'";
@@ -328,4 +385,88 @@ mod test {
 
         TestRunner::new(src, (9, 3)).instance().render_assert(expected);
     }
+
+    #[test]
+    fn function_call() {
+        let src = "text format('{} of {}', value, 10) ' ' upper('items')";
+        let expected_first = r#"
+               ╔══════════════╗
+               ║0 of 10 ITEMS ║
+               ╚══════════════╝
+           "#;
+
+        let expected_second = r#"
+               ╔══════════════╗
+               ║3 of 10 ITEMS ║
+               ╚══════════════╝
+           "#;
+
+        TestRunner::new(src, (14, 1))
+            .instance()
+            .render_assert(expected_first)
+            .with_state(|state| state.value.set(3))
+            .render_assert(expected_second);
+    }
+
+    #[test]
+    fn conditional_value() {
+        let src = "text [foreground: value > 1 ? 'red' : 'green'] value > 1 ? 'many' : 'one'";
+        let expected_first = "
+               ╔═════╗
+               ║one  ║
+                 [1..4] fg:Green
+               ╚═════╝
+           ";
+
+        let expected_second = "
+               ╔═════╗
+               ║many ║
+                 [1..5] fg:Red
+               ╚═════╝
+           ";
+
+        TestRunner::new(src, (5, 1))
+            .instance()
+            .style_assert(expected_first)
+            .with_state(|state| state.value.set(3))
+            .style_assert(expected_second);
+    }
+
+    #[test]
+    fn interpolated_value() {
+        let src = "text [foreground: value > 1 ? 'red' : 'green'] 'v: {value} \\{x\\}'";
+        let expected_first = "
+               ╔═══════════╗
+               ║v: 0 {x}   ║
+                 [1..9] fg:Green
+               ╚═══════════╝
+           ";
+
+        let expected_second = "
+               ╔═══════════╗
+               ║v: 123 {x} ║
+                 [1..11] fg:Red
+               ╚═══════════╝
+           ";
+
+        TestRunner::new(src, (11, 1))
+            .instance()
+            .style_assert(expected_first)
+            .with_state(|state| state.value.set(123))
+            .style_assert(expected_second);
+    }
+
+    #[test]
+    fn interpolated_constant_matches_value() {
+        // The constant is folded when the template is compiled,
+        // the value is formatted at runtime
+        let src = "text '{1.5}' ' ' '{value + 1.5}'";
+        let expected = "
+               ╔═════════════╗
+               ║1.5000 1.5000║
+               ╚═════════════╝
+           ";
+
+        TestRunner::new(src, (13, 1)).instance().render_assert(expected);
+    }
 }
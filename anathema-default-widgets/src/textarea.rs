@@ -0,0 +1,943 @@
+use std::collections::VecDeque;
+use std::ops::ControlFlow;
+
+use anathema_geometry::{LocalPos, Size};
+use anathema_state::{State, Value};
+use anathema_templates::diagnostics::{Attribute, AttributeType};
+use anathema_widgets::components::events::{KeyCode, KeyEvent, KeyState};
+use anathema_widgets::components::{Component, Context};
+use anathema_widgets::layout::text::{Segment, Strings};
+use anathema_widgets::layout::{Constraints, LayoutCtx, PositionCtx};
+use anathema_widgets::paint::{CellAttributes, PaintCtx, SizePos};
+use anathema_widgets::{AttributeStorage, Elements, LayoutChildren, PaintChildren, PositionChildren, Widget, WidgetId};
+
+use crate::editing::{byte_index, next_word, prev_word};
+use crate::input::{moved, InputEvent, CURSOR, FOCUSED, PLACEHOLDER, VALUE};
+use crate::text::{WRAP, WRAPS};
+use crate::{HEIGHT, WIDTH};
+
+// Max number of undo steps
+const MAX_HISTORY: usize = 1000;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum EditKind {
+    Insert,
+    DeleteBack,
+    DeleteForward,
+}
+
+#[derive(Debug)]
+struct Snapshot {
+    text: String,
+    cursor: usize,
+}
+
+/// Multi-line editable text with undo and redo.
+///
+/// The cursor is a char index into the text, and the line and
+/// column are derived from the cursor (both start at zero).
+///
+/// Consecutive edits of the same kind are undone together:
+/// typing a word is undone as one step, and so is deleting a run of characters.
+/// Moving the cursor, pasting, or inserting a newline starts a new undo step.
+///
+/// ```
+/// # use anathema_default_widgets::EditorBuffer;
+/// let mut buffer = EditorBuffer::new();
+/// buffer.insert_str("hello");
+/// buffer.insert_newline();
+/// buffer.insert_str("world");
+/// assert_eq!((buffer.line(), buffer.column()), (1, 5));
+///
+/// buffer.undo();
+/// assert_eq!(buffer.text(), "hello\n");
+/// ```
+#[derive(Debug, Default)]
+pub struct EditorBuffer {
+    text: String,
+    cursor: usize,
+    // The column to return to when moving between lines of different length
+    goal_column: Option<usize>,
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+    last_edit: Option<EditKind>,
+}
+
+impl EditorBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// The number of chars in the text
+    pub fn len(&self) -> usize {
+        self.text.chars().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// The line the cursor is on
+    pub fn line(&self) -> usize {
+        self.text.chars().take(self.cursor).filter(|c| *c == '\n').count()
+    }
+
+    /// The column of the cursor, in chars from the start of the line
+    pub fn column(&self) -> usize {
+        self.cursor - self.line_start(self.cursor)
+    }
+
+    /// Replace the text, placing the cursor at the end.
+    /// This clears the undo history.
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.text = text.into();
+        self.cursor = self.len();
+        self.goal_column = None;
+        self.undo.clear();
+        self.redo.clear();
+        self.last_edit = None;
+    }
+
+    /// Insert a string at the cursor as a single undo step.
+    /// Carriage returns are removed and other control characters,
+    /// apart from newlines and tabs, are ignored.
+    pub fn insert_str(&mut self, s: &str) -> bool {
+        let inserted = s
+            .replace("\r\n", "\n")
+            .chars()
+            .filter(|c| !c.is_control() || *c == '\n' || *c == '\t')
+            .collect::<String>();
+
+        if inserted.is_empty() {
+            return false;
+        }
+
+        self.begin_edit(None);
+        self.insert(&inserted);
+        true
+    }
+
+    /// Insert a char at the cursor.
+    /// Typing is coalesced into one undo step per word.
+    pub fn insert_char(&mut self, c: char) -> bool {
+        if c == '\n' {
+            return self.insert_newline();
+        }
+
+        if c.is_control() && c != '\t' {
+            return false;
+        }
+
+        // Start a new undo step at the end of a word
+        let prev = self.cursor.checked_sub(1).and_then(|i| self.text.chars().nth(i));
+        match prev {
+            Some(prev) if c.is_whitespace() && !prev.is_whitespace() => self.begin_edit(None),
+            _ => self.begin_edit(Some(EditKind::Insert)),
+        }
+
+        self.insert(c.encode_utf8(&mut [0; 4]));
+        self.last_edit = Some(EditKind::Insert);
+        true
+    }
+
+    pub fn insert_newline(&mut self) -> bool {
+        self.begin_edit(None);
+        self.insert("\n");
+        true
+    }
+
+    /// Delete the char before the cursor.
+    pub fn delete_back(&mut self) -> bool {
+        if self.cursor == 0 {
+            return false;
+        }
+
+        self.begin_edit(Some(EditKind::DeleteBack));
+        self.delete_range(self.cursor - 1, self.cursor)
+    }
+
+    /// Delete the char after the cursor.
+    pub fn delete_forward(&mut self) -> bool {
+        if self.cursor == self.len() {
+            return false;
+        }
+
+        self.begin_edit(Some(EditKind::DeleteForward));
+        self.delete_range(self.cursor, self.cursor + 1)
+    }
+
+    /// Delete the word before the cursor.
+    pub fn delete_word_back(&mut self) -> bool {
+        let start = prev_word(&self.text, self.cursor);
+        if start == self.cursor {
+            return false;
+        }
+
+        self.begin_edit(None);
+        self.delete_range(start, self.cursor)
+    }
+
+    /// Delete the word after the cursor.
+    pub fn delete_word_forward(&mut self) -> bool {
+        let end = next_word(&self.text, self.cursor);
+        if end == self.cursor {
+            return false;
+        }
+
+        self.begin_edit(None);
+        self.delete_range(self.cursor, end)
+    }
+
+    /// Undo the last edit.
+    /// Returns false if there is nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        let Some(snapshot) = self.undo.pop_back() else { return false };
+        let current = self.restore(snapshot);
+        self.redo.push(current);
+        true
+    }
+
+    /// Redo the last undone edit.
+    /// Returns false if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        let Some(snapshot) = self.redo.pop() else { return false };
+        let current = self.restore(snapshot);
+        self.undo.push_back(current);
+        true
+    }
+
+    pub fn left(&mut self) -> bool {
+        self.move_to(self.cursor.saturating_sub(1))
+    }
+
+    pub fn right(&mut self) -> bool {
+        self.move_to(self.cursor + 1)
+    }
+
+    /// Move the cursor to the start of the current or previous word.
+    pub fn word_left(&mut self) -> bool {
+        self.move_to(prev_word(&self.text, self.cursor))
+    }
+
+    /// Move the cursor to the end of the current or next word.
+    pub fn word_right(&mut self) -> bool {
+        self.move_to(next_word(&self.text, self.cursor))
+    }
+
+    /// Move the cursor to the previous line,
+    /// keeping the column if the line is long enough.
+    pub fn up(&mut self) -> bool {
+        let start = self.line_start(self.cursor);
+        if start == 0 {
+            return self.move_to(0);
+        }
+
+        let column = self.goal_column.unwrap_or(self.cursor - start);
+        let prev_start = self.line_start(start - 1);
+        let cursor = (prev_start + column).min(start - 1);
+        self.move_to(cursor);
+        self.goal_column = Some(column);
+        true
+    }
+
+    /// Move the cursor to the next line,
+    /// keeping the column if the line is long enough.
+    pub fn down(&mut self) -> bool {
+        let end = self.line_end(self.cursor);
+        if end == self.len() {
+            return self.move_to(end);
+        }
+
+        let column = self.goal_column.unwrap_or(self.cursor - self.line_start(self.cursor));
+        let next_start = end + 1;
+        let cursor = (next_start + column).min(self.line_end(next_start));
+        self.move_to(cursor);
+        self.goal_column = Some(column);
+        true
+    }
+
+    /// Move the cursor to the start of the line
+    pub fn home(&mut self) -> bool {
+        self.move_to(self.line_start(self.cursor))
+    }
+
+    /// Move the cursor to the end of the line
+    pub fn end(&mut self) -> bool {
+        self.move_to(self.line_end(self.cursor))
+    }
+
+    /// Move the cursor to the start of the text
+    pub fn start_of_text(&mut self) -> bool {
+        self.move_to(0)
+    }
+
+    /// Move the cursor to the end of the text
+    pub fn end_of_text(&mut self) -> bool {
+        self.move_to(self.len())
+    }
+
+    /// Apply a key event.
+    ///
+    /// * Arrow keys, `Home` and `End` move the cursor
+    /// * `ctrl+home` and `ctrl+end` move to the start and end of the text
+    /// * Holding ctrl or alt moves and deletes by word
+    /// * `ctrl+z` undo, `ctrl+y` or `ctrl+shift+z` redo
+    /// * `ctrl+w` deletes the previous word
+    pub fn on_key(&mut self, key: &KeyEvent) -> InputEvent {
+        if let KeyState::Release = key.state {
+            return InputEvent::Ignored;
+        }
+
+        let word = key.ctrl() || key.alt();
+
+        let changed = match key.code {
+            KeyCode::Char('z' | 'Z') if key.ctrl() && key.shift() => self.redo(),
+            KeyCode::Char('Z') if key.ctrl() => self.redo(),
+            KeyCode::Char('z') if key.ctrl() => self.undo(),
+            KeyCode::Char('y') if key.ctrl() => self.redo(),
+            KeyCode::Char('w') if key.ctrl() => self.delete_word_back(),
+            KeyCode::Char(_) if word => return InputEvent::Ignored,
+            KeyCode::Char(c) => self.insert_char(c),
+            KeyCode::Enter => self.insert_newline(),
+            KeyCode::Backspace if word => self.delete_word_back(),
+            KeyCode::Backspace => self.delete_back(),
+            KeyCode::Delete if word => self.delete_word_forward(),
+            KeyCode::Delete => self.delete_forward(),
+            KeyCode::Left if word => return moved(self.word_left()),
+            KeyCode::Left => return moved(self.left()),
+            KeyCode::Right if word => return moved(self.word_right()),
+            KeyCode::Right => return moved(self.right()),
+            KeyCode::Up => return moved(self.up()),
+            KeyCode::Down => return moved(self.down()),
+            KeyCode::Home if key.ctrl() => return moved(self.start_of_text()),
+            KeyCode::Home => return moved(self.home()),
+            KeyCode::End if key.ctrl() => return moved(self.end_of_text()),
+            KeyCode::End => return moved(self.end()),
+            _ => return InputEvent::Ignored,
+        };
+
+        match changed {
+            true => InputEvent::Changed,
+            false => InputEvent::Ignored,
+        }
+    }
+
+    // Save the current text as an undo step, unless the edit
+    // can be coalesced with the previous edit.
+    fn begin_edit(&mut self, kind: Option<EditKind>) {
+        self.goal_column = None;
+        self.redo.clear();
+
+        if kind.is_some() && kind == self.last_edit {
+            return;
+        }
+
+        self.last_edit = kind;
+        self.undo.push_back(Snapshot {
+            text: self.text.clone(),
+            cursor: self.cursor,
+        });
+
+        if self.undo.len() > MAX_HISTORY {
+            self.undo.pop_front();
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) -> Snapshot {
+        let current = Snapshot {
+            text: std::mem::replace(&mut self.text, snapshot.text),
+            cursor: self.cursor,
+        };
+        self.cursor = snapshot.cursor;
+        self.goal_column = None;
+        self.last_edit = None;
+        current
+    }
+
+    fn insert(&mut self, s: &str) {
+        let index = byte_index(&self.text, self.cursor);
+        self.text.insert_str(index, s);
+        self.cursor += s.chars().count();
+    }
+
+    fn delete_range(&mut self, start: usize, end: usize) -> bool {
+        let range = byte_index(&self.text, start)..byte_index(&self.text, end);
+        self.text.replace_range(range, "");
+        self.cursor = start;
+        true
+    }
+
+    fn move_to(&mut self, cursor: usize) -> bool {
+        let cursor = cursor.min(self.len());
+        self.goal_column = None;
+        self.last_edit = None;
+        let moved = self.cursor != cursor;
+        self.cursor = cursor;
+        moved
+    }
+
+    fn line_start(&self, from: usize) -> usize {
+        let chars = self.text.chars().take(from).collect::<Vec<_>>();
+        chars.iter().rposition(|c| *c == '\n').map(|i| i + 1).unwrap_or(0)
+    }
+
+    fn line_end(&self, from: usize) -> usize {
+        from + self.text.chars().skip(from).take_while(|c| *c != '\n').count()
+    }
+}
+
+// Find the row and column of the cursor in the wrapped text.
+fn cursor_position(text: &str, strings: &Strings, cursor: usize, width: usize) -> (usize, usize) {
+    let index = byte_index(text, cursor);
+    let (row, col) = strings.cursor_position(index);
+
+    // Place the cursor on the next row if the line is full
+    match col >= width && width > 0 {
+        true => (row + 1, 0),
+        false => (row, col),
+    }
+}
+
+/// Text area widget
+/// ```ignore
+/// Attributes:
+/// * cursor
+/// * focused
+/// * placeholder
+/// * wrap
+/// * width
+/// * height
+/// ```
+///
+/// Multiple lines of text that scroll vertically to keep the cursor visible.
+/// The terminal cursor is placed at the `cursor` position while `focused` is true.
+///
+/// The widget only displays the text, see [`TextEditor`] for the component
+/// that handles the editing.
+#[derive(Debug, Default)]
+pub struct TextArea {
+    text: String,
+    strings: Strings,
+    // Row and column of the cursor
+    cursor: (usize, usize),
+    rows: usize,
+    // Index of the first visible row
+    offset: usize,
+}
+
+const ATTRIBUTES: &[Attribute] = &[
+    Attribute::new(WIDTH, AttributeType::Unsigned),
+    Attribute::new(HEIGHT, AttributeType::Unsigned),
+    Attribute::new(WRAP, AttributeType::OneOf(WRAPS)),
+    Attribute::new(CURSOR, AttributeType::Unsigned),
+    Attribute::new(FOCUSED, AttributeType::Bool),
+    Attribute::new(PLACEHOLDER, AttributeType::Str),
+];
+
+impl Widget for TextArea {
+    fn layout<'bp>(
+        &mut self,
+        _: LayoutChildren<'_, '_, 'bp>,
+        mut constraints: Constraints,
+        id: WidgetId,
+        ctx: &mut LayoutCtx<'_, 'bp>,
+    ) -> Size {
+        let attributes = ctx.attribs.get(id);
+
+        if let Some(width) = attributes.get_usize(WIDTH) {
+            constraints.make_width_tight(width);
+        }
+
+        if let Some(height) = attributes.get_usize(HEIGHT) {
+            constraints.make_height_tight(height);
+        }
+
+        self.text.clear();
+        attributes.value().map(|value| {
+            value.str_iter(|s| {
+                self.text.push_str(s);
+                ControlFlow::Continue(())
+            })
+        });
+
+        let width = constraints.max_width();
+        let wrap = attributes.get(WRAP).unwrap_or_default();
+        self.strings = Strings::new(Size::new(width, usize::MAX), wrap);
+
+        match self.text.is_empty() {
+            true => {
+                attributes.with_str(PLACEHOLDER, &mut |s| {
+                    self.strings.add_str(s);
+                });
+                self.cursor = (0, 0);
+            }
+            false => {
+                self.strings.add_str(&self.text);
+            }
+        }
+
+        let size = self.strings.finish();
+
+        if !self.text.is_empty() {
+            let len = self.text.chars().count();
+            let cursor = attributes.get_usize(CURSOR).unwrap_or(len).min(len);
+            self.cursor = cursor_position(&self.text, &self.strings, cursor, width);
+        }
+
+        self.rows = self.strings.lines().count().max(self.cursor.0 + 1);
+
+        // Fill the available space, unless it's unbounded
+        let width = match width {
+            // Leave room for the cursor
+            usize::MAX => size.width + 1,
+            width => width,
+        };
+
+        let height = match constraints.max_height() {
+            usize::MAX => self.rows,
+            height => height,
+        };
+
+        Size::new(width.max(constraints.min_width), height.max(constraints.min_height))
+    }
+
+    fn position<'bp>(
+        &mut self,
+        _: PositionChildren<'_, '_, 'bp>,
+        _: WidgetId,
+        _: &AttributeStorage<'bp>,
+        _: PositionCtx,
+    ) {
+        // NOTE
+        // No positioning is done in here, it's all done when painting
+    }
+
+    fn paint<'bp>(
+        &mut self,
+        _: PaintChildren<'_, '_, 'bp>,
+        id: WidgetId,
+        attribute_storage: &AttributeStorage<'bp>,
+        mut ctx: PaintCtx<'_, SizePos>,
+    ) {
+        let attributes = attribute_storage.get(id);
+        let height = ctx.local_size.height;
+
+        // Style the entire text area so the background covers the empty space
+        for y in 0..height {
+            for x in 0..ctx.local_size.width {
+                ctx.set_attributes(attributes, LocalPos::new(x as u16, y as u16));
+            }
+        }
+
+        // Scroll the text so the cursor is visible,
+        // without leaving empty rows at the bottom
+        let (row, col) = self.cursor;
+        self.offset = self.offset.min(row).min(self.rows.saturating_sub(height));
+        if row >= self.offset + height {
+            self.offset = (row + 1).saturating_sub(height);
+        }
+
+        for (y, line) in self.strings.lines().skip(self.offset).take(height).enumerate() {
+            let mut pos = LocalPos::new(0, y as u16);
+            for entry in line.entries {
+                let Segment::Str(s) = entry else { continue };
+                if let Some(new_pos) = ctx.place_glyphs(s, pos) {
+                    for x in pos.x..new_pos.x {
+                        ctx.set_attributes(attributes, (x, pos.y).into());
+                    }
+                    pos = new_pos;
+                }
+            }
+        }
+
+        if attributes.get_bool(FOCUSED) && height > 0 {
+            ctx.set_cursor(LocalPos::new(col as u16, (row - self.offset) as u16));
+        }
+    }
+
+    fn attributes() -> Option<&'static [Attribute]> {
+        Some(ATTRIBUTES)
+    }
+}
+
+/// The state of a [`TextEditor`].
+#[derive(Debug, State)]
+pub struct TextEditorState {
+    pub text: Value<String>,
+    pub cursor: Value<usize>,
+    pub line: Value<usize>,
+    pub column: Value<usize>,
+    pub focused: Value<bool>,
+}
+
+impl TextEditorState {
+    pub fn new() -> Self {
+        Self {
+            text: String::new().into(),
+            cursor: 0.into(),
+            line: 0.into(),
+            column: 0.into(),
+            focused: false.into(),
+        }
+    }
+}
+
+impl Default for TextEditorState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// An editable multi-line text area.
+///
+/// The text editor publishes `change` when the text changes.
+/// The `line` and `column` of the cursor are available in the state.
+///
+/// Pasting text requires bracketed paste to be enabled on the backend.
+///
+/// External state:
+/// * value (replaces the text when the value changes)
+/// * placeholder
+/// * wrap
+/// * width
+/// * height
+///
+/// ```ignore
+/// builder.register_prototype("editor", TextEditor::TEMPLATE.to_template(), TextEditor::new, TextEditorState::new)?;
+/// ```
+///
+/// ```ignore
+/// @editor (change->on_change) { "value": message, "placeholder": "Commit message", "height": 10 }
+/// ```
+#[derive(Debug, Default)]
+pub struct TextEditor {
+    buffer: EditorBuffer,
+}
+
+impl TextEditor {
+    /// The template for the text editor
+    pub const TEMPLATE: &'static str = "textarea [cursor: cursor, focused: focused, placeholder: placeholder, wrap: wrap, width: width, height: height, foreground: foreground, background: background] text";
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn apply(&self, event: InputEvent, state: &mut TextEditorState, context: &mut Context<'_, TextEditor>) {
+        match event {
+            InputEvent::Changed => {
+                state.text.set(self.buffer.text().to_string());
+                self.update_cursor(state);
+                context.publish("change", |state| &state.text);
+            }
+            InputEvent::Moved => self.update_cursor(state),
+            InputEvent::Submit | InputEvent::Ignored => (),
+        }
+    }
+
+    fn update_cursor(&self, state: &mut TextEditorState) {
+        state.cursor.set(self.buffer.cursor());
+        state.line.set(self.buffer.line());
+        state.column.set(self.buffer.column());
+    }
+}
+
+impl Component for TextEditor {
+    type Message = ();
+    type State = TextEditorState;
+
+    fn on_focus(&mut self, state: &mut Self::State, _: Elements<'_, '_>, _: Context<'_, Self>) {
+        state.focused.set(true);
+    }
+
+    fn on_blur(&mut self, state: &mut Self::State, _: Elements<'_, '_>, _: Context<'_, Self>) {
+        state.focused.set(false);
+    }
+
+    fn on_key(&mut self, key: KeyEvent, state: &mut Self::State, _: Elements<'_, '_>, mut context: Context<'_, Self>) {
+        let event = self.buffer.on_key(&key);
+        self.apply(event, state, &mut context);
+    }
+
+    fn on_paste(&mut self, text: &str, state: &mut Self::State, _: Elements<'_, '_>, mut context: Context<'_, Self>) {
+        let event = match self.buffer.insert_str(text) {
+            true => InputEvent::Changed,
+            false => InputEvent::Ignored,
+        };
+        self.apply(event, state, &mut context);
+    }
+
+    fn on_external_change(
+        &mut self,
+        key: &str,
+        state: &mut Self::State,
+        _: Elements<'_, '_>,
+        context: Context<'_, Self>,
+    ) {
+        if key != VALUE {
+            return;
+        }
+
+        let Some(value) = context.get_external(VALUE) else { return };
+        let Some(value) = value.to_common().map(|value| value.to_string()) else { return };
+
+        // The parent echoing back a published change is not a new value
+        if value != self.buffer.text() {
+            self.buffer.set_text(value);
+            state.text.set(self.buffer.text().to_string());
+            self.update_cursor(state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anathema_widgets::components::events::Modifiers;
+    use anathema_widgets::layout::text::Wrap;
+
+    use super::*;
+    use crate::testing::TestRunner;
+
+    fn key(code: impl Into<KeyCode>, modifiers: Modifiers) -> KeyEvent {
+        KeyEvent {
+            code: code.into(),
+            modifiers,
+            state: KeyState::Press,
+        }
+    }
+
+    fn type_str(buffer: &mut EditorBuffer, s: &str) {
+        s.chars().for_each(|c| {
+            buffer.insert_char(c);
+        });
+    }
+
+    fn position(text: &str, width: usize, cursor: usize) -> (usize, usize) {
+        let mut strings = Strings::new(Size::new(width, usize::MAX), Wrap::Normal);
+        strings.add_str(text);
+        strings.finish();
+        cursor_position(text, &strings, cursor, width)
+    }
+
+    #[test]
+    fn lines_and_columns() {
+        let mut buffer = EditorBuffer::new();
+        buffer.set_text("one\ntwo three\nfour");
+        assert_eq!((buffer.line(), buffer.column()), (2, 4));
+
+        buffer.up();
+        assert_eq!((buffer.line(), buffer.column()), (1, 4));
+
+        // The column is remembered when passing a shorter line
+        buffer.end();
+        buffer.up();
+        assert_eq!((buffer.line(), buffer.column()), (0, 3));
+        buffer.down();
+        assert_eq!((buffer.line(), buffer.column()), (1, 9));
+        buffer.down();
+        assert_eq!((buffer.line(), buffer.column()), (2, 4));
+
+        buffer.home();
+        buffer.left();
+        assert_eq!((buffer.line(), buffer.column()), (1, 9));
+        buffer.word_left();
+        assert_eq!((buffer.line(), buffer.column()), (1, 4));
+    }
+
+    #[test]
+    fn edit_across_lines() {
+        let mut buffer = EditorBuffer::new();
+        buffer.set_text("ab\ncd");
+        buffer.up();
+        buffer.delete_forward();
+        assert_eq!(buffer.text(), "abcd");
+        buffer.insert_newline();
+        buffer.insert_newline();
+        buffer.delete_back();
+        assert_eq!(buffer.text(), "ab\ncd");
+        assert_eq!((buffer.line(), buffer.column()), (1, 0));
+    }
+
+    #[test]
+    fn undo_coalesces_typing() {
+        let mut buffer = EditorBuffer::new();
+        type_str(&mut buffer, "hello world");
+        assert_eq!(buffer.text(), "hello world");
+
+        buffer.undo();
+        assert_eq!(buffer.text(), "hello");
+        buffer.undo();
+        assert_eq!(buffer.text(), "");
+        assert!(!buffer.undo());
+
+        buffer.redo();
+        buffer.redo();
+        assert_eq!(buffer.text(), "hello world");
+        assert_eq!(buffer.cursor(), 11);
+        assert!(!buffer.redo());
+    }
+
+    #[test]
+    fn undo_coalesces_deletes() {
+        let mut buffer = EditorBuffer::new();
+        buffer.insert_str("hello world");
+        buffer.delete_back();
+        buffer.delete_back();
+        buffer.home();
+        buffer.delete_forward();
+        assert_eq!(buffer.text(), "ello wor");
+
+        buffer.undo();
+        assert_eq!(buffer.text(), "hello wor");
+        buffer.undo();
+        assert_eq!(buffer.text(), "hello world");
+    }
+
+    #[test]
+    fn edit_clears_redo() {
+        let mut buffer = EditorBuffer::new();
+        buffer.insert_str("a");
+        buffer.insert_str("b");
+        buffer.undo();
+        buffer.insert_str("c");
+        assert!(!buffer.redo());
+        assert_eq!(buffer.text(), "ac");
+    }
+
+    #[test]
+    fn paste() {
+        let mut buffer = EditorBuffer::new();
+        buffer.insert_str("one\r\ntwo\u{7}");
+        assert_eq!(buffer.text(), "one\ntwo");
+        assert!(!buffer.insert_str("\u{7}"));
+
+        // A paste is undone in one step
+        buffer.undo();
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn key_events() {
+        let mut buffer = EditorBuffer::new();
+        assert_eq!(buffer.on_key(&key('a', Modifiers::empty())), InputEvent::Changed);
+        assert_eq!(
+            buffer.on_key(&key(KeyCode::Enter, Modifiers::empty())),
+            InputEvent::Changed
+        );
+        assert_eq!(buffer.on_key(&key(KeyCode::Up, Modifiers::empty())), InputEvent::Moved);
+        assert_eq!(buffer.on_key(&key('z', Modifiers::CTRL)), InputEvent::Changed);
+        assert_eq!(buffer.text(), "a");
+        assert_eq!(
+            buffer.on_key(&key('Z', Modifiers::CTRL | Modifiers::SHIFT)),
+            InputEvent::Changed
+        );
+        assert_eq!(buffer.text(), "a\n");
+        assert_eq!(buffer.on_key(&key('y', Modifiers::CTRL)), InputEvent::Ignored);
+    }
+
+    #[test]
+    fn wrapped_cursor_position() {
+        // hello
+        // world
+        assert_eq!(position("hello world", 5, 0), (0, 0));
+        assert_eq!(position("hello world", 5, 4), (0, 4));
+        assert_eq!(position("hello world", 5, 5), (1, 0));
+        assert_eq!(position("hello world", 5, 6), (1, 0));
+        assert_eq!(position("hello world", 5, 11), (2, 0));
+
+        // ab
+        //  c
+        assert_eq!(position("ab\n c", 5, 2), (0, 2));
+        assert_eq!(position("ab\n c", 5, 3), (1, 0));
+        assert_eq!(position("ab\n c", 5, 5), (1, 2));
+
+        // ab
+        //
+        assert_eq!(position("ab\n", 5, 3), (1, 0));
+
+        // one
+        // two
+        assert_eq!(position("one two", 5, 7), (1, 3));
+    }
+
+    #[test]
+    fn render_textarea() {
+        let src = "textarea [cursor: 11, focused: true] 'hello world'";
+        let expected = "
+            ╔═════╗
+            ║hello║
+            ║world║
+            ║     ║
+            ╚═════╝
+        ";
+
+        let mut runner = TestRunner::new(src, (5, 3));
+        let mut instance = runner.instance();
+        instance.render_assert(expected).cursor_assert(Some((1, 3)));
+    }
+
+    #[test]
+    fn scroll_to_cursor() {
+        let src = "textarea [cursor: value, focused: true] 'one\ntwo\nthree\nfour'";
+
+        let mut runner = TestRunner::new(src, (5, 2));
+        let mut instance = runner.instance();
+        instance.with_state(|state| state.value.set(18));
+        instance
+            .render_assert(
+                "
+                ╔═════╗
+                ║three║
+                ║four ║
+                ╚═════╝
+                ",
+            )
+            .cursor_assert(Some((5, 2)));
+
+        instance.with_state(|state| state.value.set(0));
+        instance.cursor_assert(Some((1, 1))).render_assert(
+            "
+            ╔═════╗
+            ║one  ║
+            ║two  ║
+            ╚═════╝
+            ",
+        );
+    }
+
+    #[test]
+    fn render_placeholder() {
+        let src = "textarea [placeholder: 'notes', focused: true] ''";
+        let expected = "
+            ╔═════╗
+            ║notes║
+            ║     ║
+            ╚═════╝
+        ";
+
+        let mut runner = TestRunner::new(src, (5, 2));
+        let mut instance = runner.instance();
+        instance.render_assert(expected).cursor_assert(Some((1, 1)));
+    }
+
+    #[test]
+    fn text_editor_template() {
+        let src = "@editor { placeholder: 'notes' }";
+        let expected = "
+            ╔═════╗
+            ║notes║
+            ╚═════╝
+        ";
+
+        TestRunner::with_components(src, &[("editor", TextEditor::TEMPLATE)], (5, 1))
+            .instance()
+            .render_assert(expected);
+    }
+}
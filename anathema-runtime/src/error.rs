@@ -10,6 +10,7 @@ pub enum Error {
     Template(TemplateError),
     Notify(notify::Error),
     Widget(anathema_widgets::error::Error),
+    ExitValue,
     Stop,
 }
 
@@ -20,6 +21,7 @@ impl Display for Error {
             Error::Stop => write!(f, "stopping"),
             Error::Notify(err) => write!(f, "{err}"),
             Error::Widget(err) => write!(f, "{err}"),
+            Error::ExitValue => write!(f, "the exit value does not match the return type of `Runtime::run`"),
         }
     }
 }
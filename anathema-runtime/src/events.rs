@@ -1,13 +1,23 @@
+use std::any::Any;
 use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::time::{Duration, Instant};
 
 use anathema_backend::Backend;
 use anathema_geometry::Size;
 use anathema_state::{AnyState, CommonVal, States};
-use anathema_widgets::components::events::{Event, KeyCode, KeyEvent, KeyState};
+use anathema_widgets::components::animation::Animations;
+use anathema_widgets::components::events::{Event, KeyCode, KeyEvent, KeyState, MouseEvent, MouseState};
+use anathema_widgets::components::exit::Exit;
+use anathema_widgets::components::keymap::{Keymap, KeymapResult};
+use anathema_widgets::components::tasks::Tasks;
+use anathema_widgets::components::timers::Timers;
 use anathema_widgets::components::{AssociatedEvents, ComponentId, Emitter, FocusQueue, UntypedContext};
 use anathema_widgets::layout::{Constraints, Viewport};
-use anathema_widgets::{AttributeStorage, Components, DirtyWidgets, Elements, WidgetKind, WidgetTree};
+use anathema_widgets::{
+    component_at, AttributeStorage, Components, DirtyWidgets, Elements, FloatingWidgets, WidgetId, WidgetKind,
+    WidgetTree,
+};
 
 use crate::error::{Error, Result};
 use crate::tree::Tree;
@@ -16,14 +26,7 @@ use crate::tree::Tree;
 //   - Ctrl-c quit test -
 // -----------------------------------------------------------------------------
 fn is_ctrl_c(event: Event) -> bool {
-    matches!(
-        event,
-        Event::Key(KeyEvent {
-            ctrl: true,
-            code: KeyCode::Char('c'),
-            ..
-        }),
-    )
+    matches!(event, Event::Key(key) if key.code == KeyCode::Char('c') && key.ctrl())
 }
 
 // If the event is tab/back tab then the event is consumed
@@ -102,28 +105,145 @@ fn tab<'bp>(event_ctx: &mut EventCtx<'_, '_, 'bp>, tree: &mut WidgetTree<'bp>, e
         return None;
     }
 
-    // Mouse events are global
-    if let Event::Mouse(_) = event {
+    Some(event)
+}
+
+// Send a mouse event to a component, if the component still exists
+fn send_mouse<'bp>(
+    widget_id: WidgetId,
+    mouse: MouseEvent,
+    tree: &mut WidgetTree<'bp>,
+    event_ctx: &mut EventCtx<'_, '_, 'bp>,
+) {
+    let Some(state_id) = event_ctx
+        .components
+        .iter()
+        .find(|entry| entry.widget_id == widget_id)
+        .map(|entry| entry.state_id)
+    else {
+        return;
+    };
+
+    tree.with_component(widget_id, state_id, event_ctx, |comp, ctx| {
+        comp.any_event(ctx, Event::Mouse(mouse))
+    });
+}
+
+pub(super) struct EventHandler<T> {
+    global: T,
+    // Component currently under the cursor
+    hovered: Option<WidgetId>,
+    // Component that received the last mouse down event
+    pressed: Option<WidgetId>,
+    // Keys of an abandoned global key sequence, waiting to be passed on
+    replay: VecDeque<Event>,
+    // The time the next partial key sequence times out
+    keymap_deadline: Option<Instant>,
+}
+
+impl<T: GlobalEvents> EventHandler<T> {
+    pub fn new(global: T) -> Self {
+        Self {
+            global,
+            hovered: None,
+            pressed: None,
+            replay: VecDeque::new(),
+            keymap_deadline: None,
+        }
+    }
+
+    pub(super) fn keymap_deadline(&self) -> Option<Instant> {
+        self.keymap_deadline
+    }
+
+    // Send the keys of timed out component key sequences to the components,
+    // and find the time the next partial key sequence times out.
+    // The keys of a timed out global key sequence are passed on the next time events are handled.
+    pub(super) fn expire_keymaps<'bp>(
+        &mut self,
+        now: Instant,
+        tree: &mut WidgetTree<'bp>,
+        event_ctx: &mut EventCtx<'_, '_, 'bp>,
+    ) {
+        let mut deadline = self.global.keymap().and_then(|keymap| keymap.deadline());
+
         for i in 0..event_ctx.components.len() {
             let (widget_id, state_id) = event_ctx
                 .components
                 .get(i)
                 .expect("components can not change during this call");
 
-            tree.with_component(widget_id, state_id, event_ctx, |comp, ctx| comp.any_event(ctx, event));
+            let next = tree.with_component(widget_id, state_id, event_ctx, |comp, ctx| {
+                if comp.any_keymap_deadline().is_some_and(|deadline| deadline <= now) {
+                    comp.any_keymap_expire(ctx, now);
+                }
+                comp.any_keymap_deadline()
+            });
+
+            if let Some(next) = next.flatten() {
+                deadline = Some(deadline.map_or(next, |deadline| deadline.min(next)));
+            }
         }
+
+        self.keymap_deadline = deadline;
     }
 
-    Some(event)
-}
+    // Mouse events are only sent to the topmost component under the cursor.
+    //
+    // * When the component under the cursor changes the previous component
+    //   receives `MouseState::Leave` and the new one `MouseState::Enter`.
+    // * Moving the cursor is sent as `MouseState::Hover`.
+    // * Drag and release events are sent to the component that received the
+    //   mouse down event, even if the cursor has left that component.
+    fn mouse<'bp>(
+        &mut self,
+        mouse: MouseEvent,
+        tree: &mut WidgetTree<'bp>,
+        floating_widgets: &FloatingWidgets,
+        event_ctx: &mut EventCtx<'_, '_, 'bp>,
+    ) {
+        let under_cursor = component_at(mouse.pos(), tree, event_ctx.attribute_storage, floating_widgets);
 
-pub(super) struct EventHandler<T> {
-    global: T,
-}
+        let target = match (mouse.state, self.pressed) {
+            (MouseState::Drag(_) | MouseState::Up(_), Some(pressed)) => Some(pressed),
+            _ => under_cursor,
+        };
 
-impl<T: GlobalEvents> EventHandler<T> {
-    pub fn new(global: T) -> Self {
-        Self { global }
+        match mouse.state {
+            MouseState::Down(_) => self.pressed = under_cursor,
+            MouseState::Up(_) => self.pressed = None,
+            _ => (),
+        }
+
+        if self.hovered != under_cursor {
+            if let Some(widget_id) = self.hovered.take() {
+                let leave = MouseEvent {
+                    state: MouseState::Leave,
+                    ..mouse
+                };
+                send_mouse(widget_id, leave, tree, event_ctx);
+            }
+
+            if let Some(widget_id) = under_cursor {
+                let enter = MouseEvent {
+                    state: MouseState::Enter,
+                    ..mouse
+                };
+                send_mouse(widget_id, enter, tree, event_ctx);
+            }
+
+            self.hovered = under_cursor;
+        }
+
+        let Some(widget_id) = target else { return };
+        let mouse = match mouse.state {
+            MouseState::Move => MouseEvent {
+                state: MouseState::Hover,
+                ..mouse
+            },
+            _ => mouse,
+        };
+        send_mouse(widget_id, mouse, tree, event_ctx);
     }
 
     pub(super) fn set_initial_focus<'bp>(&mut self, tree: &mut WidgetTree<'bp>, event_ctx: &mut EventCtx<'_, '_, 'bp>) {
@@ -150,17 +270,34 @@ impl<T: GlobalEvents> EventHandler<T> {
 
     pub(super) fn handle<'bp>(
         &mut self,
+        pending_event: &mut Option<Event>,
         poll_duration: Duration,
         fps_now: Instant,
         sleep_micros: u128,
         backend: &mut impl Backend,
         viewport: &mut Viewport,
         tree: &mut WidgetTree<'bp>,
+        floating_widgets: &FloatingWidgets,
         constraints: &mut Constraints,
         event_ctx: &mut EventCtx<'_, '_, 'bp>,
     ) -> Result<()> {
-        while let Some(event) = backend.next_event(poll_duration) {
-            let event = match self.global.enable_tab_navigation() {
+        // The keys of a timed out global key sequence are passed on
+        if let Some(keymap) = self.global.keymap() {
+            keymap.expire(Instant::now());
+            self.replay.extend(keymap.take_abandoned().into_iter().map(Event::Key));
+        }
+
+        loop {
+            // Replayed keys have already been through tab navigation and the global keymap
+            let (event, replayed) = match self.replay.pop_front() {
+                Some(event) => (event, true),
+                None => match pending_event.take().or_else(|| backend.next_event(poll_duration)) {
+                    Some(event) => (event, false),
+                    None => break,
+                },
+            };
+
+            let event = match self.global.enable_tab_navigation() && !replayed {
                 false => event,
                 true => match tab(event_ctx, tree, event) {
                     None => return Ok(()),
@@ -173,21 +310,52 @@ impl<T: GlobalEvents> EventHandler<T> {
             let mut global_ctx = GlobalContext {
                 focus_queue: event_ctx.focus_queue,
                 emitter: event_ctx.context.emitter,
+                exit: event_ctx.context.exit,
             };
 
-            let event = match is_ctrl_c(event) {
+            let event = match is_ctrl_c(event) && !replayed {
                 true => self.global.ctrl_c(event, &mut elements, &mut global_ctx),
                 false => Some(event),
             };
 
+            let Some(event) = event else { return Ok(()) };
+
+            // Global key bindings
+            let event = match event {
+                Event::Key(key) if !replayed => {
+                    let result = self.global.keymap().map(|keymap| keymap.feed(key));
+                    let abandoned = self.global.keymap().map(Keymap::take_abandoned).unwrap_or_default();
+                    let event = match result {
+                        Some(KeymapResult::Action(action)) => {
+                            action(&mut self.global, &mut elements, &mut global_ctx);
+                            None
+                        }
+                        Some(KeymapResult::Pending) => None,
+                        Some(KeymapResult::NoMatch) | None => Some(event),
+                    };
+
+                    // The keys of an abandoned key sequence are passed on before this key
+                    match abandoned.is_empty() {
+                        true => event,
+                        false => {
+                            self.replay.extend(abandoned.into_iter().map(Event::Key).chain(event));
+                            self.replay.pop_front()
+                        }
+                    }
+                }
+                _ => Some(event),
+            };
+
             let Some(event) = event else { return Ok(()) };
             let event = self.global.handle(event, &mut elements, &mut global_ctx);
             let Some(event) = event else { return Ok(()) };
 
-            // Ignore mouse events, as they are handled by global event
-            if !event.is_mouse_event() {
-                if let Some((widget_id, state_id)) = event_ctx.components.get(event_ctx.components.tab_index) {
-                    tree.with_component(widget_id, state_id, event_ctx, |comp, ctx| comp.any_event(ctx, event));
+            match event {
+                Event::Mouse(mouse) => self.mouse(mouse, tree, floating_widgets, event_ctx),
+                _ => {
+                    if let Some((widget_id, state_id)) = event_ctx.components.get(event_ctx.components.tab_index) {
+                        tree.with_component(widget_id, state_id, event_ctx, |comp, ctx| comp.any_event(ctx, event));
+                    }
                 }
             }
 
@@ -244,6 +412,9 @@ impl<T: GlobalEvents> EventHandler<T> {
                         attribute_storage: event_ctx.attribute_storage,
                         assoc_events: event_ctx.assoc_events,
                         focus_queue: event_ctx.focus_queue,
+                        timers: event_ctx.timers,
+                        animations: event_ctx.animations,
+                        tasks: event_ctx.tasks,
                         context: event_ctx.context,
                         dirty_widgets: event_ctx.dirty_widgets,
                     };
@@ -313,12 +484,16 @@ pub(crate) struct EventCtx<'a, 'rt, 'bp> {
     pub attribute_storage: &'a mut AttributeStorage<'bp>,
     pub assoc_events: &'a mut AssociatedEvents,
     pub focus_queue: &'a mut FocusQueue<'static>,
+    pub timers: &'a mut Timers,
+    pub animations: &'a mut Animations,
+    pub tasks: &'a mut Tasks,
     pub context: UntypedContext<'rt>,
 }
 
 pub struct GlobalContext<'rt> {
     emitter: &'rt Emitter,
     focus_queue: &'rt mut FocusQueue<'static>,
+    exit: &'rt Exit,
 }
 
 impl<'rt> GlobalContext<'rt> {
@@ -334,8 +509,24 @@ impl<'rt> GlobalContext<'rt> {
     pub fn set_focus(&mut self, key: impl Into<Cow<'static, str>>, value: impl Into<CommonVal<'static>>) {
         self.focus_queue.push(key.into(), value.into());
     }
+
+    /// Stop the runtime after the current frame.
+    /// `Runtime::run` returns `Ok(None)`.
+    pub fn quit(&self) {
+        self.exit.quit();
+    }
+
+    /// Stop the runtime after the current frame and return the value from `Runtime::run`.
+    /// The value has to be of the exit type of the runtime, otherwise `Runtime::run`
+    /// returns an error.
+    pub fn quit_with<R: Any + Send>(&self, value: R) {
+        self.exit.quit_with(value);
+    }
 }
 
+/// An action bound to a key sequence in the global keymap.
+pub type GlobalAction<G> = fn(&mut G, &mut Elements<'_, '_>, &mut GlobalContext<'_>);
+
 pub trait GlobalEvents {
     fn handle(&mut self, event: Event, elements: &mut Elements<'_, '_>, ctx: &mut GlobalContext<'_>) -> Option<Event>;
 
@@ -347,6 +538,51 @@ pub trait GlobalEvents {
     fn enable_tab_navigation(&mut self) -> bool {
         true
     }
+
+    /// Global key bindings.
+    ///
+    /// Key events are matched against the keymap before they are passed
+    /// to [`GlobalEvents::handle`] and the focused component.
+    /// Keys that are part of a key sequence are not passed on, unless the
+    /// sequence is broken by another key or times out.
+    ///
+    /// ```
+    /// # use anathema_runtime::{GlobalAction, GlobalContext, GlobalEvents};
+    /// # use anathema_widgets::components::events::Event;
+    /// # use anathema_widgets::components::keymap::{Keymap, ParseKeyError};
+    /// # use anathema_widgets::Elements;
+    /// struct Global {
+    ///     keymap: Keymap<GlobalAction<Self>>,
+    /// }
+    ///
+    /// impl Global {
+    ///     fn new() -> Result<Self, ParseKeyError> {
+    ///         Ok(Self {
+    ///             keymap: Keymap::new().bind("ctrl+x ctrl+s", Self::save as GlobalAction<Self>)?,
+    ///         })
+    ///     }
+    ///
+    ///     fn save(&mut self, _: &mut Elements<'_, '_>, ctx: &mut GlobalContext<'_>) {
+    ///         ctx.set_focus("id", "save-dialog");
+    ///     }
+    /// }
+    ///
+    /// impl GlobalEvents for Global {
+    ///     fn handle(&mut self, event: Event, _: &mut Elements<'_, '_>, _: &mut GlobalContext<'_>) -> Option<Event> {
+    ///         Some(event)
+    ///     }
+    ///
+    ///     fn keymap(&mut self) -> Option<&mut Keymap<GlobalAction<Self>>> {
+    ///         Some(&mut self.keymap)
+    ///     }
+    /// }
+    /// ```
+    fn keymap(&mut self) -> Option<&mut Keymap<GlobalAction<Self>>>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
 impl GlobalEvents for () {
@@ -0,0 +1,116 @@
+use std::time::Instant;
+
+use anathema_backend::Backend;
+use anathema_state::States;
+use anathema_templates::Globals;
+use anathema_widgets::components::animation::Animations;
+use anathema_widgets::components::tasks::Tasks;
+use anathema_widgets::components::timers::Timers;
+use anathema_widgets::components::{AssociatedEvents, FocusQueue};
+use anathema_widgets::{AttributeStorage, WidgetTree};
+
+use crate::error::{Error, Result};
+use crate::events::GlobalEvents;
+use crate::Runtime;
+
+// The widget tree and the state that lives as long as the tree
+pub(crate) struct TreeState<'bp> {
+    pub(crate) tree: WidgetTree<'bp>,
+    pub(crate) states: States,
+    pub(crate) attribute_storage: AttributeStorage<'bp>,
+    pub(crate) globals: &'bp Globals,
+    pub(crate) assoc_events: AssociatedEvents,
+    pub(crate) focus_queue: FocusQueue<'static>,
+    pub(crate) timers: Timers,
+    pub(crate) tasks: Tasks,
+    pub(crate) animations: Animations,
+    // Start of the current frame
+    pub(crate) fps_now: Instant,
+    // Time of the last tick, used to calculate the delta time
+    pub(crate) dt: Instant,
+    // Number of frames run
+    pub(crate) frame: usize,
+    // True if the next frame should not wait for an event
+    pub(crate) ticking: bool,
+}
+
+impl<'bp> TreeState<'bp> {
+    pub(crate) fn new(
+        tree: WidgetTree<'bp>,
+        states: States,
+        attribute_storage: AttributeStorage<'bp>,
+        globals: &'bp Globals,
+    ) -> Self {
+        Self {
+            tree,
+            states,
+            attribute_storage,
+            globals,
+            assoc_events: AssociatedEvents::new(),
+            focus_queue: FocusQueue::new(),
+            timers: Timers::new(),
+            tasks: Tasks::new(),
+            animations: Animations::new(),
+            fps_now: Instant::now(),
+            dt: Instant::now(),
+            frame: 0,
+            ticking: false,
+        }
+    }
+}
+
+/// Step through the frames of a runtime, see [`Runtime::with_frames`].
+///
+/// Every frame waits the same way the runtime does, for the next event,
+/// message or timer, or for the next frame if something is ticking.
+pub struct Frames<'rt, 'bp, T, G, R = ()> {
+    runtime: &'rt mut Runtime<T, G, R>,
+    state: &'rt mut TreeState<'bp>,
+    stopped: bool,
+}
+
+impl<'rt, 'bp, T, G, R> Frames<'rt, 'bp, T, G, R>
+where
+    T: Backend,
+    G: GlobalEvents,
+{
+    pub(crate) fn new(runtime: &'rt mut Runtime<T, G, R>, state: &'rt mut TreeState<'bp>) -> Self {
+        Self {
+            runtime,
+            state,
+            stopped: false,
+        }
+    }
+
+    /// Run a number of frames.
+    /// Once the runtime is stopped, or a component asks to exit, no more frames are run.
+    pub fn run(&mut self, frames: usize) -> Result<()> {
+        for _ in 0..frames {
+            if self.stopped {
+                break;
+            }
+
+            match self.runtime.frame(self.state) {
+                Ok(true) => (),
+                Ok(false) | Err(Error::Stop) => self.stopped = true,
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true once the runtime is stopped, or a component asked to exit
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Access the backend
+    pub fn backend(&self) -> &T {
+        &self.runtime.backend
+    }
+
+    /// Mutable access to the backend
+    pub fn backend_mut(&mut self) -> &mut T {
+        &mut self.runtime.backend
+    }
+}
@@ -2,8 +2,8 @@
 //   - Runtime -
 //   1. Creating the initial widget tree
 //   2. Runtime loop      <--------------------------------+
-//    ^  2.1. Wait for messages                            |
-//    |  2.2. Wait for events                              v
+//    ^  2.1. Handle timers and messages                   |
+//    |  2.2. Handle events                                v
 //    |  2.4. Was there events / messages / data changes? (no) (yes)
 //    |                                                         ^
 //    |                                                         |
@@ -13,11 +13,13 @@
 //    |       1. Layout
 //    |       2. Position
 //    |       3. Draw
-//    +------ 4. Run again
+//    |       4. Sleep until the next frame is allowed
+//    +------ 5. Sleep until there is an event, a message or a timer is due
 //
 // -----------------------------------------------------------------------------
 
-use std::fmt::Write;
+use std::any::Any;
+use std::marker::PhantomData;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
 
@@ -26,32 +28,44 @@ use anathema_default_widgets::register_default_widgets;
 use anathema_state::{
     clear_all_changes, clear_all_futures, clear_all_subs, drain_changes, drain_futures, Changes, FutureValues, States,
 };
+use anathema_store::smallmap::SmallIndex;
 use anathema_store::tree::root_node;
 use anathema_templates::blueprints::Blueprint;
-use anathema_templates::{Document, Globals, ToSourceKind};
+use anathema_templates::{Document, Expression, Globals, ToSourceKind};
+use anathema_widgets::components::animation::Animations;
+use anathema_widgets::components::events::Event;
+use anathema_widgets::components::exit::{Exit, ExitHandle};
+use anathema_widgets::components::tasks::Tasks;
+use anathema_widgets::components::timers::Timers;
 use anathema_widgets::components::{
-    AssociatedEvents, Component, ComponentId, ComponentKind, ComponentRegistry, Emitter, FocusQueue, UntypedContext,
-    ViewMessage,
+    AssociatedEvents, Component, ComponentId, ComponentKind, ComponentRegistry, Emitter, FocusQueue, Recipient,
+    UntypedContext, ViewMessage,
 };
+use anathema_widgets::expressions::EvalValue;
+use anathema_widgets::functions::{FunctionResult, FunctionValue};
 use anathema_widgets::layout::{Constraints, Viewport};
 use anathema_widgets::{
     eval_blueprint, try_resolve_future_values, update_tree, AttributeStorage, Components, DirtyWidgets, EvalContext,
-    Factory, FloatingWidgets, Scope, WidgetKind, WidgetTree,
+    Factory, FloatingWidgets, Scope, WidgetId, WidgetKind, WidgetTree,
 };
 use events::{EventCtx, EventHandler};
-use notify::{recommended_watcher, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use flume::Selector;
+use notify::{recommended_watcher, RecommendedWatcher, RecursiveMode, Watcher};
 use tree::Tree;
 
-pub use self::events::{GlobalContext, GlobalEvents};
+pub use self::events::{GlobalAction, GlobalContext, GlobalEvents};
+pub use self::frames::Frames;
+use self::frames::TreeState;
 pub use crate::error::{Error, Result};
 
 static REBUILD: AtomicBool = AtomicBool::new(false);
 
 mod error;
 mod events;
+mod frames;
 mod tree;
 
-pub struct RuntimeBuilder<T, G> {
+pub struct RuntimeBuilder<T, G, R = ()> {
     document: Document,
     component_registry: ComponentRegistry,
     backend: T,
@@ -59,9 +73,11 @@ pub struct RuntimeBuilder<T, G> {
     message_receiver: flume::Receiver<ViewMessage>,
     emitter: Emitter,
     global_events: G,
+    exit: Exit,
+    exit_value: PhantomData<R>,
 }
 
-impl<T, G: GlobalEvents> RuntimeBuilder<T, G> {
+impl<T, G: GlobalEvents, R> RuntimeBuilder<T, G, R> {
     /// Registers a [Component] with the runtime.
     /// This returns a unique [ComponentId] that is used to send messages to the component.
     ///
@@ -81,7 +97,7 @@ impl<T, G: GlobalEvents> RuntimeBuilder<T, G> {
         Ok(id.into())
     }
 
-    pub fn global_events<U>(self, global_events: U) -> RuntimeBuilder<T, U> {
+    pub fn global_events<U>(self, global_events: U) -> RuntimeBuilder<T, U, R> {
         RuntimeBuilder {
             document: self.document,
             component_registry: self.component_registry,
@@ -90,9 +106,60 @@ impl<T, G: GlobalEvents> RuntimeBuilder<T, G> {
             message_receiver: self.message_receiver,
             emitter: self.emitter,
             global_events,
+            exit: self.exit,
+            exit_value: self.exit_value,
         }
     }
 
+    /// Set the type of the value returned from [`Runtime::run`].
+    /// The value is set with an [`ExitHandle`], see [`RuntimeBuilder::exit_handle`].
+    ///
+    /// This has to be called before any exit handles are created,
+    /// as they are tied to the type of the exit value.
+    pub fn exit_value<U>(self) -> RuntimeBuilder<T, G, U> {
+        RuntimeBuilder {
+            document: self.document,
+            component_registry: self.component_registry,
+            backend: self.backend,
+            factory: self.factory,
+            message_receiver: self.message_receiver,
+            emitter: self.emitter,
+            global_events: self.global_events,
+            exit: self.exit,
+            exit_value: PhantomData,
+        }
+    }
+
+    /// Returns an [`ExitHandle`] to stop the runtime with a value,
+    /// which is returned from [`Runtime::run`].
+    ///
+    /// ```
+    /// # use anathema_runtime::Runtime;
+    /// # use anathema_templates::Document;
+    /// # use anathema_backend::test::TestBackend;
+    /// # let backend = TestBackend::new((10, 10));
+    /// let builder = Runtime::builder(Document::new("text"), backend).exit_value::<String>();
+    /// let exit = builder.exit_handle();
+    /// exit.quit_with(String::from("selected"));
+    /// ```
+    ///
+    /// The value has to be of the exit type of the runtime:
+    /// ```compile_fail
+    /// # use anathema_runtime::Runtime;
+    /// # use anathema_templates::Document;
+    /// # use anathema_backend::test::TestBackend;
+    /// # let backend = TestBackend::new((10, 10));
+    /// let builder = Runtime::builder(Document::new("text"), backend).exit_value::<String>();
+    /// let exit = builder.exit_handle();
+    /// exit.quit_with(1);
+    /// ```
+    pub fn exit_handle(&self) -> ExitHandle<R>
+    where
+        R: Any + Send,
+    {
+        self.exit.handle()
+    }
+
     /// Registers a [Component] as a prototype with the [Runtime],
     /// which allows for multiple instances of the component to exist the templates.
     pub fn register_prototype<FC, FS, C>(
@@ -141,23 +208,73 @@ impl<T, G: GlobalEvents> RuntimeBuilder<T, G> {
         Ok(id.into())
     }
 
+    /// Registers a partial: a template without state that is inlined with `include "name"`.
+    ///
+    /// ```ignore
+    /// runtime.register_partial("card", "card.aml")?;
+    /// ```
+    /// ```text
+    /// include "card" { title: "Hello" }
+    /// ```
+    pub fn register_partial(&mut self, ident: impl Into<String>, template: impl ToSourceKind) -> Result<()> {
+        self.document.add_partial(ident, template.to_source_kind())?;
+        Ok(())
+    }
+
+    /// Registers a function that can be called from templates.
+    ///
+    /// ```ignore
+    /// runtime.register_function("double", |args| {
+    ///     let value = args.first()?.load_number()?;
+    ///     Some((value.as_int() * 2).into())
+    /// });
+    /// ```
+    /// The function can also be used as a filter, e.g `text count | double`,
+    /// where the filtered value is the first argument.
+    /// A function with the same name as a built-in function will replace the built-in one.
+    pub fn register_function(&mut self, ident: &str, f: impl Fn(&[EvalValue<'_>]) -> Option<FunctionValue> + 'static) {
+        self.factory.register_function(ident, f);
+    }
+
+    /// Registers a function that can fail.
+    /// The error is shown once the current frame is done.
+    ///
+    /// ```ignore
+    /// runtime.register_fallible_function("sqrt", |args| {
+    ///     let Some(value) = args.first().and_then(|arg| arg.load_number()) else { return Ok(None) };
+    ///     match value.as_float() {
+    ///         value if value < 0.0 => Err(format!("sqrt: {value} is negative")),
+    ///         value => Ok(Some(value.sqrt().into())),
+    ///     }
+    /// });
+    /// ```
+    pub fn register_fallible_function(
+        &mut self,
+        ident: &str,
+        f: impl Fn(&[EvalValue<'_>]) -> FunctionResult + 'static,
+    ) {
+        self.factory.register_fallible_function(ident, f);
+    }
+
     /// Returns an [Emitter] to send messages to components
     pub fn emitter(&self) -> Emitter {
         self.emitter.clone()
     }
 
-    fn set_watcher(&mut self) -> Result<RecommendedWatcher> {
+    fn set_watcher(&mut self, reload: flume::Sender<()>) -> Result<RecommendedWatcher> {
         let paths = self
             .document
             .template_paths()
             .filter_map(|p| p.canonicalize().ok())
             .collect::<Vec<_>>();
 
-        let mut watcher = recommended_watcher(move |event: std::result::Result<Event, _>| match event {
+        let mut watcher = recommended_watcher(move |event: std::result::Result<notify::Event, _>| match event {
             Ok(event) => match event.kind {
                 notify::EventKind::Create(_) | notify::EventKind::Remove(_) | notify::EventKind::Modify(_) => {
                     if paths.iter().any(|p| event.paths.contains(p)) {
                         REBUILD.store(true, Ordering::Relaxed);
+                        // Wake up the runtime if it's sleeping
+                        let _ = reload.try_send(());
                     }
                 }
                 notify::EventKind::Any | notify::EventKind::Access(_) | notify::EventKind::Other => (),
@@ -178,14 +295,17 @@ impl<T, G: GlobalEvents> RuntimeBuilder<T, G> {
 
     /// Builds the [Runtime].
     /// Fails if compiling the [Document] or creating the file watcher fails.
-    pub fn finish(mut self) -> Result<Runtime<T, G>>
+    pub fn finish(mut self) -> Result<Runtime<T, G, R>>
     where
         T: Backend,
     {
         let (blueprint, globals) = self.document.compile()?;
-        let watcher = match self.document.hot_reload {
-            false => None,
-            true => Some(self.set_watcher()?),
+        let (watcher, reload) = match self.document.hot_reload {
+            false => (None, None),
+            true => {
+                let (tx, rx) = flume::bounded(1);
+                (Some(self.set_watcher(tx)?), Some(rx))
+            }
         };
 
         let (width, height) = self.backend.size().into();
@@ -210,6 +330,13 @@ impl<T, G: GlobalEvents> RuntimeBuilder<T, G> {
             floating_widgets: FloatingWidgets::empty(),
             components: Components::new(),
             dirty_widgets: DirtyWidgets::empty(),
+            exit: self.exit,
+            exit_value: self.exit_value,
+            events: None,
+            pending_event: None,
+            pending_message: None,
+            reload,
+            last_render: Instant::now(),
             event_handler: EventHandler::new(self.global_events),
         };
 
@@ -227,7 +354,12 @@ impl<T, G: GlobalEvents> RuntimeBuilder<T, G> {
 /// let document = Document::new("border");
 /// let mut runtime = Runtime::builder(document, backend).finish().unwrap();
 /// ```
-pub struct Runtime<T, G> {
+///
+/// `R` is the type of the value returned from [`Runtime::run`],
+/// see [`RuntimeBuilder::exit_value`].
+pub struct Runtime<T, G, R = ()> {
+    /// The upper bound for the number of frames rendered per second.
+    /// This is also the rate at which ticking components are ticked.
     pub fps: u16,
 
     _watcher: Option<RecommendedWatcher>,
@@ -255,6 +387,14 @@ pub struct Runtime<T, G> {
     // * Event handling
     components: Components,
     dirty_widgets: DirtyWidgets,
+    exit: Exit,
+    exit_value: PhantomData<R>,
+    // * Waiting
+    events: Option<flume::Receiver<Event>>,
+    pending_event: Option<Event>,
+    pending_message: Option<ViewMessage>,
+    reload: Option<flume::Receiver<()>>,
+    last_render: Instant,
     // tab_indices: TabIndices,
 
     // -----------------------------------------------------------------------------
@@ -281,7 +421,6 @@ where
 
         let (message_sender, message_receiver) = flume::unbounded();
         register_default_widgets(&mut factory);
-
         RuntimeBuilder {
             backend,
             document,
@@ -290,11 +429,13 @@ where
             emitter: message_sender.into(),
             message_receiver,
             global_events: (),
+            exit: Exit::new(),
+            exit_value: PhantomData,
         }
     }
 }
 
-impl<T, G> Runtime<T, G>
+impl<T, G, R> Runtime<T, G, R>
 where
     T: Backend,
     G: GlobalEvents,
@@ -384,11 +525,15 @@ where
         attribute_storage: &mut AttributeStorage<'bp>,
         assoc_events: &mut AssociatedEvents,
         focus_queue: &mut FocusQueue<'static>,
+        timers: &mut Timers,
+        animations: &mut Animations,
+        tasks: &mut Tasks,
     ) -> Duration {
         let context = UntypedContext {
             emitter: &self.emitter,
             viewport: self.viewport,
             strings: &mut self.document.strings,
+            exit: &self.exit,
         };
 
         let mut event_ctx = EventCtx {
@@ -398,15 +543,23 @@ where
             attribute_storage,
             assoc_events,
             focus_queue,
+            timers,
+            animations,
+            tasks,
             context,
         };
 
-        while let Ok(msg) = self.message_receiver.try_recv() {
-            if let Some((widget_id, state_id)) = event_ctx
-                .components
-                .get_by_component_id(msg.recipient())
-                .map(|e| (e.widget_id, e.state_id))
-            {
+        while let Some(msg) = self
+            .pending_message
+            .take()
+            .or_else(|| self.message_receiver.try_recv().ok())
+        {
+            let entry = match msg.recipient() {
+                Recipient::Component(id) => event_ctx.components.get_by_component_id(id),
+                Recipient::Widget(id) => event_ctx.components.get_by_widget_id(id),
+            };
+
+            if let Some((widget_id, state_id)) = entry.map(|e| (e.widget_id, e.state_id)) {
                 tree.with_component(widget_id, state_id, &mut event_ctx, |a, b| {
                     a.any_message(msg.payload(), b)
                 });
@@ -421,32 +574,132 @@ where
         fps_now.elapsed()
     }
 
-    /// Start the runtime
-    pub fn run(&mut self) {
+    /// Start the runtime.
+    ///
+    /// The runtime runs until it's stopped, either by a [`Event::Stop`],
+    /// a component calling `quit` on its context, or an [`ExitHandle`].
+    /// The value passed to `quit_with` is returned, and `None` otherwise.
+    /// If the value is not of type `R` an [`Error::ExitValue`] is returned.
+    ///
+    /// ```ignore
+    /// let mut builder = Runtime::builder(document, backend).exit_value::<String>();
+    /// let exit = builder.exit_handle();
+    /// builder.register_component("picker", "picker.aml", Picker { exit }, PickerState::new())?;
+    /// let selected = builder.finish()?.run()?;
+    /// if let Some(selected) = selected {
+    ///     println!("{selected}");
+    /// }
+    /// ```
+    pub fn run(&mut self) -> Result<Option<R>>
+    where
+        R: 'static,
+    {
         self.backend.finalize();
+        self.events = self.backend.event_receiver();
+        self.exit.clear();
         loop {
             match self.internal_run() {
-                Ok(()) => (),
-                Err(Error::Stop) => return,
+                Ok(()) if self.exit.is_requested() => break,
+                Err(Error::Stop) => break,
+                // Rebuild the tree from the templates on disk
+                Ok(()) => {
+                    if let Err(err) = self.reload() {
+                        self.show_error(err);
+                    }
+                }
                 Err(err) => self.show_error(err),
             }
         }
+
+        match self.exit.take() {
+            Some(value) => value.downcast().map(|value| Some(*value)).map_err(|_| Error::ExitValue),
+            None => Ok(None),
+        }
+    }
+
+    /// Build the tree and run a fixed number of frames.
+    /// Every call builds a new tree, use [`Runtime::with_frames`] to keep
+    /// the tree and the state between frames.
+    ///
+    /// This is intended for testing together with the
+    /// [`TestBackend`](anathema_backend::test::TestBackend).
+    ///
+    /// ```
+    /// # use anathema_runtime::Runtime;
+    /// # use anathema_templates::Document;
+    /// # use anathema_backend::test::TestBackend;
+    /// let document = Document::new("text 'hello'");
+    /// let backend = TestBackend::new((5, 1));
+    /// let mut runtime = Runtime::builder(document, backend).finish().unwrap();
+    /// runtime.run_frames(1).unwrap();
+    /// assert_eq!(runtime.backend().last_frame().unwrap().to_string(), "hello\n");
+    /// ```
+    pub fn run_frames(&mut self, frames: usize) -> Result<()> {
+        self.with_frames(|f| f.run(frames))?
+    }
+
+    /// Build the tree and step through frames, keeping the tree and the state
+    /// (timers, animations and focus) between the frames.
+    /// The tree is dropped when `f` returns.
+    ///
+    /// This is intended for testing together with the
+    /// [`TestBackend`](anathema_backend::test::TestBackend).
+    ///
+    /// ```
+    /// # use anathema_runtime::Runtime;
+    /// # use anathema_templates::Document;
+    /// # use anathema_backend::test::TestBackend;
+    /// let document = Document::new("text 'hello'");
+    /// let backend = TestBackend::new((5, 1));
+    /// let mut runtime = Runtime::builder(document, backend).finish().unwrap();
+    /// runtime
+    ///     .with_frames(|frames| {
+    ///         frames.run(1)?;
+    ///         frames.backend_mut().resize_to((3, 1));
+    ///         frames.run(1)
+    ///     })
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(runtime.backend().last_frame().unwrap().to_string(), "hel\n");
+    /// ```
+    pub fn with_frames<U>(&mut self, f: impl FnOnce(&mut Frames<'_, '_, T, G, R>) -> U) -> Result<U> {
+        self.backend.finalize();
+        self.events = self.backend.event_receiver();
+        self.exit.clear();
+        self.with_tree(|runtime, state| {
+            let mut frames = Frames::new(runtime, state);
+            Ok(f(&mut frames))
+        })
+    }
+
+    /// Access the backend
+    pub fn backend(&self) -> &T {
+        &self.backend
+    }
+
+    /// Mutable access to the backend
+    pub fn backend_mut(&mut self) -> &mut T {
+        &mut self.backend
     }
 
     // 1 - Tries to build the tree
     // 2 - Selects the first [Component] and calls [Component::on_focus] on it
-    // 3 - Repeatedly calls [Self::tick] until [REBUILD] is set to true or an error occurs. Using the [Error::Stop] breaks the main loop.
+    // 3 - Repeatedly calls [Self::frame] until [REBUILD] is set to true,
+    //     a component asks to quit, or an error occurs. Using the [Error::Stop] breaks the main loop.
     // 4 - Resets using [Self::reset]
-    // 5 - Recursively calls [Self::internal_run].
-    // TODO: We should move this into a loop in [Self::run].
+    // [Self::run] calls this in a loop, reloading the templates in between.
     fn internal_run(&mut self) -> Result<()> {
-        let mut fps_now = Instant::now();
-        let sleep_micros = ((1.0 / self.fps as f64) * 1000.0 * 1000.0) as u128;
+        self.with_tree(|runtime, state| {
+            while runtime.frame(state)? {}
+            Ok(())
+        })
+    }
+
+    // Build the tree and call `f` with the tree and the state that lives as long as the tree,
+    // then reset the runtime.
+    fn with_tree<U>(&mut self, f: impl for<'bp> FnOnce(&mut Self, &mut TreeState<'bp>) -> Result<U>) -> Result<U> {
         let mut tree = WidgetTree::empty();
         let mut attribute_storage = AttributeStorage::empty();
-        let mut assoc_events = AssociatedEvents::new();
-        let mut focus_queue = FocusQueue::new();
-
         let mut states = States::new();
         let mut scope = Scope::new();
         let globals = self.globals.take();
@@ -465,102 +718,126 @@ where
         let blueprint = self.blueprint.clone();
 
         // First build the tree
-        let res = eval_blueprint(&blueprint, &mut ctx, root_node(), &mut tree);
-
-        match res {
-            Ok(_) => (),
-            Err(err) => {
-                match self.reset(tree, &mut states) {
-                    Ok(()) => (),
-                    Err(err) => return Err(err),
-                }
-                return Err(err.into());
-            }
+        let res = self
+            .factory
+            .functions()
+            .check(&blueprint)
+            .and_then(|()| eval_blueprint(&blueprint, &mut ctx, root_node(), &mut tree));
+
+        if let Err(err) = res {
+            self.reset(tree, &mut states);
+            self.globals = globals;
+            return Err(err.into());
         }
 
-        let mut dt = Instant::now();
+        let mut state = TreeState::new(tree, states, attribute_storage, &globals);
 
         // Initial layout, position and paint
         WidgetCycle::new(
             &mut self.backend,
-            &mut tree,
+            &mut state.tree,
             self.constraints,
-            &attribute_storage,
+            &state.attribute_storage,
             &self.floating_widgets,
             self.viewport,
         )
         .run();
         self.backend.render();
         self.backend.clear();
+        self.last_render = Instant::now();
 
         // Try to set focus on the first available component
         let context = UntypedContext {
             emitter: &self.emitter,
             viewport: self.viewport,
             strings: &self.document.strings,
+            exit: &self.exit,
         };
 
         let mut event_ctx = EventCtx {
             components: &mut self.components,
             dirty_widgets: &mut self.dirty_widgets,
-            states: &mut states,
-            attribute_storage: &mut attribute_storage,
-            assoc_events: &mut assoc_events,
+            states: &mut state.states,
+            attribute_storage: &mut state.attribute_storage,
+            assoc_events: &mut state.assoc_events,
             context,
-            focus_queue: &mut focus_queue,
+            focus_queue: &mut state.focus_queue,
+            timers: &mut state.timers,
+            animations: &mut state.animations,
+            tasks: &mut state.tasks,
         };
 
-        self.event_handler.set_initial_focus(&mut tree, &mut event_ctx);
+        self.event_handler.set_initial_focus(&mut state.tree, &mut event_ctx);
 
-        loop {
-            self.tick(
-                fps_now,
-                &mut dt,
-                sleep_micros,
-                &mut tree,
-                &mut states,
-                &mut attribute_storage,
-                &globals,
-                &mut assoc_events,
-                &mut focus_queue,
-            )?;
-
-            if REBUILD.swap(false, Ordering::Relaxed) {
-                break;
-            }
+        let res = f(self, &mut state);
+        let TreeState {
+            tree,
+            mut states,
+            attribute_storage,
+            mut tasks,
+            ..
+        } = state;
+        // The results of the tasks would go to the components of this tree
+        tasks.cancel_all();
+        self.reset(tree, &mut states);
+        drop(attribute_storage);
+        self.globals = globals;
+        res
+    }
+
+    // Run a single frame, waiting first if this is not the first frame.
+    // Returns false if the tree has to be rebuilt or the runtime should exit.
+    fn frame(&mut self, state: &mut TreeState<'_>) -> Result<bool> {
+        let sleep_micros = ((1.0 / self.fps as f64) * 1000.0 * 1000.0) as u128;
 
-            fps_now = Instant::now();
+        if state.frame > 0 {
+            self.wait(state.fps_now, sleep_micros, state.ticking, &state.timers);
+            state.fps_now = Instant::now();
         }
+        state.frame += 1;
 
-        self.reset(tree, &mut states)
+        state.ticking = self.tick(
+            state.fps_now,
+            &mut state.dt,
+            sleep_micros,
+            &mut state.tree,
+            &mut state.states,
+            &mut state.attribute_storage,
+            state.globals,
+            &mut state.assoc_events,
+            &mut state.focus_queue,
+            &mut state.timers,
+            &mut state.animations,
+            &mut state.tasks,
+        )?;
+
+        let stop = REBUILD.swap(false, Ordering::Relaxed) || self.exit.is_requested();
+        Ok(!stop)
     }
 
     pub fn show_error(&mut self, err: Error) {
+        // The error is passed as a global rather than written into the template,
+        // as the source snippet can contain anything the template syntax can
         let tpl = "
             align [alignment: 'centre']
                 border [background: 'red']
                     vstack
-                        @errors
-        "
-        .to_string();
-
-        let errors = err.to_string().lines().fold(String::new(), |mut s, line| {
-            let _ = writeln!(&mut s, "text [foreground: 'black'] '{line}'");
-            s
-        });
+                        for line in error_lines
+                            text [foreground: 'black'] line
+        ";
 
         let mut document = Document::new(tpl);
-        let _component_id = document.add_component("errors", errors.to_template());
-        let (blueprint, globals) = document.compile().expect("the error template can't fail");
+        let (blueprint, mut globals) = document.compile().expect("the error template can't fail");
+        let lines = err.to_string().lines().map(Expression::from).collect::<Vec<_>>();
+        globals.insert("error_lines", Expression::List(lines.into()));
         self.blueprint = blueprint;
         self.globals = globals;
     }
 
     // Resets the Runtime:
-    // * Reloads all components
+    // * Clears all pending changes and futures
     // * Moves all the components from the tree back to the registry.
-    // * Recompiles the document
-    fn reset(&mut self, tree: WidgetTree<'_>, states: &mut States) -> Result<()> {
+    fn reset(&mut self, tree: WidgetTree<'_>, states: &mut States) {
         clear_all_futures();
         clear_all_changes();
         clear_all_subs();
@@ -568,10 +845,6 @@ where
         self.components = Components::new();
         self.floating_widgets = FloatingWidgets::empty();
 
-        // The only way we can get here is if we break the loop
-        // as a result of the hot_reload triggering or when building the first tree fails.
-        self.document.reload_templates()?;
-
         // Move all components from the tree back to the registry.
         for (_, widget) in tree.values().into_iter() {
             let WidgetKind::Component(comp) = widget else { continue };
@@ -580,11 +853,15 @@ where
             self.component_registry
                 .return_component(comp.component_id, comp.dyn_component, state);
         }
+    }
 
+    // Reloads the templates and recompiles the document.
+    // This is done when hot reloading, not when the runtime stops.
+    fn reload(&mut self) -> Result<()> {
+        self.document.reload_templates()?;
         let (blueprint, globals) = self.document.compile()?;
         self.blueprint = blueprint;
         self.globals = globals;
-
         Ok(())
     }
 
@@ -599,10 +876,16 @@ where
         globals: &'bp Globals,
         assoc_events: &mut AssociatedEvents,
         focus_queue: &mut FocusQueue<'static>,
-    ) -> Result<()> {
+        timers: &mut Timers,
+        animations: &mut Animations,
+        tasks: &mut Tasks,
+    ) -> Result<bool> {
         // Clear the text buffer
         // self.string_storage.clear();
 
+        // Send the messages of all timers that are due
+        timers.send_due(Instant::now(), &self.emitter);
+
         // Pull and keep consuming events while there are events present in the queue.
         let poll_duration = self.handle_messages(
             fps_now,
@@ -612,15 +895,33 @@ where
             attribute_storage,
             assoc_events,
             focus_queue,
+            timers,
+            animations,
+            tasks,
+        );
+
+        // Call the `tick` function on all ticking components
+        let ticking = self.tick_components(
+            tree,
+            states,
+            attribute_storage,
+            dt.elapsed(),
+            assoc_events,
+            focus_queue,
+            timers,
+            animations,
+            tasks,
         );
 
-        // Call the `tick` function on all components
-        self.tick_components(tree, states, attribute_storage, dt.elapsed(), assoc_events, focus_queue);
+        // Advance all running animations to the time of the frame
+        animations.update(fps_now, states);
+        let ticking = ticking || !animations.is_empty();
 
         let context = UntypedContext {
             emitter: &self.emitter,
             viewport: self.viewport,
             strings: &self.document.strings,
+            exit: &self.exit,
         };
 
         let mut event_ctx = EventCtx {
@@ -631,25 +932,58 @@ where
             assoc_events,
             context,
             focus_queue,
+            timers,
+            animations,
+            tasks,
+        };
+
+        let viewport_size = self.viewport.size();
+
+        // Events are already waiting in the receiver, so there is no need to block
+        let poll_duration = match self.events {
+            Some(_) => Duration::ZERO,
+            None => poll_duration,
         };
 
         self.event_handler.handle(
+            &mut self.pending_event,
             poll_duration,
             fps_now,
             sleep_micros,
             &mut self.backend,
             &mut self.viewport,
             tree,
+            &self.floating_widgets,
             &mut self.constraints,
             &mut event_ctx,
         )?;
 
+        self.event_handler.expire_keymaps(Instant::now(), tree, &mut event_ctx);
+
         *dt = Instant::now();
 
         self.apply_futures(globals, tree, states, attribute_storage);
 
         self.apply_changes(globals, tree, states, attribute_storage);
 
+        // The changes made by components in response to changes
+        // in their external state are applied in the same frame
+        let external_changes = self.components.take_external_changes();
+        if !external_changes.is_empty() {
+            self.external_changes(
+                external_changes,
+                tree,
+                states,
+                attribute_storage,
+                assoc_events,
+                focus_queue,
+                timers,
+                animations,
+                tasks,
+            );
+            self.apply_changes(globals, tree, states, attribute_storage);
+        }
+
         // -----------------------------------------------------------------------------
         //   - Update dirty widgets -
         //   Mark dirty widgets for redraw, along with their parents
@@ -662,12 +996,17 @@ where
             self.floating_widgets.try_remove(key);
             // TODO: this function is rubbish and has to be rewritten
             self.components.dodgy_remove(key);
+            tasks.cancel(key);
+            timers.remove(key);
+            animations.remove(key);
         }
 
         // -----------------------------------------------------------------------------
         //   - Layout, position and paint -
         // -----------------------------------------------------------------------------
-        let needs_reflow = !self.changes.is_empty() || !self.dirty_widgets.is_empty();
+        // A resize has to redraw everything even if nothing else changed
+        let resized = viewport_size != self.viewport.size();
+        let needs_reflow = resized || !self.changes.is_empty() || !self.dirty_widgets.is_empty();
         if needs_reflow {
             let mut cycle = WidgetCycle::new(
                 &mut self.backend,
@@ -683,14 +1022,132 @@ where
             self.backend.clear();
             self.changes.clear();
             self.dirty_widgets.clear();
+            self.last_render = Instant::now();
         }
 
-        let sleep = sleep_micros.saturating_sub(fps_now.elapsed().as_micros()) as u64;
-        if sleep > 0 {
-            std::thread::sleep(Duration::from_micros(sleep));
+        // Errors reported by template functions while laying out and painting
+        if let Some(err) = self.factory.functions().take_error() {
+            return Err(err.into());
         }
 
-        Ok(())
+        Ok(ticking)
+    }
+
+    // Sleep until there is something to do:
+    // * An event or a message is received
+    // * A template changed (when hot reloading)
+    // * A timer is due
+    // * The next frame is due, if there are ticking components
+    //
+    // After a frame is rendered there is no waking up until the next frame is allowed,
+    // so the frame rate never exceeds `fps`.
+    fn wait(&mut self, fps_now: Instant, sleep_micros: u128, ticking: bool, timers: &Timers) {
+        let frame = Duration::from_micros(sleep_micros as u64);
+        let next_frame = fps_now + frame;
+
+        if self.last_render >= fps_now {
+            sleep_until(self.last_render + frame);
+        }
+
+        let mut deadline = timers.next_deadline();
+
+        // A partial key sequence is passed on when it times out
+        if let Some(keymap_deadline) = self.event_handler.keymap_deadline() {
+            deadline = Some(deadline.map_or(keymap_deadline, |deadline| deadline.min(keymap_deadline)));
+        }
+
+        if ticking {
+            deadline = Some(deadline.map_or(next_frame, |deadline| deadline.min(next_frame)));
+        }
+
+        // A backend without an event receiver has to be polled every frame
+        let Some(events) = &self.events else {
+            sleep_until(deadline.map_or(next_frame, |deadline| deadline.min(next_frame)));
+            return;
+        };
+
+        enum Wake {
+            Event(Event),
+            Message(ViewMessage),
+            Reload,
+            Disconnected,
+        }
+
+        let mut selector = Selector::new()
+            .recv(events, |event| event.map_or(Wake::Disconnected, Wake::Event))
+            .recv(&self.message_receiver, |msg| {
+                msg.map_or(Wake::Disconnected, Wake::Message)
+            });
+
+        if let Some(reload) = &self.reload {
+            selector = selector.recv(reload, |_| Wake::Reload);
+        }
+
+        let wake = match deadline {
+            Some(deadline) => selector.wait_deadline(deadline).ok(),
+            None => Some(selector.wait()),
+        };
+
+        match wake {
+            Some(Wake::Event(event)) => self.pending_event = Some(event),
+            Some(Wake::Message(msg)) => self.pending_message = Some(msg),
+            // The `REBUILD` flag is checked after the next tick
+            Some(Wake::Reload) => (),
+            // Fall back to polling the backend
+            Some(Wake::Disconnected) => self.events = None,
+            None => (),
+        }
+    }
+
+    // Let components know that their external state changed
+    fn external_changes<'bp>(
+        &mut self,
+        changes: Vec<(WidgetId, SmallIndex)>,
+        tree: &mut WidgetTree<'bp>,
+        states: &mut States,
+        attribute_storage: &mut AttributeStorage<'bp>,
+        assoc_events: &mut AssociatedEvents,
+        focus_queue: &mut FocusQueue<'static>,
+        timers: &mut Timers,
+        animations: &mut Animations,
+        tasks: &mut Tasks,
+    ) {
+        let context = UntypedContext {
+            emitter: &self.emitter,
+            viewport: self.viewport,
+            strings: &self.document.strings,
+            exit: &self.exit,
+        };
+
+        let mut event_ctx = EventCtx {
+            components: &mut self.components,
+            dirty_widgets: &mut self.dirty_widgets,
+            states,
+            attribute_storage,
+            assoc_events,
+            focus_queue,
+            timers,
+            animations,
+            tasks,
+            context,
+        };
+
+        for (widget_id, index) in changes {
+            let Some(WidgetKind::Component(component)) = tree.get_ref_by_id(widget_id) else { continue };
+            let Some(external_state) = &component.external_state else { continue };
+            let Some(key) = external_state
+                .iter()
+                .find(|(_, (i, _))| *i == index)
+                .map(|(key, _)| *key)
+            else {
+                continue;
+            };
+
+            let state_id = component.state_id;
+            tree.with_component(widget_id, state_id, &mut event_ctx, |comp, ctx| {
+                comp.any_external_change(ctx, key)
+            });
+        }
     }
 
     fn tick_components<'bp>(
@@ -701,13 +1158,18 @@ where
         dt: Duration,
         assoc_events: &mut AssociatedEvents,
         focus_queue: &mut FocusQueue<'static>,
-    ) {
+        timers: &mut Timers,
+        animations: &mut Animations,
+        tasks: &mut Tasks,
+    ) -> bool {
         let context = UntypedContext {
             emitter: &self.emitter,
             viewport: self.viewport,
             strings: &self.document.strings,
+            exit: &self.exit,
         };
 
+        let mut ticking = false;
         for i in 0..self.components.len() {
             let (widget_id, state_id) = self
                 .components
@@ -721,10 +1183,29 @@ where
                 attribute_storage,
                 assoc_events,
                 focus_queue,
+                timers,
+                animations,
+                tasks,
                 context,
             };
 
-            tree.with_component(widget_id, state_id, &mut event_ctx, |a, b| a.any_tick(b, dt));
+            let ticks = tree.with_component(widget_id, state_id, &mut event_ctx, |a, b| {
+                let ticks = a.any_ticks();
+                if ticks {
+                    a.any_tick(b, dt);
+                }
+                ticks
+            });
+            ticking |= ticks.unwrap_or(false);
         }
+
+        ticking
+    }
+}
+
+fn sleep_until(deadline: Instant) {
+    let duration = deadline.saturating_duration_since(Instant::now());
+    if !duration.is_zero() {
+        std::thread::sleep(duration);
     }
 }
@@ -40,10 +40,15 @@ impl<'bp> Tree<'bp> for WidgetTree<'bp> {
 
             let component_ctx = ComponentContext::new(
                 state_id,
+                component.component_id,
+                widget_id,
                 component.parent,
                 component.assoc_functions,
                 event_ctx.assoc_events,
                 event_ctx.focus_queue,
+                event_ctx.timers,
+                event_ctx.animations,
+                event_ctx.tasks,
                 component.external_state.as_ref(),
             );
 
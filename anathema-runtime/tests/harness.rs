@@ -0,0 +1,799 @@
+extern crate anathema_state as anathema;
+
+use std::time::Duration;
+
+use anathema_backend::test::{assert_snapshot, TestBackend};
+use anathema_backend::Backend;
+use anathema_default_widgets::{TextEditor, TextEditorState, TextInput, TextInputState};
+use anathema_geometry::Size;
+use anathema_runtime::{GlobalAction, GlobalContext, GlobalEvents, Runtime};
+use anathema_state::{State, Value};
+use anathema_store::tree::{Node, TreeValues};
+use anathema_templates::{Document, ToSourceKind};
+use anathema_widgets::components::animation::{Animation, Easing};
+use anathema_widgets::components::events::{Event, KeyCode, KeyEvent, KeyState, Modifiers};
+use anathema_widgets::components::exit::ExitHandle;
+use anathema_widgets::components::keymap::Keymap;
+use anathema_widgets::components::timers::TimerHandle;
+use anathema_widgets::components::{Component, Context};
+use anathema_widgets::{AttributeStorage, Element, Elements, WidgetKind};
+
+struct Counter;
+
+#[derive(State)]
+struct CounterState {
+    count: Value<usize>,
+    last: Value<String>,
+}
+
+impl Component for Counter {
+    type Message = ();
+    type State = CounterState;
+
+    fn on_key(
+        &mut self,
+        key: KeyEvent,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self>,
+    ) {
+        *state.count.to_mut() += 1;
+        if let KeyCode::Char(c) = key.code {
+            state.last.set(c.to_string());
+        }
+    }
+}
+
+fn runtime(size: (u16, u16)) -> Runtime<TestBackend, ()> {
+    let doc = Document::new("@counter");
+    let mut builder = Runtime::builder(doc, TestBackend::new(size));
+    let template = "
+        vstack
+            text 'count: ' count
+            text 'last: ' last
+    ";
+    builder
+        .register_component(
+            "counter",
+            template.to_template(),
+            Counter,
+            CounterState {
+                count: 0.into(),
+                last: String::new().into(),
+            },
+        )
+        .unwrap();
+
+    builder.finish().unwrap()
+}
+
+#[test]
+fn scripted_key_events() {
+    let mut runtime = runtime((10, 2));
+    runtime.backend_mut().press('a').next_frame().type_str("bc");
+    runtime.run_frames(2).unwrap();
+
+    let frames = runtime.backend().frames();
+    // The initial frame and one frame per batch of events
+    assert_eq!(frames.len(), 3);
+    assert_snapshot("tests/snapshots/initial.snap", &frames[0]);
+    assert_snapshot("tests/snapshots/first_key.snap", &frames[1]);
+    assert_snapshot("tests/snapshots/all_keys.snap", &frames[2]);
+}
+
+#[test]
+fn scripted_resize() {
+    let mut runtime = runtime((10, 2));
+    runtime.backend_mut().next_frame().resize_to((8, 1));
+    runtime.run_frames(2).unwrap();
+
+    let frame = runtime.backend().last_frame().unwrap();
+    assert_eq!(frame.size(), (8, 1).into());
+    assert_snapshot("tests/snapshots/resized.snap", frame);
+}
+
+#[test]
+fn step_through_frames() {
+    let mut runtime = runtime((10, 2));
+    runtime
+        .with_frames(|frames| {
+            frames.backend_mut().press('a');
+            frames.run(1)?;
+            frames.backend_mut().press('b');
+            frames.run(1)
+        })
+        .unwrap()
+        .unwrap();
+
+    // The tree is only built once, so there is only one initial frame
+    let frames = runtime.backend().frames();
+    assert_eq!(frames.len(), 3);
+    assert_eq!(frames[2].to_string(), "count: 2  \nlast: b   \n");
+}
+
+#[test]
+fn error_screen() {
+    let doc = Document::new("vstack\n    nope 'a {b}'");
+    let mut runtime = Runtime::builder(doc, TestBackend::new((40, 9))).finish().unwrap();
+
+    let err = runtime.run_frames(1).unwrap_err();
+    runtime.show_error(err);
+    runtime.run_frames(1).unwrap();
+
+    let frame = runtime.backend().last_frame().unwrap().to_string();
+    assert!(frame.contains("error: element `nope` does not exist"));
+    assert!(frame.contains("2 |     nope 'a {b}'"));
+    assert!(frame.contains("  |     ^"));
+}
+
+#[test]
+fn function_errors() {
+    let doc = Document::new("text nope(1)");
+    let mut runtime = Runtime::builder(doc, TestBackend::new((40, 1))).finish().unwrap();
+    let err = runtime.run_frames(1).unwrap_err().to_string();
+    assert!(err.starts_with("error: function `nope` does not exist"));
+    assert!(err.contains("1 | text nope(1)"));
+
+    let doc = Document::new("text round(1.5, -1)");
+    let mut runtime = Runtime::builder(doc, TestBackend::new((40, 1))).finish().unwrap();
+    let err = runtime.run_frames(2).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("round: the precision has to be between 0 and 15, got -1"));
+}
+
+struct Keys(Keymap<&'static str>);
+
+#[derive(State)]
+struct KeysState {
+    typed: Value<String>,
+}
+
+impl Component for Keys {
+    type Message = &'static str;
+    type State = KeysState;
+
+    fn on_key(
+        &mut self,
+        key: KeyEvent,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self>,
+    ) {
+        if let Some(c) = key.get_char() {
+            state.typed.to_mut().push(c);
+        }
+    }
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self>,
+    ) {
+        state.typed.to_mut().push_str(message);
+    }
+
+    fn keymap(&mut self) -> Option<&mut Keymap<Self::Message>> {
+        Some(&mut self.0)
+    }
+}
+
+fn keys<G: GlobalEvents>(keymap: Keymap<&'static str>, global: G) -> Runtime<TestBackend, G> {
+    let doc = Document::new("@keys");
+    let mut builder = Runtime::builder(doc, TestBackend::new((10, 1))).global_events(global);
+    builder
+        .register_component(
+            "keys",
+            "text typed".to_template(),
+            Keys(keymap),
+            KeysState {
+                typed: String::new().into(),
+            },
+        )
+        .unwrap();
+    builder.finish().unwrap()
+}
+
+#[test]
+fn keymap_passes_on_abandoned_keys() {
+    let keymap = Keymap::new().bind("g g", "<top>").unwrap();
+    let mut runtime = keys(keymap, ());
+    runtime.backend_mut().type_str("gxgg");
+    runtime.run_frames(1).unwrap();
+
+    let frame = runtime.backend().last_frame().unwrap().to_string();
+    assert_eq!(frame, "gx<top>   \n");
+}
+
+#[test]
+fn keymap_flushes_timed_out_keys() {
+    let keymap = Keymap::new().bind("g g", "<top>").unwrap().with_timeout(Duration::ZERO);
+    let mut runtime = keys(keymap, ());
+    runtime.backend_mut().press('g');
+    runtime.run_frames(1).unwrap();
+
+    let frame = runtime.backend().last_frame().unwrap().to_string();
+    assert_eq!(frame, "g         \n");
+}
+
+struct Global(Keymap<GlobalAction<Self>>);
+
+impl GlobalEvents for Global {
+    fn handle(&mut self, event: Event, _: &mut Elements<'_, '_>, _: &mut GlobalContext<'_>) -> Option<Event> {
+        Some(event)
+    }
+
+    fn keymap(&mut self) -> Option<&mut Keymap<GlobalAction<Self>>> {
+        Some(&mut self.0)
+    }
+}
+
+#[test]
+fn global_keymap_passes_on_abandoned_keys() {
+    fn quit(_: &mut Global, _: &mut Elements<'_, '_>, ctx: &mut GlobalContext<'_>) {
+        ctx.quit();
+    }
+
+    let global = Global(Keymap::new().bind("z z", quit as GlobalAction<Global>).unwrap());
+    let mut runtime = keys(Keymap::new(), global);
+    runtime.backend_mut().type_str("zab");
+    // Handling events stops for the frame when the global keymap consumes a key
+    runtime.run_frames(2).unwrap();
+
+    let frame = runtime.backend().last_frame().unwrap().to_string();
+    assert_eq!(frame, "zab       \n");
+}
+
+#[derive(Default)]
+struct Ticker(Option<TimerHandle>);
+
+#[derive(State)]
+struct TickerState {
+    ticks: Value<usize>,
+}
+
+impl Component for Ticker {
+    type Message = usize;
+    type State = TickerState;
+
+    fn on_focus(&mut self, _state: &mut Self::State, _elements: Elements<'_, '_>, mut context: Context<'_, Self>) {
+        self.0 = Some(context.set_interval(Duration::ZERO, 1));
+    }
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self>,
+    ) {
+        *state.ticks.to_mut() += message;
+        if *state.ticks.to_ref() == 2 {
+            self.0.take().unwrap().cancel();
+        }
+    }
+}
+
+#[test]
+fn interval_until_cancelled() {
+    let doc = Document::new("@ticker");
+    let mut builder = Runtime::builder(doc, TestBackend::new((10, 1)));
+    builder
+        .register_component(
+            "ticker",
+            "text 'ticks: ' ticks".to_template(),
+            Ticker::default(),
+            TickerState { ticks: 0.into() },
+        )
+        .unwrap();
+    let mut runtime = builder.finish().unwrap();
+    runtime.run_frames(5).unwrap();
+
+    let frame = runtime.backend().last_frame().unwrap().to_string();
+    assert_eq!(frame, "ticks: 2  \n");
+}
+
+struct Delayed;
+
+#[derive(State)]
+struct DelayedState {
+    value: Value<usize>,
+}
+
+impl Component for Delayed {
+    type Message = usize;
+    type State = DelayedState;
+
+    fn on_external_change(
+        &mut self,
+        _key: &str,
+        _state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        mut context: Context<'_, Self>,
+    ) {
+        let Some(value) = context.get_external("value").and_then(|v| v.load_number()) else { return };
+        context.set_timeout(Duration::ZERO, value.as_uint());
+    }
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self>,
+    ) {
+        *state.value.to_mut() = message;
+    }
+}
+
+#[test]
+fn timer_messages_go_to_their_instance() {
+    let doc = Document::new("vstack\n    for i in [1, 2, 3]\n        @delayed { \"value\": i }");
+    let mut builder = Runtime::builder(doc, TestBackend::new((1, 3)));
+    builder
+        .register_prototype(
+            "delayed",
+            "text value".to_template(),
+            || Delayed,
+            || DelayedState { value: 0.into() },
+        )
+        .unwrap();
+    let mut runtime = builder.finish().unwrap();
+    runtime.run_frames(3).unwrap();
+
+    let frame = runtime.backend().last_frame().unwrap().to_string();
+    assert_eq!(frame, "1\n2\n3\n");
+}
+
+struct Worker;
+
+#[derive(State)]
+struct WorkerState {
+    total: Value<usize>,
+}
+
+impl Component for Worker {
+    type Message = usize;
+    type State = WorkerState;
+
+    fn on_focus(&mut self, _state: &mut Self::State, _elements: Elements<'_, '_>, mut context: Context<'_, Self>) {
+        context.spawn(|| (1..=10).sum::<usize>());
+        context.spawn_future(async { 100_usize });
+    }
+
+    fn message(
+        &mut self,
+        message: Self::Message,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self>,
+    ) {
+        *state.total.to_mut() += message;
+    }
+}
+
+#[test]
+fn spawned_tasks_send_messages() {
+    let doc = Document::new("@worker");
+    let mut builder = Runtime::builder(doc, TestBackend::new((10, 1)));
+    builder
+        .register_component(
+            "worker",
+            "text 'total: ' total".to_template(),
+            Worker,
+            WorkerState { total: 0.into() },
+        )
+        .unwrap();
+    let mut runtime = builder.finish().unwrap();
+    runtime.run_frames(5).unwrap();
+
+    let frame = runtime.backend().last_frame().unwrap().to_string();
+    assert_eq!(frame, "total: 155\n");
+}
+
+// A backend that delivers events through a receiver,
+// like the tui backend does.
+struct ChannelBackend {
+    inner: TestBackend,
+    events: flume::Receiver<Event>,
+}
+
+impl Backend for ChannelBackend {
+    fn size(&self) -> Size {
+        self.inner.size()
+    }
+
+    fn next_event(&mut self, _timeout: Duration) -> Option<Event> {
+        self.events.try_recv().ok()
+    }
+
+    fn event_receiver(&mut self) -> Option<flume::Receiver<Event>> {
+        Some(self.events.clone())
+    }
+
+    fn resize(&mut self, new_size: Size) {
+        self.inner.resize(new_size)
+    }
+
+    fn paint<'bp>(
+        &mut self,
+        element: &mut Element<'bp>,
+        children: &[Node],
+        values: &mut TreeValues<WidgetKind<'bp>>,
+        attribute_storage: &AttributeStorage<'bp>,
+        ignore_floats: bool,
+    ) {
+        self.inner
+            .paint(element, children, values, attribute_storage, ignore_floats)
+    }
+
+    fn render(&mut self) {
+        self.inner.render()
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear()
+    }
+}
+
+#[test]
+fn sleep_until_event() {
+    let (tx, rx) = flume::unbounded();
+    let backend = ChannelBackend {
+        inner: TestBackend::new((10, 2)),
+        events: rx,
+    };
+
+    let doc = Document::new("@counter");
+    let mut builder = Runtime::builder(doc, backend);
+    let template = "
+        vstack
+            text 'count: ' count
+            text 'last: ' last
+    ";
+    builder
+        .register_component(
+            "counter",
+            template.to_template(),
+            Counter,
+            CounterState {
+                count: 0.into(),
+                last: String::new().into(),
+            },
+        )
+        .unwrap();
+    let mut runtime = builder.finish().unwrap();
+
+    std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(50));
+        let key = KeyEvent {
+            code: KeyCode::Char('a'),
+            modifiers: Modifiers::empty(),
+            state: KeyState::Press,
+        };
+        tx.send(Event::Key(key)).unwrap();
+    });
+
+    // The first frame has nothing to do, and the second frame is not run until the event arrives,
+    // so the key is handled in the second frame
+    runtime.run_frames(2).unwrap();
+
+    let frames = runtime.backend().inner.frames();
+    assert_eq!(frames.len(), 2);
+    assert_snapshot("tests/snapshots/first_key.snap", &frames[1]);
+}
+
+struct Clock;
+
+#[derive(State)]
+struct ClockState {
+    ticks: Value<usize>,
+}
+
+impl Component for Clock {
+    type Message = ();
+    type State = ClockState;
+
+    const TICKS: bool = true;
+
+    fn tick(
+        &mut self,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self>,
+        _dt: Duration,
+    ) {
+        *state.ticks.to_mut() += 1;
+    }
+}
+
+#[test]
+fn tick_every_frame() {
+    let doc = Document::new("@clock");
+    let mut builder = Runtime::builder(doc, TestBackend::new((3, 1)));
+    builder
+        .register_component(
+            "clock",
+            "text ticks".to_template(),
+            Clock,
+            ClockState { ticks: 0.into() },
+        )
+        .unwrap();
+    let mut runtime = builder.finish().unwrap();
+
+    // A ticking component does not wait for events
+    runtime.run_frames(3).unwrap();
+    let frame = runtime.backend().last_frame().unwrap().to_string();
+    assert_eq!(frame, "3  \n");
+}
+
+struct Fader;
+
+#[derive(State)]
+struct FaderState {
+    level: Value<u8>,
+    done: Value<bool>,
+}
+
+impl Component for Fader {
+    type Message = ();
+    type State = FaderState;
+
+    fn on_focus(&mut self, _state: &mut Self::State, _elements: Elements<'_, '_>, mut context: Context<'_, Self>) {
+        let animation = Animation::new(10, Duration::from_millis(50))
+            .easing(Easing::EaseOut)
+            .on_complete(|state: &mut FaderState| state.done.set(true));
+        context.animate(|state| &mut state.level, animation);
+    }
+}
+
+#[test]
+fn animate_state_value() {
+    let doc = Document::new("@fader");
+    let mut builder = Runtime::builder(doc, TestBackend::new((16, 1)));
+    builder
+        .register_component(
+            "fader",
+            "text level ' ' done".to_template(),
+            Fader,
+            FaderState {
+                level: 0.into(),
+                done: false.into(),
+            },
+        )
+        .unwrap();
+    let mut runtime = builder.finish().unwrap();
+    runtime
+        .with_frames(|frames| {
+            // The animation starts with the first frame, so it's not done yet
+            frames.run(1)?;
+            let frame = frames.backend().last_frame().unwrap().to_string();
+            assert_eq!(frame, "0 false         \n");
+
+            // The animation keeps the runtime ticking until it's done
+            let mut count = 0;
+            while !frames.backend().last_frame().unwrap().to_string().contains("true") {
+                assert!(count < 100, "the animation never finished");
+                frames.run(1)?;
+                count += 1;
+            }
+            Ok::<_, anathema_runtime::Error>(())
+        })
+        .unwrap()
+        .unwrap();
+
+    let frame = runtime.backend().last_frame().unwrap().to_string();
+    assert_eq!(frame, "10 true         \n");
+}
+
+struct Picker {
+    exit: ExitHandle<String>,
+}
+
+#[derive(State)]
+struct PickerState {
+    selected: Value<String>,
+}
+
+impl Component for Picker {
+    type Message = ();
+    type State = PickerState;
+
+    fn on_key(
+        &mut self,
+        key: KeyEvent,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        context: Context<'_, Self>,
+    ) {
+        match key.code {
+            KeyCode::Char(c) => state.selected.set(c.to_string()),
+            KeyCode::Enter => self.exit.quit_with(state.selected.to_ref().clone()),
+            KeyCode::Esc => context.quit(),
+            KeyCode::Delete => context.quit_with(state.selected.to_ref().clone()),
+            KeyCode::Backspace => context.quit_with(state.selected.to_ref().len()),
+            _ => (),
+        }
+    }
+}
+
+fn picker() -> Runtime<TestBackend, (), String> {
+    let doc = Document::new("@picker");
+    let mut builder = Runtime::builder(doc, TestBackend::new((5, 1))).exit_value::<String>();
+    let exit = builder.exit_handle();
+    builder
+        .register_component(
+            "picker",
+            "text selected".to_template(),
+            Picker { exit },
+            PickerState {
+                selected: String::new().into(),
+            },
+        )
+        .unwrap();
+    builder.finish().unwrap()
+}
+
+#[test]
+fn quit_with_exit_value() {
+    let mut runtime = picker();
+    runtime.backend_mut().type_str("ab").next_frame().press(KeyCode::Enter);
+    let selected = runtime.run().unwrap();
+    assert_eq!(selected.as_deref(), Some("b"));
+
+    // The runtime can be started again after it stopped
+    runtime.backend_mut().press('c').press(KeyCode::Esc);
+    assert!(runtime.run().unwrap().is_none());
+    let frame = runtime.backend().last_frame().unwrap().to_string();
+    assert_eq!(frame, "c    \n");
+}
+
+#[test]
+fn quit_with_context_value() {
+    let mut runtime = picker();
+    runtime.backend_mut().press('a').press(KeyCode::Delete);
+    assert_eq!(runtime.run().unwrap().as_deref(), Some("a"));
+
+    // Quitting without a value clears the previous value
+    runtime.backend_mut().press(KeyCode::Enter).press(KeyCode::Esc);
+    assert!(runtime.run().unwrap().is_none());
+
+    // The value has to be of the exit type of the runtime
+    runtime.backend_mut().press(KeyCode::Backspace);
+    let err = runtime.run().unwrap_err();
+    assert!(matches!(err, anathema_runtime::Error::ExitValue));
+}
+
+#[test]
+fn quit_without_reloading_templates() {
+    let path = std::env::temp_dir().join(format!("anathema-picker-{}.aml", std::process::id()));
+    std::fs::write(&path, "text selected").unwrap();
+
+    let mut doc = Document::new("@picker");
+    doc.hot_reload = false;
+    let mut builder = Runtime::builder(doc, TestBackend::new((5, 1))).exit_value::<String>();
+    let exit = builder.exit_handle();
+    builder
+        .register_component(
+            "picker",
+            path.to_str().unwrap(),
+            Picker { exit },
+            PickerState {
+                selected: String::new().into(),
+            },
+        )
+        .unwrap();
+    let mut runtime = builder.finish().unwrap();
+
+    // Exiting does not read the template again
+    std::fs::remove_file(&path).unwrap();
+    runtime.backend_mut().press('a').press(KeyCode::Enter);
+    assert_eq!(runtime.run().unwrap().as_deref(), Some("a"));
+}
+
+struct Form;
+
+#[derive(State)]
+struct FormState {
+    name: Value<String>,
+}
+
+impl Component for Form {
+    type Message = ();
+    type State = FormState;
+
+    fn on_key(
+        &mut self,
+        key: KeyEvent,
+        state: &mut Self::State,
+        _elements: Elements<'_, '_>,
+        _context: Context<'_, Self>,
+    ) {
+        match key.get_char() {
+            Some('r') => state.name.set(String::new()),
+            Some(c) => state.name.set(c.to_string().repeat(3)),
+            None => (),
+        }
+    }
+}
+
+fn form(input: &str) -> Runtime<TestBackend, ()> {
+    let doc = Document::new("@form");
+    let mut builder = Runtime::builder(doc, TestBackend::new((8, 1)));
+    builder
+        .register_component(
+            "form",
+            input.to_template(),
+            Form,
+            FormState {
+                name: String::from("hello").into(),
+            },
+        )
+        .unwrap();
+    builder
+        .register_prototype(
+            "input",
+            TextInput::TEMPLATE.to_template(),
+            TextInput::new,
+            TextInputState::new,
+        )
+        .unwrap();
+    builder
+        .register_prototype(
+            "editor",
+            TextEditor::TEMPLATE.to_template(),
+            TextEditor::new,
+            TextEditorState::new,
+        )
+        .unwrap();
+    builder.finish().unwrap()
+}
+
+#[test]
+fn text_input_follows_parent_value() {
+    let mut runtime = form("@input { \"value\": name }");
+    // Enter is ignored by the form, leaving the first frame to the initial value
+    runtime
+        .backend_mut()
+        .press(KeyCode::Enter)
+        .next_frame()
+        .press('x')
+        .next_frame()
+        .press('r');
+    runtime.run_frames(3).unwrap();
+
+    let frames = runtime
+        .backend()
+        .frames()
+        .iter()
+        .map(|frame| frame.to_string().trim_end().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(frames, ["", "hello", "xxx", ""]);
+}
+
+#[test]
+fn text_editor_follows_parent_value() {
+    let mut runtime = form("@editor { \"value\": name }");
+    runtime.backend_mut().press(KeyCode::Enter).next_frame().press('x');
+    runtime.run_frames(2).unwrap();
+
+    let frames = runtime
+        .backend()
+        .frames()
+        .iter()
+        .map(|frame| frame.to_string().trim_end().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(frames, ["", "hello", "xxx"]);
+}
+
+#[test]
+fn text_input_max_length() {
+    let mut runtime = form("@input { \"max_length\": 3 }");
+    runtime.backend_mut().press(KeyCode::Tab).paste("abcdef").press('g');
+    runtime.run_frames(2).unwrap();
+
+    let frame = runtime.backend().last_frame().unwrap().to_string();
+    assert_eq!(frame.trim_end(), "abc");
+}
@@ -36,6 +36,10 @@ pub trait AnyState: 'static {
 
     fn state_lookup(&self, path: Path<'_>) -> Option<PendingValue>;
 
+    fn state_key(&self, index: usize) -> Option<Rc<str>>;
+
+    fn state_key_of(&self, value: PendingValue) -> Option<Rc<str>>;
+
     fn to_number(&self) -> Option<Number>;
 
     fn to_bool(&self) -> bool;
@@ -64,6 +68,14 @@ impl AnyState for Box<dyn AnyState> {
         self.as_ref().state_lookup(path)
     }
 
+    fn state_key(&self, index: usize) -> Option<Rc<str>> {
+        self.as_ref().state_key(index)
+    }
+
+    fn state_key_of(&self, value: PendingValue) -> Option<Rc<str>> {
+        self.as_ref().state_key_of(value)
+    }
+
     fn to_number(&self) -> Option<Number> {
         self.as_ref().to_number()
     }
@@ -98,6 +110,14 @@ impl<T: State> AnyState for T {
         <Self as State>::state_lookup(self, path)
     }
 
+    fn state_key(&self, index: usize) -> Option<Rc<str>> {
+        <Self as State>::state_key(self, index)
+    }
+
+    fn state_key_of(&self, value: PendingValue) -> Option<Rc<str>> {
+        <Self as State>::state_key_of(self, value)
+    }
+
     fn to_number(&self) -> Option<Number> {
         <Self as State>::to_number(self)
     }
@@ -126,6 +146,18 @@ pub trait State: 'static {
         None
     }
 
+    /// Get the key of the entry at a given index.
+    /// This is only implemented by collections with keys, such as a `Map`.
+    fn state_key(&self, _index: usize) -> Option<Rc<str>> {
+        None
+    }
+
+    /// Get the key of the entry holding the value.
+    /// This is only implemented by collections with keys, such as a `Map`.
+    fn state_key_of(&self, _value: PendingValue) -> Option<Rc<str>> {
+        None
+    }
+
     /// Get the length of any underlying collection.
     /// If the state is not a collection it should return zero
     fn count(&self) -> usize {
@@ -152,6 +184,14 @@ impl State for Box<dyn State> {
         self.as_ref().state_lookup(path)
     }
 
+    fn state_key(&self, index: usize) -> Option<Rc<str>> {
+        self.as_ref().state_key(index)
+    }
+
+    fn state_key_of(&self, value: PendingValue) -> Option<Rc<str>> {
+        self.as_ref().state_key_of(value)
+    }
+
     fn to_number(&self) -> Option<Number> {
         self.as_ref().to_number()
     }
@@ -178,6 +218,14 @@ impl<T: 'static + State> State for Value<T> {
         self.to_ref().state_lookup(path)
     }
 
+    fn state_key(&self, index: usize) -> Option<Rc<str>> {
+        self.to_ref().state_key(index)
+    }
+
+    fn state_key_of(&self, value: PendingValue) -> Option<Rc<str>> {
+        self.to_ref().state_key_of(value)
+    }
+
     fn to_number(&self) -> Option<Number> {
         self.to_ref().to_number()
     }
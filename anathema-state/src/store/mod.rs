@@ -22,7 +22,7 @@ thread_local! {
     static FUTURE_VALUES: RefCell<FutureValues> = const { RefCell::new(Stack::empty()) };
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 /// A value key is a composite from an owned key and a sub key.
 pub struct ValueKey(OwnedKey, SubKey);
 
@@ -34,7 +34,7 @@ impl KeyIndex {
 }
 
 // The key associated with the value that is being subscribed to.
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub(crate) struct SubKey(u32);
 
 impl From<SubKey> for usize {
@@ -2,11 +2,18 @@ use std::collections::HashMap;
 use std::rc::Rc;
 
 use super::Value;
-use crate::{CommonVal, Path, PendingValue, State, Subscriber, ValueRef};
+use crate::store::changed;
+use crate::{Change, CommonVal, Path, PendingValue, State, Subscriber, ValueRef};
 
+/// A map of values.
+///
+/// The entries are iterated in the order they were inserted.
 #[derive(Debug)]
 pub struct Map<T> {
     inner: HashMap<Rc<str>, Value<T>>,
+    keys: Vec<Rc<str>>,
+    // The key of each value, for finding the entry of a value without a scan
+    keys_by_value: HashMap<PendingValue, Rc<str>>,
 }
 
 impl<T: 'static + State> Map<T> {
@@ -25,24 +32,52 @@ impl<T: 'static + State> Map<T> {
 
 impl<T: 'static + State> Value<Map<T>> {
     pub fn empty() -> Self {
-        let map = Map { inner: HashMap::new() };
+        let map = Map {
+            inner: HashMap::new(),
+            keys: vec![],
+            keys_by_value: HashMap::new(),
+        };
         Value::new(map)
     }
 
     /// Insert a value into the `Map`.
     /// The value will be wrapped in a `Value<T>` so it's not advisable to insert pre-wrapped
     /// value.
+    ///
+    /// Replacing the value of an existing key keeps the position of the key.
     pub fn insert(&mut self, map_key: impl Into<Rc<str>>, value: impl Into<Value<T>>) {
+        let key = self.key;
         let map_key = map_key.into();
         let map = &mut *self.to_mut();
         let value = value.into();
-        map.inner.insert(map_key, value);
+
+        let index = match map.keys.iter().position(|k| *k == map_key) {
+            Some(index) => {
+                changed(key.sub(), Change::Removed(index as u32));
+                index
+            }
+            None => {
+                map.keys.push(map_key.clone());
+                map.keys.len() - 1
+            }
+        };
+
+        changed(key.sub(), Change::Inserted(index as u32, value.to_pending()));
+        map.keys_by_value.insert(value.to_pending(), map_key.clone());
+        if let Some(old) = map.inner.insert(map_key, value) {
+            map.keys_by_value.remove(&old.to_pending());
+        }
     }
 
     pub fn remove(&mut self, map_key: &str) -> Option<Value<T>> {
-        let _key = self.key;
+        let key = self.key;
         let map = &mut *self.to_mut();
-        map.inner.remove(map_key)
+        let value = map.inner.remove(map_key)?;
+        map.keys_by_value.remove(&value.to_pending());
+        let index = map.keys.iter().position(|k| &**k == map_key)?;
+        map.keys.remove(index);
+        changed(key.sub(), Change::Removed(index as u32));
+        Some(value)
     }
 }
 
@@ -54,19 +89,35 @@ impl<T: 'static + State> State for Map<T> {
     }
 
     fn state_lookup(&self, path: Path<'_>) -> Option<PendingValue> {
-        let Path::Key(k) = path else { return None };
-        let value = self.inner.get(k)?;
+        let value = match path {
+            Path::Key(k) => self.inner.get(k)?,
+            // Used when iterating over the map
+            Path::Index(idx) => self.inner.get(self.keys.get(idx)?)?,
+        };
         Some(value.to_pending())
     }
 
+    fn state_key(&self, index: usize) -> Option<Rc<str>> {
+        self.keys.get(index).cloned()
+    }
+
+    fn state_key_of(&self, value: PendingValue) -> Option<Rc<str>> {
+        self.keys_by_value.get(&value).cloned()
+    }
+
     fn to_common(&self) -> Option<CommonVal<'_>> {
         None
     }
+
+    fn count(&self) -> usize {
+        self.keys.len()
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::store::testing::drain_changes;
 
     #[test]
     fn insert() {
@@ -91,4 +142,62 @@ mod test {
         map.remove("a");
         assert!(value_ref.value::<i32>().is_none());
     }
+
+    #[test]
+    fn insertion_order() {
+        let mut map = Map::empty();
+        map.insert("b", 1);
+        map.insert("a", 2);
+        map.insert("c", 3);
+        map.remove("a");
+        map.insert("b", 4);
+
+        let map = map.to_ref();
+        assert_eq!(map.count(), 2);
+        assert_eq!(map.state_key(0).as_deref(), Some("b"));
+        assert_eq!(map.state_key(1).as_deref(), Some("c"));
+        assert_eq!(map.state_lookup(0.into()), Some(map.get("b").unwrap().to_pending()));
+    }
+
+    #[test]
+    fn key_of_value() {
+        let mut map = Map::empty();
+        map.insert("a", 1);
+        map.insert("b", 2);
+        let old = map.to_ref().get("a").unwrap().to_pending();
+        map.insert("a", 3);
+
+        let map = map.to_ref();
+        let a = map.get("a").unwrap().to_pending();
+        assert_eq!(map.state_key_of(a).as_deref(), Some("a"));
+        assert_eq!(map.state_key_of(old), None);
+    }
+
+    #[test]
+    fn notify_insert_and_remove() {
+        let mut map = Map::empty();
+        let _vr = map.value_ref(Subscriber::ZERO);
+        map.insert("a", 1);
+        map.insert("b", 2);
+        map.insert("a", 3);
+        map.remove("a");
+
+        // Changes are drained in reverse order
+        let changes = drain_changes()
+            .into_iter()
+            .rev()
+            .map(|(_, change)| change)
+            .filter(|change| !matches!(change, Change::Changed))
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            changes[..],
+            [
+                Change::Inserted(0, _),
+                Change::Inserted(1, _),
+                Change::Removed(0),
+                Change::Inserted(0, _),
+                Change::Removed(0),
+            ]
+        ));
+    }
 }
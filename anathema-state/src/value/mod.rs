@@ -371,7 +371,7 @@ impl Drop for ValueRef {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct PendingValue(ValueKey);
 
 impl PendingValue {
@@ -115,7 +115,7 @@ impl<T> Entry<T> {
     // Insert an Occupied entry in place of a vacant one.
     fn swap(&mut self, value: T, gen: Gen) {
         debug_assert!(matches!(self, Entry::Vacant(_)));
-        std::mem::swap(self, &mut Entry::Occupied(value, gen));
+        *self = Entry::Occupied(value, gen);
     }
 
     // Create a new occupied entry
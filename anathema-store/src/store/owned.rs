@@ -6,7 +6,7 @@ use super::Slab;
 // -----------------------------------------------------------------------------
 //   - Key -
 // -----------------------------------------------------------------------------
-#[derive(Debug, Copy, Clone, PartialEq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct OwnedKey(u32);
 
 impl From<usize> for OwnedKey {
@@ -18,11 +18,22 @@ impl<'a, 'filter, T, Fil> TreeForEach<'a, 'filter, T, Fil> {
         F: FnMut(&mut Fil::Output, TreeForEach<'_, '_, T, Fil>) -> ControlFlow<()>,
         Fil: TreeFilter<Input = T>,
     {
-        self.inner_for_each(&mut f);
+        // Breaking only stops the iteration early, there is nothing to do after it
+        let _ = self.inner_for_each(&mut f);
     }
 
-    /// Apply to the first element that matches the filter
-    pub fn first<F>(&mut self, f: &mut F)
+    /// Apply to the first element that matches the filter.
+    /// Returns `true` if an element matched.
+    pub fn first<F>(&mut self, f: &mut F) -> bool
+    where
+        F: FnMut(&mut Fil::Output, &[Node], &mut TreeValues<T>),
+        Fil: TreeFilter<Input = T>,
+    {
+        self.inner_first(f).is_break()
+    }
+
+    // Breaks once the function is applied to an element
+    fn inner_first<F>(&mut self, f: &mut F) -> ControlFlow<()>
     where
         F: FnMut(&mut Fil::Output, &[Node], &mut TreeValues<T>),
         Fil: TreeFilter<Input = T>,
@@ -39,16 +50,17 @@ impl<'a, 'filter, T, Fil> TreeForEach<'a, 'filter, T, Fil> {
                             values,
                             filter: self.filter,
                         };
-                        for_each.first(f);
-                        ControlFlow::Break(())
+                        for_each.inner_first(f)
                     }
                     ControlFlow::Continue(Some(val)) => {
                         f(val, node.children(), values);
                         ControlFlow::Break(())
                     }
                 }
-            });
+            })?;
         }
+
+        ControlFlow::Continue(())
     }
 
     fn inner_for_each<F>(&mut self, f: &mut F) -> ControlFlow<()>
@@ -0,0 +1,187 @@
+use std::path::{Path, PathBuf};
+
+use manyhow::{bail, manyhow, Result};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Ident, LitStr};
+
+/// Embed a template in the binary.
+///
+/// The template is lexed and parsed at compile time,
+/// so syntax errors in the template become compile errors.
+///
+/// The path is relative to the root of the crate (the directory containing `Cargo.toml`).
+/// The macro produces a [`SourceKind`](../anathema/templates/enum.SourceKind.html)
+/// that can be used in place of a path when registering components.
+/// The crate using the macro has to depend on either `anathema` or `anathema-templates`:
+///
+/// ```ignore
+/// runtime.register_component("main", anathema::template!("templates/main.aml"), Main, MainState::new())?;
+/// ```
+#[manyhow]
+#[proc_macro]
+pub fn template(input: LitStr) -> Result {
+    let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let path = Path::new(&root).join(input.value());
+    let manifest = std::fs::read_to_string(Path::new(&root).join("Cargo.toml")).unwrap_or_default();
+    embed(&input, path, templates_crate(&manifest))
+}
+
+// The path to the templates crate from the crate using the macro,
+// found among the dependencies in its manifest
+fn templates_crate(manifest: &str) -> TokenStream {
+    let mut anathema = None;
+    let mut table = None;
+    let mut dependencies = vec![];
+
+    for line in manifest.lines().map(str::trim) {
+        if let Some(header) = line.strip_prefix('[') {
+            let header = header.trim_end_matches(']');
+            // `[dependencies.name]` is a table for the dependency `name`
+            table = header
+                .split_once("dependencies")
+                .map(|(_, rest)| rest.strip_prefix('.').map(str::to_string));
+            if let Some(Some(name)) = &table {
+                dependencies.push((name.clone(), name.clone()));
+            }
+            continue;
+        }
+
+        let Some(name) = &table else { continue };
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let key = key.trim().trim_matches('"');
+
+        match name {
+            // A renamed dependency names the package with `package = "..."`
+            Some(_) if key == "package" => {
+                if let Some((_, package)) = dependencies.last_mut() {
+                    *package = value.trim().trim_matches('"').to_string();
+                }
+            }
+            Some(_) => {}
+            None => {
+                // `name.workspace = true`
+                let name = key.split('.').next().unwrap_or(key);
+                let package = value
+                    .split_once("package")
+                    .and_then(|(_, package)| package.split('"').nth(1))
+                    .unwrap_or(name);
+                dependencies.push((name.to_string(), package.to_string()));
+            }
+        }
+    }
+
+    for (name, package) in dependencies {
+        let ident = Ident::new(&name.replace('-', "_"), Span::call_site());
+        match package.as_str() {
+            "anathema-templates" => return quote!(::#ident),
+            "anathema" => anathema = Some(quote!(::#ident::templates)),
+            _ => {}
+        }
+    }
+
+    anathema.unwrap_or_else(|| quote!(::anathema::templates))
+}
+
+fn embed(input: &LitStr, path: PathBuf, templates: TokenStream) -> Result {
+    let template = match std::fs::read_to_string(&path) {
+        Ok(template) => template,
+        Err(err) => bail!(input, "failed to read `{}`: {err}", path.display()),
+    };
+
+    if let Err(err) = anathema_templates::parse(&template) {
+        match err.location {
+            Some(mut location) => {
+                location.path = Some(path.as_path().into());
+                bail!(input, "{}\n{location}", err.kind);
+            }
+            None => bail!(input, "{}", err.kind),
+        }
+    }
+
+    // Including the file makes the compiler rebuild when the template changes
+    let path = path.display().to_string();
+    Ok(quote! {
+        #templates::SourceKind::Str(::std::string::String::from(::std::include_str!(#path)))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use manyhow::ToTokensError;
+
+    use super::*;
+
+    fn embed_template(name: &str, template: &str) -> Result {
+        let path = std::env::temp_dir().join(format!("anathema-templates-macro-{name}.aml"));
+        std::fs::write(&path, template).unwrap();
+        embed(
+            &LitStr::new(name, Span::call_site()),
+            path,
+            quote!(::anathema::templates),
+        )
+    }
+
+    #[test]
+    fn embed_valid_template() {
+        let output = embed_template("valid", "vstack\n    text 'hello'").unwrap().to_string();
+        assert!(output.contains(":: anathema :: templates :: SourceKind :: Str"));
+        assert!(output.contains("include_str !"));
+        assert!(output.contains("anathema-templates-macro-valid.aml"));
+    }
+
+    #[test]
+    fn syntax_error() {
+        let err = embed_template("invalid", "vstack\n    text 'hello").unwrap_err();
+        let output = err.into_token_stream().to_string();
+        assert!(output.contains("unterminated string"));
+        assert!(output.contains("anathema-templates-macro-invalid.aml:2:10"));
+    }
+
+    #[test]
+    fn missing_file() {
+        let path = std::env::temp_dir().join("anathema-templates-macro-missing.aml");
+        let err = embed(
+            &LitStr::new("missing.aml", Span::call_site()),
+            path,
+            quote!(::anathema::templates),
+        )
+        .unwrap_err();
+        let output = err.into_token_stream().to_string();
+        assert!(output.contains("failed to read"));
+    }
+
+    #[test]
+    fn find_templates_crate() {
+        let path = |manifest: &str| templates_crate(manifest).to_string();
+
+        assert_eq!(path("[dependencies]\nanathema = \"0.3\""), ":: anathema :: templates");
+        assert_eq!(
+            path("[dependencies]\nanathema-templates = { path = \"../anathema-templates\" }"),
+            ":: anathema_templates"
+        );
+        assert_eq!(
+            path("[dependencies]\nui = { package = \"anathema\", version = \"0.3\" }"),
+            ":: ui :: templates"
+        );
+        assert_eq!(
+            path("[dependencies.tpl]\npackage = \"anathema-templates\"\nversion = \"0.3\""),
+            ":: tpl"
+        );
+        assert_eq!(
+            path("[package]\nname = \"anathema\"\n\n[dev-dependencies]\nanathema-templates.workspace = true"),
+            ":: anathema_templates"
+        );
+
+        assert_eq!(
+            path("[dependencies.anathema-templates]\npath = \"../anathema-templates\""),
+            ":: anathema_templates"
+        );
+
+        // The templates crate is used directly when it's a dependency
+        assert_eq!(
+            path("[dependencies]\nanathema = \"0.3\"\nanathema-templates = \"0.3\""),
+            ":: anathema_templates"
+        );
+    }
+}
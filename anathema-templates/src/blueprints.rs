@@ -4,6 +4,7 @@ use std::rc::Rc;
 use anathema_store::smallmap::SmallMap;
 use anathema_store::storage::strings::StringId;
 
+use crate::error::Location;
 use crate::{Expression, WidgetComponentId};
 
 #[derive(Debug, Clone, PartialEq)]
@@ -12,19 +13,29 @@ pub struct Single {
     pub children: Vec<Blueprint>,
     pub attributes: SmallMap<Rc<str>, Expression>,
     pub value: Option<Expression>,
+    /// Where the node is declared in the template
+    pub location: Option<Location>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct For {
+    /// The index of the item, or the key of the entry when iterating over a map
+    pub key: Option<Rc<str>>,
     pub binding: Rc<str>,
     pub data: Expression,
     pub body: Vec<Blueprint>,
+    /// Shown while the collection is empty
+    pub else_body: Vec<Blueprint>,
+    /// Where the loop is declared in the template
+    pub location: Option<Location>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ControlFlow {
     pub if_node: If,
     pub elses: Vec<Else>,
+    /// Where the `if` is declared in the template
+    pub location: Option<Location>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -39,6 +50,22 @@ pub struct Else {
     pub body: Vec<Blueprint>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct Switch {
+    pub value: Expression,
+    pub cases: Vec<Case>,
+    /// Where the `switch` is declared in the template
+    pub location: Option<Location>,
+}
+
+/// A `case` in a `switch`.
+/// The value is `None` for the `default` case.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Case {
+    pub value: Option<Expression>,
+    pub body: Vec<Blueprint>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Component {
     pub id: WidgetComponentId,
@@ -47,6 +74,8 @@ pub struct Component {
     pub state: Option<Rc<HashMap<Rc<str>, Expression>>>,
     pub assoc_functions: Vec<(StringId, StringId)>,
     pub parent: Option<WidgetComponentId>,
+    /// Where the component is declared in the template
+    pub location: Option<Location>,
 }
 
 /// A blueprint represents what widget should be built from the information
@@ -55,6 +84,7 @@ pub enum Blueprint {
     Single(Single),
     For(For),
     ControlFlow(ControlFlow),
+    Switch(Switch),
     Component(Component),
 }
 
@@ -66,6 +96,7 @@ macro_rules! single {
             children: vec![],
             attributes: SmallMap::empty(),
             value: None,
+            location: None,
         })
     };
     ($ident:expr, $children:expr) => {
@@ -74,6 +105,7 @@ macro_rules! single {
             children: $children,
             attributes: SmallMap::empty(),
             value: None,
+            location: None,
         })
     };
 }
@@ -1,5 +1,7 @@
+use std::collections::HashMap;
 use std::fs::read_to_string;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 use anathema_store::slab::Index;
 use anathema_store::smallmap::SmallMap;
@@ -8,7 +10,8 @@ use anathema_store::storage::strings::{StringId, Strings};
 use anathema_store::storage::Storage;
 
 use crate::blueprints::Blueprint;
-use crate::error::{Error, Result};
+use crate::error::{Error, ErrorKind, Result, Template};
+use crate::expressions::Expression;
 use crate::statements::eval::Scope;
 use crate::statements::parser::Parser;
 use crate::statements::{Context, Statements};
@@ -42,6 +45,12 @@ impl<T: AsRef<str>> ToSourceKind for T {
 pub enum SourceKind {
     Path(PathBuf),
     Str(String),
+    /// A template that was already read from `path`.
+    /// Errors point at the path, and the file is read again on reload.
+    Loaded {
+        path: PathBuf,
+        template: String,
+    },
 }
 
 impl ToSourceKind for SourceKind {
@@ -78,6 +87,18 @@ pub(crate) enum ComponentSource {
     Empty,
 }
 
+impl ComponentSource {
+    /// The template and the path it was loaded from.
+    /// `None` if the component has no template.
+    pub(crate) fn template(&self) -> Option<(&str, Option<&Path>)> {
+        match self {
+            Self::File { path, template } => Some((template, Some(path))),
+            Self::InMemory(template) => Some((template, None)),
+            Self::Empty => None,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub struct WidgetComponentId(u32);
 
@@ -102,6 +123,8 @@ impl From<usize> for WidgetComponentId {
 pub(crate) struct ComponentTemplates {
     dependencies: Stack<WidgetComponentId>,
     components: Storage<WidgetComponentId, String, ComponentSource>,
+    partial_dependencies: Stack<String>,
+    partials: HashMap<String, ComponentSource>,
 }
 
 impl ComponentTemplates {
@@ -109,7 +132,57 @@ impl ComponentTemplates {
         Self {
             dependencies: Stack::empty(),
             components: Storage::empty(),
+            partial_dependencies: Stack::empty(),
+            partials: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn insert_partial(&mut self, name: impl Into<String>, template: ComponentSource) {
+        self.partials.insert(name.into(), template);
+    }
+
+    /// Compile a partial in place.
+    /// Only the parameters it's given are replaced, not the parameters of the template
+    /// it's included from. Any other name is resolved where the partial is included.
+    pub(crate) fn load_partial(
+        &mut self,
+        name: String,
+        globals: &mut Variables,
+        slots: SmallMap<StringId, Vec<Blueprint>>,
+        strings: &mut Strings,
+        parent: Option<WidgetComponentId>,
+        params: HashMap<Rc<str>, Expression>,
+    ) -> Result<Vec<Blueprint>> {
+        if self.partial_dependencies.iter().any(|dep| *dep == name) {
+            return Err(ErrorKind::CircularDependency.into());
         }
+
+        let Some(partial_src) = self.partials.remove(&name) else {
+            return Err(ErrorKind::MissingPartial(name).into());
+        };
+
+        let (template, path) = match &partial_src {
+            ComponentSource::File { template, path } => (template, Some(path.as_path())),
+            ComponentSource::InMemory(template) => (template, None),
+            ComponentSource::Empty => unreachable!("partials are always inserted with a template"),
+        };
+
+        self.partial_dependencies.push(name.clone());
+        let template = Template::new(template, path);
+        let ret = self.compile(template, globals, slots, strings, parent, params);
+        self.partial_dependencies.pop();
+
+        self.partials.insert(name, partial_src);
+
+        ret
+    }
+
+    pub(crate) fn get(&self, id: WidgetComponentId) -> Option<(&str, &ComponentSource)> {
+        self.components.get(id).map(|(name, src)| (name.as_str(), src))
+    }
+
+    pub(crate) fn get_partial(&self, name: &str) -> Option<&ComponentSource> {
+        self.partials.get(name)
     }
 
     pub(crate) fn insert_id(&mut self, name: impl Into<String>) -> WidgetComponentId {
@@ -129,19 +202,20 @@ impl ComponentTemplates {
         strings: &mut Strings,
     ) -> Result<Vec<Blueprint>> {
         if self.dependencies.contains(&parent_id) {
-            return Err(Error::CircularDependency);
+            return Err(ErrorKind::CircularDependency.into());
         }
 
         self.dependencies.push(parent_id);
 
         let ret = match self.components.remove(parent_id) {
             Some((key, component_src)) => {
-                let template = match &component_src {
-                    ComponentSource::File { template, .. } => template,
-                    ComponentSource::InMemory(template) => template,
-                    ComponentSource::Empty => return Err(Error::MissingComponent(key)),
+                let (template, path) = match &component_src {
+                    ComponentSource::File { template, path } => (template, Some(path.as_path())),
+                    ComponentSource::InMemory(template) => (template, None),
+                    ComponentSource::Empty => return Err(ErrorKind::MissingComponent(key).into()),
                 };
-                let ret = self.compile(template, globals, slots, strings, parent_id);
+                let template = Template::new(template, path);
+                let ret = self.compile(template, globals, slots, strings, Some(parent_id), HashMap::new());
                 // This will re-insert the component in the same location
                 // as it was removed from since nothing else has
                 // written to the component storage since the component
@@ -160,25 +234,31 @@ impl ComponentTemplates {
 
     fn compile(
         &mut self,
-        template: &str,
+        template: Template,
         globals: &mut Variables,
         slots: SmallMap<StringId, Vec<Blueprint>>,
         strings: &mut Strings,
-        parent: WidgetComponentId,
+        parent: Option<WidgetComponentId>,
+        params: HashMap<Rc<str>, Expression>,
     ) -> Result<Vec<Blueprint>> {
-        let tokens = Lexer::new(template, strings).collect::<Result<Vec<_>>>()?;
-        let tokens = Tokens::new(tokens, template.len());
-        let parser = Parser::new(tokens, strings, template, self);
+        let tokens = Lexer::new(template.src(), strings)
+            .collect::<Result<Vec<_>>>()
+            .map_err(|err| err.with_path(template.path()))?;
+        let tokens = Tokens::new(tokens, template.src().len());
+        let parser = Parser::new(tokens, strings, template.src(), self);
 
-        let statements = parser.collect::<Result<Statements>>()?;
+        let statements = parser
+            .collect::<Result<Statements>>()
+            .map_err(|err| err.with_path(template.path()))?;
 
-        let mut context = Context::new(globals, self, strings, slots, Some(parent));
+        let mut context = Context::new(globals, self, strings, slots, parent, params, template);
 
         Scope::new(statements).eval(&mut context)
     }
 
     pub(crate) fn file_paths(&self) -> impl Iterator<Item = &PathBuf> {
-        self.components.iter().filter_map(|(_, (_, src))| match src {
+        let components = self.components.iter().map(|(_, (_, src))| src);
+        components.chain(self.partials.values()).filter_map(|src| match src {
             ComponentSource::File { path, .. } => Some(path),
             ComponentSource::InMemory(_) => None,
             ComponentSource::Empty => None,
@@ -186,7 +266,8 @@ impl ComponentTemplates {
     }
 
     pub(crate) fn reload(&mut self) -> std::prelude::v1::Result<(), Error> {
-        for (_, component) in self.components.iter_mut() {
+        let components = self.components.iter_mut().map(|(_, component)| component);
+        for component in components.chain(self.partials.values_mut()) {
             match component {
                 ComponentSource::File { path, template } => {
                     *template = read_to_string(path)?;
@@ -0,0 +1,259 @@
+use std::fmt::{self, Display, Formatter};
+
+use anathema_state::Color;
+
+use crate::error::Location;
+use crate::expressions::Expression;
+use crate::primitives::Primitive;
+
+/// Describes the widgets available to templates.
+/// This is used by [`Document::check`](crate::Document::check).
+pub trait WidgetSchema {
+    /// Returns true if a widget with this name exists
+    fn has_widget(&self, ident: &str) -> bool;
+
+    /// The attributes the widget accepts.
+    /// If this returns `None` the attributes of the widget are not checked.
+    fn attributes(&self, ident: &str) -> Option<&[Attribute]>;
+}
+
+/// An attribute a widget accepts, and the type of its value
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Attribute {
+    pub name: &'static str,
+    pub ty: AttributeType,
+}
+
+impl Attribute {
+    pub const fn new(name: &'static str, ty: AttributeType) -> Self {
+        Self { name, ty }
+    }
+}
+
+/// The type of value an attribute accepts.
+///
+/// Only values written in the template are checked.
+/// A value from state, a function or a loop is only known once the template is running.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum AttributeType {
+    Any,
+    Bool,
+    Number,
+    /// A number that is zero or more
+    Unsigned,
+    /// A string or a character
+    Str,
+    /// The name of a colour, a hex value (`#ff0000`) or an ansi value
+    Color,
+    /// One of the given words
+    OneOf(&'static [&'static str]),
+    /// One of the given words, or a list of them
+    Flags(&'static [&'static str]),
+}
+
+impl AttributeType {
+    /// Returns `Some(false)` if the value is never of this type,
+    /// and `None` if the value isn't known until the template is running.
+    pub(crate) fn accepts(&self, value: &Expression) -> Option<bool> {
+        use {Expression as E, Primitive as P};
+
+        let accepts = match (self, value) {
+            (Self::Any, _) => true,
+            (Self::Bool, E::Primitive(P::Bool(_))) => true,
+            (Self::Number | Self::Unsigned, E::Primitive(P::Int(_) | P::Float(_))) => true,
+            (Self::Number, E::Negative(value)) => return Self::Number.accepts(value),
+            // A negative number is never of any other type
+            (_, E::Negative(value)) => return Self::Number.accepts(value).map(|_| false),
+            (Self::Str, E::Str(_) | E::TextSegments(_) | E::Primitive(P::Char(_))) => true,
+            (Self::Color, E::Primitive(P::Hex(_))) => true,
+            (Self::Color, E::Primitive(P::Int(ansi))) => u8::try_from(*ansi).is_ok(),
+            (Self::Color, E::Str(color)) => color.parse::<Color>().is_ok(),
+            (Self::OneOf(words) | Self::Flags(words), E::Str(word)) => words.contains(&&**word),
+            (Self::Flags(words), E::List(list)) => list
+                .iter()
+                .all(|value| Self::OneOf(words).accepts(value) != Some(false)),
+            (Self::Color | Self::OneOf(_) | Self::Flags(_), E::TextSegments(_)) => return None,
+            (_, E::Primitive(_) | E::Str(_) | E::TextSegments(_) | E::List(_) | E::Map(_)) => false,
+            _ => return None,
+        };
+
+        Some(accepts)
+    }
+}
+
+impl Display for AttributeType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let words = |f: &mut Formatter<'_>, words: &[&str]| {
+            let words = words.iter().map(|word| format!("`{word}`")).collect::<Vec<_>>();
+            write!(f, "{}", words.join(", "))
+        };
+
+        match self {
+            Self::Any => write!(f, "any value"),
+            Self::Bool => write!(f, "a bool"),
+            Self::Number => write!(f, "a number"),
+            Self::Unsigned => write!(f, "a number that is zero or more"),
+            Self::Str => write!(f, "a string"),
+            Self::Color => write!(f, "a colour"),
+            Self::OneOf(one_of) => {
+                write!(f, "one of ")?;
+                words(f, one_of)
+            }
+            Self::Flags(flags) => {
+                write!(f, "one or a list of ")?;
+                words(f, flags)
+            }
+        }
+    }
+}
+
+/// A problem found in a template by [`Document::check`](crate::Document::check).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+    pub location: Location,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "warning: {}", self.kind)?;
+        write!(f, "{}", self.location)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiagnosticKind {
+    UnknownWidget(String),
+    UnknownAttribute {
+        widget: String,
+        attribute: String,
+        /// A known attribute with a similar name
+        suggestion: Option<&'static str>,
+    },
+    InvalidAttributeValue {
+        widget: String,
+        attribute: String,
+        expected: AttributeType,
+    },
+    MissingComponent(String),
+    MissingPartial(String),
+    /// An associated function (`->`) outside of a component template,
+    /// where there is no parent component to handle it
+    UnhandledAssociation {
+        internal: String,
+        external: String,
+    },
+    UnusedDeclaration(String),
+    UnreachableElse,
+}
+
+impl Display for DiagnosticKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownWidget(ident) => write!(f, "unknown widget `{ident}`"),
+            Self::UnknownAttribute {
+                widget,
+                attribute,
+                suggestion,
+            } => {
+                write!(f, "unknown attribute `{attribute}` on `{widget}`")?;
+                match suggestion {
+                    Some(suggestion) => write!(f, ", did you mean `{suggestion}`?"),
+                    None => Ok(()),
+                }
+            }
+            Self::InvalidAttributeValue {
+                widget,
+                attribute,
+                expected,
+            } => write!(f, "`{attribute}` on `{widget}` expects {expected}"),
+            Self::MissingComponent(name) => write!(f, "`@{name}` is not a registered component"),
+            Self::MissingPartial(name) => write!(f, "`{name}` is not a registered partial"),
+            Self::UnhandledAssociation { internal, external } => write!(
+                f,
+                "`{internal}->{external}` is not inside a component, so there is no component to handle it"
+            ),
+            Self::UnusedDeclaration(name) => write!(f, "`{name}` is declared but never used"),
+            Self::UnreachableElse => write!(f, "unreachable else branch"),
+        }
+    }
+}
+
+// Find the candidate closest to the input,
+// as long as it's close enough to be a typo.
+pub(crate) fn suggest(input: &str, candidates: impl IntoIterator<Item = &'static str>) -> Option<&'static str> {
+    let max_distance = (input.chars().count() / 3).max(2);
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(input, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+// Levenshtein distance
+fn edit_distance(lhs: &str, rhs: &str) -> usize {
+    let rhs = rhs.chars().collect::<Vec<_>>();
+    let mut row = (0..=rhs.len()).collect::<Vec<_>>();
+
+    for (i, l) in lhs.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, r) in rhs.iter().enumerate() {
+            let substitution = prev + usize::from(l != *r);
+            prev = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(prev + 1);
+        }
+    }
+
+    row[rhs.len()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("foregound", "foreground"), 1);
+        assert_eq!(edit_distance("widht", "width"), 2);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn suggestions() {
+        let candidates = ["foreground", "background", "width"];
+        assert_eq!(suggest("foregound", candidates), Some("foreground"));
+        assert_eq!(suggest("widht", candidates), Some("width"));
+        assert_eq!(suggest("height", candidates), None);
+    }
+
+    #[test]
+    fn attribute_types() {
+        use crate::expressions::{list, num, strlit};
+
+        let accepts = |ty: AttributeType, value: Box<Expression>| ty.accepts(&value);
+        let sides = AttributeType::Flags(&["top", "bottom"]);
+
+        assert_eq!(accepts(AttributeType::Unsigned, num(1)), Some(true));
+        assert_eq!(
+            accepts(AttributeType::Unsigned, Expression::Negative(num(1)).into()),
+            Some(false)
+        );
+        assert_eq!(
+            accepts(AttributeType::Number, Expression::Negative(num(1)).into()),
+            Some(true)
+        );
+        assert_eq!(accepts(AttributeType::Bool, strlit("true")), Some(false));
+        assert_eq!(accepts(AttributeType::Color, strlit("red")), Some(true));
+        assert_eq!(accepts(AttributeType::Color, strlit("rde")), Some(false));
+        assert_eq!(accepts(AttributeType::Color, num(300)), Some(false));
+        assert_eq!(accepts(sides, list([strlit("top"), strlit("bottom")])), Some(true));
+        assert_eq!(accepts(sides, list([strlit("top"), strlit("middle")])), Some(false));
+        assert_eq!(
+            accepts(AttributeType::Bool, Expression::Ident("value".into()).into()),
+            None
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs::read_to_string;
 use std::path::PathBuf;
 
@@ -6,7 +7,9 @@ use anathema_store::storage::strings::Strings;
 
 use crate::blueprints::Blueprint;
 use crate::components::{ComponentSource, ComponentTemplates, SourceKind};
-use crate::error::{Error, Result};
+use crate::diagnostics::{Diagnostic, WidgetSchema};
+use crate::error::{ErrorKind, Result, Template};
+use crate::statements::check::Checker;
 use crate::statements::eval::Scope;
 use crate::statements::parser::Parser;
 use crate::statements::{Context, Statements};
@@ -49,12 +52,43 @@ impl Document {
                 let template = read_to_string(&path)?;
                 ComponentSource::File { path, template }
             }
+            SourceKind::Loaded { path, template } => ComponentSource::File { path, template },
         };
 
         let id = self.components.insert(name, component_src);
         Ok(id.into())
     }
 
+    /// Add a partial that can be inlined with `include "name"`.
+    ///
+    /// Unlike a component a partial has no state and no Rust counterpart.
+    /// It's compiled in place, with the map following the name as its parameters.
+    ///
+    /// The parameters are replaced by the expressions they are given, and a `let` or a
+    /// loop binding with the same name shadows a parameter.
+    /// Every other name is resolved where the partial is included, the same as if the
+    /// markup was written there: in the state of the component, loop bindings and globals.
+    /// ```
+    /// # use anathema_templates::{Document, ToSourceKind};
+    /// let mut doc = Document::new("include \"title\" { text: 'hello' }");
+    /// doc.add_partial("title", "text [bold: true] text".to_template()).unwrap();
+    /// let (blueprint, _) = doc.compile().unwrap();
+    /// ```
+    #[allow(private_bounds)]
+    pub fn add_partial(&mut self, name: impl Into<String>, src: SourceKind) -> Result<()> {
+        let partial_src = match src {
+            SourceKind::Str(s) => ComponentSource::InMemory(s),
+            SourceKind::Path(path) => {
+                let template = read_to_string(&path)?;
+                ComponentSource::File { path, template }
+            }
+            SourceKind::Loaded { path, template } => ComponentSource::File { path, template },
+        };
+
+        self.components.insert_partial(name, partial_src);
+        Ok(())
+    }
+
     pub fn compile(&mut self) -> Result<(Blueprint, Globals)> {
         self.strings = Strings::empty();
         self.globals = Variables::default();
@@ -71,15 +105,48 @@ impl Document {
             components: &mut self.components,
             slots: SmallMap::empty(),
             current_component_parent: None,
+            params: HashMap::new(),
+            template: Template::new(&self.template, None),
         };
 
         let mut blueprints = Scope::new(statements).eval(&mut context)?;
         match blueprints.is_empty() {
-            true => Err(Error::EmptyTemplate),
+            true => Err(ErrorKind::EmptyTemplate.into()),
             false => Ok((blueprints.remove(0), self.globals.take().into())),
         }
     }
 
+    /// Check the document, its components and partials for problems
+    /// that would otherwise be silently ignored or only show up at runtime,
+    /// such as unknown widgets, misspelled attributes or attribute values of the wrong type.
+    ///
+    /// Unlike [`Document::compile`] this reports every problem it finds,
+    /// and only fails if a template can't be parsed.
+    /// ```
+    /// # use anathema_templates::Document;
+    /// # use anathema_templates::diagnostics::{Attribute, AttributeType, WidgetSchema};
+    /// struct Schema;
+    ///
+    /// impl WidgetSchema for Schema {
+    ///     fn has_widget(&self, ident: &str) -> bool {
+    ///         ident == "text"
+    ///     }
+    ///
+    ///     fn attributes(&self, _: &str) -> Option<&[Attribute]> {
+    ///         const TEXT: &[Attribute] = &[Attribute::new("bold", AttributeType::Bool)];
+    ///         Some(TEXT)
+    ///     }
+    /// }
+    ///
+    /// let mut doc = Document::new("text [bodl: true] 'hello'");
+    /// let diagnostics = doc.check(&Schema).unwrap();
+    /// assert_eq!(diagnostics.len(), 1);
+    /// ```
+    pub fn check(&mut self, schema: &impl WidgetSchema) -> Result<Vec<Diagnostic>> {
+        self.strings = Strings::empty();
+        Checker::new(schema, &mut self.strings, &mut self.components).check(&self.template)
+    }
+
     pub fn template_paths(&self) -> impl Iterator<Item = &PathBuf> {
         self.components.file_paths()
     }
@@ -88,3 +155,23 @@ impl Document {
         self.components.reload()
     }
 }
+
+/// Lex and parse a template without compiling it.
+///
+/// This finds syntax errors, but not errors that depend on other templates,
+/// such as missing components or partials.
+/// ```
+/// # use anathema_templates::parse;
+/// assert!(parse("vstack\n    text 'hello'").is_ok());
+///
+/// let err = parse("vstack\n    text 'hello").unwrap_err();
+/// assert_eq!(err.location.unwrap().line(), 2);
+/// ```
+pub fn parse(template: &str) -> Result<()> {
+    let mut strings = Strings::empty();
+    let mut components = ComponentTemplates::new();
+
+    let tokens = Lexer::new(template, &mut strings).collect::<Result<Vec<_>>>()?;
+    let tokens = Tokens::new(tokens, template.len());
+    Parser::new(tokens, &mut strings, template, &mut components).try_for_each(|statement| statement.map(|_| ()))
+}
@@ -0,0 +1,168 @@
+use std::fmt::{self, Debug, Display, Formatter};
+use std::path::Path;
+use std::sync::Arc;
+
+/// The location of an error in a template.
+///
+/// This is an offset into the template, which is shared between every location in it.
+/// The line, column and line of source are only looked up when they are needed.
+///
+/// Line number and column starts at one, not zero,
+/// because actual humans might read this.
+#[derive(Clone, PartialEq)]
+pub struct Location {
+    /// Path to the template, if it was loaded from a file
+    pub path: Option<Arc<Path>>,
+    offset: usize,
+    src: Arc<str>,
+}
+
+impl Location {
+    pub fn new(offset: usize, src: impl Into<Arc<str>>) -> Self {
+        let src = src.into();
+        Self {
+            path: None,
+            offset: offset.min(src.len()),
+            src,
+        }
+    }
+
+    pub(crate) fn with_path(mut self, path: Option<&Path>) -> Self {
+        self.path = path.map(Into::into);
+        self
+    }
+
+    /// Byte offset into the template
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn line(&self) -> usize {
+        1 + self.src[..self.line_start()].matches('\n').count()
+    }
+
+    /// Column in characters (not bytes)
+    pub fn col(&self) -> usize {
+        1 + self.src[self.line_start()..self.offset].chars().count()
+    }
+
+    /// The line of source the location is on
+    pub fn src_line(&self) -> &str {
+        let start = self.line_start();
+        let end = self.src[self.offset..]
+            .find('\n')
+            .map(|i| self.offset + i)
+            .unwrap_or(self.src.len());
+        self.src[start..end].trim_end_matches('\r')
+    }
+
+    fn line_start(&self) -> usize {
+        self.src[..self.offset].rfind('\n').map(|i| i + 1).unwrap_or(0)
+    }
+}
+
+impl Debug for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Location")
+            .field("path", &self.path)
+            .field("line", &self.line())
+            .field("col", &self.col())
+            .finish()
+    }
+}
+
+// Rendered the same way rustc renders a span:
+//
+//  --> path/to/template.aml:2:10
+//   |
+// 2 |     text 'hello
+//   |          ^
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let line = self.line();
+        let col = self.col();
+        let src_line = self.src_line();
+        let gutter = line.to_string().len();
+
+        match &self.path {
+            Some(path) => writeln!(f, "{:gutter$}--> {}:{line}:{col}", "", path.display())?,
+            None => writeln!(f, "{:gutter$}--> {line}:{col}", "")?,
+        }
+
+        // Tabs are kept so the caret lines up with the source line
+        let padding = src_line
+            .chars()
+            .take(col - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+
+        writeln!(f, "{:gutter$} |", "")?;
+        writeln!(f, "{line} | {src_line}")?;
+        write!(f, "{:gutter$} | {padding}^", "")
+    }
+}
+
+/// A template and the path it was loaded from.
+/// Every location in the template shares it.
+#[derive(Clone)]
+pub(crate) struct Template {
+    src: Arc<str>,
+    path: Option<Arc<Path>>,
+}
+
+impl Template {
+    pub(crate) fn new(src: &str, path: Option<&Path>) -> Self {
+        Self {
+            src: src.into(),
+            path: path.map(Into::into),
+        }
+    }
+
+    pub(crate) fn src(&self) -> &str {
+        &self.src
+    }
+
+    pub(crate) fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    pub(crate) fn location(&self, offset: usize) -> Location {
+        Location {
+            path: self.path.clone(),
+            offset: offset.min(self.src.len()),
+            src: self.src.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn location_of_offset() {
+        let src = "a\n    text 'hello\nb";
+        let location = Location::new(src.find('\'').unwrap(), src);
+        assert_eq!(location.line(), 2);
+        assert_eq!(location.col(), 10);
+        assert_eq!(location.src_line(), "    text 'hello");
+    }
+
+    #[test]
+    fn column_counts_chars() {
+        let src = "text 'åäö' x";
+        let location = Location::new(src.rfind('x').unwrap(), src);
+        assert_eq!(location.col(), 12);
+    }
+
+    #[test]
+    fn render_location() {
+        let src = "a\n    text 'hello\nb";
+        let location = Location::new(src.find('\'').unwrap(), src).with_path(Some(Path::new("tpl.aml")));
+        let expected = " --> tpl.aml:2:10
+  |
+2 |     text 'hello
+  |          ^";
+        assert_eq!(location.to_string(), expected);
+    }
+}
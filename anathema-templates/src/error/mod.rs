@@ -1,45 +1,95 @@
 use std::error::Error as StdError;
 use std::fmt::{self, Display, Formatter};
+use std::path::Path;
 
-pub(crate) use self::parse::{src_line_no, ParseError, ParseErrorKind};
+pub use self::location::Location;
+pub(crate) use self::location::Template;
+pub use self::parse::{ParseError, ParseErrorKind};
 
+mod location;
 mod parse;
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 #[derive(Debug)]
-pub enum Error {
-    ParseError(ParseError),
-    CircularDependency,
-    MissingComponent(String),
-    EmptyTemplate,
-    EmptyBody,
-    Io(std::io::Error),
+pub struct Error {
+    pub kind: ErrorKind,
+    /// Where in the template the error occurred.
+    /// This is `None` for errors that aren't tied to a template (e.g io errors)
+    pub location: Option<Location>,
+}
+
+impl Error {
+    pub(crate) fn new(kind: ErrorKind, location: Location) -> Self {
+        Self {
+            kind,
+            location: Some(location),
+        }
+    }
+
+    pub(crate) fn with_path(mut self, path: Option<&Path>) -> Self {
+        self.location = self.location.map(|location| location.with_path(path));
+        self
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match self {
-            Error::ParseError(err) => write!(f, "{err}"),
-            Error::CircularDependency => write!(f, "circular dependency"),
-            Error::MissingComponent(name) => write!(f, "`@{name}` is not a registered component"),
-            Error::EmptyTemplate => write!(f, "empty template"),
-            Error::EmptyBody => write!(f, "if or else node has no children"),
-            Error::Io(err) => write!(f, "{err}"),
+        write!(f, "error: {}", self.kind)?;
+        match &self.location {
+            Some(location) => write!(f, "\n{location}"),
+            None => Ok(()),
         }
     }
 }
 
 impl StdError for Error {}
 
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Self { kind, location: None }
+    }
+}
+
 impl From<ParseError> for Error {
-    fn from(value: ParseError) -> Self {
-        Self::ParseError(value)
+    fn from(err: ParseError) -> Self {
+        Self::new(ErrorKind::ParseError(err.kind), err.location)
     }
 }
 
 impl From<std::io::Error> for Error {
-    fn from(value: std::io::Error) -> Self {
-        Self::Io(value)
+    fn from(err: std::io::Error) -> Self {
+        ErrorKind::Io(err).into()
+    }
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    ParseError(ParseErrorKind),
+    CircularDependency,
+    MissingComponent(String),
+    MissingPartial(String),
+    InvalidInclude,
+    EmptyTemplate,
+    EmptyBody,
+    InvalidSwitch,
+    InvalidForElse,
+    Io(std::io::Error),
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ParseError(kind) => write!(f, "{kind}"),
+            Self::CircularDependency => write!(f, "circular dependency"),
+            Self::MissingComponent(name) => write!(f, "`@{name}` is not a registered component"),
+            Self::MissingPartial(name) => write!(f, "`{name}` is not a registered partial"),
+            Self::InvalidInclude => write!(f, "include only takes a map of parameters"),
+            Self::EmptyTemplate => write!(f, "empty template"),
+            Self::EmptyBody => write!(f, "if, else, case or default node has no children"),
+            Self::InvalidSwitch => write!(f, "switch can only contain case and default nodes"),
+            Self::InvalidForElse => write!(f, "for can only be followed by a single else, without a condition"),
+            Self::Io(err) => write!(f, "{err}"),
+        }
     }
 }
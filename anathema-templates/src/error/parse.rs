@@ -2,90 +2,30 @@ use std::error::Error as StdError;
 use std::fmt::{self, Display, Formatter};
 use std::ops::Range;
 
+use super::Location;
 use crate::token::Operator;
 
-// Line number and column starts at one, not zero,
-// because actual humans might read this
-pub(crate) fn src_line_no(end: usize, src: &str) -> (usize, usize) {
-    let mut line_no = 1;
-    let mut pos = 0;
-
-    while let Some(p) = &src[pos..end].find('\n') {
-        pos += p + 1;
-        line_no += 1;
-    }
-
-    // Set the column to at least one, as zero makes no sense to the end user
-    let col = 1 + end - pos;
-
-    (line_no, col)
-}
-
 #[derive(Debug, Clone, PartialEq)]
 pub struct ParseError {
     pub kind: ParseErrorKind,
-    pub line: usize,
-    pub col: usize,
-    pub src: String,
+    pub location: Location,
 }
 
 impl StdError for ParseError {}
 
 impl ParseError {
     pub(crate) fn new(range: Range<usize>, src: &str, kind: ParseErrorKind) -> Self {
-        let (line, col) = src_line_no(range.end, src);
         Self {
-            line,
-            col,
-            src: src.to_string(),
             kind,
+            location: Location::new(range.start, src),
         }
     }
 }
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        let start_line = self.line;
-        let lines = self.src.lines().enumerate().skip(start_line.saturating_sub(2)).take(3);
-
-        let msg = match &self.kind {
-            ParseErrorKind::UnterminatedString => "unterminated string".into(),
-            ParseErrorKind::UnterminatedAttributes => "unterminated attributes (missing `]`)".into(),
-            ParseErrorKind::UnterminatedAssociation => "unterminated association (missing `)`)".into(),
-            ParseErrorKind::UnterminatedElement => "unterminated element".into(),
-            ParseErrorKind::InvalidToken { expected } => {
-                format!("invalid token (expected: \"{expected}\")")
-            }
-            ParseErrorKind::InvalidNumber => "invalid number".into(),
-            ParseErrorKind::InvalidIndex => "invalid index".into(),
-            ParseErrorKind::InvalidPath => "invalid path".into(),
-            ParseErrorKind::InvalidHexValue => "invalid hex value".into(),
-            ParseErrorKind::UnexpectedEof => "unexpected end of file".into(),
-            ParseErrorKind::TrailingPipe => "trailing pipe character".into(),
-            ParseErrorKind::InvalidDedent => "dedent does not match previous indentation levels".into(),
-            ParseErrorKind::InvalidOperator(_op) => "invalid operator: {op}".into(),
-            ParseErrorKind::UnexpectedToken(_msg) => "unexpected token: {msg}".into(),
-            ParseErrorKind::InvalidKey => todo!(),
-        };
-
-        writeln!(f, "error on line {start_line}: {msg}")?;
-
-        for (no, line) in lines {
-            let no = no + 1;
-            let mark = if self.line == no { "-> " } else { "   " };
-            let mark_line = format!("{mark}{no}");
-            writeln!(f, "{mark_line} {line}")?;
-
-            // TODO:
-            // This has a bug, it "points" to the wrong value.
-            // This is most likely because the offsets are wrong.
-            //
-            // if self.line == no {
-            //     writeln!(f, "{:_<width$}|", "_", width = self.col + mark_line.len())?;
-            // }
-        }
-
-        Ok(())
+        writeln!(f, "error: {}", self.kind)?;
+        write!(f, "{}", self.location)
     }
 }
 
@@ -95,6 +35,7 @@ pub enum ParseErrorKind {
     UnterminatedElement,
     UnterminatedAttributes,
     UnterminatedAssociation,
+    EmptyInterpolation,
     InvalidToken { expected: &'static str },
     InvalidNumber,
     InvalidIndex,
@@ -107,3 +48,26 @@ pub enum ParseErrorKind {
     UnexpectedToken(String),
     InvalidKey,
 }
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnterminatedString => write!(f, "unterminated string"),
+            Self::UnterminatedAttributes => write!(f, "unterminated attributes (missing `]`)"),
+            Self::UnterminatedAssociation => write!(f, "unterminated association (missing `)`)"),
+            Self::UnterminatedElement => write!(f, "unterminated element"),
+            Self::EmptyInterpolation => write!(f, "empty interpolation"),
+            Self::InvalidToken { expected } => write!(f, "invalid token (expected: \"{expected}\")"),
+            Self::InvalidNumber => write!(f, "invalid number"),
+            Self::InvalidIndex => write!(f, "invalid index"),
+            Self::InvalidPath => write!(f, "invalid path"),
+            Self::InvalidHexValue => write!(f, "invalid hex value"),
+            Self::UnexpectedEof => write!(f, "unexpected end of file"),
+            Self::TrailingPipe => write!(f, "trailing pipe character"),
+            Self::InvalidDedent => write!(f, "dedent does not match previous indentation levels"),
+            Self::InvalidOperator(op) => write!(f, "invalid operator: {op}"),
+            Self::UnexpectedToken(msg) => write!(f, "unexpected token: {msg}"),
+            Self::InvalidKey => write!(f, "invalid key"),
+        }
+    }
+}
@@ -20,6 +20,13 @@ pub fn eval(expr: Expr, strings: &Strings) -> Result<Expression, ParseErrorKind>
             let string = strings.get_unchecked(string_id);
             Expression::Str(Rc::from(string))
         }
+        Expr::TextSegments(segments) => {
+            let segments = segments
+                .into_iter()
+                .map(|expr| eval(expr, strings))
+                .collect::<Result<_, _>>()?;
+            Expression::TextSegments(segments)
+        }
         Expr::Array { lhs, index } => {
             let lhs = eval(*lhs, strings)?;
             let index = eval(*index, strings)?;
@@ -72,6 +79,7 @@ pub fn eval(expr: Expr, strings: &Strings) -> Result<Expression, ParseErrorKind>
                 };
                 Expression::Equality(eval(*lhs, strings)?.into(), eval(*rhs, strings)?.into(), equality)
             }
+            Operator::Range => Expression::Range(eval(*lhs, strings)?.into(), eval(*rhs, strings)?.into()),
             _ => return Err(ParseErrorKind::InvalidToken { expected: "" }),
         },
         Expr::Unary { op, expr } => {
@@ -116,6 +124,23 @@ pub fn eval(expr: Expr, strings: &Strings) -> Result<Expression, ParseErrorKind>
                 args: args.into_boxed_slice(),
             }
         }
+        Expr::Filter { value, name, args } => {
+            let args = args
+                .into_iter()
+                .map(|expr| eval(expr, strings))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Expression::Filter {
+                value: eval(*value, strings)?.into(),
+                name: Rc::from(strings.get_unchecked(name)),
+                args: args.into_boxed_slice(),
+            }
+        }
+        Expr::Conditional { cond, lhs, rhs } => Expression::Conditional(
+            eval(*cond, strings)?.into(),
+            eval(*lhs, strings)?.into(),
+            eval(*rhs, strings)?.into(),
+        ),
     };
 
     Ok(output)
@@ -138,6 +163,12 @@ mod test {
         parse_expr(&mut tokens, &strings).unwrap()
     }
 
+    #[test]
+    fn conditional() {
+        let expr = eval_src("a.b ? 'yes' : x + 1");
+        assert_eq!(expr.to_string(), "a[b] ? yes : x + 1");
+    }
+
     #[test]
     fn ident() {
         let expr = eval_src("ident");
@@ -204,6 +235,12 @@ mod test {
         assert_eq!(expr.to_string(), "double quote");
     }
 
+    #[test]
+    fn text_segments() {
+        let expr = eval_src("'Item {item.name} ({loop})'");
+        assert_eq!(expr.to_string(), "\"Item {item[name]} ({loop})\"");
+    }
+
     #[test]
     fn addition() {
         let expr = eval_src("-2 + -3");
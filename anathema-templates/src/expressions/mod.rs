@@ -35,6 +35,8 @@ pub enum Expression {
     Str(Rc<str>),
     List(Rc<[Self]>),
     Map(Rc<HashMap<Rc<str>, Self>>),
+    /// An interpolated string: `"Item {item.name}"`
+    TextSegments(Rc<[Self]>),
 
     // Unary
     Not(Box<Self>),
@@ -42,6 +44,8 @@ pub enum Expression {
 
     // Conditionals
     Equality(Box<Self>, Box<Self>, Equality),
+    /// `cond ? lhs : rhs`
+    Conditional(Box<Self>, Box<Self>, Box<Self>),
 
     // Lookup
     Ident(Rc<str>),
@@ -49,9 +53,23 @@ pub enum Expression {
 
     // Operations
     Op(Box<Self>, Box<Self>, Op),
+    /// `from..to`, excluding `to`
+    Range(Box<Self>, Box<Self>),
 
     // Function call
-    Call { fun: Box<Self>, args: Box<[Self]> },
+    Call {
+        fun: Box<Self>,
+        args: Box<[Self]>,
+    },
+    /// `value | name(args)`, calling the function `name` with
+    /// the value as the first argument.
+    /// The pipe binds the loosest of all operators,
+    /// so `a ? b : c | upper` filters the result of the conditional.
+    Filter {
+        value: Box<Self>,
+        name: Rc<str>,
+        args: Box<[Self]>,
+    },
 }
 
 impl From<Box<Expression>> for Expression {
@@ -128,6 +146,26 @@ impl Display for Expression {
                     args.iter().map(|val| val.to_string()).collect::<Vec<_>>().join(", ")
                 )
             }
+            Self::Filter { value, name, args } => match args.is_empty() {
+                true => write!(f, "{value} | {name}"),
+                false => write!(
+                    f,
+                    "{value} | {name}({})",
+                    args.iter().map(|val| val.to_string()).collect::<Vec<_>>().join(", ")
+                ),
+            },
+            Self::Conditional(cond, lhs, rhs) => write!(f, "{cond} ? {lhs} : {rhs}"),
+            Self::Range(from, to) => write!(f, "{from}..{to}"),
+            Self::TextSegments(segments) => {
+                write!(f, "\"")?;
+                for segment in segments.iter() {
+                    match segment {
+                        Self::Str(s) => write!(f, "{}", s.replace('{', "\\{").replace('}', "\\}"))?,
+                        expr => write!(f, "{{{expr}}}")?,
+                    }
+                }
+                write!(f, "\"")
+            }
         }
     }
 }
@@ -158,6 +196,10 @@ pub fn modulo(lhs: Box<Expression>, rhs: Box<Expression>) -> Box<Expression> {
     Expression::Op(lhs, rhs, Op::Mod).into()
 }
 
+pub fn range(from: Box<Expression>, to: Box<Expression>) -> Box<Expression> {
+    Expression::Range(from, to).into()
+}
+
 pub fn sub(lhs: Box<Expression>, rhs: Box<Expression>) -> Box<Expression> {
     Expression::Op(lhs, rhs, Op::Sub).into()
 }
@@ -209,6 +251,11 @@ pub fn list<E: Into<Expression>>(input: impl IntoIterator<Item = E>) -> Box<Expr
     Expression::List(vec.into()).into()
 }
 
+pub fn text_segments<E: Into<Expression>>(input: impl IntoIterator<Item = E>) -> Box<Expression> {
+    let vec = input.into_iter().map(|val| val.into()).collect::<Vec<_>>();
+    Expression::TextSegments(vec.into()).into()
+}
+
 pub fn map<E: Into<Expression>>(input: impl IntoIterator<Item = (&'static str, E)>) -> Box<Expression> {
     let input = input.into_iter().map(|(k, v)| (k.into(), v.into()));
     let hm: HashMap<Rc<str>, Expression> = HashMap::from_iter(input);
@@ -240,3 +287,16 @@ pub fn and(lhs: Box<Expression>, rhs: Box<Expression>) -> Box<Expression> {
 pub fn or(lhs: Box<Expression>, rhs: Box<Expression>) -> Box<Expression> {
     Expression::Equality(lhs, rhs, Equality::Or).into()
 }
+
+pub fn conditional(cond: Box<Expression>, lhs: Box<Expression>, rhs: Box<Expression>) -> Box<Expression> {
+    Expression::Conditional(cond, lhs, rhs).into()
+}
+
+pub fn filter(value: Box<Expression>, name: &str, args: impl IntoIterator<Item = Box<Expression>>) -> Box<Expression> {
+    Expression::Filter {
+        value,
+        name: name.into(),
+        args: args.into_iter().map(|arg| *arg).collect(),
+    }
+    .into()
+}
@@ -10,14 +10,17 @@ use crate::token::{Kind, Operator, Tokens, Value};
 
 pub(crate) mod prec {
     pub const INITIAL: u8 = 0;
-    pub const CONDITIONAL: u8 = 2;
-    pub const EQUALITY: u8 = 3;
-    pub const LOGICAL: u8 = 4;
-    pub const SUM: u8 = 5;
-    pub const PRODUCT: u8 = 6;
-    pub const PREFIX: u8 = 8;
-    pub const CALL: u8 = 10;
-    pub const SUBCRIPT: u8 = 11;
+    pub const PIPE: u8 = 1;
+    pub const TERNARY: u8 = 2;
+    pub const RANGE: u8 = 3;
+    pub const CONDITIONAL: u8 = 4;
+    pub const EQUALITY: u8 = 5;
+    pub const LOGICAL: u8 = 6;
+    pub const SUM: u8 = 7;
+    pub const PRODUCT: u8 = 8;
+    pub const PREFIX: u8 = 9;
+    pub const CALL: u8 = 11;
+    pub const SUBCRIPT: u8 = 12;
 }
 
 fn get_precedence(op: Operator) -> u8 {
@@ -31,6 +34,9 @@ fn get_precedence(op: Operator) -> u8 {
         }
         Operator::EqualEqual | Operator::NotEqual => prec::EQUALITY,
         Operator::Or | Operator::And => prec::CONDITIONAL,
+        Operator::Range => prec::RANGE,
+        Operator::Question => prec::TERNARY,
+        Operator::Pipe => prec::PIPE,
 
         _ => prec::INITIAL,
     }
@@ -54,12 +60,23 @@ pub(crate) enum Expr {
         fun: Box<Expr>,
         args: Vec<Expr>,
     },
+    Filter {
+        value: Box<Expr>,
+        name: StringId,
+        args: Vec<Expr>,
+    },
     Array {
         lhs: Box<Expr>,
         index: Box<Expr>,
     },
     List(Vec<Expr>),
     Map(Vec<(Expr, Expr)>),
+    Conditional {
+        cond: Box<Expr>,
+        lhs: Box<Expr>,
+        rhs: Box<Expr>,
+    },
+    TextSegments(Vec<Expr>),
 }
 
 impl Display for Expr {
@@ -87,6 +104,15 @@ impl Display for Expr {
                 let s = args.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", ");
                 write!(f, "{fun}({s})")
             }
+            Expr::Filter { value, name, args } => {
+                let s = args.iter().map(|a| a.to_string()).collect::<Vec<_>>().join(", ");
+                write!(f, "(| {value} {name} [{s}])")
+            }
+            Expr::Conditional { cond, lhs, rhs } => write!(f, "(? {cond} {lhs} {rhs})"),
+            Expr::TextSegments(segments) => {
+                let s = segments.iter().map(|e| e.to_string()).collect::<Vec<_>>().join(" ");
+                write!(f, "(text {s})")
+            }
         }
     }
 }
@@ -116,6 +142,14 @@ fn expr_bp(tokens: &mut Tokens, precedence: u8) -> Result<Expr, ParseErrorKind>
             Value::Primitive(val) => Expr::Primitive(val),
             Value::Ident(ident) => Expr::Ident(ident),
             Value::String(sid) => Expr::Str(sid),
+            // Each segment is either a string or an expression in parens,
+            // so parsing with the highest precedence yields one segment at a time
+            Value::TextSegments(count) => {
+                let segments = (0..count)
+                    .map(|_| expr_bp(tokens, prec::SUBCRIPT))
+                    .collect::<Result<_, _>>()?;
+                Expr::TextSegments(segments)
+            }
         },
         Kind::Eof => panic!("unexpected eof"),
         // TODO: see panic
@@ -161,6 +195,43 @@ fn expr_bp(tokens: &mut Tokens, precedence: u8) -> Result<Expr, ParseErrorKind>
                 };
                 continue;
             }
+            // Conditional: `cond ? lhs : rhs`.
+            // The right hand side stops at a pipe, so a filter applies to the whole
+            // conditional, but not at another conditional: `a ? b : c ? d : e`
+            Operator::Question => {
+                let lhs = expr_bp(tokens, prec::INITIAL)?;
+                let Kind::Op(Operator::Colon) = tokens.next_no_indent() else {
+                    return Err(ParseErrorKind::InvalidToken { expected: ":" });
+                };
+                let rhs = expr_bp(tokens, prec::PIPE)?;
+                left = Expr::Conditional {
+                    cond: Box::new(left),
+                    lhs: Box::new(lhs),
+                    rhs: Box::new(rhs),
+                };
+                continue;
+            }
+            // Filter: `value | name` or `value | name(args)`
+            Operator::Pipe => {
+                let Kind::Value(Value::Ident(name)) = tokens.next_no_indent() else {
+                    return Err(ParseErrorKind::InvalidToken {
+                        expected: "filter name",
+                    });
+                };
+                let args = match tokens.peek_skip_indent() {
+                    Kind::Op(Operator::LParen) => {
+                        tokens.consume();
+                        parse_args(tokens)?
+                    }
+                    _ => vec![],
+                };
+                left = Expr::Filter {
+                    value: Box::new(left),
+                    name,
+                    args,
+                };
+                continue;
+            }
             _ => {}
         }
 
@@ -178,6 +249,16 @@ fn expr_bp(tokens: &mut Tokens, precedence: u8) -> Result<Expr, ParseErrorKind>
 }
 
 fn parse_function(tokens: &mut Tokens, left: Expr) -> Result<Expr, ParseErrorKind> {
+    let expr = Expr::Call {
+        fun: Box::new(left),
+        args: parse_args(tokens)?,
+    };
+
+    Ok(expr)
+}
+
+// Parse arguments up to and including the closing parenthesis
+fn parse_args(tokens: &mut Tokens) -> Result<Vec<Expr>, ParseErrorKind> {
     let mut args = vec![];
 
     loop {
@@ -195,12 +276,7 @@ fn parse_function(tokens: &mut Tokens, left: Expr) -> Result<Expr, ParseErrorKin
         args.push(expr_bp(tokens, prec::INITIAL)?);
     }
 
-    let expr = Expr::Call {
-        fun: Box::new(left),
-        args,
-    };
-
-    Ok(expr)
+    Ok(args)
 }
 
 fn parse_collection(tokens: &mut Tokens) -> Result<Expr, ParseErrorKind> {
@@ -345,6 +421,36 @@ mod test {
         assert_eq!(parse(input), "(+ 5 (% 1 2))");
     }
 
+    #[test]
+    fn range() {
+        let input = "0..a + 1";
+        assert_eq!(parse(input), "(.. 0 (+ <sid 0> 1))");
+
+        let input = "a > 1 ? 0..2 : b";
+        assert_eq!(parse(input), "(? (> <sid 0> 1) (.. 0 2) <sid 1>)");
+    }
+
+    #[test]
+    fn filter() {
+        let input = "a + 1 | round | truncate(2, b)";
+        assert_eq!(parse(input), "(| (| (+ <sid 0> 1) <sid 1> []) <sid 2> [2, <sid 3>])");
+
+        let input = "a ? b : c | upper";
+        assert_eq!(parse(input), "(| (? <sid 0> <sid 1> <sid 2>) <sid 3> [])");
+
+        let input = "a ? b : c ? d : e | upper";
+        assert_eq!(
+            parse(input),
+            "(| (? <sid 0> <sid 1> (? <sid 2> <sid 3> <sid 4>)) <sid 5> [])"
+        );
+
+        let input = "a ? b | upper : (c | lower)";
+        assert_eq!(
+            parse(input),
+            "(? <sid 0> (| <sid 1> <sid 2> []) (| <sid 3> <sid 4> []))"
+        );
+    }
+
     #[test]
     fn list() {
         let input = "[1, 2, a, 4]";
@@ -376,6 +482,36 @@ mod test {
         assert_eq!(parse(input), "(&& (== 1 2) (== 3 4))");
     }
 
+    #[test]
+    fn conditional() {
+        let input = "a > 1 ? 'big' : 'small'";
+        assert_eq!(parse(input), "(? (> <sid 0> 1) \"<sid 1>\" \"<sid 2>\")");
+
+        let input = "a ? 1 : b ? 2 : 3";
+        assert_eq!(parse(input), "(? <sid 0> 1 (? <sid 1> 2 3))");
+
+        let input = "a || b ? 1 + 2 : 3";
+        assert_eq!(parse(input), "(? (|| <sid 0> <sid 1>) (+ 1 2) 3)");
+    }
+
+    #[test]
+    fn conditional_in_map() {
+        let input = "{a: b ? 1 : 2, c: 3}";
+        assert_eq!(parse(input), "{<sid 0>: (? <sid 1> 1 2), <sid 2>: 3}");
+    }
+
+    #[test]
+    fn text_segments() {
+        let input = "'a {b + 1} c'.upper()";
+        assert_eq!(
+            parse(input),
+            "(. (text \"<sid 0>\" (+ <sid 1> 1) \"<sid 2>\") <sid 3>)()"
+        );
+
+        let input = "'{a}' == b";
+        assert_eq!(parse(input), "(== (text <sid 0>) <sid 1>)");
+    }
+
     #[test]
     fn not() {
         let input = "1 != 2 && 3 != 4";
@@ -0,0 +1,729 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use anathema_store::storage::strings::Strings;
+
+use crate::components::ComponentTemplates;
+use crate::error::Result;
+use crate::expressions::parser::prec;
+use crate::expressions::{Equality, Expression, Op};
+use crate::primitives::Primitive;
+use crate::statements::parser::Parser;
+use crate::statements::Statement;
+use crate::token::Tokens;
+use crate::Lexer;
+
+const INDENT: &str = "    ";
+const QUOTE: char = '"';
+// Higher than any operator, so atoms are never wrapped in parens
+const ATOM: u8 = u8::MAX;
+
+/// Format a template.
+///
+/// The template is parsed and printed back in a canonical form:
+/// * four spaces of indentation per level
+/// * double quoted strings (unless the string contains an unescaped double quote)
+/// * attributes on one line as `[key: value, key: value]`
+/// * at most one empty line between lines
+///
+/// Comments are preserved.
+/// A comment on its own line is placed before the line following it,
+/// and a comment at the end of a line stays on that line.
+///
+/// Since maps are unordered, map keys are printed in alphabetical order.
+/// ```
+/// # use anathema_templates::format;
+/// let src = "vstack\n  text [ bold:true ] 'hello' // greeting\n";
+/// let output = format(src).unwrap();
+/// assert_eq!(output, "vstack\n    text [bold: true] \"hello\" // greeting\n");
+/// ```
+pub fn format(src: &str) -> Result<String> {
+    let mut strings = Strings::empty();
+    let mut components = ComponentTemplates::new();
+
+    let mut lexer = Lexer::new(src, &mut strings);
+    let tokens = lexer.by_ref().collect::<Result<Vec<_>>>()?;
+    let comments = std::mem::take(&mut lexer.comments);
+    let tokens = Tokens::new(tokens, src.len());
+
+    let statements = Parser::new(tokens, &mut strings, src, &mut components).collect::<Result<Vec<_>>>()?;
+
+    let source = Source::new(src);
+    let mut lines: Vec<Line<'_>> = vec![];
+    let mut depth = 0;
+
+    for (statement, offset) in statements {
+        let line_no = source.line_of(offset);
+
+        let head = match statement {
+            Statement::ScopeStart => {
+                depth += 1;
+                continue;
+            }
+            Statement::ScopeEnd => {
+                depth -= 1;
+                continue;
+            }
+            Statement::Eof => break,
+            Statement::LoadAttribute { key, value } => {
+                let line = lines.last_mut().expect("attributes always follow a node");
+                line.attributes
+                    .push(format!("{}: {}", strings.get_unchecked(key), expr(&value)));
+                line.end = line_no;
+                continue;
+            }
+            Statement::AssociatedFunction { internal, external } => {
+                let line = lines
+                    .last_mut()
+                    .expect("associated functions always follow a component");
+                let internal = strings.get_unchecked(internal);
+                let external = strings.get_unchecked(external);
+                line.associations.push(format!("{internal}->{external}"));
+                line.end = line_no;
+                continue;
+            }
+            Statement::LoadValue(value) => {
+                let line = lines.last_mut().expect("values always follow a node");
+                line.value = Some(node_value(&value));
+                line.end = line_no;
+                continue;
+            }
+            Statement::Node(ident) => strings.get_unchecked(ident),
+            Statement::Component(id) => {
+                let (name, _) = components.get(id).expect("the parser registers every component");
+                format!("@{name}")
+            }
+            Statement::Include(name) => format!("include {}", quote(&strings.get_unchecked(name))),
+            Statement::ComponentSlot(ident) => format!("${}", strings.get_unchecked(ident)),
+            Statement::For { key, binding, data } => {
+                let key = key
+                    .map(|key| format!("{}, ", strings.get_unchecked(key)))
+                    .unwrap_or_default();
+                format!("for {key}{} in {}", strings.get_unchecked(binding), expr(&data))
+            }
+            Statement::Declaration { binding, value } => {
+                format!("let {} = {}", strings.get_unchecked(binding), expr(&value))
+            }
+            Statement::If(cond) => format!("if {}", expr(&cond)),
+            Statement::Else(Some(cond)) => format!("else if {}", expr(&cond)),
+            Statement::Else(None) => "else".into(),
+            Statement::Switch(value) => format!("switch {}", expr(&value)),
+            Statement::Case(Some(value)) => format!("case {}", expr(&value)),
+            Statement::Case(None) => "default".into(),
+        };
+
+        lines.push(Line::new(depth, line_no, head));
+    }
+
+    // Comments at the end of a line stay there,
+    // the rest are printed on their own lines
+    let mut own_line_comments = vec![];
+    for range in comments {
+        let line_no = source.line_of(range.start);
+        let text = src[range.clone()].trim_end();
+        let is_trailing = !src[source.line_starts[line_no]..range.start].trim().is_empty();
+
+        let line = lines.iter_mut().rev().find(|line| line.start <= line_no);
+        match line {
+            Some(line) if is_trailing && line.start == line_no && line.comment.is_none() => line.comment = Some(text),
+            _ => own_line_comments.push((line_no, text)),
+        }
+    }
+
+    let mut output = String::new();
+    let mut prev_end = None;
+    let mut lines_written = 0;
+    let mut comments = own_line_comments.into_iter().peekable();
+
+    loop {
+        let next_line = lines.get(lines_written);
+        let comment = comments.next_if(|(line_no, _)| next_line.map(|line| *line_no < line.start).unwrap_or(true));
+
+        let start = match (comment, next_line) {
+            (Some((line_no, _)), _) => line_no,
+            (None, Some(line)) => line.start,
+            (None, None) => break,
+        };
+
+        // Keep a single empty line where there was at least one
+        if let Some(prev_end) = prev_end {
+            if (prev_end + 1..start).any(|line_no| source.is_blank(line_no)) {
+                output.push('\n');
+            }
+        }
+
+        let end = match (comment, next_line) {
+            (Some((line_no, text)), _) => {
+                let depth = source.depth_at(source.indent(line_no), &lines[..lines_written]);
+                write_line(&mut output, depth, text);
+                line_no
+            }
+            (_, Some(line)) => {
+                line.write(&mut output);
+                lines_written += 1;
+                line.end
+            }
+            (None, None) => unreachable!(),
+        };
+
+        prev_end = Some(end);
+    }
+
+    Ok(output)
+}
+
+// -----------------------------------------------------------------------------
+//     - Lines -
+// -----------------------------------------------------------------------------
+struct Line<'src> {
+    depth: usize,
+    // The source lines the statements starts and ends on
+    start: usize,
+    end: usize,
+    head: String,
+    associations: Vec<String>,
+    attributes: Vec<String>,
+    value: Option<String>,
+    comment: Option<&'src str>,
+}
+
+impl<'src> Line<'src> {
+    fn new(depth: usize, line_no: usize, head: String) -> Self {
+        Self {
+            depth,
+            start: line_no,
+            end: line_no,
+            head,
+            associations: vec![],
+            attributes: vec![],
+            value: None,
+            comment: None,
+        }
+    }
+
+    fn write(&self, output: &mut String) {
+        let mut line = self.head.clone();
+
+        if !self.associations.is_empty() {
+            let _ = write!(line, " ({})", self.associations.join(", "));
+        }
+
+        if !self.attributes.is_empty() {
+            let _ = write!(line, " [{}]", self.attributes.join(", "));
+        }
+
+        if let Some(value) = &self.value {
+            let _ = write!(line, " {value}");
+        }
+
+        if let Some(comment) = self.comment {
+            let _ = write!(line, " {comment}");
+        }
+
+        write_line(output, self.depth, &line);
+    }
+}
+
+fn write_line(output: &mut String, depth: usize, line: &str) {
+    for _ in 0..depth {
+        output.push_str(INDENT);
+    }
+    output.push_str(line);
+    output.push('\n');
+}
+
+// -----------------------------------------------------------------------------
+//     - Source -
+// -----------------------------------------------------------------------------
+struct Source<'src> {
+    src: &'src str,
+    line_starts: Vec<usize>,
+}
+
+impl<'src> Source<'src> {
+    fn new(src: &'src str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(src.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self { src, line_starts }
+    }
+
+    // Zero based line number of a byte offset
+    fn line_of(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|&start| start <= offset) - 1
+    }
+
+    fn line(&self, line_no: usize) -> &'src str {
+        let start = self.line_starts[line_no];
+        let end = self.line_starts.get(line_no + 1).copied().unwrap_or(self.src.len());
+        &self.src[start..end]
+    }
+
+    fn is_blank(&self, line_no: usize) -> bool {
+        self.line(line_no).trim().is_empty()
+    }
+
+    // Indent the same way the lexer counts it
+    fn indent(&self, line_no: usize) -> usize {
+        self.line(line_no)
+            .chars()
+            .take_while(|c| c.is_whitespace() && *c != '\n')
+            .count()
+    }
+
+    // The depth of something indented by `indent`,
+    // relative to the closest previous line that is not indented further
+    fn depth_at(&self, indent: usize, prev_lines: &[Line<'_>]) -> usize {
+        let parent = prev_lines
+            .iter()
+            .rev()
+            .map(|line| (self.indent(line.start), line.depth))
+            .find(|(line_indent, _)| *line_indent <= indent);
+
+        match parent {
+            Some((line_indent, depth)) if line_indent == indent => depth,
+            Some((_, depth)) => depth + 1,
+            None => 0,
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+//     - Expressions -
+// -----------------------------------------------------------------------------
+
+// Multiple values after a node are parsed into a list
+fn node_value(value: &Expression) -> String {
+    let value = match value {
+        Expression::List(values) if values.len() > 1 => values.iter().map(expr).collect::<Vec<_>>().join(" "),
+        value => expr(value),
+    };
+
+    // A list directly after the node would be read as attributes
+    match value.starts_with('[') {
+        true => format!("({value})"),
+        false => value,
+    }
+}
+
+fn expr(expression: &Expression) -> String {
+    let mut output = String::new();
+    write_expr(&mut output, expression, prec::INITIAL);
+    output
+}
+
+fn precedence(expression: &Expression) -> u8 {
+    match expression {
+        Expression::Filter { .. } => prec::PIPE,
+        Expression::Conditional(..) => prec::TERNARY,
+        Expression::Range(..) => prec::RANGE,
+        Expression::Equality(_, _, Equality::And | Equality::Or) => prec::CONDITIONAL,
+        Expression::Equality(_, _, Equality::Eq | Equality::NotEq) => prec::EQUALITY,
+        Expression::Equality(..) => prec::LOGICAL,
+        Expression::Op(_, _, Op::Add | Op::Sub) => prec::SUM,
+        Expression::Op(..) => prec::PRODUCT,
+        Expression::Not(_) | Expression::Negative(_) => prec::PREFIX,
+        _ => ATOM,
+    }
+}
+
+fn write_expr(output: &mut String, expression: &Expression, min_precedence: u8) {
+    let precedence = precedence(expression);
+    let parens = precedence < min_precedence;
+    if parens {
+        output.push('(');
+    }
+
+    match expression {
+        Expression::Primitive(primitive) => write_primitive(output, primitive),
+        Expression::Str(s) => output.push_str(&quote(s)),
+        Expression::Ident(ident) => output.push_str(ident),
+        Expression::List(list) => {
+            output.push('[');
+            write_list(output, list);
+            output.push(']');
+        }
+        Expression::Map(map) => {
+            let entries = map.iter().collect::<BTreeMap<_, _>>();
+
+            output.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    output.push_str(", ");
+                }
+                output.push_str(&quote(key));
+                output.push_str(": ");
+                write_expr(output, value, prec::INITIAL);
+            }
+            output.push('}');
+        }
+        Expression::TextSegments(segments) => {
+            let quote = quote_char(segments.iter().filter_map(|segment| match segment {
+                Expression::Str(s) => Some(&**s),
+                _ => None,
+            }));
+
+            output.push(quote);
+            for segment in segments.iter() {
+                match segment {
+                    Expression::Str(s) => escape_braces(output, s),
+                    expression => {
+                        output.push('{');
+                        write_expr(output, expression, prec::INITIAL);
+                        output.push('}');
+                    }
+                }
+            }
+            output.push(quote);
+        }
+        Expression::Not(expr) => {
+            output.push('!');
+            write_expr(output, expr, prec::PREFIX);
+        }
+        Expression::Negative(expr) => {
+            output.push('-');
+            write_expr(output, expr, prec::PREFIX);
+        }
+        Expression::Equality(lhs, rhs, equality) => {
+            let op = match equality {
+                Equality::Eq => "==",
+                Equality::NotEq => "!=",
+                Equality::And => "&&",
+                Equality::Or => "||",
+                Equality::Gt => ">",
+                Equality::Gte => ">=",
+                Equality::Lt => "<",
+                Equality::Lte => "<=",
+            };
+            write_binary(output, lhs, op, rhs, precedence);
+        }
+        Expression::Op(lhs, rhs, op) => {
+            let op = match op {
+                Op::Add => "+",
+                Op::Sub => "-",
+                Op::Div => "/",
+                Op::Mul => "*",
+                Op::Mod => "%",
+            };
+            write_binary(output, lhs, op, rhs, precedence);
+        }
+        // Ranges don't chain, so both sides bind tighter
+        Expression::Range(from, to) => {
+            write_expr(output, from, prec::RANGE + 1);
+            output.push_str("..");
+            write_expr(output, to, prec::RANGE + 1);
+        }
+        Expression::Conditional(cond, lhs, rhs) => {
+            write_expr(output, cond, prec::TERNARY + 1);
+            output.push_str(" ? ");
+            write_expr(output, lhs, prec::INITIAL);
+            output.push_str(" : ");
+            // A filter has to keep its parens, or it would apply to the whole conditional
+            write_expr(output, rhs, prec::TERNARY);
+        }
+        Expression::Index(lhs, index) => {
+            write_expr(output, lhs, ATOM);
+            match &**index {
+                Expression::Str(key) if is_ident(key) => {
+                    output.push('.');
+                    output.push_str(key);
+                }
+                index => {
+                    output.push('[');
+                    write_expr(output, index, prec::INITIAL);
+                    output.push(']');
+                }
+            }
+        }
+        Expression::Call { fun, args } => {
+            write_expr(output, fun, ATOM);
+            output.push('(');
+            write_list(output, args);
+            output.push(')');
+        }
+        Expression::Filter { value, name, args } => {
+            write_expr(output, value, prec::PIPE);
+            output.push_str(" | ");
+            output.push_str(name);
+            if !args.is_empty() {
+                output.push('(');
+                write_list(output, args);
+                output.push(')');
+            }
+        }
+    }
+
+    if parens {
+        output.push(')');
+    }
+}
+
+// Binary operators are left associative
+fn write_binary(output: &mut String, lhs: &Expression, op: &str, rhs: &Expression, precedence: u8) {
+    write_expr(output, lhs, precedence);
+    let _ = write!(output, " {op} ");
+    write_expr(output, rhs, precedence + 1);
+}
+
+fn write_list(output: &mut String, list: &[Expression]) {
+    for (i, expression) in list.iter().enumerate() {
+        if i > 0 {
+            output.push_str(", ");
+        }
+        write_expr(output, expression, prec::INITIAL);
+    }
+}
+
+fn write_primitive(output: &mut String, primitive: &Primitive) {
+    let _ = match primitive {
+        Primitive::Bool(b) => write!(output, "{b}"),
+        Primitive::Char(c) => write!(output, "{}", quote(&c.to_string())),
+        Primitive::Int(i) => write!(output, "{i}"),
+        // Make sure a float is still a float when parsed again
+        Primitive::Float(f) if f.fract() == 0.0 => write!(output, "{f}.0"),
+        Primitive::Float(f) => write!(output, "{f}"),
+        Primitive::Hex(hex) => write!(output, "#{:02x}{:02x}{:02x}", hex.r, hex.g, hex.b),
+    };
+}
+
+fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    let starts_with_letter = matches!(chars.next(), Some('a'..='z' | 'A'..='Z' | '_'));
+    let is_keyword = matches!(s, "for" | "in" | "if" | "else" | "include" | "true" | "false" | "let");
+    starts_with_letter && !is_keyword && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// The lexer keeps escaped quotes as they are (`\"`),
+// so a string can be written with either quote
+// as long as every occurrence of that quote is escaped.
+fn quote_char<'a>(mut strings: impl Iterator<Item = &'a str> + Clone) -> char {
+    let is_escaped = |s: &str, quote: char| s.match_indices(quote).all(|(i, _)| s[..i].ends_with('\\'));
+
+    match strings.clone().all(|s| is_escaped(s, QUOTE)) {
+        true => QUOTE,
+        false if strings.all(|s| is_escaped(s, '\'')) => '\'',
+        false => QUOTE,
+    }
+}
+
+fn quote(s: &str) -> String {
+    let quote = quote_char(std::iter::once(s));
+    let mut output = String::with_capacity(s.len() + 2);
+    output.push(quote);
+    escape_braces(&mut output, s);
+    output.push(quote);
+    output
+}
+
+// A single opening brace starts an interpolation,
+// where as `{{` and `{}` are left as they are.
+// A backslash is escaped if it would otherwise escape the next char
+// (or the closing quote).
+fn escape_braces(output: &mut String, s: &str) {
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if matches!(chars.peek(), None | Some('\\' | '{' | '}')) => output.push_str("\\\\"),
+            '{' if matches!(chars.peek(), Some('{' | '}')) => {
+                output.push(c);
+                output.extend(chars.next());
+            }
+            '{' => output.push_str("\\{"),
+            c => output.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::statements::Statements;
+
+    fn statements(src: &str) -> Statements {
+        let mut strings = Strings::empty();
+        let mut components = ComponentTemplates::new();
+        let tokens = Lexer::new(src, &mut strings).collect::<Result<Vec<_>>>().unwrap();
+        let tokens = Tokens::new(tokens, src.len());
+        Parser::new(tokens, &mut strings, src, &mut components)
+            .map(|statement| statement.map(|(statement, _)| statement))
+            .collect::<Result<_>>()
+            .unwrap()
+    }
+
+    // Formatting the output again should not change it,
+    // and it should parse to the same thing as the input
+    fn assert_format(src: &str, expected: &str) {
+        let output = format(src).unwrap();
+        assert_eq!(output, expected);
+        assert_eq!(format(&output).unwrap(), output);
+        assert_eq!(statements(src), statements(&output));
+    }
+
+    #[test]
+    fn indentation() {
+        let src = "
+vstack
+  border
+   text 'a'
+  text 'b'
+text 'c'
+";
+        let expected = "vstack
+    border
+        text \"a\"
+    text \"b\"
+text \"c\"
+";
+        assert_format(src, expected);
+    }
+
+    #[test]
+    fn attributes() {
+        let src = "text [ foreground:'red' ,bold:true ,width :#fF0000] 'hello'";
+        let expected = "text [foreground: \"red\", bold: true, width: #ff0000] \"hello\"\n";
+        assert_format(src, expected);
+    }
+
+    #[test]
+    fn multiline_attributes() {
+        let src = "
+border [
+    width: 10,
+    height: 2.0,
+]
+    text 'a'
+";
+        let expected = "border [width: 10, height: 2.0]\n    text \"a\"\n";
+        assert_format(src, expected);
+    }
+
+    #[test]
+    fn quotes() {
+        let src = r#"text 'it says "hi"' 'plain' "\"quoted\"" 'it\'s'"#;
+        let expected = "text 'it says \"hi\"' \"plain\" \"\\\"quoted\\\"\" \"it\\'s\"\n";
+        assert_format(src, expected);
+    }
+
+    #[test]
+    fn comments() {
+        let src = "
+// top
+vstack // the stack
+    // child
+  text 'a'
+        // under a
+  text 'b'
+// end
+";
+        let expected = "// top
+vstack // the stack
+    // child
+    text \"a\"
+        // under a
+    text \"b\"
+// end
+";
+        assert_format(src, expected);
+    }
+
+    #[test]
+    fn empty_lines() {
+        let src = "\n\ntext 'a'\n\n\n\ntext 'b'\n\n";
+        let expected = "text \"a\"\n\ntext \"b\"\n";
+        assert_format(src, expected);
+    }
+
+    #[test]
+    fn control_flow() {
+        let src = "
+let  x =  1
+for  item in  items
+  if item.done && !x
+    text item.name
+  else if x > 2
+    text 'x'
+  else
+    switch  x
+      case 1
+        text 'one'
+      default
+        text 'other'
+for  key ,value in  map
+  text key value
+for n in 0 .. count+1
+  text n
+else
+  text 'empty'
+";
+        let expected = "let x = 1
+for item in items
+    if item.done && !x
+        text item.name
+    else if x > 2
+        text \"x\"
+    else
+        switch x
+            case 1
+                text \"one\"
+            default
+                text \"other\"
+for key, value in map
+    text key value
+for n in 0..count + 1
+    text n
+else
+    text \"empty\"
+";
+        assert_format(src, expected);
+    }
+
+    #[test]
+    fn components() {
+        let src = "
+@button ( click -> increment,focus->focus ) {'caption': 'Increment'}
+include 'row' {b: 2,a: 1}
+$children
+";
+        let expected = "@button (click->increment, focus->focus) {\"caption\": \"Increment\"}
+include \"row\" {\"a\": 1, \"b\": 2}
+$children
+";
+        assert_format(src, expected);
+    }
+
+    #[test]
+    fn expressions() {
+        let src = "text [a: (a + b) * c, b: a - (b - c), c: -a.b[0], d: f(x,y), e: a['key'], f: a['for']]";
+        let expected = "text [a: (a + b) * c, b: a - (b - c), c: -a.b[0], d: f(x, y), e: a.key, f: a[\"for\"]]\n";
+        assert_format(src, expected);
+
+        let src = "let x = (a ? b : c) ? d : !(a && b) || c >= 1 + 2 * 3";
+        let expected = "let x = (a ? b : c) ? d : !(a && b) || c >= 1 + 2 * 3\n";
+        assert_format(src, expected);
+    }
+
+    #[test]
+    fn filters() {
+        let src = "text [a: x|upper, b: (a ? b : c) | truncate(2,'..'), c: a ? b : (c | upper)] 'size: {s | bytes}'";
+        let expected = "text [a: x | upper, b: a ? b : c | truncate(2, \"..\"), c: a ? b : (c | upper)] \"size: {s | bytes}\"\n";
+        assert_format(src, expected);
+    }
+
+    #[test]
+    fn interpolation() {
+        let src = "text 'hello {name}, \\{ {{ }}' 'size: {a.b + 1}'";
+        let expected = "text \"hello {name}, \\{ {{ }}\" \"size: {a.b + 1}\"\n";
+        assert_format(src, expected);
+
+        let src = "text 'a\\\\{b} c\\\\'";
+        let expected = "text \"a\\\\{b} c\\\\\"\n";
+        assert_format(src, expected);
+    }
+
+    #[test]
+    fn invalid_template() {
+        let err = format("vstack\n    text 'hello").unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::ParseError(_)));
+    }
+}
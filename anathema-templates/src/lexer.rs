@@ -1,4 +1,6 @@
+use std::collections::VecDeque;
 use std::iter::Peekable;
+use std::ops::Range;
 use std::str::CharIndices;
 
 use anathema_store::storage::strings::Strings;
@@ -21,6 +23,15 @@ pub struct Lexer<'src, 'strings> {
     pub(super) src: &'src str,
     pub(crate) strings: &'strings mut Strings,
     chars: Peekable<CharIndices<'src>>,
+    // Tokens produced by interpolated strings
+    pending: VecDeque<Token>,
+    // Byte ranges of the comments skipped so far (excluding the newline)
+    pub(crate) comments: Vec<Range<usize>>,
+}
+
+enum Segment {
+    Text(usize, String),
+    Expr(usize, usize),
 }
 
 impl<'src, 'strings> Lexer<'src, 'strings> {
@@ -29,10 +40,16 @@ impl<'src, 'strings> Lexer<'src, 'strings> {
             chars: src.char_indices().peekable(),
             strings,
             src,
+            pending: VecDeque::new(),
+            comments: vec![],
         }
     }
 
     fn next_token(&mut self) -> Result<Token> {
+        if let Some(token) = self.pending.pop_front() {
+            return Ok(token);
+        }
+
         let (index, c) = match self.chars.next() {
             None => return self.eof(),
             Some(c) => c,
@@ -46,12 +63,16 @@ impl<'src, 'strings> Lexer<'src, 'strings> {
             // -----------------------------------------------------------------------------
             ('/', Some('/')) => {
                 self.chars.next(); // consume the second slash
-                loop {
-                    if let Some((_, '\n')) | None = self.chars.peek() {
-                        break;
+                let end = loop {
+                    match self.chars.peek() {
+                        Some((end, '\n')) => break *end,
+                        None => break self.src.len(),
+                        Some(_) => {
+                            self.chars.next();
+                        }
                     }
-                    self.chars.next();
-                }
+                };
+                self.comments.push(index..end);
                 self.next_token()
             }
             ('&', Some('&')) => {
@@ -82,6 +103,10 @@ impl<'src, 'strings> Lexer<'src, 'strings> {
                 let _ = self.chars.next();
                 Ok(Kind::Op(Operator::Association).to_token(index))
             }
+            ('.', Some('.')) => {
+                let _ = self.chars.next();
+                Ok(Kind::Op(Operator::Range).to_token(index))
+            }
 
             // -----------------------------------------------------------------------------
             //     - Single tokens -
@@ -93,8 +118,10 @@ impl<'src, 'strings> Lexer<'src, 'strings> {
             ('{', _) => Ok(Kind::Op(Operator::LCurly).to_token(index)),
             ('}', _) => Ok(Kind::Op(Operator::RCurly).to_token(index)),
             (':', _) => Ok(Kind::Op(Operator::Colon).to_token(index)),
+            ('?', _) => Ok(Kind::Op(Operator::Question).to_token(index)),
             (',', _) => Ok(Kind::Op(Operator::Comma).to_token(index)),
             ('.', _) => Ok(Kind::Op(Operator::Dot).to_token(index)),
+            ('|', _) => Ok(Kind::Op(Operator::Pipe).to_token(index)),
             ('!', _) => Ok(Kind::Op(Operator::Not).to_token(index)),
             ('+', _) => Ok(Kind::Op(Operator::Plus).to_token(index)),
             ('-', _) => Ok(Kind::Op(Operator::Minus).to_token(index)),
@@ -144,33 +171,135 @@ impl<'src, 'strings> Lexer<'src, 'strings> {
         Ok(Token(Kind::Eof, self.src.len()))
     }
 
+    // A string can contain expressions in curly braces: `"Item {item.name}"`.
+    // Literal braces are escaped with a backslash: `"\\{not an expression\\}"`,
+    // and a backslash is escaped with another backslash.
+    // `{}`, `{{` and `}}` are left as they are, as they are used by format strings,
+    // and so is a `{` without a closing `}`.
+    //
+    // An interpolated string is turned into a `TextSegments` token followed by
+    // one token per text segment, and the tokens of each expression wrapped in parens.
     fn take_string(&mut self, start_char: char, start_index: usize) -> Result<Token> {
+        let mut segments = vec![];
+        let mut text_start = start_index + 1;
+        let mut text = String::new();
+
         loop {
             let n = self.chars.next();
             match n {
-                Some((end, nc)) if nc == start_char => {
-                    let string = self.strings.push(self.src[start_index + 1..end].to_string());
-                    break Ok(Kind::Value(Value::String(string)).to_token(start_index));
-                }
-                Some((_, '\\')) => {
+                Some((_, nc)) if nc == start_char => break,
+                Some((_, '\\')) => match self.chars.peek() {
                     // escaping string terminator
-                    if let Some((_, next)) = self.chars.peek() {
-                        if *next == start_char {
-                            self.chars.next();
-                        }
+                    Some((_, next)) if *next == start_char => {
+                        text.push('\\');
+                        text.push(*next);
+                        self.chars.next();
+                    }
+                    // escaping braces and backslashes
+                    Some((_, next @ ('{' | '}' | '\\'))) => {
+                        text.push(*next);
+                        self.chars.next();
                     }
+                    _ => text.push('\\'),
+                },
+                Some((_, '{')) if matches!(self.chars.peek(), Some((_, '{' | '}'))) => {
+                    text.push('{');
+                    text.extend(self.chars.next().map(|(_, c)| c));
                 }
+                Some((index, '{')) => match self.take_interpolation(index)? {
+                    Some(end) => {
+                        if !text.is_empty() {
+                            segments.push(Segment::Text(text_start, std::mem::take(&mut text)));
+                        }
+                        segments.push(Segment::Expr(index + 1, end));
+                        text_start = end + 1;
+                    }
+                    None => text.push('{'),
+                },
+                Some((_, c)) => text.push(c),
                 None => {
-                    break Err(ParseError::new(
+                    return Err(ParseError::new(
                         start_index..self.src.len(),
                         self.src,
                         ParseErrorKind::UnterminatedString,
                     )
                     .into())
                 }
-                _ => {} // consume chars
             }
         }
+
+        if segments.is_empty() {
+            let string = self.strings.push(text);
+            return Ok(Kind::Value(Value::String(string)).to_token(start_index));
+        }
+
+        if !text.is_empty() {
+            segments.push(Segment::Text(text_start, text));
+        }
+
+        let count = segments.len();
+        for segment in segments {
+            match segment {
+                Segment::Text(index, text) => {
+                    let string = self.strings.push(text);
+                    self.pending
+                        .push_back(Kind::Value(Value::String(string)).to_token(index));
+                }
+                Segment::Expr(start, end) => {
+                    self.pending.push_back(Kind::Op(Operator::LParen).to_token(start));
+                    let mut chars = self.src[..end].char_indices().peekable();
+                    while chars.next_if(|(index, _)| *index < start).is_some() {}
+                    let lexer = Lexer {
+                        src: &self.src[..end],
+                        strings: self.strings,
+                        chars,
+                        pending: VecDeque::new(),
+                        comments: vec![],
+                    };
+                    for token in lexer {
+                        self.pending.push_back(token?);
+                    }
+                    self.pending.push_back(Kind::Op(Operator::RParen).to_token(end));
+                }
+            }
+        }
+
+        Ok(Kind::Value(Value::TextSegments(count)).to_token(start_index))
+    }
+
+    // Find the closing brace of an interpolation, skipping nested braces and strings.
+    // Returns the index of the closing brace, or `None` if there is no closing brace
+    // on the same line, in which case nothing is consumed.
+    fn take_interpolation(&mut self, start_index: usize) -> Result<Option<usize>> {
+        let mut chars = self.chars.clone();
+        let mut depth = 0;
+        let mut quote = None;
+        let mut is_empty = true;
+
+        loop {
+            let Some((index, c)) = chars.next() else { return Ok(None) };
+
+            match (c, quote) {
+                ('\n', _) => return Ok(None),
+                (c, Some(q)) if c == q => quote = None,
+                (_, Some(_)) => {}
+                ('"' | '\'', None) => quote = Some(c),
+                ('{', None) => depth += 1,
+                ('}', None) if depth == 0 => {
+                    if is_empty {
+                        return Err(
+                            ParseError::new(start_index..index, self.src, ParseErrorKind::EmptyInterpolation).into(),
+                        );
+                    }
+                    self.chars = chars;
+                    break Ok(Some(index));
+                }
+                ('}', None) => depth -= 1,
+                _ => {}
+            }
+
+            is_empty &= c.is_whitespace();
+        }
     }
 
     fn take_number(&mut self, index: usize) -> Result<Token> {
@@ -181,6 +310,10 @@ impl<'src, 'strings> Lexer<'src, 'strings> {
 
         while let Some((e, c @ ('0'..='9' | '.'))) = self.chars.peek() {
             if *c == '.' {
+                // `0..10` is a range and not a float
+                if self.src[*e + 1..].starts_with('.') {
+                    break;
+                }
                 parse_float = true;
             }
             end = *e;
@@ -205,7 +338,7 @@ impl<'src, 'strings> Lexer<'src, 'strings> {
 
     fn take_ident_or_keyword(&mut self, index: usize) -> Kind {
         let mut end = index;
-        while let Some((e, 'a'..='z' | 'A'..='Z' | '_' | '|' | '0'..='9')) = self.chars.peek() {
+        while let Some((e, 'a'..='z' | 'A'..='Z' | '_' | '0'..='9')) = self.chars.peek() {
             end = *e;
             self.chars.next();
         }
@@ -307,13 +440,17 @@ mod test {
     fn error_kind(input: &str) -> ParseErrorKind {
         let mut strings = Strings::empty();
         let mut lexer = Lexer::new(input, &mut strings);
-        match lexer.next().unwrap().unwrap_err() {
-            crate::error::Error::ParseError(err) => err.kind,
-            crate::error::Error::CircularDependency
-            | crate::error::Error::MissingComponent(_)
-            | crate::error::Error::EmptyTemplate
-            | crate::error::Error::EmptyBody
-            | crate::error::Error::Io(_) => panic!("invalid error"),
+        match lexer.next().unwrap().unwrap_err().kind {
+            crate::error::ErrorKind::ParseError(kind) => kind,
+            crate::error::ErrorKind::CircularDependency
+            | crate::error::ErrorKind::MissingComponent(_)
+            | crate::error::ErrorKind::MissingPartial(_)
+            | crate::error::ErrorKind::InvalidInclude
+            | crate::error::ErrorKind::EmptyTemplate
+            | crate::error::ErrorKind::EmptyBody
+            | crate::error::ErrorKind::InvalidSwitch
+            | crate::error::ErrorKind::InvalidForElse
+            | crate::error::ErrorKind::Io(_) => panic!("invalid error"),
         }
     }
 
@@ -351,7 +488,11 @@ mod test {
 
     #[test]
     fn double_char_token() {
-        let inputs = [("<=", Operator::LessThanOrEqual), ("&&", Operator::And)];
+        let inputs = [
+            ("<=", Operator::LessThanOrEqual),
+            ("&&", Operator::And),
+            ("..", Operator::Range),
+        ];
 
         for (input, expected) in inputs {
             let actual = operator(input);
@@ -381,6 +522,44 @@ mod test {
         }
     }
 
+    #[test]
+    fn range() {
+        let mut strings = Strings::empty();
+        let kinds = Lexer::new("0..10 1.5..2", &mut strings)
+            .map(|token| token.unwrap().0)
+            .collect::<Vec<_>>();
+        let expected = vec![
+            Kind::Value(0.into()),
+            Kind::Op(Operator::Range),
+            Kind::Value(10.into()),
+            Kind::Indent(1),
+            Kind::Value(1.5.into()),
+            Kind::Op(Operator::Range),
+            Kind::Value(2.into()),
+        ];
+        assert_eq!(kinds, expected);
+    }
+
+    #[test]
+    fn pipe() {
+        let mut strings = Strings::empty();
+        let kinds = Lexer::new("a | b(1)|c", &mut strings)
+            .map(|token| token.unwrap().0)
+            .filter(|kind| !matches!(kind, Kind::Indent(_)))
+            .collect::<Vec<_>>();
+        let expected = vec![
+            Kind::Value(Value::Ident(0.into())),
+            Kind::Op(Operator::Pipe),
+            Kind::Value(Value::Ident(1.into())),
+            Kind::Op(Operator::LParen),
+            Kind::Value(1.into()),
+            Kind::Op(Operator::RParen),
+            Kind::Op(Operator::Pipe),
+            Kind::Value(Value::Ident(2.into())),
+        ];
+        assert_eq!(kinds, expected);
+    }
+
     #[test]
     fn floats() {
         let inputs = [("1.0", 1f64), ("0.555", 0.555f64)];
@@ -419,6 +598,85 @@ mod test {
         }
     }
 
+    #[test]
+    fn escaped_braces() {
+        let mut strings = Strings::empty();
+        let input = r#""\{not\} interpolated""#;
+        let Kind::Value(Value::String(string_id)) = Lexer::new(input, &mut strings).next().unwrap().unwrap().0 else {
+            panic!("invalid token")
+        };
+        assert_eq!(strings.get_unchecked(string_id), "{not} interpolated");
+    }
+
+    #[test]
+    fn interpolated_string() {
+        let mut strings = Strings::empty();
+        let input = "'a {b.c} {d ? \"}\" : 1}'";
+        let kinds = Lexer::new(input, &mut strings)
+            .map(|token| token.unwrap().0)
+            .filter(|kind| !matches!(kind, Kind::Indent(_)))
+            .map(|kind| kind.to_string())
+            .collect::<Vec<_>>();
+
+        let expected = [
+            "<value <text segments 4>>",
+            "<value \"<sid 0>\">",
+            "<op (>",
+            "<value <sid 1>>",
+            "<op .>",
+            "<value <sid 2>>",
+            "<op )>",
+            "<value \"<sid 3>\">",
+            "<op (>",
+            "<value <sid 4>>",
+            "<op ?>",
+            "<value \"<sid 5>\">",
+            "<op :>",
+            "<value 1>",
+            "<op )>",
+        ];
+        assert_eq!(kinds, expected);
+        assert_eq!(strings.get_unchecked(0.into()), "a ");
+        assert_eq!(strings.get_unchecked(5.into()), "}");
+    }
+
+    #[test]
+    fn format_string() {
+        let mut strings = Strings::empty();
+        let input = "'{} of {{}}'";
+        let Kind::Value(Value::String(string_id)) = Lexer::new(input, &mut strings).next().unwrap().unwrap().0 else {
+            panic!("invalid token")
+        };
+        assert_eq!(strings.get_unchecked(string_id), "{} of {{}}");
+    }
+
+    #[test]
+    fn invalid_interpolation() {
+        assert_eq!(error_kind("'a { }'"), ParseErrorKind::EmptyInterpolation);
+    }
+
+    #[test]
+    fn unmatched_brace() {
+        let mut strings = Strings::empty();
+        let mut lexer = Lexer::new("'a {b' 'c'", &mut strings);
+        let Kind::Value(Value::String(string_id)) = lexer.next().unwrap().unwrap().0 else {
+            panic!("invalid token")
+        };
+        assert_eq!(strings.get_unchecked(string_id), "a {b");
+    }
+
+    #[test]
+    fn escaped_backslash() {
+        let mut strings = Strings::empty();
+        let input = r#""\\{a}""#;
+        let kinds = Lexer::new(input, &mut strings)
+            .map(|token| token.unwrap().0.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(kinds[0], "<value <text segments 2>>");
+        assert_eq!(strings.get_unchecked(0.into()), "\\");
+    }
+
     #[test]
     fn consume_whitespace() {
         let input = "   ";
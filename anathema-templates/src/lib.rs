@@ -1,15 +1,18 @@
 pub use crate::components::{SourceKind, ToSourceKind, WidgetComponentId};
-pub use crate::document::Document;
+pub use crate::document::{parse, Document};
 pub use crate::expressions::Expression;
+pub use crate::format::format;
 pub use crate::lexer::Lexer;
 pub use crate::primitives::Primitive;
 pub use crate::variables::Globals;
 
 pub mod blueprints;
 pub(crate) mod components;
+pub mod diagnostics;
 mod document;
 pub mod error;
 pub mod expressions;
+mod format;
 mod lexer;
 mod primitives;
 mod statements;
@@ -0,0 +1,537 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use anathema_store::storage::strings::{StringId, Strings};
+
+use super::{Statement, Statements};
+use crate::components::ComponentTemplates;
+use crate::diagnostics::{suggest, Diagnostic, DiagnosticKind, WidgetSchema};
+use crate::error::{Location, Result, Template};
+use crate::expressions::{Equality, Expression};
+use crate::primitives::Primitive;
+use crate::statements::parser::Parser;
+use crate::token::Tokens;
+use crate::{Lexer, WidgetComponentId};
+
+// The template being checked
+struct Source {
+    template: Template,
+    // The component the template belongs to, the same as
+    // `Context::component_parent` when the template is compiled
+    parent: Option<WidgetComponentId>,
+}
+
+impl Source {
+    fn location(&self, offset: usize) -> Location {
+        self.template.location(offset)
+    }
+}
+
+/// Walks the statements of a template, its components and partials
+/// and reports problems that won't stop it from compiling.
+///
+/// Unlike evaluation this does not stop at the first problem.
+pub(crate) struct Checker<'a, S> {
+    schema: &'a S,
+    strings: &'a mut Strings,
+    components: &'a mut ComponentTemplates,
+    diagnostics: Vec<Diagnostic>,
+    declarations: Vec<(String, Location)>,
+    used: HashSet<String>,
+    // Every component and partial is only checked once,
+    // no matter how many times it's used
+    checked_components: HashSet<WidgetComponentId>,
+    // A partial is checked once inside a component and once outside,
+    // as associated functions are only handled inside a component
+    checked_partials: HashSet<(String, bool)>,
+}
+
+impl<'a, S: WidgetSchema> Checker<'a, S> {
+    pub(crate) fn new(schema: &'a S, strings: &'a mut Strings, components: &'a mut ComponentTemplates) -> Self {
+        Self {
+            schema,
+            strings,
+            components,
+            diagnostics: vec![],
+            declarations: vec![],
+            used: HashSet::new(),
+            checked_components: HashSet::new(),
+            checked_partials: HashSet::new(),
+        }
+    }
+
+    pub(crate) fn check(mut self, template: &str) -> Result<Vec<Diagnostic>> {
+        self.check_template(template, None, None)?;
+
+        // Declarations are global, so they can only be
+        // considered unused once every template is checked
+        for (name, location) in std::mem::take(&mut self.declarations) {
+            if !self.used.contains(&name) {
+                self.report(DiagnosticKind::UnusedDeclaration(name), location);
+            }
+        }
+
+        Ok(self.diagnostics)
+    }
+
+    fn check_template(&mut self, src: &str, path: Option<&Path>, parent: Option<WidgetComponentId>) -> Result<()> {
+        let tokens = Lexer::new(src, self.strings)
+            .collect::<Result<Vec<_>>>()
+            .map_err(|err| err.with_path(path))?;
+        let tokens = Tokens::new(tokens, src.len());
+        let statements = Parser::new(tokens, self.strings, src, self.components)
+            .collect::<Result<Statements>>()
+            .map_err(|err| err.with_path(path))?;
+
+        let source = Source {
+            template: Template::new(src, path),
+            parent,
+        };
+        self.check_scope(statements, &source)
+    }
+
+    fn check_scope(&mut self, mut statements: Statements, source: &Source) -> Result<()> {
+        while let Some((statement, offset)) = statements.next() {
+            match statement {
+                Statement::Node(ident) => {
+                    self.check_node(ident, offset, &mut statements, source);
+                    self.check_scope(statements.take_scope(), source)?;
+                }
+                Statement::Component(component_id) => {
+                    // Associated functions are sent to the parent component,
+                    // so without a parent there is nothing to handle them
+                    let assoc_functions = statements.take_assoc_functions();
+                    if source.parent.is_none() {
+                        for (internal, external) in assoc_functions {
+                            let kind = DiagnosticKind::UnhandledAssociation {
+                                internal: self.strings.get_unchecked(internal),
+                                external: self.strings.get_unchecked(external),
+                            };
+                            self.report(kind, source.location(offset));
+                        }
+                    }
+
+                    for (_, value, _) in statements.take_attributes() {
+                        self.used(&value);
+                    }
+
+                    if let Some(state) = statements.take_value() {
+                        self.used(&state);
+                    }
+
+                    let mut scope = statements.take_scope();
+                    while scope.next_slot().is_some() {
+                        self.check_scope(scope.take_scope(), source)?;
+                    }
+
+                    self.check_component(component_id, offset, source)?;
+                }
+                Statement::Include(name) => {
+                    if let Some(params) = statements.take_value() {
+                        self.used(&params);
+                    }
+                    self.check_partial(name, offset, source)?;
+                }
+                Statement::For { data, .. } => {
+                    self.used(&data);
+                    self.check_scope(statements.take_scope(), source)?;
+                    if statements.next_else().is_some() {
+                        self.check_scope(statements.take_scope(), source)?;
+                    }
+                }
+                Statement::Declaration { binding, value } => {
+                    self.used(&value);
+                    let binding = self.strings.get_unchecked(binding);
+                    self.declarations.push((binding, source.location(offset)));
+                }
+                Statement::If(cond) => {
+                    self.used(&cond);
+                    let mut unreachable = const_bool(&cond) == Some(true);
+                    let mut conditions = vec![cond];
+                    self.check_scope(statements.take_scope(), source)?;
+
+                    while let Some((cond, offset)) = statements.next_else() {
+                        // An else is never shown after a condition that is always true,
+                        // or if its condition is always false or the same as an earlier condition
+                        let never_true = cond
+                            .as_ref()
+                            .is_some_and(|cond| const_bool(cond) == Some(false) || conditions.contains(cond));
+                        if unreachable || never_true {
+                            self.report(DiagnosticKind::UnreachableElse, source.location(offset));
+                        }
+
+                        match cond {
+                            Some(cond) => {
+                                self.used(&cond);
+                                unreachable |= const_bool(&cond) == Some(true);
+                                conditions.push(cond);
+                            }
+                            None => unreachable = true,
+                        }
+
+                        self.check_scope(statements.take_scope(), source)?;
+                    }
+                }
+                Statement::Switch(value) => {
+                    self.used(&value);
+                    let mut scope = statements.take_scope();
+                    while let Some((value, _)) = scope.next_case() {
+                        if let Some(value) = value {
+                            self.used(&value);
+                        }
+                        self.check_scope(scope.take_scope(), source)?;
+                    }
+                }
+                Statement::Eof => break,
+                // The remaining statements are either consumed
+                // as part of the statements above, or are
+                // reported as errors when the template is compiled.
+                Statement::ComponentSlot(_)
+                | Statement::ScopeStart
+                | Statement::ScopeEnd
+                | Statement::LoadAttribute { .. }
+                | Statement::AssociatedFunction { .. }
+                | Statement::Else(_)
+                | Statement::Case(_)
+                | Statement::LoadValue(_) => continue,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_node(&mut self, ident: StringId, offset: usize, statements: &mut Statements, source: &Source) {
+        let ident = self.strings.get_unchecked(ident);
+        let attributes = statements.take_attributes();
+
+        if !self.schema.has_widget(&ident) {
+            self.report(DiagnosticKind::UnknownWidget(ident.clone()), source.location(offset));
+        } else if let Some(known) = self.schema.attributes(&ident) {
+            for (key, value, offset) in &attributes {
+                let key = self.strings.get_unchecked(*key);
+                let kind = match known.iter().find(|attribute| attribute.name == key) {
+                    Some(attribute) => match attribute.ty.accepts(value) {
+                        Some(false) => DiagnosticKind::InvalidAttributeValue {
+                            widget: ident.clone(),
+                            attribute: key,
+                            expected: attribute.ty,
+                        },
+                        Some(true) | None => continue,
+                    },
+                    None => DiagnosticKind::UnknownAttribute {
+                        widget: ident.clone(),
+                        suggestion: suggest(&key, known.iter().map(|attribute| attribute.name)),
+                        attribute: key,
+                    },
+                };
+                self.report(kind, source.location(*offset));
+            }
+        }
+
+        for (_, value, _) in attributes {
+            self.used(&value);
+        }
+
+        if let Some(value) = statements.take_value() {
+            self.used(&value);
+        }
+    }
+
+    fn check_component(&mut self, component_id: WidgetComponentId, offset: usize, source: &Source) -> Result<()> {
+        let Some((name, component_src)) = self.components.get(component_id) else { return Ok(()) };
+
+        let Some((template, path)) = component_src.template() else {
+            let kind = DiagnosticKind::MissingComponent(name.to_string());
+            self.report(kind, source.location(offset));
+            return Ok(());
+        };
+
+        if !self.checked_components.insert(component_id) {
+            return Ok(());
+        }
+
+        let (template, path) = (template.to_string(), path.map(Path::to_path_buf));
+        self.check_template(&template, path.as_deref(), Some(component_id))
+    }
+
+    fn check_partial(&mut self, name: StringId, offset: usize, source: &Source) -> Result<()> {
+        let name = self.strings.get_unchecked(name);
+
+        let Some((template, path)) = self.components.get_partial(&name).and_then(|src| src.template()) else {
+            self.report(DiagnosticKind::MissingPartial(name), source.location(offset));
+            return Ok(());
+        };
+
+        let (template, path) = (template.to_string(), path.map(Path::to_path_buf));
+
+        if !self.checked_partials.insert((name, source.parent.is_some())) {
+            return Ok(());
+        }
+
+        // A partial is compiled in place, so it belongs
+        // to the component of the template it's included from
+        self.check_template(&template, path.as_deref(), source.parent)
+    }
+
+    fn report(&mut self, kind: DiagnosticKind, location: Location) {
+        // A partial that is checked both inside and outside of
+        // a component would otherwise report the same problem twice
+        let diagnostic = Diagnostic { kind, location };
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    // Record all the identifiers used by an expression
+    fn used(&mut self, expr: &Expression) {
+        match expr {
+            Expression::Ident(ident) => {
+                self.used.insert(ident.to_string());
+            }
+            Expression::Primitive(_) | Expression::Str(_) => {}
+            Expression::List(list) | Expression::TextSegments(list) => list.iter().for_each(|e| self.used(e)),
+            Expression::Map(map) => map.values().for_each(|e| self.used(e)),
+            Expression::Not(expr) | Expression::Negative(expr) => self.used(expr),
+            Expression::Equality(lhs, rhs, _)
+            | Expression::Index(lhs, rhs)
+            | Expression::Op(lhs, rhs, _)
+            | Expression::Range(lhs, rhs) => {
+                self.used(lhs);
+                self.used(rhs);
+            }
+            Expression::Conditional(cond, lhs, rhs) => {
+                self.used(cond);
+                self.used(lhs);
+                self.used(rhs);
+            }
+            Expression::Call { fun, args } => {
+                self.used(fun);
+                args.iter().for_each(|e| self.used(e));
+            }
+            Expression::Filter { value, args, .. } => {
+                self.used(value);
+                args.iter().for_each(|e| self.used(e));
+            }
+        }
+    }
+}
+
+// The value of a condition, if it only depends on values written in the template
+fn const_bool(expr: &Expression) -> Option<bool> {
+    use {Equality as Eq, Expression as E};
+
+    let is_literal = |expr: &Expression| matches!(expr, E::Primitive(_) | E::Str(_));
+
+    match expr {
+        E::Primitive(Primitive::Bool(value)) => Some(*value),
+        E::Not(expr) => const_bool(expr).map(|value| !value),
+        E::Equality(lhs, rhs, Eq::And) => match (const_bool(lhs), const_bool(rhs)) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        },
+        E::Equality(lhs, rhs, Eq::Or) => match (const_bool(lhs), const_bool(rhs)) {
+            (Some(true), _) | (_, Some(true)) => Some(true),
+            (Some(false), Some(false)) => Some(false),
+            _ => None,
+        },
+        E::Equality(lhs, rhs, Eq::Eq) if is_literal(lhs) && is_literal(rhs) => Some(lhs == rhs),
+        E::Equality(lhs, rhs, Eq::NotEq) if is_literal(lhs) && is_literal(rhs) => Some(lhs != rhs),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::diagnostics::{Attribute, AttributeType};
+    use crate::{Document, ToSourceKind};
+
+    struct Schema;
+
+    impl WidgetSchema for Schema {
+        fn has_widget(&self, ident: &str) -> bool {
+            matches!(ident, "text" | "vstack" | "custom")
+        }
+
+        fn attributes(&self, ident: &str) -> Option<&[Attribute]> {
+            const TEXT: &[Attribute] = &[
+                Attribute::new("foreground", AttributeType::Color),
+                Attribute::new("bold", AttributeType::Bool),
+            ];
+            const VSTACK: &[Attribute] = &[Attribute::new("width", AttributeType::Unsigned)];
+
+            match ident {
+                "text" => Some(TEXT),
+                "vstack" => Some(VSTACK),
+                _ => None,
+            }
+        }
+    }
+
+    fn check(doc: &mut Document) -> Vec<DiagnosticKind> {
+        doc.check(&Schema).unwrap().into_iter().map(|d| d.kind).collect()
+    }
+
+    #[test]
+    fn unknown_widget() {
+        let mut doc = Document::new("vstack\n    txt 'hello'\n    custom [anything: 1]");
+        let diagnostics = doc.check(&Schema).unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::UnknownWidget("txt".into()));
+        assert_eq!((diagnostics[0].location.line(), diagnostics[0].location.col()), (2, 5));
+    }
+
+    #[test]
+    fn unknown_attribute() {
+        let mut doc = Document::new("text [foregound: 'red', bold: true, other: 1] 'hello'");
+        let diagnostics = doc.check(&Schema).unwrap();
+        let kinds = diagnostics.iter().map(|d| d.kind.clone()).collect::<Vec<_>>();
+        let expected = vec![
+            DiagnosticKind::UnknownAttribute {
+                widget: "text".into(),
+                attribute: "foregound".into(),
+                suggestion: Some("foreground"),
+            },
+            DiagnosticKind::UnknownAttribute {
+                widget: "text".into(),
+                attribute: "other".into(),
+                suggestion: None,
+            },
+        ];
+        assert_eq!(kinds, expected);
+        assert_eq!(diagnostics[0].location.col(), 7);
+    }
+
+    #[test]
+    fn invalid_attribute_value() {
+        let src = "
+            vstack [width: -1]
+                text [foreground: 'rde', bold: 'yes'] 'hello'
+                text [foreground: #ff0000, bold: state.bold] 'hello'
+        ";
+        let mut doc = Document::new(src);
+        let kinds = check(&mut doc);
+        let expected = vec![
+            DiagnosticKind::InvalidAttributeValue {
+                widget: "vstack".into(),
+                attribute: "width".into(),
+                expected: AttributeType::Unsigned,
+            },
+            DiagnosticKind::InvalidAttributeValue {
+                widget: "text".into(),
+                attribute: "foreground".into(),
+                expected: AttributeType::Color,
+            },
+            DiagnosticKind::InvalidAttributeValue {
+                widget: "text".into(),
+                attribute: "bold".into(),
+                expected: AttributeType::Bool,
+            },
+        ];
+        assert_eq!(kinds, expected);
+    }
+
+    #[test]
+    fn missing_component() {
+        let mut doc = Document::new("vstack\n    @present\n    @missing");
+        doc.add_component("present", "text [colour: 1]".to_template()).unwrap();
+        let kinds = check(&mut doc);
+        assert_eq!(kinds.len(), 2);
+        assert!(matches!(&kinds[0], DiagnosticKind::UnknownAttribute { attribute, .. } if attribute == "colour"));
+        assert_eq!(kinds[1], DiagnosticKind::MissingComponent("missing".into()));
+    }
+
+    #[test]
+    fn unhandled_association() {
+        let mut doc = Document::new("@outer (click->on_click)");
+        doc.add_component("outer", "@inner (click->on_click)".to_template())
+            .unwrap();
+        doc.add_component("inner", "text 'hi'".to_template()).unwrap();
+        let kinds = check(&mut doc);
+        let expected = vec![DiagnosticKind::UnhandledAssociation {
+            internal: "click".into(),
+            external: "on_click".into(),
+        }];
+        assert_eq!(kinds, expected);
+    }
+
+    #[test]
+    fn unhandled_association_in_partial() {
+        // The partial is only outside of a component where it's included from the root
+        let mut doc = Document::new("vstack\n    @comp\n    include \"button\"");
+        doc.add_component("comp", "include \"button\"".to_template()).unwrap();
+        doc.add_component("inner", "text 'hi'".to_template()).unwrap();
+        doc.add_partial("button", "@inner (click->on_click)".to_template())
+            .unwrap();
+        let kinds = check(&mut doc);
+        let expected = vec![DiagnosticKind::UnhandledAssociation {
+            internal: "click".into(),
+            external: "on_click".into(),
+        }];
+        assert_eq!(kinds, expected);
+    }
+
+    #[test]
+    fn unused_declaration() {
+        let src = "
+            let used = 1
+            let unused = 2
+            let in_component = 3
+            vstack
+                text used
+                @comp
+        ";
+        let mut doc = Document::new(src);
+        doc.add_component("comp", "text in_component".to_template()).unwrap();
+        let diagnostics = doc.check(&Schema).unwrap();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].kind, DiagnosticKind::UnusedDeclaration("unused".into()));
+        assert_eq!(diagnostics[0].location.line(), 3);
+    }
+
+    #[test]
+    fn unreachable_else() {
+        let src = "
+            if x
+                text
+            else
+                text
+            else if y
+                text
+            if true
+                text
+            else
+                text
+            if x
+                text
+            else if !(true && x)
+                text
+            else if x
+                text
+            else if 1 == 2
+                text
+            else
+                text
+        ";
+        let mut doc = Document::new(src);
+        let diagnostics = doc.check(&Schema).unwrap();
+        let lines = diagnostics
+            .iter()
+            .inspect(|d| assert_eq!(d.kind, DiagnosticKind::UnreachableElse))
+            .map(|d| d.location.line())
+            .collect::<Vec<_>>();
+        assert_eq!(lines, vec![6, 10, 16, 18]);
+    }
+
+    #[test]
+    fn missing_and_recursive_partials() {
+        let mut doc = Document::new("include \"a\"\ninclude \"missing\"");
+        doc.add_partial("a", "include \"a\"\ntext [bld: true]".to_template())
+            .unwrap();
+        let kinds = check(&mut doc);
+        assert_eq!(kinds.len(), 2);
+        assert!(matches!(&kinds[0], DiagnosticKind::UnknownAttribute { attribute, .. } if attribute == "bld"));
+        assert_eq!(kinds[1], DiagnosticKind::MissingPartial("missing".into()));
+    }
+}
@@ -1,5 +1,7 @@
 use std::collections::HashMap;
 
+use anathema_state::CommonVal;
+
 use super::Context;
 use crate::expressions::{Expression, Op};
 use crate::primitives::Primitive;
@@ -17,7 +19,7 @@ fn eval_path(expr: &Expression, ctx: &Context<'_>) -> Option<Expression> {
 
     match expr {
         // NOTE: if `None` is not returned here then overriding globals in templates will fail
-        E::Ident(_) => None, //ctx.fetch(ident),
+        E::Ident(ident) => ctx.param(ident).cloned(), //ctx.fetch(ident),
         E::Str(strlit) => ctx.fetch(strlit),
         E::Index(lhs, rhs) => match eval_path(lhs, ctx)? {
             E::List(list) => match const_eval(rhs.clone(), ctx) {
@@ -56,14 +58,44 @@ pub(crate) fn const_eval(expr: impl Into<Expression>, ctx: &Context<'_>) -> Expr
         E::Not(expr) => E::Not(ce!(*expr)),
         E::Negative(expr) => E::Negative(ce!(*expr)),
         E::Equality(lhs, rhs, eq) => E::Equality(ce!(*lhs), ce!(*rhs), eq),
+        // Only pick a branch if the condition is known at compile time
+        E::Conditional(cond, lhs, rhs) => match const_eval(*cond, ctx) {
+            E::Primitive(P::Bool(true)) => const_eval(*lhs, ctx),
+            E::Primitive(P::Bool(false)) => const_eval(*rhs, ctx),
+            cond => E::Conditional(cond.into(), ce!(*lhs), ce!(*rhs)),
+        },
 
-        E::Ident(_) => eval_path(&expr, ctx).map(|e| ce!(e)).unwrap_or(expr),
-        E::Index(..) => eval_path(&expr, ctx).map(|e| ce!(e)).unwrap_or(expr),
+        // Parameters are evaluated where the partial is included,
+        // so they are not evaluated again
+        E::Ident(ref ident) => ctx.param(ident).cloned().unwrap_or(expr),
+        E::Index(ref lhs, ref rhs) => match eval_path(&expr, ctx) {
+            Some(e) => ce!(e),
+            None => E::Index(ce!(*lhs.clone()), ce!(*rhs.clone())),
+        },
 
         E::List(list) => {
             let list = list.iter().cloned().map(|expr| ce!(expr)).collect();
             E::List(list)
         }
+        // Interpolated strings with only constant segments become a single string.
+        // Primitives are formatted the same way as the runtime formats values.
+        E::TextSegments(segments) => {
+            let segments = segments.iter().cloned().map(|expr| ce!(expr)).collect::<Vec<_>>();
+            let is_const = segments.iter().all(|expr| matches!(expr, E::Str(_) | E::Primitive(_)));
+            match is_const {
+                true => E::Str(
+                    segments
+                        .iter()
+                        .map(|expr| match expr {
+                            E::Primitive(val) => CommonVal::from(*val).to_string(),
+                            expr => expr.to_string(),
+                        })
+                        .collect::<String>()
+                        .into(),
+                ),
+                false => E::TextSegments(segments.into()),
+            }
+        }
         E::Map(map) => {
             let hm = HashMap::from_iter(map.iter().map(|(k, v)| (k.clone(), ce!(v.clone()))));
             E::Map(hm.into())
@@ -81,17 +113,25 @@ pub(crate) fn const_eval(expr: impl Into<Expression>, ctx: &Context<'_>) -> Expr
             }
             (lhs, rhs) => E::Op(lhs.into(), rhs.into(), op),
         },
+        E::Range(from, to) => E::Range(ce!(*from), ce!(*to)),
         E::Call { fun, args } => E::Call {
             fun: fun.clone(),
             args: args.iter().map(|expr| ce!(expr.clone())).collect(),
         },
+        E::Filter { value, name, args } => E::Filter {
+            value: ce!(*value),
+            name: name.clone(),
+            args: args.iter().map(|expr| ce!(expr.clone())).collect(),
+        },
     }
 }
 
 #[cfg(test)]
 mod test {
+    use {Expression as E, Primitive as P};
+
     use super::*;
-    use crate::expressions::{add, div, mul, num, sub};
+    use crate::expressions::{add, boolean, conditional, div, float, ident, mul, num, strlit, sub, text_segments};
     use crate::statements::with_context;
 
     #[test]
@@ -133,4 +173,41 @@ mod test {
             assert_eq!(output, *num(1));
         });
     }
+
+    #[test]
+    fn conditional_branch() {
+        with_context(|ctx| {
+            let expr = conditional(boolean(true), strlit("a"), strlit("b"));
+            assert_eq!(const_eval(expr, &ctx), *strlit("a"));
+
+            let expr = conditional(boolean(false), strlit("a"), add(num(1), num(2)));
+            assert_eq!(const_eval(expr, &ctx), *num(3));
+        });
+    }
+
+    #[test]
+    fn interpolated_string() {
+        with_context(|ctx| {
+            let expr = text_segments([strlit("a"), add(num(1), num(2))]);
+            assert_eq!(const_eval(expr, &ctx), *strlit("a3"));
+
+            let expr = text_segments([strlit("a"), ident("b")]);
+            assert_eq!(const_eval(expr.clone(), &ctx), *expr);
+
+            // Primitives are formatted like values at runtime
+            let hex = Box::new(E::Primitive(P::from((1, 2, 3))));
+            let expr = text_segments([float(1.5), strlit(" "), hex]);
+            let expected = format!("{} {}", CommonVal::Float(1.5), CommonVal::from((1, 2, 3)));
+            assert_eq!(const_eval(expr, &ctx), *strlit(&expected));
+        });
+    }
+
+    #[test]
+    fn dynamic_conditional() {
+        with_context(|ctx| {
+            let expr = conditional(ident("a"), add(num(1), num(2)), strlit("b"));
+            let expected = conditional(ident("a"), num(3), strlit("b"));
+            assert_eq!(const_eval(expr, &ctx), *expected);
+        });
+    }
 }
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use anathema_store::smallmap::SmallMap;
@@ -5,8 +6,8 @@ use anathema_store::storage::strings::StringId;
 
 use super::const_eval::const_eval;
 use super::{Context, Statement, Statements};
-use crate::blueprints::{Blueprint, Component, ControlFlow, Else, For, If, Single};
-use crate::error::{Error, Result};
+use crate::blueprints::{Blueprint, Case, Component, ControlFlow, Else, For, If, Single, Switch};
+use crate::error::{ErrorKind, Result};
 use crate::expressions::Expression;
 use crate::WidgetComponentId;
 
@@ -22,15 +23,19 @@ impl Scope {
     pub(crate) fn eval(mut self, ctx: &mut Context<'_>) -> Result<Vec<Blueprint>> {
         let mut output = vec![];
 
-        while let Some(statement) = self.statements.next() {
+        while let Some((statement, offset)) = self.statements.next() {
             match statement {
-                Statement::Node(ident) => output.push(self.eval_node(ident, ctx)?),
-                Statement::Component(component_id) => output.push(self.eval_component(component_id, ctx)?),
-                Statement::For { binding, data } => output.push(self.eval_for(binding, data, ctx)?),
-                Statement::If(cond) => output.push(self.eval_if(cond, ctx)?),
+                Statement::Node(ident) => output.push(self.eval_node(ident, offset, ctx)?),
+                Statement::Component(component_id) => output.push(self.eval_component(component_id, offset, ctx)?),
+                Statement::Include(name) => output.extend(self.eval_include(name, offset, ctx)?),
+                Statement::For { key, binding, data } => output.push(self.eval_for(key, binding, data, offset, ctx)?),
+                Statement::If(cond) => output.push(self.eval_if(cond, offset, ctx)?),
+                Statement::Switch(value) => output.push(self.eval_switch(value, offset, ctx)?),
                 Statement::Declaration { binding, value } => {
                     let value = const_eval(value, ctx);
                     let binding = ctx.strings.get_unchecked(binding);
+                    // The declaration shadows any parameter with the same name
+                    ctx.params.remove(binding.as_str());
                     ctx.globals.declare(binding, value);
                 }
                 Statement::ComponentSlot(slot_id) => {
@@ -46,6 +51,7 @@ impl Scope {
                 | Statement::LoadAttribute { .. }
                 | Statement::AssociatedFunction { .. }
                 | Statement::Else(_)
+                | Statement::Case(_)
                 | Statement::LoadValue(_) => {
                     unreachable!("\"{statement:?}\" found: this is a bug in Anathema. Please open an issue")
                 }
@@ -55,7 +61,7 @@ impl Scope {
         Ok(output)
     }
 
-    fn eval_node(&mut self, ident: StringId, ctx: &mut Context<'_>) -> Result<Blueprint> {
+    fn eval_node(&mut self, ident: StringId, offset: usize, ctx: &mut Context<'_>) -> Result<Blueprint> {
         let ident = ctx.strings.get_unchecked(ident);
         let attributes = self.eval_attributes(ctx)?;
         let value = self.statements.take_value().map(|v| const_eval(v, ctx));
@@ -66,18 +72,57 @@ impl Scope {
             children,
             attributes,
             value,
+            location: Some(ctx.location(offset)),
         });
         Ok(node)
     }
 
-    fn eval_for(&mut self, binding: StringId, data: Expression, ctx: &mut Context<'_>) -> Result<Blueprint> {
+    fn eval_for(
+        &mut self,
+        key: Option<StringId>,
+        binding: StringId,
+        data: Expression,
+        offset: usize,
+        ctx: &mut Context<'_>,
+    ) -> Result<Blueprint> {
         let data = const_eval(data, ctx);
+        let key = key.map(|key| ctx.strings.get_unchecked(key));
         let binding = ctx.strings.get_unchecked(binding);
-        let body = self.consume_scope(ctx)?;
+
+        // The loop bindings shadow any parameter with the same name
+        let bindings = key.iter().chain(Some(&binding));
+        let params = bindings
+            .filter_map(|name| Some((name.as_str(), ctx.params.remove(name.as_str())?)))
+            .collect::<Vec<_>>();
+        let body = self.consume_scope(ctx);
+        for (name, param) in params {
+            ctx.params.insert(name.into(), param);
+        }
+        let body = body?;
+
+        let mut else_body = vec![];
+        if let Some((cond, else_offset)) = self.statements.next_else() {
+            if cond.is_some() {
+                return Err(ctx.error(ErrorKind::InvalidForElse, else_offset));
+            }
+
+            else_body = self.consume_scope(ctx)?;
+            if else_body.is_empty() {
+                return Err(ctx.error(ErrorKind::EmptyBody, else_offset));
+            }
+
+            if let Some((_, else_offset)) = self.statements.next_else() {
+                return Err(ctx.error(ErrorKind::InvalidForElse, else_offset));
+            }
+        }
+
         let node = Blueprint::For(For {
+            key: key.as_deref().map(Into::into),
             binding: binding.into(),
             data,
             body,
+            else_body,
+            location: Some(ctx.location(offset)),
         });
         Ok(node)
     }
@@ -90,7 +135,7 @@ impl Scope {
     fn eval_attributes(&mut self, ctx: &mut Context<'_>) -> Result<SmallMap<Rc<str>, Expression>> {
         let mut hm = SmallMap::empty();
 
-        for (key, value) in self.statements.take_attributes() {
+        for (key, value, _) in self.statements.take_attributes() {
             let value = const_eval(value, ctx);
             let key = ctx.strings.get_unchecked(key);
             hm.set(key.into(), value);
@@ -99,29 +144,84 @@ impl Scope {
         Ok(hm)
     }
 
-    fn eval_if(&mut self, cond: Expression, ctx: &mut Context<'_>) -> Result<Blueprint> {
+    fn eval_if(&mut self, cond: Expression, offset: usize, ctx: &mut Context<'_>) -> Result<Blueprint> {
         let cond = const_eval(cond, ctx);
         let body = self.consume_scope(ctx)?;
         if body.is_empty() {
-            return Err(Error::EmptyBody);
+            return Err(ctx.error(ErrorKind::EmptyBody, offset));
         }
 
         let if_node = If { cond, body };
         let mut elses = vec![];
-        while let Some(cond) = self.statements.next_else() {
+        while let Some((cond, else_offset)) = self.statements.next_else() {
             let cond = cond.map(|v| const_eval(v, ctx));
             let body = self.consume_scope(ctx)?;
 
             if body.is_empty() {
-                return Err(Error::EmptyBody);
+                return Err(ctx.error(ErrorKind::EmptyBody, else_offset));
             }
 
             elses.push(Else { cond, body });
         }
-        Ok(Blueprint::ControlFlow(ControlFlow { if_node, elses }))
+
+        let location = Some(ctx.location(offset));
+        Ok(Blueprint::ControlFlow(ControlFlow {
+            if_node,
+            elses,
+            location,
+        }))
+    }
+
+    fn eval_switch(&mut self, value: Expression, offset: usize, ctx: &mut Context<'_>) -> Result<Blueprint> {
+        let value = const_eval(value, ctx);
+        let mut scope = self.statements.take_scope();
+        let mut cases = vec![];
+
+        while let Some((value, case_offset)) = scope.next_case() {
+            let value = value.map(|v| const_eval(v, ctx));
+            let body = Scope::new(scope.take_scope()).eval(ctx)?;
+
+            if body.is_empty() {
+                return Err(ctx.error(ErrorKind::EmptyBody, case_offset));
+            }
+
+            cases.push(Case { value, body });
+        }
+
+        if let Some(invalid) = scope.offset() {
+            return Err(ctx.error(ErrorKind::InvalidSwitch, invalid));
+        }
+
+        let location = Some(ctx.location(offset));
+        Ok(Blueprint::Switch(Switch { value, cases, location }))
     }
 
-    fn eval_component(&mut self, component_id: WidgetComponentId, ctx: &mut Context<'_>) -> Result<Blueprint> {
+    fn eval_include(&mut self, name: StringId, offset: usize, ctx: &mut Context<'_>) -> Result<Vec<Blueprint>> {
+        let name = ctx.strings.get_unchecked(name);
+
+        if !self.statements.take_assoc_functions().is_empty() || !self.statements.take_attributes().is_empty() {
+            return Err(ctx.error(ErrorKind::InvalidInclude, offset));
+        }
+
+        let params = match self.statements.take_value().map(|v| const_eval(v, ctx)) {
+            Some(Expression::Map(map)) => map.as_ref().clone(),
+            Some(_) => return Err(ctx.error(ErrorKind::InvalidInclude, offset)),
+            None => HashMap::new(),
+        };
+
+        if !self.statements.take_scope().is_empty() {
+            return Err(ctx.error(ErrorKind::InvalidInclude, offset));
+        }
+
+        ctx.load_partial(name, params, offset)
+    }
+
+    fn eval_component(
+        &mut self,
+        component_id: WidgetComponentId,
+        offset: usize,
+        ctx: &mut Context<'_>,
+    ) -> Result<Blueprint> {
         let parent = ctx.component_parent();
 
         // Associated functions
@@ -149,7 +249,7 @@ impl Scope {
             slots.set(slot_id, body);
         }
 
-        let body = ctx.load_component(component_id, slots)?;
+        let body = ctx.load_component(component_id, slots, offset)?;
 
         let component = Component {
             id: component_id,
@@ -158,6 +258,7 @@ impl Scope {
             state,
             assoc_functions,
             parent,
+            location: Some(ctx.location(offset)),
         };
 
         Ok(Blueprint::Component(component))
@@ -169,13 +270,68 @@ mod test {
 
     use super::*;
     use crate::document::Document;
+    use crate::error::Error;
+    use crate::expressions::{ident, index, list, num, range, strlit};
     use crate::{single, ToSourceKind};
 
+    fn without_locations(blueprint: Blueprint) -> Blueprint {
+        match blueprint {
+            Blueprint::Single(single) => Blueprint::Single(Single {
+                location: None,
+                children: single.children.into_iter().map(without_locations).collect(),
+                ..single
+            }),
+            blueprint => blueprint,
+        }
+    }
+
     #[test]
     fn eval_node() {
         let mut doc = Document::new("node");
         let (bp, _) = doc.compile().unwrap();
-        assert_eq!(bp, single!("node"));
+        assert_eq!(without_locations(bp), single!("node"));
+    }
+
+    #[test]
+    fn eval_node_location() {
+        let src = "
+        a
+            b
+        ";
+        let mut doc = Document::new(src);
+        let (blueprint, _) = doc.compile().unwrap();
+        let Blueprint::Single(Single { children, .. }) = blueprint else { panic!("expected a node") };
+        let Blueprint::Single(Single { location, .. }) = &children[0] else { panic!("expected a node") };
+        let location = location.as_ref().unwrap();
+        assert_eq!((location.line(), location.col()), (3, 13));
+    }
+
+    #[test]
+    fn eval_control_flow_locations() {
+        let src = "
+vstack
+    for x in [1]
+        text
+    if true
+        text
+    switch 1
+        case 1
+            text
+";
+        let mut doc = Document::new(src);
+        let (blueprint, _) = doc.compile().unwrap();
+        let Blueprint::Single(Single { children, .. }) = blueprint else { panic!("expected a node") };
+        let locations = children
+            .iter()
+            .map(|child| match child {
+                Blueprint::For(For { location, .. })
+                | Blueprint::ControlFlow(ControlFlow { location, .. })
+                | Blueprint::Switch(Switch { location, .. }) => location.as_ref().unwrap(),
+                _ => panic!("expected a loop, an if or a switch"),
+            })
+            .map(|location| (location.line(), location.col()))
+            .collect::<Vec<_>>();
+        assert_eq!(locations, [(3, 5), (5, 5), (7, 5)]);
     }
 
     #[test]
@@ -186,7 +342,7 @@ mod test {
         ";
         let mut doc = Document::new(src);
         let (blueprint, _) = doc.compile().unwrap();
-        assert_eq!(blueprint, single!("a", vec![single!("b")]));
+        assert_eq!(without_locations(blueprint), single!("a", vec![single!("b")]));
     }
 
     #[test]
@@ -201,6 +357,40 @@ mod test {
         assert!(matches!(blueprint, Blueprint::Single(Single { value: Some(_), .. })));
     }
 
+    #[test]
+    fn eval_switch() {
+        let src = "
+            switch a
+                case 1
+                    node
+                case 2
+                    node
+                default
+                    node
+        ";
+        let mut doc = Document::new(src);
+        let (blueprint, _) = doc.compile().unwrap();
+        let Blueprint::Switch(switch) = blueprint else { panic!("expected a switch") };
+        assert_eq!(switch.cases.len(), 3);
+        assert!(switch.cases[2].value.is_none());
+    }
+
+    #[test]
+    fn eval_switch_invalid_statement() {
+        let src = "
+            switch a
+                node
+        ";
+        let mut doc = Document::new(src);
+        assert!(matches!(
+            doc.compile(),
+            Err(Error {
+                kind: ErrorKind::InvalidSwitch,
+                ..
+            })
+        ));
+    }
+
     #[test]
     fn eval_for() {
         let src = "
@@ -212,6 +402,189 @@ mod test {
         assert!(matches!(blueprint, Blueprint::For(For { .. })));
     }
 
+    #[test]
+    fn eval_for_else() {
+        let src = "
+            for a in 0..count
+                node
+            else
+                text
+        ";
+        let mut doc = Document::new(src);
+        let (blueprint, _) = doc.compile().unwrap();
+        let Blueprint::For(For { data, else_body, .. }) = blueprint else { panic!("expected a for loop") };
+        assert_eq!(data, *range(num(0), ident("count")));
+        assert_eq!(else_body.len(), 1);
+    }
+
+    #[test]
+    fn eval_invalid_for_else() {
+        let src = "
+            for a in a
+                node
+            else if a
+                node
+        ";
+        let mut doc = Document::new(src);
+        assert!(matches!(
+            doc.compile(),
+            Err(Error {
+                kind: ErrorKind::InvalidForElse,
+                ..
+            })
+        ));
+
+        let src = "
+            for a in a
+                node
+            else
+                node
+            else
+                node
+        ";
+        let mut doc = Document::new(src);
+        assert!(matches!(
+            doc.compile(),
+            Err(Error {
+                kind: ErrorKind::InvalidForElse,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn eval_include() {
+        let src = "
+            vstack
+                include \"row\" { label: 'a', value: x }
+                include \"row\" { label: 'b', value: x.y }
+        ";
+
+        let mut doc = Document::new(src);
+        doc.add_partial("row", "text label\ntext value".to_template()).unwrap();
+        let (blueprint, _) = doc.compile().unwrap();
+
+        let Blueprint::Single(vstack) = blueprint else { panic!("expected a vstack") };
+        let values = vstack
+            .children
+            .iter()
+            .map(|child| match child {
+                Blueprint::Single(Single { value, .. }) => value.clone().unwrap(),
+                _ => panic!("expected text"),
+            })
+            .collect::<Vec<_>>();
+
+        let expected = vec![*strlit("a"), *ident("x"), *strlit("b"), *index(ident("x"), strlit("y"))];
+        assert_eq!(values, expected);
+    }
+
+    #[test]
+    fn eval_include_scope() {
+        // `value` is shadowed by the loop binding
+        // and `other` is not a parameter
+        let src = "include \"list\" { value: 1 }";
+        let partial = "
+            vstack
+                for value in [2]
+                    text value other
+                text value
+        ";
+
+        let mut doc = Document::new(src);
+        doc.add_partial("list", partial.to_template()).unwrap();
+        let (blueprint, _) = doc.compile().unwrap();
+
+        let Blueprint::Single(vstack) = blueprint else { panic!("expected a vstack") };
+        let Blueprint::For(For { body, .. }) = &vstack.children[0] else { panic!("expected a for loop") };
+        let Blueprint::Single(Single { value, .. }) = &body[0] else { panic!("expected text") };
+        assert_eq!(value.as_ref().unwrap(), &*list([ident("value"), ident("other")]));
+
+        let Blueprint::Single(Single { value, .. }) = &vstack.children[1] else { panic!("expected text") };
+        assert_eq!(value.as_ref().unwrap(), &*num(1));
+    }
+
+    #[test]
+    fn eval_include_declaration_shadows_param() {
+        let src = "include \"title\" { title: 'a' }";
+        let partial = "
+            let title = title
+            text title
+        ";
+
+        let mut doc = Document::new(src);
+        doc.add_partial("title", partial.to_template()).unwrap();
+        let (blueprint, globals) = doc.compile().unwrap();
+
+        let Blueprint::Single(Single { value, .. }) = blueprint else { panic!("expected text") };
+        assert_eq!(value.unwrap(), *ident("title"));
+        assert_eq!(globals.get("title").unwrap(), &*strlit("a"));
+    }
+
+    #[test]
+    fn eval_missing_partial() {
+        let mut doc = Document::new("include \"missing\"");
+        assert!(matches!(doc.compile(), Err(Error { kind: ErrorKind::MissingPartial(name), .. }) if name == "missing"));
+    }
+
+    #[test]
+    fn eval_circular_include() {
+        let mut doc = Document::new("include \"a\"");
+        doc.add_partial("a", "include \"b\"".to_template()).unwrap();
+        doc.add_partial("b", "include \"a\"".to_template()).unwrap();
+        assert!(matches!(
+            doc.compile(),
+            Err(Error {
+                kind: ErrorKind::CircularDependency,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn eval_include_invalid_params() {
+        let mut doc = Document::new("include \"a\" 1");
+        doc.add_partial("a", "node".to_template()).unwrap();
+        assert!(matches!(
+            doc.compile(),
+            Err(Error {
+                kind: ErrorKind::InvalidInclude,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn eval_error_location() {
+        let src = "
+            vstack
+                if x
+        ";
+        let mut doc = Document::new(src);
+        let err = doc.compile().unwrap_err();
+        let expected = "error: if, else, case or default node has no children
+ --> 3:17
+  |
+3 |                 if x
+  |                 ^";
+        assert_eq!(err.to_string(), expected);
+    }
+
+    #[test]
+    fn eval_error_location_in_file() {
+        let path = std::env::temp_dir().join("anathema_eval_error_location_in_file.aml");
+        std::fs::write(&path, "text\ninclude \"missing\"").unwrap();
+
+        let mut doc = Document::new("@comp");
+        doc.add_component("comp", path.clone().into()).unwrap();
+        let err = doc.compile().unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(err.kind, ErrorKind::MissingPartial(_)));
+        let location = err.location.unwrap();
+        assert_eq!(location.path.as_deref(), Some(path.as_path()));
+        assert_eq!((location.line(), location.col()), (2, 1));
+    }
+
     #[test]
     fn eval_component() {
         let src = "@comp {a: 1}";
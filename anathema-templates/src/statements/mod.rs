@@ -1,13 +1,17 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
 use anathema_store::smallmap::SmallMap;
 use anathema_store::storage::strings::{StringId, Strings};
 
 use crate::blueprints::Blueprint;
 use crate::components::ComponentTemplates;
-use crate::error::Result;
+use crate::error::{Error, ErrorKind, Location, Result, Template};
 use crate::expressions::Expression;
 use crate::variables::Variables;
 use crate::WidgetComponentId;
 
+pub(crate) mod check;
 mod const_eval;
 pub(crate) mod eval;
 pub(super) mod parser;
@@ -18,6 +22,10 @@ pub(crate) struct Context<'vars> {
     pub(crate) strings: &'vars mut Strings,
     pub(crate) slots: SmallMap<StringId, Vec<Blueprint>>,
     pub(crate) current_component_parent: Option<WidgetComponentId>,
+    /// Parameters of the partial being compiled
+    pub(crate) params: HashMap<Rc<str>, Expression>,
+    /// The template being compiled, used for error locations
+    pub(crate) template: Template,
 }
 
 impl<'vars> Context<'vars> {
@@ -27,6 +35,8 @@ impl<'vars> Context<'vars> {
         strings: &'vars mut Strings,
         slots: SmallMap<StringId, Vec<Blueprint>>,
         current_component_parent: Option<WidgetComponentId>,
+        params: HashMap<Rc<str>, Expression>,
+        template: Template,
     ) -> Self {
         Self {
             globals,
@@ -34,6 +44,8 @@ impl<'vars> Context<'vars> {
             strings,
             slots,
             current_component_parent,
+            params,
+            template,
         }
     }
 }
@@ -47,13 +59,54 @@ impl Context<'_> {
         self.globals.fetch(key)
     }
 
+    fn param(&self, key: &str) -> Option<&Expression> {
+        self.params.get(key)
+    }
+
+    fn location(&self, offset: usize) -> Location {
+        self.template.location(offset)
+    }
+
+    fn error(&self, kind: ErrorKind, offset: usize) -> Error {
+        Error::new(kind, self.location(offset))
+    }
+
+    // Errors from loading a component or partial that doesn't
+    // have a location of its own are located at the node that loaded it
+    fn locate(&self, mut err: Error, offset: usize) -> Error {
+        if err.location.is_none() {
+            err.location = Some(self.location(offset));
+        }
+        err
+    }
+
+    fn load_partial(
+        &mut self,
+        name: String,
+        params: HashMap<Rc<str>, Expression>,
+        offset: usize,
+    ) -> Result<Vec<Blueprint>> {
+        self.components
+            .load_partial(
+                name,
+                self.globals,
+                self.slots.clone(),
+                self.strings,
+                self.current_component_parent,
+                params,
+            )
+            .map_err(|err| self.locate(err, offset))
+    }
+
     fn load_component(
         &mut self,
         parent_component_id: WidgetComponentId,
         slots: SmallMap<StringId, Vec<Blueprint>>,
+        offset: usize,
     ) -> Result<Vec<Blueprint>> {
         self.components
             .load(parent_component_id, self.globals, slots, self.strings)
+            .map_err(|err| self.locate(err, offset))
     }
 }
 
@@ -63,55 +116,73 @@ pub(crate) enum Statement {
     LoadAttribute { key: StringId, value: Expression },
     AssociatedFunction { internal: StringId, external: StringId },
     Component(WidgetComponentId),
+    /// Inline a partial by name
+    Include(StringId),
     ComponentSlot(StringId),
     Node(StringId),
-    For { binding: StringId, data: Expression },
+    /// `for value in data` or `for key, value in data`
+    For { key: Option<StringId>, binding: StringId, data: Expression },
     Declaration { binding: StringId, value: Expression },
     If(Expression),
     Else(Option<Expression>),
+    Switch(Expression),
+    /// A `case` with a value, or `default` without one
+    Case(Option<Expression>),
     ScopeStart,
     ScopeEnd,
     Eof,
 }
 
+/// Statements along with the offset in the source they start at
 #[derive(Debug, PartialEq)]
-pub(crate) struct Statements(Vec<Statement>);
+pub(crate) struct Statements(Vec<(Statement, usize)>);
 
-impl From<Vec<Statement>> for Statements {
-    fn from(value: Vec<Statement>) -> Self {
+impl From<Vec<(Statement, usize)>> for Statements {
+    fn from(value: Vec<(Statement, usize)>) -> Self {
         Self(value)
     }
 }
 
+impl FromIterator<(Statement, usize)> for Statements {
+    fn from_iter<T: IntoIterator<Item = (Statement, usize)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+// Statements without a source, every offset is zero
 impl FromIterator<Statement> for Statements {
     fn from_iter<T: IntoIterator<Item = Statement>>(iter: T) -> Self {
-        Self(iter.into_iter().collect())
+        Self(iter.into_iter().map(|statement| (statement, 0)).collect())
     }
 }
 
 impl Statements {
-    fn next(&mut self) -> Option<Statement> {
+    fn next(&mut self) -> Option<(Statement, usize)> {
         match self.is_empty() {
             false => Some(self.0.remove(0)),
             true => None,
         }
     }
 
+    fn first(&self) -> Option<&Statement> {
+        self.0.first().map(|(statement, _)| statement)
+    }
+
     fn take_value(&mut self) -> Option<Expression> {
-        match matches!(&self.0.first(), Some(Statement::LoadValue(_))) {
+        match matches!(self.first(), Some(Statement::LoadValue(_))) {
             true => match self.0.remove(0) {
-                Statement::LoadValue(expr) => Some(expr),
+                (Statement::LoadValue(expr), _) => Some(expr),
                 _ => unreachable!(),
             },
             false => None,
         }
     }
 
-    fn take_attributes(&mut self) -> Vec<(StringId, Expression)> {
+    fn take_attributes(&mut self) -> Vec<(StringId, Expression, usize)> {
         let mut v = vec![];
-        while matches!(&self.0.first(), Some(Statement::LoadAttribute { .. })) {
+        while matches!(self.first(), Some(Statement::LoadAttribute { .. })) {
             match self.0.remove(0) {
-                Statement::LoadAttribute { key, value } => v.push((key, value)),
+                (Statement::LoadAttribute { key, value }, offset) => v.push((key, value, offset)),
                 _ => unreachable!(),
             }
         }
@@ -120,9 +191,9 @@ impl Statements {
 
     fn take_assoc_functions(&mut self) -> Vec<(StringId, StringId)> {
         let mut v = vec![];
-        while matches!(&self.0.first(), Some(Statement::AssociatedFunction { .. })) {
+        while matches!(self.first(), Some(Statement::AssociatedFunction { .. })) {
             match self.0.remove(0) {
-                Statement::AssociatedFunction { internal, external } => v.push((internal, external)),
+                (Statement::AssociatedFunction { internal, external }, _) => v.push((internal, external)),
                 _ => unreachable!(),
             }
         }
@@ -134,14 +205,14 @@ impl Statements {
             return vec![].into();
         }
 
-        if self.0[0] != Statement::ScopeStart {
+        if self.first() != Some(&Statement::ScopeStart) {
             return vec![].into();
         }
 
         let mut level = 0;
 
         for i in 0..self.0.len() {
-            match &self.0[i] {
+            match &self.0[i].0 {
                 Statement::ScopeStart => level += 1,
                 Statement::ScopeEnd if level - 1 == 0 => {
                     let mut scope = self.0.split_off(i);
@@ -158,10 +229,20 @@ impl Statements {
         panic!("unclosed scope");
     }
 
-    fn next_else(&mut self) -> Option<Option<Expression>> {
-        match matches!(self.0.first(), Some(Statement::Else(_))) {
+    fn next_else(&mut self) -> Option<(Option<Expression>, usize)> {
+        match matches!(self.first(), Some(Statement::Else(_))) {
             true => match self.0.remove(0) {
-                Statement::Else(cond) => Some(cond),
+                (Statement::Else(cond), offset) => Some((cond, offset)),
+                _ => unreachable!(),
+            },
+            false => None,
+        }
+    }
+
+    fn next_case(&mut self) -> Option<(Option<Expression>, usize)> {
+        match matches!(self.first(), Some(Statement::Case(_))) {
+            true => match self.0.remove(0) {
+                (Statement::Case(value), offset) => Some((value, offset)),
                 _ => unreachable!(),
             },
             false => None,
@@ -169,15 +250,20 @@ impl Statements {
     }
 
     fn next_slot(&mut self) -> Option<StringId> {
-        match matches!(self.0.first(), Some(Statement::ComponentSlot(_))) {
+        match matches!(self.first(), Some(Statement::ComponentSlot(_))) {
             true => match self.0.remove(0) {
-                Statement::ComponentSlot(slot_id) => Some(slot_id),
+                (Statement::ComponentSlot(slot_id), _) => Some(slot_id),
                 _ => unreachable!(),
             },
             false => None,
         }
     }
 
+    /// The offset of the next statement
+    fn offset(&self) -> Option<usize> {
+        self.0.first().map(|(_, offset)| *offset)
+    }
+
     fn is_empty(&self) -> bool {
         self.0.is_empty()
     }
@@ -199,6 +285,8 @@ where
         components: &mut components,
         slots: SmallMap::empty(),
         current_component_parent: None,
+        params: HashMap::new(),
+        template: Template::new("", None),
     };
 
     f(context)
@@ -226,6 +314,10 @@ mod test {
         Statement::Component(id.into())
     }
 
+    pub(crate) fn include(name: impl Into<StringId>) -> Statement {
+        Statement::Include(name.into())
+    }
+
     pub(crate) fn slot(id: impl Into<StringId>) -> Statement {
         Statement::ComponentSlot(id.into())
     }
@@ -243,6 +335,19 @@ mod test {
 
     pub(crate) fn for_loop(binding: impl Into<StringId>, data: impl Into<Expression>) -> Statement {
         Statement::For {
+            key: None,
+            binding: binding.into(),
+            data: data.into(),
+        }
+    }
+
+    pub(crate) fn keyed_for_loop(
+        key: impl Into<StringId>,
+        binding: impl Into<StringId>,
+        data: impl Into<Expression>,
+    ) -> Statement {
+        Statement::For {
+            key: Some(key.into()),
             binding: binding.into(),
             data: data.into(),
         }
@@ -267,6 +372,18 @@ mod test {
         Statement::Else(None)
     }
 
+    pub(crate) fn switch(value: impl Into<Expression>) -> Statement {
+        Statement::Switch(value.into())
+    }
+
+    pub(crate) fn case(value: impl Into<Expression>) -> Statement {
+        Statement::Case(Some(value.into()))
+    }
+
+    pub(crate) fn default_case() -> Statement {
+        Statement::Case(None)
+    }
+
     pub(crate) fn scope_start() -> Statement {
         Statement::ScopeStart
     }
@@ -2,7 +2,7 @@ use anathema_store::storage::strings::{StringId, Strings};
 
 use super::Statement;
 use crate::components::ComponentTemplates;
-use crate::error::{src_line_no, ParseError, ParseErrorKind, Result};
+use crate::error::{Location, ParseError, ParseErrorKind, Result};
 use crate::expressions::parser::parse_expr;
 use crate::expressions::Expression;
 use crate::token::{Kind, Operator, Tokens, Value};
@@ -14,6 +14,7 @@ enum State {
     ExitScope,
     ParseFor,
     ParseIf,
+    ParseSwitch,
     ParseDeclaration,
     ParseComponent,
     ParseAssociatedFunctions,
@@ -68,12 +69,9 @@ impl<'src, 'strings, 'view> Parser<'src, 'strings, 'view> {
     }
 
     fn error(&self, kind: ParseErrorKind) -> ParseError {
-        let (line, col) = src_line_no(self.tokens.previous().1, self.src);
         ParseError {
-            line,
-            col,
-            src: self.src.to_string(),
             kind,
+            location: Location::new(self.tokens.previous().1, self.src),
         }
     }
 
@@ -84,7 +82,8 @@ impl<'src, 'strings, 'view> Parser<'src, 'strings, 'view> {
         }
     }
 
-    pub(crate) fn parse(&mut self) -> Result<Statement> {
+    // Returns the statement along with the offset in the source it starts at
+    pub(crate) fn parse(&mut self) -> Result<(Statement, usize)> {
         // * It is okay to advance the state once and only once
         //   in any parse function using `self.next_state()`.
         //   The exception to this is he parse view function
@@ -92,10 +91,12 @@ impl<'src, 'strings, 'view> Parser<'src, 'strings, 'view> {
         //   There is one exception of this, and that's when moving from
         //   `ParseAttributes` to `ParseText`.
         loop {
+            let offset = self.tokens.offset();
             let output = match self.state {
                 State::EnterScope => self.enter_scope()?,
                 State::ParseFor => self.parse_for()?,
                 State::ParseIf => self.parse_if()?,
+                State::ParseSwitch => self.parse_switch()?,
                 State::ParseDeclaration => self.parse_declaration()?,
                 State::ParseComponent => self.parse_component()?,
                 State::ParseAssociatedFunctions => {
@@ -123,7 +124,7 @@ impl<'src, 'strings, 'view> Parser<'src, 'strings, 'view> {
             };
 
             match output {
-                Some(inst) => break Ok(inst),
+                Some(inst) => break Ok((inst, offset)),
                 None => continue,
             }
         }
@@ -134,7 +135,8 @@ impl<'src, 'strings, 'view> Parser<'src, 'strings, 'view> {
             State::EnterScope => self.state = State::ExitScope,
             State::ExitScope => self.state = State::ParseFor,
             State::ParseFor => self.state = State::ParseIf,
-            State::ParseIf => self.state = State::ParseDeclaration,
+            State::ParseIf => self.state = State::ParseSwitch,
+            State::ParseSwitch => self.state = State::ParseDeclaration,
             State::ParseDeclaration => self.state = State::ParseIdent,
             State::ParseIdent => self.state = State::ParseComponent,
             State::ParseComponent => self.state = State::ParseAssociatedFunctions,
@@ -242,6 +244,10 @@ impl<'src, 'strings, 'view> Parser<'src, 'strings, 'view> {
             return Ok(None);
         }
 
+        if self.is_include() {
+            return self.parse_include().map(Some);
+        }
+
         let ident = self.read_ident()?;
 
         self.tokens.consume_indent();
@@ -257,7 +263,15 @@ impl<'src, 'strings, 'view> Parser<'src, 'strings, 'view> {
 
         self.tokens.consume();
 
-        let binding = self.read_ident()?;
+        let mut key = None;
+        let mut binding = self.read_ident()?;
+
+        // `for key, value in data`
+        if Kind::Op(Operator::Comma) == self.tokens.peek_skip_indent() {
+            self.tokens.consume();
+            key = Some(binding);
+            binding = self.read_ident()?;
+        }
 
         if Kind::In != self.tokens.peek_skip_indent() {
             return Err(self.error(ParseErrorKind::InvalidToken { expected: "in" }));
@@ -271,7 +285,7 @@ impl<'src, 'strings, 'view> Parser<'src, 'strings, 'view> {
             Err(e) => return Err(self.error(e)),
         };
         self.next_state();
-        Ok(Some(Statement::For { data, binding }))
+        Ok(Some(Statement::For { key, binding, data }))
     }
 
     fn parse_if(&mut self) -> Result<Option<Statement>, ParseError> {
@@ -299,6 +313,45 @@ impl<'src, 'strings, 'view> Parser<'src, 'strings, 'view> {
         }
     }
 
+    // `switch`, `case` and `default` are only keywords at the start of a statement,
+    // anywhere else they are regular identifiers.
+    fn parse_switch(&mut self) -> Result<Option<Statement>, ParseError> {
+        let Kind::Value(Value::Ident(ident)) = self.tokens.peek_skip_indent() else {
+            self.next_state();
+            return Ok(None);
+        };
+
+        let statement = match self.strings.get_ref_unchecked(ident) {
+            "switch" => {
+                self.tokens.consume();
+                let value = parse_expr(&mut self.tokens, self.strings).map_err(|e| self.error(e))?;
+                Some(Statement::Switch(value))
+            }
+            "case" => {
+                self.tokens.consume();
+                let value = parse_expr(&mut self.tokens, self.strings).map_err(|e| self.error(e))?;
+                Some(Statement::Case(Some(value)))
+            }
+            "default" => {
+                self.tokens.consume();
+                Some(Statement::Case(None))
+            }
+            _ => None,
+        };
+
+        self.next_state();
+        Ok(statement)
+    }
+
+    // `include` is only a keyword at the start of a statement,
+    // anywhere else it is a regular identifier.
+    fn is_include(&mut self) -> bool {
+        match self.tokens.peek_skip_indent() {
+            Kind::Value(Value::Ident(ident)) => self.strings.get_ref_unchecked(ident) == "include",
+            _ => false,
+        }
+    }
+
     fn parse_declaration(&mut self) -> Result<Option<Statement>, ParseError> {
         // Check if it's a declaration otherwise move on
         match self.tokens.peek_skip_indent() {
@@ -325,9 +378,12 @@ impl<'src, 'strings, 'view> Parser<'src, 'strings, 'view> {
     }
 
     fn parse_component(&mut self) -> Result<Option<Statement>, ParseError> {
-        if Kind::Component != self.tokens.peek_skip_indent() {
-            self.next_state();
-            return Ok(None);
+        match self.tokens.peek_skip_indent() {
+            Kind::Component => {}
+            _ => {
+                self.next_state();
+                return Ok(None);
+            }
         }
 
         self.tokens.consume();
@@ -342,6 +398,23 @@ impl<'src, 'strings, 'view> Parser<'src, 'strings, 'view> {
         Ok(Some(Statement::Component(component_id)))
     }
 
+    fn parse_include(&mut self) -> Result<Statement, ParseError> {
+        self.tokens.consume();
+
+        let name = match self.tokens.next_no_indent() {
+            Kind::Value(Value::String(name)) => name,
+            _ => {
+                return Err(self.error(ParseErrorKind::InvalidToken {
+                    expected: "partial name",
+                }))
+            }
+        };
+        self.tokens.consume_indent();
+
+        self.next_state();
+        Ok(Statement::Include(name))
+    }
+
     fn parse_associated_functions(&mut self) -> Result<bool, ParseError> {
         if Kind::Op(Operator::LParen) == self.tokens.peek_skip_indent() {
             self.tokens.consume();
@@ -523,7 +596,7 @@ impl<'src, 'strings, 'view> Parser<'src, 'strings, 'view> {
 //     - Iterator -
 // -----------------------------------------------------------------------------
 impl Iterator for Parser<'_, '_, '_> {
-    type Item = Result<Statement>;
+    type Item = Result<(Statement, usize)>;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.done {
@@ -531,9 +604,9 @@ impl Iterator for Parser<'_, '_, '_> {
         }
 
         match self.parse() {
-            Ok(Statement::Eof) => {
+            Ok((Statement::Eof, offset)) => {
                 self.done = true;
-                Some(Ok(Statement::Eof))
+                Some(Ok((Statement::Eof, offset)))
             }
             Err(e) => {
                 self.state = State::Done;
@@ -547,12 +620,12 @@ impl Iterator for Parser<'_, '_, '_> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::error::Error;
-    use crate::expressions::{ident, list, map, num, strlit};
+    use crate::error::ErrorKind;
+    use crate::expressions::{conditional, ident, list, map, num, strlit};
     use crate::lexer::Lexer;
     use crate::statements::test::{
-        associated_fun, component, decl, else_stmt, eof, for_loop, if_else, if_stmt, load_attrib, load_value, node,
-        scope_end, scope_start, slot,
+        associated_fun, case, component, decl, default_case, else_stmt, eof, for_loop, if_else, if_stmt, include,
+        keyed_for_loop, load_attrib, load_value, node, scope_end, scope_start, slot, switch,
     };
 
     fn parse(src: &str) -> Vec<Result<Statement>> {
@@ -562,7 +635,9 @@ mod test {
         let tokens = Tokens::new(lexer.collect::<Result<Vec<_>>>().unwrap(), src.len());
         let parser = Parser::new(tokens, &mut strings, src, &mut components);
 
-        parser.collect::<Vec<_>>()
+        parser
+            .map(|res| res.map(|(statement, _)| statement))
+            .collect::<Vec<_>>()
     }
 
     fn parse_ok(src: &str) -> Vec<Statement> {
@@ -570,8 +645,12 @@ mod test {
     }
 
     fn parse_err(src: &str) -> ParseError {
-        match parse(src).into_iter().collect::<Result<Vec<_>>>().unwrap_err() {
-            Error::ParseError(err) => err,
+        let err = parse(src).into_iter().collect::<Result<Vec<_>>>().unwrap_err();
+        match err.kind {
+            ErrorKind::ParseError(kind) => ParseError {
+                kind,
+                location: err.location.unwrap(),
+            },
             _ => panic!("invalid error kind"),
         }
     }
@@ -591,6 +670,20 @@ mod test {
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn parse_conditional_attributes_and_value() {
+        let src = "a [b: c ? 'x' : 'y'] c ? 1 : 2";
+        let expected = vec![
+            node(0),
+            load_attrib(1, conditional(ident("c"), strlit("x"), strlit("y"))),
+            load_value(conditional(ident("c"), num(1), num(2))),
+            eof(),
+        ];
+
+        let actual = parse_ok(src);
+        assert_eq!(expected, actual);
+    }
+
     #[test]
     fn parse_text() {
         let src = "a 'a'      \n\n//some comments \n    ";
@@ -667,6 +760,19 @@ mod test {
         assert_eq!(statements.remove(0), scope_end());
     }
 
+    #[test]
+    fn parse_keyed_for_loop() {
+        let src = "
+        for key, value in data
+            x
+        ";
+        let mut statements = parse_ok(src);
+        assert_eq!(statements.remove(0), keyed_for_loop(0, 1, ident("data")));
+        assert_eq!(statements.remove(0), scope_start());
+        assert_eq!(statements.remove(0), node(3));
+        assert_eq!(statements.remove(0), scope_end());
+    }
+
     #[test]
     fn parse_scopes_and_for() {
         let src = "
@@ -720,6 +826,84 @@ mod test {
         assert_eq!(statements.remove(0), scope_end());
     }
 
+    #[test]
+    fn parse_switch() {
+        let src = "
+        switch data
+            case 'a'
+                x
+            default
+                y
+        ";
+        let mut statements = parse_ok(src);
+
+        assert_eq!(statements.remove(0), switch(ident("data")));
+        assert_eq!(statements.remove(0), scope_start());
+        assert_eq!(statements.remove(0), case(strlit("a")));
+        assert_eq!(statements.remove(0), scope_start());
+        assert_eq!(statements.remove(0), node(4));
+        assert_eq!(statements.remove(0), scope_end());
+        assert_eq!(statements.remove(0), default_case());
+        assert_eq!(statements.remove(0), scope_start());
+        assert_eq!(statements.remove(0), node(6));
+        assert_eq!(statements.remove(0), scope_end());
+        assert_eq!(statements.remove(0), scope_end());
+    }
+
+    #[test]
+    fn switch_keywords_are_contextual() {
+        let src = "
+        text default
+        vstack [case: switch]
+        ";
+        let mut statements = parse_ok(src);
+
+        assert_eq!(statements.remove(0), node(0));
+        assert_eq!(statements.remove(0), load_value(ident("default")));
+        assert_eq!(statements.remove(0), node(2));
+        assert_eq!(statements.remove(0), load_attrib(3, ident("switch")));
+    }
+
+    #[test]
+    fn parse_include() {
+        let src = "
+        include \"card\" { title: x }
+        include \"card\"
+        ";
+        let mut statements = parse_ok(src);
+
+        assert_eq!(statements.remove(0), include(1));
+        assert_eq!(statements.remove(0), load_value(map([("title", ident("x"))])));
+        assert_eq!(statements.remove(0), include(1));
+        assert_eq!(statements.remove(0), eof());
+    }
+
+    #[test]
+    fn include_keyword_is_contextual() {
+        let src = "
+        text include
+        vstack [include: x]
+        ";
+        let mut statements = parse_ok(src);
+
+        assert_eq!(statements.remove(0), node(0));
+        assert_eq!(statements.remove(0), load_value(ident("include")));
+        assert_eq!(statements.remove(0), node(2));
+        assert_eq!(statements.remove(0), load_attrib(1, ident("x")));
+    }
+
+    #[test]
+    fn parse_include_without_name() {
+        let src = "include card";
+        let err = parse_err(src);
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::InvalidToken {
+                expected: "partial name"
+            }
+        );
+    }
+
     #[test]
     fn parse_if_else_if_else() {
         let src = "
@@ -826,9 +1010,7 @@ mod test {
             kind: ParseErrorKind::InvalidToken {
                 expected: "valid token, found statement",
             },
-            line: 1,
-            col: 9,
-            src: "let x = let y = 1".to_string(),
+            location: Location::new(8, src),
         };
 
         assert_eq!(err, expected);
@@ -32,8 +32,11 @@ pub enum Operator {
     And,
     Or,
     Dot,
+    Range,
+    Pipe,
     Comma,
     Colon,
+    Question,
     Association,
 }
 
@@ -64,8 +67,11 @@ impl Display for Operator {
             Self::And => write!(f, "&&"),
             Self::Or => write!(f, "||"),
             Self::Dot => write!(f, "."),
+            Self::Range => write!(f, ".."),
+            Self::Pipe => write!(f, "|"),
             Self::Comma => write!(f, ","),
             Self::Colon => write!(f, ":"),
+            Self::Question => write!(f, "?"),
             Self::LCurly => write!(f, "{{"),
             Self::RCurly => write!(f, "}}"),
             Self::Association => write!(f, "->"),
@@ -78,6 +84,9 @@ pub(crate) enum Value {
     Primitive(Primitive),
     String(StringId),
     Ident(StringId),
+    /// An interpolated string made up of the next `n` segments,
+    /// where each segment is either a string or an expression in parens
+    TextSegments(usize),
 }
 
 impl<T: Into<Primitive>> From<T> for Value {
@@ -92,6 +101,7 @@ impl Display for Value {
             Self::Primitive(val) => write!(f, "{val}"),
             Self::String(s) => write!(f, "\"{s}\""),
             Self::Ident(id) => write!(f, "{id}"),
+            Self::TextSegments(count) => write!(f, "<text segments {count}>"),
         }
     }
 }
@@ -227,6 +237,15 @@ impl Tokens {
             .0
     }
 
+    /// The offset of the next token, ignoring whitespace
+    pub fn offset(&self) -> usize {
+        self.inner[self.index..]
+            .iter()
+            .find(|token| !matches!(token.0, Kind::Indent(_) | Kind::Newline))
+            .map(|token| token.1)
+            .unwrap_or(self.eof)
+    }
+
     pub fn previous(&self) -> Token {
         assert!(self.index != 0);
         self.inner
@@ -1,12 +1,9 @@
 use std::collections::HashMap;
 use std::rc::Rc;
 
-#[cfg(not(target_os = "windows"))]
-use anathema_debug::DebugWriter;
 use anathema_store::slab::Slab;
 
 use crate::expressions::Expression;
-use crate::primitives::Primitive;
 
 #[derive(Debug, Default, Clone)]
 pub struct Globals(HashMap<Rc<str>, Expression>);
@@ -23,6 +20,11 @@ impl Globals {
     pub fn take(&mut self) -> Self {
         std::mem::take(self)
     }
+
+    /// Insert a global value, replacing any existing value with the same name
+    pub fn insert(&mut self, ident: impl Into<Rc<str>>, value: impl Into<Expression>) {
+        self.0.insert(ident.into(), value.into());
+    }
 }
 
 impl From<Variables> for Globals {
@@ -46,24 +48,6 @@ impl From<VarId> for usize {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum Variable {
-    Static(Primitive),
-    Str(Rc<str>),
-}
-
-impl From<&str> for Variable {
-    fn from(value: &str) -> Self {
-        Self::Str(value.into())
-    }
-}
-
-impl From<Primitive> for Variable {
-    fn from(value: Primitive) -> Self {
-        Self::Static(value)
-    }
-}
-
 /// The scope id acts as a path made up of indices
 /// into the scope tree.
 /// E.g `[0, 1, 0]` would point to `root.children[0].children[1].children[0]`.
@@ -371,57 +355,6 @@ impl From<Variables> for HashMap<Rc<str>, Expression> {
     }
 }
 
-#[cfg(not(target_os = "windows"))]
-pub struct ScopeDebug<'a> {
-    level: usize,
-    scope: &'a Scope,
-    store: &'a Slab<VarId, Expression>,
-}
-
-#[cfg(not(target_os = "windows"))]
-impl DebugWriter for ScopeDebug<'_> {
-    fn write(&mut self, output: &mut impl std::fmt::Write) -> std::fmt::Result {
-        let indent = " ".repeat(self.level * 4);
-
-        for (key, var_ids) in self.scope.variables.iter() {
-            let iter = var_ids.iter().filter_map(|id| self.store.get(*id));
-
-            for (cntr, val) in iter.enumerate() {
-                if cntr > 0 {
-                    writeln!(output, ", ")?;
-                }
-                writeln!(output, "{indent}{key}: {val:?}")?;
-            }
-        }
-
-        for child in &self.scope.children {
-            ScopeDebug {
-                level: self.level + 1,
-                scope: child,
-                store: self.store,
-            }
-            .write(output)?;
-        }
-
-        Ok(())
-    }
-}
-
-#[cfg(not(target_os = "windows"))]
-pub struct VariablesDebug<'a>(pub(crate) &'a Variables);
-
-#[cfg(not(target_os = "windows"))]
-impl DebugWriter for VariablesDebug<'_> {
-    fn write(&mut self, output: &mut impl std::fmt::Write) -> std::fmt::Result {
-        ScopeDebug {
-            level: 0,
-            scope: &self.0.root.0,
-            store: &self.0.store,
-        }
-        .write(output)
-    }
-}
-
 #[cfg(test)]
 mod test {
     use super::*;
@@ -0,0 +1,579 @@
+//! Tween state values over time.
+//!
+//! An [`Animation`] is started from a component [`Context`](super::Context)
+//! and is driven by the runtime, once per frame, until it's done.
+//! The time of an animation is the time of the frames, starting with the first frame
+//! after the animation was created.
+//! ```
+//! # use std::time::Duration;
+//! # use anathema_widgets::components::animation::{Animation, Easing, Repeat};
+//! # use anathema_state::Color;
+//! let animation: Animation<(), Color> = Animation::new(Color::Rgb(255, 0, 0), Duration::from_millis(250))
+//!     .easing(Easing::EaseOut)
+//!     .then(Color::Rgb(0, 0, 255), Duration::from_millis(250))
+//!     .repeat(Repeat::Forever);
+//! ```
+use std::any::Any;
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::f64::consts::PI;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use anathema_state::{AnyState, Color, StateId, States, Value};
+
+use crate::WidgetId;
+
+/// Easing curves.
+/// See [easings.net](https://easings.net) for a visual representation.
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum Easing {
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Cubic ease in and out
+    Cubic,
+    /// Overshoot the target and settle like a spring
+    Spring,
+}
+
+impl Easing {
+    /// Map the progress `t` (`0.0..=1.0`) of an animation onto the curve.
+    pub fn apply(self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(2),
+            Easing::EaseInOut if t < 0.5 => 2.0 * t * t,
+            Easing::EaseInOut => 1.0 - (-2.0 * t + 2.0).powi(2) / 2.0,
+            Easing::Cubic if t < 0.5 => 4.0 * t.powi(3),
+            Easing::Cubic => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            Easing::Spring => 1.0 - (-6.0 * t).exp() * (4.0 * PI * t).cos(),
+        }
+    }
+}
+
+/// Linear interpolation between two values.
+pub trait Lerp: Clone {
+    /// Interpolate between `self` and `to`, where `t` is usually in the range `0.0..=1.0`.
+    /// Easing curves like [`Easing::Spring`] can produce values outside of that range.
+    fn lerp(&self, to: &Self, t: f64) -> Self;
+}
+
+macro_rules! impl_lerp_float {
+    ($t:ty) => {
+        impl Lerp for $t {
+            fn lerp(&self, to: &Self, t: f64) -> Self {
+                self + (to - self) * t as $t
+            }
+        }
+    };
+}
+
+macro_rules! impl_lerp_int {
+    ($t:ty) => {
+        impl Lerp for $t {
+            fn lerp(&self, to: &Self, t: f64) -> Self {
+                let from = *self as f64;
+                (from + (*to as f64 - from) * t).round() as $t
+            }
+        }
+    };
+}
+
+impl_lerp_float!(f32);
+impl_lerp_float!(f64);
+impl_lerp_int!(u8);
+impl_lerp_int!(i8);
+impl_lerp_int!(u16);
+impl_lerp_int!(i16);
+impl_lerp_int!(u32);
+impl_lerp_int!(i32);
+impl_lerp_int!(u64);
+impl_lerp_int!(i64);
+impl_lerp_int!(usize);
+impl_lerp_int!(isize);
+
+/// Only [`Color::Rgb`] is interpolated.
+/// Any other color changes to the target color once the animation is done.
+impl Lerp for Color {
+    fn lerp(&self, to: &Self, t: f64) -> Self {
+        match (self, to) {
+            (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => {
+                Color::Rgb(r1.lerp(r2, t), g1.lerp(g2, t), b1.lerp(b2, t))
+            }
+            _ if t < 1.0 => *self,
+            _ => *to,
+        }
+    }
+}
+
+/// How many times an animation is played.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Repeat {
+    Times(u32),
+    Forever,
+}
+
+struct Step<V> {
+    to: V,
+    duration: Duration,
+    easing: Easing,
+}
+
+/// A sequence of tweens, starting from the current value.
+///
+/// `T` is the state of the component running the animation.
+pub struct Animation<T, V> {
+    steps: Vec<Step<V>>,
+    repeat: Repeat,
+    on_complete: Option<Box<dyn FnOnce(&mut T)>>,
+}
+
+impl<T, V: Lerp> Animation<T, V> {
+    /// Tween the value to `to` over the given duration.
+    pub fn new(to: V, duration: Duration) -> Self {
+        Self {
+            steps: vec![Step {
+                to,
+                duration,
+                easing: Easing::Linear,
+            }],
+            repeat: Repeat::Times(1),
+            on_complete: None,
+        }
+    }
+
+    /// Set the easing curve of the last step.
+    pub fn easing(mut self, easing: Easing) -> Self {
+        if let Some(step) = self.steps.last_mut() {
+            step.easing = easing;
+        }
+        self
+    }
+
+    /// Once the previous step is done, tween the value to `to` over the given duration.
+    pub fn then(mut self, to: V, duration: Duration) -> Self {
+        self.steps.push(Step {
+            to,
+            duration,
+            easing: Easing::Linear,
+        });
+        self
+    }
+
+    /// Play the animation more than once.
+    /// Every repeat starts from the value the animation started with.
+    pub fn repeat(mut self, repeat: Repeat) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Called with the component state once the animation is done.
+    /// This is not called if the animation is cancelled.
+    pub fn on_complete(mut self, f: impl FnOnce(&mut T) + 'static) -> Self {
+        self.on_complete = Some(Box::new(f));
+        self
+    }
+
+    fn total(&self) -> Duration {
+        self.steps.iter().map(|step| step.duration).sum()
+    }
+
+    // The value `elapsed` into the sequence, or `None` if the animation is done
+    fn value_at(&self, from: &V, elapsed: Duration) -> Option<V> {
+        const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+        let total = self.total();
+        let mut offset = match total.is_zero() {
+            true => return None,
+            false => {
+                let iteration = elapsed.as_nanos() / total.as_nanos();
+                if let Repeat::Times(times) = self.repeat {
+                    if iteration >= times as u128 {
+                        return None;
+                    }
+                }
+                // The offset is less than `total`, so the seconds fit in a `u64`
+                let nanos = elapsed.as_nanos() % total.as_nanos();
+                Duration::new((nanos / NANOS_PER_SEC) as u64, (nanos % NANOS_PER_SEC) as u32)
+            }
+        };
+
+        let mut prev = from;
+        for step in &self.steps {
+            if offset < step.duration {
+                let t = offset.as_secs_f64() / step.duration.as_secs_f64();
+                return Some(prev.lerp(&step.to, step.easing.apply(t)));
+            }
+            offset -= step.duration;
+            prev = &step.to;
+        }
+
+        None
+    }
+}
+
+/// A handle to an animation started with [`Context::animate`](super::Context::animate).
+///
+/// Dropping the handle does not cancel the animation.
+#[derive(Debug, Clone)]
+pub struct AnimationHandle(Rc<Cell<bool>>);
+
+impl AnimationHandle {
+    /// Stop the animation, leaving the value as it is.
+    pub fn cancel(&self) {
+        self.0.set(true);
+    }
+
+    /// Returns true if the animation was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.get()
+    }
+}
+
+trait AnyAnimation {
+    // Returns false once the animation is done
+    fn update(&mut self, now: Instant, state: &mut dyn Any) -> bool;
+
+    // The path to the animated value: its position in the state
+    fn path(&mut self, state: &mut dyn Any) -> Option<usize>;
+}
+
+struct Running<T, V, F> {
+    select: F,
+    animation: Animation<T, V>,
+    // Set by the first update
+    start: Option<Instant>,
+    from: Option<V>,
+}
+
+impl<T, V, F> AnyAnimation for Running<T, V, F>
+where
+    T: 'static,
+    V: Lerp + AnyState + 'static,
+    F: FnMut(&mut T) -> &mut Value<V>,
+{
+    fn update(&mut self, now: Instant, state: &mut dyn Any) -> bool {
+        let Some(state) = state.downcast_mut::<T>() else { return false };
+        let value = (self.select)(state);
+        let from = self.from.get_or_insert_with(|| V::clone(&value.to_ref()));
+
+        let start = *self.start.get_or_insert(now);
+        let elapsed = now.saturating_duration_since(start);
+        match self.animation.value_at(from, elapsed) {
+            Some(new_value) => {
+                value.set(new_value);
+                true
+            }
+            None => {
+                if let Some(step) = self.animation.steps.last() {
+                    value.set(step.to.clone());
+                }
+                if let Some(f) = self.animation.on_complete.take() {
+                    f(state);
+                }
+                false
+            }
+        }
+    }
+
+    fn path(&mut self, state: &mut dyn Any) -> Option<usize> {
+        let state = state.downcast_mut::<T>()?;
+        let base = state as *const T as usize;
+        let value = (self.select)(state) as *const Value<V> as usize;
+        Some(value.wrapping_sub(base))
+    }
+}
+
+struct Entry {
+    widget_id: WidgetId,
+    state_id: StateId,
+    cancelled: Rc<Cell<bool>>,
+    animation: Box<dyn AnyAnimation>,
+}
+
+/// Running animations.
+///
+/// There is only one animation per value, identified by the widget id
+/// and the path to the value in the state:
+/// a new animation of a value replaces the one that is running.
+pub struct Animations {
+    inner: Vec<Entry>,
+}
+
+impl Animations {
+    pub fn new() -> Self {
+        Self { inner: vec![] }
+    }
+
+    pub(super) fn push<T, V, F>(
+        &mut self,
+        widget_id: WidgetId,
+        state_id: StateId,
+        select: F,
+        animation: Animation<T, V>,
+    ) -> AnimationHandle
+    where
+        T: 'static,
+        V: Lerp + AnyState + 'static,
+        F: FnMut(&mut T) -> &mut Value<V> + 'static,
+    {
+        let cancelled = Rc::new(Cell::new(false));
+        let running = Running {
+            select,
+            animation,
+            start: None,
+            from: None,
+        };
+
+        self.inner.push(Entry {
+            widget_id,
+            state_id,
+            cancelled: cancelled.clone(),
+            animation: Box::new(running),
+        });
+
+        AnimationHandle(cancelled)
+    }
+
+    /// Advance all animations to `now`, the time of the current frame.
+    /// Animations that are done, cancelled or replaced are removed.
+    pub fn update(&mut self, now: Instant, states: &mut States) {
+        self.replace(states);
+
+        self.inner.retain_mut(|entry| {
+            if entry.cancelled.get() {
+                return false;
+            }
+
+            match states.get_mut(entry.state_id) {
+                Some(state) => entry.animation.update(now, state.to_any_mut()),
+                None => false,
+            }
+        });
+    }
+
+    // Cancel the animations of values that have a newer animation.
+    // The paths are resolved in the same frame, so they are the same
+    // for the same value of a state.
+    fn replace(&mut self, states: &mut States) {
+        let mut paths = HashSet::new();
+        for entry in self.inner.iter_mut().rev() {
+            if entry.cancelled.get() {
+                continue;
+            }
+
+            let Some(state) = states.get_mut(entry.state_id) else { continue };
+            let Some(path) = entry.animation.path(state.to_any_mut()) else { continue };
+            if !paths.insert((entry.widget_id, path)) {
+                entry.cancelled.set(true);
+            }
+        }
+    }
+
+    /// Remove all animations belonging to a component
+    pub fn remove(&mut self, widget_id: WidgetId) {
+        self.inner.retain(|entry| entry.widget_id != widget_id);
+    }
+
+    /// Returns true if there are no running animations
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ms(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    fn start<T, V, F>(select: F, animation: Animation<T, V>) -> Running<T, V, F>
+    where
+        F: FnMut(&mut T) -> &mut Value<V>,
+    {
+        Running {
+            select,
+            animation,
+            start: None,
+            from: None,
+        }
+    }
+
+    #[test]
+    fn easing_bounds() {
+        let easings = [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+            Easing::Cubic,
+        ];
+
+        for easing in easings {
+            assert_eq!(easing.apply(0.0), 0.0);
+            assert_eq!(easing.apply(1.0), 1.0);
+        }
+
+        assert_eq!(Easing::EaseIn.apply(0.5), 0.25);
+        assert_eq!(Easing::EaseOut.apply(0.5), 0.75);
+        assert_eq!(Easing::EaseInOut.apply(0.5), 0.5);
+        assert_eq!(Easing::Cubic.apply(0.25), 0.0625);
+
+        // The spring overshoots before settling
+        assert!(Easing::Spring.apply(0.25) > 1.0);
+        assert!((Easing::Spring.apply(1.0) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn lerp() {
+        assert_eq!(0.0f64.lerp(&10.0, 0.25), 2.5);
+        assert_eq!(0u8.lerp(&255, 0.5), 128);
+        assert_eq!(10i32.lerp(&-10, 0.5), 0);
+        // Saturates rather than wrapping around
+        assert_eq!(250u8.lerp(&255, 2.0), 255);
+
+        let color = Color::Rgb(0, 0, 0).lerp(&Color::Rgb(255, 100, 10), 0.5);
+        assert_eq!(color, Color::Rgb(128, 50, 5));
+        assert_eq!(Color::Red.lerp(&Color::Blue, 0.5), Color::Red);
+        assert_eq!(Color::Red.lerp(&Color::Blue, 1.0), Color::Blue);
+    }
+
+    #[test]
+    fn sequence() {
+        let animation = Animation::<(), f64>::new(10.0, ms(100)).then(0.0, ms(100));
+        assert_eq!(animation.value_at(&0.0, ms(0)), Some(0.0));
+        assert_eq!(animation.value_at(&0.0, ms(50)), Some(5.0));
+        assert_eq!(animation.value_at(&0.0, ms(150)), Some(5.0));
+        assert_eq!(animation.value_at(&0.0, ms(200)), None);
+    }
+
+    #[test]
+    fn repeat() {
+        let animation = Animation::<(), f64>::new(10.0, ms(100)).repeat(Repeat::Times(2));
+        assert_eq!(animation.value_at(&0.0, ms(150)), Some(5.0));
+        assert_eq!(animation.value_at(&0.0, ms(200)), None);
+
+        let animation = animation.repeat(Repeat::Forever);
+        assert_eq!(animation.value_at(&0.0, ms(1050)), Some(5.0));
+
+        // More iterations than fit in a `u32`
+        let animation = Animation::<(), f64>::new(10.0, Duration::from_nanos(2)).repeat(Repeat::Forever);
+        let elapsed = Duration::from_nanos(2 * (u32::MAX as u64 + 1) + 1);
+        assert_eq!(animation.value_at(&0.0, elapsed), Some(5.0));
+    }
+
+    #[test]
+    fn update_value() {
+        struct S {
+            value: Value<u8>,
+            done: bool,
+        }
+
+        let mut state = S {
+            value: 0.into(),
+            done: false,
+        };
+
+        let animation = Animation::new(100, ms(100)).on_complete(|state: &mut S| state.done = true);
+        let mut running = start(|state: &mut S| &mut state.value, animation);
+
+        // The animation starts with the first update
+        let now = Instant::now();
+        assert!(running.update(now, &mut state));
+        assert_eq!(state.value.copy_value(), 0);
+
+        assert!(running.update(now + ms(50), &mut state));
+        assert_eq!(state.value.copy_value(), 50);
+        assert!(!state.done);
+
+        assert!(!running.update(now + ms(150), &mut state));
+        assert_eq!(state.value.copy_value(), 100);
+        assert!(state.done);
+    }
+
+    #[test]
+    fn replace_animation() {
+        fn select(value: &mut Value<u8>) -> &mut Value<u8> {
+            value
+        }
+
+        let mut states = States::new();
+        let state_id = states.insert(Box::new(Value::new(0u8)));
+        let mut animations = Animations::new();
+        let first = animations.push(WidgetId::ZERO, state_id, select, Animation::new(100, ms(100)));
+        let second = animations.push(WidgetId::ZERO, state_id, select, Animation::new(200, ms(100)));
+
+        let now = Instant::now();
+        animations.update(now, &mut states);
+        animations.update(now + ms(100), &mut states);
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+        assert!(animations.is_empty());
+
+        let value = states
+            .get(state_id)
+            .unwrap()
+            .to_any_ref()
+            .downcast_ref::<Value<u8>>()
+            .unwrap()
+            .copy_value();
+        assert_eq!(value, 200);
+    }
+
+    #[test]
+    fn animate_fields_of_a_state() {
+        struct S {
+            a: Value<u8>,
+            b: Value<u8>,
+        }
+
+        impl anathema_state::State for S {
+            fn to_common(&self) -> Option<anathema_state::CommonVal<'_>> {
+                None
+            }
+        }
+
+        let mut states = States::new();
+        let state_id = states.insert(Box::new(S {
+            a: 0.into(),
+            b: 0.into(),
+        }));
+        let mut animations = Animations::new();
+        let a = animations.push(
+            WidgetId::ZERO,
+            state_id,
+            |s: &mut S| &mut s.a,
+            Animation::new(100, ms(100)),
+        );
+        let b = animations.push(
+            WidgetId::ZERO,
+            state_id,
+            |s: &mut S| &mut s.b,
+            Animation::new(200, ms(100)),
+        );
+        let replaced = animations.push(
+            WidgetId::ZERO,
+            state_id,
+            |s: &mut S| &mut s.a,
+            Animation::new(50, ms(100)),
+        );
+
+        let now = Instant::now();
+        animations.update(now, &mut states);
+        animations.update(now + ms(100), &mut states);
+        assert!(a.is_cancelled());
+        assert!(!b.is_cancelled());
+        assert!(!replaced.is_cancelled());
+
+        let state = states.get(state_id).unwrap().to_any_ref().downcast_ref::<S>().unwrap();
+        assert_eq!(state.a.copy_value(), 50);
+        assert_eq!(state.b.copy_value(), 200);
+    }
+}
@@ -1,4 +1,6 @@
-#[derive(Debug, Copy, Clone)]
+use super::Modifiers;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum KeyState {
     Press,
     Repeat,
@@ -8,7 +10,7 @@ pub enum KeyState {
 #[derive(Debug, Copy, Clone)]
 pub struct KeyEvent {
     pub code: KeyCode,
-    pub ctrl: bool,
+    pub modifiers: Modifiers,
     pub state: KeyState,
 }
 
@@ -19,9 +21,62 @@ impl KeyEvent {
             _ => None,
         }
     }
+
+    /// Returns true if the control key is held down.
+    /// This replaces the `ctrl` field, see [`KeyEvent::modifiers`] for all modifiers.
+    pub fn ctrl(&self) -> bool {
+        self.modifiers.contains(Modifiers::CTRL)
+    }
+
+    /// Returns true if the shift key is held down
+    pub fn shift(&self) -> bool {
+        self.modifiers.contains(Modifiers::SHIFT)
+    }
+
+    /// Returns true if the alt key is held down
+    pub fn alt(&self) -> bool {
+        self.modifiers.contains(Modifiers::ALT)
+    }
+
+    /// Returns true if the super key is held down
+    pub fn super_key(&self) -> bool {
+        self.modifiers.contains(Modifiers::SUPER)
+    }
+
+    /// Returns true if the key code is the same and
+    /// exactly the given modifiers are held down.
+    ///
+    /// Note that the shift modifier is reported along
+    /// with upper case characters.
+    ///
+    /// ```
+    /// # use anathema_widgets::components::events::{KeyCode, KeyEvent, KeyState, Modifiers};
+    /// let event = KeyEvent {
+    ///     code: KeyCode::Enter,
+    ///     modifiers: Modifiers::ALT,
+    ///     state: KeyState::Press,
+    /// };
+    ///
+    /// assert!(event.matches(KeyCode::Enter, Modifiers::ALT));
+    /// assert!(!event.matches(KeyCode::Enter, Modifiers::empty()));
+    /// assert!(!event.matches('s', Modifiers::CTRL));
+    /// ```
+    pub fn matches(&self, code: impl Into<KeyCode>, modifiers: Modifiers) -> bool {
+        self.code == code.into() && self.modifiers == modifiers
+    }
+
+    /// Returns true if this is the given character with the control key held down
+    pub fn is_ctrl(&self, c: char) -> bool {
+        self.matches(c, Modifiers::CTRL)
+    }
+
+    /// Returns true if this is the given character with the alt key held down
+    pub fn is_alt(&self, c: char) -> bool {
+        self.matches(c, Modifiers::ALT)
+    }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum KeyCode {
     Char(char),
     Tab,
@@ -50,3 +105,9 @@ pub enum KeyCode {
     Menu,
     KeypadBegin,
 }
+
+impl From<char> for KeyCode {
+    fn from(c: char) -> Self {
+        Self::Char(c)
+    }
+}
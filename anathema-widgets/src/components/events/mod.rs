@@ -1,8 +1,12 @@
 pub use self::key::{KeyCode, KeyEvent, KeyState};
+pub use self::modifiers::Modifiers;
 pub use self::mouse::{MouseButton, MouseEvent, MouseState};
+pub use self::paste::Paste;
 
 mod key;
+mod modifiers;
 mod mouse;
+mod paste;
 
 /// An event
 #[derive(Debug, Copy, Clone)]
@@ -19,6 +23,8 @@ pub enum Event {
     Key(KeyEvent),
     /// Mouse event
     Mouse(MouseEvent),
+    /// Text pasted into the terminal (requires bracketed paste)
+    Paste(Paste),
     /// Window was resized
     Resize(u16, u16),
 }
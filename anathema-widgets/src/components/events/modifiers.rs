@@ -0,0 +1,22 @@
+bitflags::bitflags! {
+    /// Modifier keys held down during a key or mouse event.
+    /// No modifiers is [`Modifiers::empty`].
+    /// ```ignore
+    /// let modifiers = Modifiers::CTRL | Modifiers::SHIFT;
+    /// ```
+    #[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+    pub struct Modifiers: u8 {
+        /// Shift key
+        const SHIFT = 0b0000_0001;
+        /// Control key
+        const CTRL =  0b0000_0010;
+        /// Alt key (option on macOS)
+        const ALT =   0b0000_0100;
+        /// Super key (command on macOS, windows key on Windows)
+        const SUPER = 0b0000_1000;
+        /// Hyper key
+        const HYPER = 0b0001_0000;
+        /// Meta key
+        const META =  0b0010_0000;
+    }
+}
@@ -1,10 +1,13 @@
 use anathema_geometry::Pos;
 
+use super::Modifiers;
+
 #[derive(Debug, Copy, Clone)]
 pub struct MouseEvent {
     pub x: u16,
     pub y: u16,
     pub state: MouseState,
+    pub modifiers: Modifiers,
 }
 
 impl MouseEvent {
@@ -33,6 +36,21 @@ impl MouseEvent {
     pub fn lsb_up(&self) -> bool {
         matches!(self.state, MouseState::Up(MouseButton::Left))
     }
+
+    /// Returns true if the control key is held down
+    pub fn ctrl(&self) -> bool {
+        self.modifiers.contains(Modifiers::CTRL)
+    }
+
+    /// Returns true if the shift key is held down
+    pub fn shift(&self) -> bool {
+        self.modifiers.contains(Modifiers::SHIFT)
+    }
+
+    /// Returns true if the alt key is held down
+    pub fn alt(&self) -> bool {
+        self.modifiers.contains(Modifiers::ALT)
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -45,6 +63,15 @@ pub enum MouseState {
     ScrollDown,
     ScrollLeft,
     ScrollRight,
+    /// The cursor moved into the component.
+    /// This is generated by the runtime and never produced by a backend.
+    Enter,
+    /// The cursor moved out of the component.
+    /// This is generated by the runtime and never produced by a backend.
+    Leave,
+    /// The cursor moved while over the component.
+    /// The runtime sends [`MouseState::Move`] to the component under the cursor as `Hover`.
+    Hover,
 }
 
 #[derive(Debug, Copy, Clone)]
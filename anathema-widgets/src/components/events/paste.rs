@@ -0,0 +1,66 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, PoisonError};
+
+// Number of pasted texts to keep
+const RETAINED: usize = 16;
+
+// The text is shared between the thread reading the terminal events and the runtime
+static PASTES: Mutex<Pastes> = Mutex::new(Pastes {
+    next: 0,
+    texts: VecDeque::new(),
+});
+
+struct Pastes {
+    next: u64,
+    texts: VecDeque<(u64, Arc<str>)>,
+}
+
+/// Text pasted into the terminal.
+///
+/// The text is stored outside of the event, so [`Event`](super::Event) can be `Copy`.
+/// Only the most recent pastes are kept around, so read the text while handling the event.
+///
+/// ```
+/// # use anathema_widgets::components::events::Paste;
+/// let paste = Paste::new("hello");
+/// assert_eq!(paste.text().as_deref(), Some("hello"));
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Paste(u64);
+
+impl Paste {
+    pub fn new(text: impl Into<Arc<str>>) -> Self {
+        let mut pastes = PASTES.lock().unwrap_or_else(PoisonError::into_inner);
+        let id = pastes.next;
+        pastes.next += 1;
+        pastes.texts.push_back((id, text.into()));
+        if pastes.texts.len() > RETAINED {
+            pastes.texts.pop_front();
+        }
+        Self(id)
+    }
+
+    /// The pasted text, or `None` if the text has been replaced by newer pastes
+    pub fn text(&self) -> Option<Arc<str>> {
+        let pastes = PASTES.lock().unwrap_or_else(PoisonError::into_inner);
+        pastes
+            .texts
+            .iter()
+            .find(|(id, _)| *id == self.0)
+            .map(|(_, text)| text.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn only_recent_pastes_are_kept() {
+        let first = Paste::new("first");
+        let pastes = (0..RETAINED).map(|i| Paste::new(i.to_string())).collect::<Vec<_>>();
+
+        assert!(first.text().is_none());
+        assert_eq!(pastes[0].text().as_deref(), Some("0"));
+    }
+}
@@ -0,0 +1,137 @@
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+use std::rc::Rc;
+
+/// A request to stop the runtime, made by
+/// [`Context::quit`](super::Context::quit) or an [`ExitHandle`].
+///
+/// The runtime stops after the current frame.
+pub struct Exit {
+    requested: Rc<Cell<bool>>,
+    value: Rc<RefCell<Option<Box<dyn Any + Send>>>>,
+}
+
+impl Exit {
+    pub fn new() -> Self {
+        Self {
+            requested: Rc::new(Cell::new(false)),
+            value: Rc::new(RefCell::new(None)),
+        }
+    }
+
+    /// Request the runtime to stop without a value.
+    /// This clears any previously stored exit value.
+    pub fn quit(&self) {
+        self.requested.set(true);
+        self.value.replace(None);
+    }
+
+    /// Request the runtime to stop with a value.
+    /// If the runtime is asked to stop more than once, the last request wins.
+    pub fn quit_with<R: Any + Send>(&self, value: R) {
+        self.requested.set(true);
+        self.value.replace(Some(Box::new(value)));
+    }
+
+    /// Returns true if the runtime should stop
+    pub fn is_requested(&self) -> bool {
+        self.requested.get()
+    }
+
+    /// Take the exit value
+    pub fn take(&self) -> Option<Box<dyn Any + Send>> {
+        self.value.take()
+    }
+
+    /// Clear the request and the exit value
+    pub fn clear(&self) {
+        self.requested.set(false);
+        self.value.replace(None);
+    }
+
+    /// Create a handle to stop the runtime with a value of type `R`.
+    pub fn handle<R>(&self) -> ExitHandle<R> {
+        ExitHandle {
+            requested: self.requested.clone(),
+            value: self.value.clone(),
+            _p: PhantomData,
+        }
+    }
+}
+
+impl Default for Exit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Stop the runtime with a value that is returned from `Runtime::run`.
+///
+/// The handle is created by the runtime builder, so the type of the value
+/// is the exit type of the runtime.
+pub struct ExitHandle<R> {
+    requested: Rc<Cell<bool>>,
+    value: Rc<RefCell<Option<Box<dyn Any + Send>>>>,
+    _p: PhantomData<R>,
+}
+
+impl<R: Any + Send> ExitHandle<R> {
+    /// Stop the runtime after the current frame.
+    /// `Runtime::run` returns `Ok(None)`.
+    pub fn quit(&self) {
+        self.requested.set(true);
+        self.value.replace(None);
+    }
+
+    /// Stop the runtime after the current frame and return the value from `Runtime::run`.
+    /// If the runtime is asked to stop more than once, the last request wins.
+    pub fn quit_with(&self, value: R) {
+        self.requested.set(true);
+        self.value.replace(Some(Box::new(value)));
+    }
+}
+
+impl<R> Clone for ExitHandle<R> {
+    fn clone(&self) -> Self {
+        Self {
+            requested: self.requested.clone(),
+            value: self.value.clone(),
+            _p: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn take<R: 'static>(exit: &Exit) -> Option<R> {
+        exit.take().map(|value| *value.downcast::<R>().unwrap())
+    }
+
+    #[test]
+    fn quit_with_value() {
+        let exit = Exit::new();
+        let handle = exit.handle::<&str>();
+        assert!(!exit.is_requested());
+
+        handle.quit_with("first");
+        handle.clone().quit_with("last");
+        assert!(exit.is_requested());
+        assert_eq!(take::<&str>(&exit), Some("last"));
+        assert!(exit.take().is_none());
+
+        exit.clear();
+        assert!(!exit.is_requested());
+        exit.quit_with("value");
+        exit.quit();
+        assert!(exit.is_requested());
+        assert!(exit.take().is_none());
+
+        handle.quit_with("value");
+        exit.clear();
+        assert!(!exit.is_requested());
+        assert!(exit.take().is_none());
+    }
+}
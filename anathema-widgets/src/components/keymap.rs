@@ -0,0 +1,531 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use super::events::{KeyCode, KeyEvent, KeyState, Modifiers};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+// -----------------------------------------------------------------------------
+//   - Error -
+// -----------------------------------------------------------------------------
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseKeyError {
+    Empty,
+    InvalidModifier(String),
+    InvalidKey(String),
+}
+
+impl Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseKeyError::Empty => write!(f, "empty key sequence"),
+            ParseKeyError::InvalidModifier(m) => write!(f, "`{m}` is not a valid modifier"),
+            ParseKeyError::InvalidKey(k) => write!(f, "`{k}` is not a valid key"),
+        }
+    }
+}
+
+impl std::error::Error for ParseKeyError {}
+
+// -----------------------------------------------------------------------------
+//   - Key chord -
+// -----------------------------------------------------------------------------
+/// A single key combined with modifiers, e.g `ctrl+x`.
+///
+/// Modifiers: `ctrl`, `shift`, `alt`, `super`, `hyper` and `meta`.
+///
+/// Named keys: `enter`, `tab`, `backtab`, `backspace`, `esc`, `space`, `plus`,
+/// `left`, `right`, `up`, `down`, `home`, `end`, `pageup`, `pagedown`,
+/// `delete`, `insert` and `f1` to `f12`.
+///
+/// Any other key is a single character.
+/// Since the terminal reports shifted characters in upper case,
+/// `shift+a` is the same as `A`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct KeyChord {
+    code: KeyCode,
+    modifiers: Modifiers,
+}
+
+impl KeyChord {
+    pub fn new(code: impl Into<KeyCode>, modifiers: Modifiers) -> Self {
+        let mut chord = Self {
+            code: code.into(),
+            modifiers,
+        };
+        chord.normalize();
+        chord
+    }
+
+    fn from_event(event: &KeyEvent) -> Self {
+        Self::new(event.code, event.modifiers)
+    }
+
+    // The shift modifier is part of the character (and of the back tab)
+    fn normalize(&mut self) {
+        if !self.modifiers.contains(Modifiers::SHIFT) {
+            return;
+        }
+
+        match self.code {
+            KeyCode::Char(c) => self.code = KeyCode::Char(c.to_ascii_uppercase()),
+            KeyCode::BackTab => (),
+            _ => return,
+        }
+
+        self.modifiers.remove(Modifiers::SHIFT);
+    }
+}
+
+impl FromStr for KeyChord {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (modifiers, key) = match s.rsplit_once('+') {
+            // The key itself is a `+`, e.g `ctrl++`
+            Some((modifiers, "")) => (modifiers.strip_suffix('+').unwrap_or(modifiers), "+"),
+            Some((modifiers, key)) => (modifiers, key),
+            None => ("", s),
+        };
+
+        let mut mods = Modifiers::empty();
+        for modifier in modifiers.split('+').filter(|m| !m.is_empty()) {
+            mods |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => Modifiers::CTRL,
+                "shift" => Modifiers::SHIFT,
+                "alt" | "option" => Modifiers::ALT,
+                "super" | "cmd" => Modifiers::SUPER,
+                "hyper" => Modifiers::HYPER,
+                "meta" => Modifiers::META,
+                _ => return Err(ParseKeyError::InvalidModifier(modifier.into())),
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (None, _) => return Err(ParseKeyError::Empty),
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "esc" | "escape" => KeyCode::Esc,
+                "space" => KeyCode::Char(' '),
+                "plus" => KeyCode::Char('+'),
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                f if f.starts_with('f') => match f[1..].parse::<u8>() {
+                    Ok(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(ParseKeyError::InvalidKey(key.into())),
+                },
+                _ => return Err(ParseKeyError::InvalidKey(key.into())),
+            },
+        };
+
+        Ok(Self::new(code, mods))
+    }
+}
+
+// -----------------------------------------------------------------------------
+//   - Key sequence -
+// -----------------------------------------------------------------------------
+/// One or more key chords separated by whitespace, e.g `g g` or `ctrl+x ctrl+s`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeySequence(Box<[KeyChord]>);
+
+impl KeySequence {
+    pub fn chords(&self) -> &[KeyChord] {
+        &self.0
+    }
+}
+
+impl FromStr for KeySequence {
+    type Err = ParseKeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chords = s
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Box<[KeyChord]>, _>>()?;
+        match chords.is_empty() {
+            true => Err(ParseKeyError::Empty),
+            false => Ok(Self(chords)),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+//   - Keymap -
+// -----------------------------------------------------------------------------
+/// The outcome of feeding a key event to a [`Keymap`].
+#[derive(Debug, PartialEq)]
+pub enum KeymapResult<A> {
+    /// A key sequence was completed
+    Action(A),
+    /// The key is part of a sequence that is not yet complete
+    Pending,
+    /// The key does not belong to any sequence
+    NoMatch,
+}
+
+/// Map key sequences to actions.
+///
+/// If a partial sequence is broken by a key that doesn't continue it, or is
+/// not completed within the timeout (one second by default), the keys of the
+/// partial sequence are abandoned. Abandoned keys should be handled as if
+/// there was no keymap, see [`Keymap::take_abandoned`].
+///
+/// A sequence fires as soon as it's complete, so a sequence that is the
+/// prefix of another sequence (e.g `g` and `g g`) will shadow the longer one.
+///
+/// ```
+/// # use anathema_widgets::components::keymap::{Keymap, KeymapResult};
+/// # use anathema_widgets::components::events::{KeyCode, KeyEvent, KeyState, Modifiers};
+/// #[derive(Debug, Clone, PartialEq)]
+/// enum Action {
+///     Top,
+///     Save,
+/// }
+///
+/// let mut keymap = Keymap::new()
+///     .bind("g g", Action::Top)?
+///     .bind("ctrl+x ctrl+s", Action::Save)?;
+///
+/// let g = KeyEvent {
+///     code: KeyCode::Char('g'),
+///     modifiers: Modifiers::empty(),
+///     state: KeyState::Press,
+/// };
+///
+/// assert_eq!(keymap.feed(g), KeymapResult::Pending);
+/// assert_eq!(keymap.feed(g), KeymapResult::Action(Action::Top));
+/// # Ok::<(), anathema_widgets::components::keymap::ParseKeyError>(())
+/// ```
+pub struct Keymap<A> {
+    bindings: Vec<(KeySequence, Box<dyn Fn() -> A>)>,
+    pending: Vec<KeyEvent>,
+    abandoned: Vec<KeyEvent>,
+    last_key: Option<Instant>,
+    timeout: Duration,
+}
+
+impl<A> Keymap<A> {
+    pub fn new() -> Self {
+        Self {
+            bindings: vec![],
+            pending: vec![],
+            abandoned: vec![],
+            last_key: None,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Set the time allowed between two keys in a sequence
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Bind a key sequence to an action.
+    /// This will replace any existing binding for the same sequence.
+    pub fn bind(mut self, sequence: &str, action: A) -> Result<Self, ParseKeyError>
+    where
+        A: Clone + 'static,
+    {
+        self.insert(sequence, action)?;
+        Ok(self)
+    }
+
+    /// Bind a key sequence to an action.
+    /// This will replace any existing binding for the same sequence.
+    pub fn insert(&mut self, sequence: &str, action: A) -> Result<(), ParseKeyError>
+    where
+        A: Clone + 'static,
+    {
+        let sequence = sequence.parse::<KeySequence>()?;
+        let action: Box<dyn Fn() -> A> = Box::new(move || action.clone());
+        match self.bindings.iter_mut().find(|(seq, _)| *seq == sequence) {
+            Some(binding) => binding.1 = action,
+            None => self.bindings.push((sequence, action)),
+        }
+        Ok(())
+    }
+
+    /// Discard any partial sequence, without abandoning the keys
+    pub fn reset(&mut self) {
+        self.pending.clear();
+        self.last_key = None;
+    }
+
+    /// Returns true if a sequence has been started but not completed
+    pub fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    /// The time at which the partial sequence times out, if there is one
+    pub fn deadline(&self) -> Option<Instant> {
+        match self.is_pending() {
+            true => self.last_key.map(|last_key| last_key + self.timeout),
+            false => None,
+        }
+    }
+
+    /// Abandon the partial sequence if it has timed out
+    pub fn expire(&mut self, now: Instant) {
+        if self.deadline().is_some_and(|deadline| now >= deadline) {
+            self.abandon();
+        }
+    }
+
+    /// Take the keys of partial sequences that were broken or timed out,
+    /// in the order they were fed to the keymap.
+    ///
+    /// These keys were consumed as [`KeymapResult::Pending`] and should be
+    /// handled as if there was no keymap, before the result of the
+    /// [`Keymap::feed`] call that abandoned them.
+    pub fn take_abandoned(&mut self) -> Vec<KeyEvent> {
+        std::mem::take(&mut self.abandoned)
+    }
+
+    /// Feed a key event to the keymap.
+    /// Key release events are ignored.
+    pub fn feed(&mut self, event: KeyEvent) -> KeymapResult<A> {
+        self.feed_at(event, Instant::now())
+    }
+
+    fn feed_at(&mut self, event: KeyEvent, now: Instant) -> KeymapResult<A> {
+        if let KeyState::Release = event.state {
+            return KeymapResult::NoMatch;
+        }
+
+        self.expire(now);
+
+        let had_pending = self.is_pending();
+        self.pending.push(event);
+
+        match self.lookup() {
+            KeymapResult::NoMatch if had_pending => {
+                // The sequence was broken, but the key might start a new one
+                self.pending.pop();
+                self.abandon();
+                self.feed_at(event, now)
+            }
+            KeymapResult::Pending => {
+                self.last_key = Some(now);
+                KeymapResult::Pending
+            }
+            result => {
+                self.reset();
+                result
+            }
+        }
+    }
+
+    fn abandon(&mut self) {
+        self.abandoned.append(&mut self.pending);
+        self.last_key = None;
+    }
+
+    fn lookup(&self) -> KeymapResult<A> {
+        let mut is_pending = false;
+        for (sequence, action) in &self.bindings {
+            let chords = sequence.chords();
+            let Some(prefix) = chords.get(..self.pending.len()) else { continue };
+            let matches = prefix
+                .iter()
+                .zip(&self.pending)
+                .all(|(chord, event)| *chord == KeyChord::from_event(event));
+            if !matches {
+                continue;
+            }
+
+            if chords.len() == self.pending.len() {
+                return KeymapResult::Action(action());
+            }
+
+            is_pending = true;
+        }
+
+        match is_pending {
+            true => KeymapResult::Pending,
+            false => KeymapResult::NoMatch,
+        }
+    }
+}
+
+impl<A> Default for Keymap<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Action {
+        Top,
+        Save,
+        Quit,
+        Submit,
+    }
+
+    fn key(code: impl Into<KeyCode>, modifiers: Modifiers) -> KeyEvent {
+        KeyEvent {
+            code: code.into(),
+            modifiers,
+            state: KeyState::Press,
+        }
+    }
+
+    fn codes(keys: Vec<KeyEvent>) -> Vec<KeyCode> {
+        keys.into_iter().map(|key| key.code).collect()
+    }
+
+    fn keymap() -> Keymap<Action> {
+        Keymap::new()
+            .bind("g g", Action::Top)
+            .and_then(|keymap| keymap.bind("ctrl+x ctrl+s", Action::Save))
+            .and_then(|keymap| keymap.bind("q", Action::Quit))
+            .and_then(|keymap| keymap.bind("alt+enter", Action::Submit))
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_chord() {
+        let chord = "ctrl+shift+a".parse::<KeyChord>().unwrap();
+        assert_eq!(chord, KeyChord::new('A', Modifiers::CTRL));
+
+        let chord = "ctrl++".parse::<KeyChord>().unwrap();
+        assert_eq!(chord, KeyChord::new('+', Modifiers::CTRL));
+
+        let chord = "f5".parse::<KeyChord>().unwrap();
+        assert_eq!(chord, KeyChord::new(KeyCode::F(5), Modifiers::empty()));
+
+        let chord = "alt+space".parse::<KeyChord>().unwrap();
+        assert_eq!(chord, KeyChord::new(' ', Modifiers::ALT));
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert_eq!(
+            "cmmd+a".parse::<KeyChord>().unwrap_err(),
+            ParseKeyError::InvalidModifier("cmmd".into())
+        );
+        assert_eq!(
+            "ctrl+nope".parse::<KeyChord>().unwrap_err(),
+            ParseKeyError::InvalidKey("nope".into())
+        );
+        assert_eq!("  ".parse::<KeySequence>().unwrap_err(), ParseKeyError::Empty);
+        assert_eq!(
+            Keymap::new().bind("g ctrl+nope", Action::Top).err(),
+            Some(ParseKeyError::InvalidKey("nope".into()))
+        );
+    }
+
+    #[test]
+    fn single_key() {
+        let mut keymap = keymap();
+        assert_eq!(
+            keymap.feed(key('q', Modifiers::empty())),
+            KeymapResult::Action(Action::Quit)
+        );
+        assert_eq!(
+            keymap.feed(key(KeyCode::Enter, Modifiers::ALT)),
+            KeymapResult::Action(Action::Submit)
+        );
+        assert_eq!(
+            keymap.feed(key(KeyCode::Enter, Modifiers::empty())),
+            KeymapResult::NoMatch
+        );
+    }
+
+    #[test]
+    fn sequence() {
+        let mut keymap = keymap();
+        assert_eq!(keymap.feed(key('x', Modifiers::CTRL)), KeymapResult::Pending);
+        assert_eq!(
+            keymap.feed(key('s', Modifiers::CTRL)),
+            KeymapResult::Action(Action::Save)
+        );
+        assert!(!keymap.is_pending());
+    }
+
+    #[test]
+    fn broken_sequence_starts_new_sequence() {
+        let mut keymap = keymap();
+        assert_eq!(keymap.feed(key('x', Modifiers::CTRL)), KeymapResult::Pending);
+        assert_eq!(
+            keymap.feed(key('q', Modifiers::empty())),
+            KeymapResult::Action(Action::Quit)
+        );
+        assert_eq!(codes(keymap.take_abandoned()), [KeyCode::Char('x')]);
+
+        assert_eq!(keymap.feed(key('g', Modifiers::empty())), KeymapResult::Pending);
+        assert_eq!(keymap.feed(key('a', Modifiers::empty())), KeymapResult::NoMatch);
+        assert_eq!(codes(keymap.take_abandoned()), [KeyCode::Char('g')]);
+        assert!(!keymap.is_pending());
+    }
+
+    #[test]
+    fn sequence_timeout() {
+        let mut keymap = keymap().with_timeout(Duration::from_millis(100));
+        let now = Instant::now();
+        let g = key('g', Modifiers::empty());
+
+        assert_eq!(keymap.feed_at(g, now), KeymapResult::Pending);
+        let later = now + Duration::from_millis(200);
+        assert_eq!(keymap.feed_at(g, later), KeymapResult::Pending);
+        assert_eq!(codes(keymap.take_abandoned()), [KeyCode::Char('g')]);
+        let later = later + Duration::from_millis(50);
+        assert_eq!(keymap.feed_at(g, later), KeymapResult::Action(Action::Top));
+        assert!(keymap.take_abandoned().is_empty());
+    }
+
+    #[test]
+    fn expire_pending_sequence() {
+        let mut keymap = keymap().with_timeout(Duration::from_millis(100));
+        let now = Instant::now();
+        assert!(keymap.deadline().is_none());
+
+        assert_eq!(keymap.feed_at(key('g', Modifiers::empty()), now), KeymapResult::Pending);
+        let deadline = keymap.deadline().unwrap();
+        assert_eq!(deadline, now + Duration::from_millis(100));
+
+        keymap.expire(deadline - Duration::from_millis(1));
+        assert!(keymap.is_pending());
+
+        keymap.expire(deadline);
+        assert!(!keymap.is_pending());
+        assert!(keymap.deadline().is_none());
+        assert_eq!(codes(keymap.take_abandoned()), [KeyCode::Char('g')]);
+    }
+
+    #[test]
+    fn release_is_ignored() {
+        let mut keymap = keymap();
+        let mut event = key('q', Modifiers::empty());
+        event.state = KeyState::Release;
+        assert_eq!(keymap.feed(event), KeymapResult::NoMatch);
+    }
+
+    #[test]
+    fn shifted_chars() {
+        let mut keymap = Keymap::new().bind("shift+g", Action::Top).unwrap();
+        assert_eq!(
+            keymap.feed(key('G', Modifiers::SHIFT)),
+            KeymapResult::Action(Action::Top)
+        );
+    }
+}
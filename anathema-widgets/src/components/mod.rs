@@ -1,9 +1,10 @@
 use std::any::Any;
 use std::borrow::Cow;
 use std::collections::VecDeque;
+use std::future::Future;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use anathema_state::{AnyState, CommonVal, SharedState, State, StateId, Value};
 use anathema_store::slab::Slab;
@@ -11,14 +12,24 @@ use anathema_store::storage::strings::{StringId, Strings};
 use anathema_templates::WidgetComponentId;
 use flume::SendError;
 
+use self::animation::{Animation, AnimationHandle, Animations, Lerp};
 use self::events::{Event, KeyEvent, MouseEvent};
+use self::exit::Exit;
+use self::keymap::{Keymap, KeymapResult};
+use self::tasks::Tasks;
+use self::timers::{TimerHandle, Timers};
 use crate::expressions::Either;
 use crate::layout::Viewport;
 use crate::nodes::ExternalState;
 use crate::widget::Parent;
-use crate::Elements;
+use crate::{Elements, WidgetId};
 
+pub mod animation;
 pub mod events;
+pub mod exit;
+pub mod keymap;
+pub mod tasks;
+pub mod timers;
 
 pub type ComponentFn = dyn Fn() -> Box<dyn AnyComponent>;
 pub type StateFn = dyn FnMut() -> Box<dyn AnyState>;
@@ -118,13 +129,23 @@ impl<T> Clone for ComponentId<T> {
 
 impl<T> Copy for ComponentId<T> {}
 
+/// The recipient of a [`ViewMessage`]
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Recipient {
+    /// A component registered with the runtime.
+    /// All instances of a prototype share the same id.
+    Component(WidgetComponentId),
+    /// A single component instance in the tree
+    Widget(WidgetId),
+}
+
 pub struct ViewMessage {
     pub(super) payload: Box<dyn Any + Send + Sync>,
-    pub(super) recipient: WidgetComponentId,
+    pub(super) recipient: Recipient,
 }
 
 impl ViewMessage {
-    pub fn recipient(&self) -> WidgetComponentId {
+    pub fn recipient(&self) -> Recipient {
         self.recipient
     }
 
@@ -150,7 +171,7 @@ impl Emitter {
     ) -> Result<(), SendError<ViewMessage>> {
         let msg = ViewMessage {
             payload: Box::new(value),
-            recipient: component_id.0,
+            recipient: Recipient::Component(component_id.0),
         };
         self.0.send(msg)
     }
@@ -162,19 +183,23 @@ impl Emitter {
     ) -> Result<(), SendError<ViewMessage>> {
         let msg = ViewMessage {
             payload: Box::new(value),
-            recipient: component_id.0,
+            recipient: Recipient::Component(component_id.0),
         };
         self.0.send_async(msg).await
     }
 }
 
-pub struct Context<'rt, T> {
+/// The context of a component, passed to the [`Component`] functions.
+///
+/// `C` is the component, so messages to the component itself
+/// (timers and tasks) have to be of the type [`Component::Message`].
+pub struct Context<'rt, C: ?Sized> {
     inner: UntypedContext<'rt>,
-    _p: PhantomData<T>,
+    _p: PhantomData<C>,
     component_ctx: ComponentContext<'rt>,
 }
 
-impl<'rt, T: 'static> Context<'rt, T> {
+impl<'rt, C: Component + 'static> Context<'rt, C> {
     fn new(context: UntypedContext<'rt>, component_ctx: ComponentContext<'rt>) -> Self {
         Self {
             inner: context,
@@ -191,7 +216,7 @@ impl<'rt, T: 'static> Context<'rt, T> {
     /// at the time of the invocation.
     pub fn publish<F, V>(&mut self, ident: &str, mut f: F)
     where
-        F: FnMut(&T) -> &Value<V> + 'static,
+        F: FnMut(&C::State) -> &Value<V> + 'static,
         V: AnyState,
     {
         let Some(internal) = self.inner.strings.lookup(ident) else { return };
@@ -209,7 +234,7 @@ impl<'rt, T: 'static> Context<'rt, T> {
             Box::new(move |state: &dyn AnyState| -> SharedState<'_> {
                 let state = state
                     .to_any_ref()
-                    .downcast_ref::<T>()
+                    .downcast_ref::<C::State>()
                     .expect("the state type is associated with the context");
 
                 let value = f(state);
@@ -240,9 +265,111 @@ impl<'rt, T: 'static> Context<'rt, T> {
     pub fn set_focus(&mut self, key: impl Into<Cow<'static, str>>, value: impl Into<CommonVal<'static>>) {
         self.component_ctx.focus_queue.push(key.into(), value.into());
     }
+
+    /// Stop the runtime after the current frame.
+    /// `Runtime::run` returns `Ok(None)`.
+    pub fn quit(&self) {
+        self.inner.exit.quit();
+    }
+
+    /// Stop the runtime after the current frame and return the value from `Runtime::run`.
+    /// The value has to be of the exit type of the runtime, otherwise `Runtime::run`
+    /// returns an error. Use an [`ExitHandle`](exit::ExitHandle) from the runtime builder
+    /// to have the type checked at compile time.
+    pub fn quit_with<R: Any + Send>(&self, value: R) {
+        self.inner.exit.quit_with(value);
+    }
+
+    /// Send a message to this component once the duration has passed.
+    /// The timer is cancelled if the component is removed from the tree.
+    /// ```ignore
+    /// context.set_timeout(Duration::from_secs(1), Message::Hide);
+    /// ```
+    pub fn set_timeout(&mut self, duration: Duration, message: C::Message) -> TimerHandle
+    where
+        C::Message: 'static + Send + Sync,
+    {
+        self.component_ctx
+            .timers
+            .add_timeout(self.component_ctx.widget_id, duration, Box::new(message))
+    }
+
+    /// Send a message to this component every time the duration has passed,
+    /// until the timer is cancelled or the component is removed from the tree.
+    /// ```ignore
+    /// context.set_interval(Duration::from_millis(500), Message::Blink);
+    /// ```
+    pub fn set_interval(&mut self, duration: Duration, message: C::Message) -> TimerHandle
+    where
+        C::Message: 'static + Clone + Send + Sync,
+    {
+        self.component_ctx.timers.add_interval(
+            self.component_ctx.widget_id,
+            duration,
+            Box::new(move || Box::new(message.clone())),
+        )
+    }
+
+    /// Run a closure on the worker pool and send the return value
+    /// to this component as a message.
+    ///
+    /// The task is cancelled if the component is removed from the tree,
+    /// and the result of a cancelled task is discarded.
+    pub fn spawn<F>(&mut self, f: F)
+    where
+        F: FnOnce() -> C::Message + Send + 'static,
+        C::Message: 'static + Send + Sync,
+    {
+        self.component_ctx
+            .tasks
+            .spawn(self.inner.emitter.clone(), self.component_ctx.widget_id, move |_| {
+                Some(Box::new(f()))
+            });
+    }
+
+    /// Run a future to completion on the worker pool and send the output
+    /// to this component as a message.
+    ///
+    /// Unlike [`Context::spawn`] the future stops being polled as soon as
+    /// the component is removed from the tree.
+    /// The future is not running inside an async runtime such as Tokio,
+    /// so it can not rely on runtime specific IO or timers.
+    pub fn spawn_future<F>(&mut self, future: F)
+    where
+        F: Future<Output = C::Message> + Send + 'static,
+        C::Message: 'static + Send + Sync,
+    {
+        self.component_ctx.tasks.spawn(
+            self.inner.emitter.clone(),
+            self.component_ctx.widget_id,
+            move |cancelled| tasks::block_on(future, cancelled).map(|output| Box::new(output) as _),
+        );
+    }
+
+    /// Tween a value in the component state, starting from the current value.
+    ///
+    /// The animation is advanced once per frame and stops
+    /// if the component is removed from the tree.
+    /// A running animation of the same value of this component is cancelled and replaced by this one.
+    /// ```ignore
+    /// let fade_in = Animation::new(1.0, Duration::from_millis(300)).easing(Easing::EaseOut);
+    /// context.animate(|state| &mut state.opacity, fade_in);
+    /// ```
+    pub fn animate<V, F>(&mut self, select: F, animation: Animation<C::State, V>) -> AnimationHandle
+    where
+        V: Lerp + AnyState + 'static,
+        F: FnMut(&mut C::State) -> &mut Value<V> + 'static,
+    {
+        self.component_ctx.animations.push(
+            self.component_ctx.widget_id,
+            self.component_ctx.state_id,
+            select,
+            animation,
+        )
+    }
 }
 
-impl<'rt, T> Deref for Context<'rt, T> {
+impl<'rt, C: ?Sized> Deref for Context<'rt, C> {
     type Target = UntypedContext<'rt>;
 
     fn deref(&self) -> &Self::Target {
@@ -250,7 +377,7 @@ impl<'rt, T> Deref for Context<'rt, T> {
     }
 }
 
-impl<'rt, T> DerefMut for Context<'rt, T> {
+impl<'rt, C: ?Sized> DerefMut for Context<'rt, C> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.inner
     }
@@ -268,35 +395,67 @@ pub struct UntypedContext<'rt> {
     pub emitter: &'rt Emitter,
     pub viewport: Viewport,
     pub strings: &'rt Strings,
+    pub exit: &'rt Exit,
 }
 
 pub struct ComponentContext<'rt> {
     pub parent: Option<Parent>,
     pub state_id: StateId,
+    pub component_id: WidgetComponentId,
+    pub widget_id: WidgetId,
     pub assoc_functions: &'rt [(StringId, StringId)],
     pub assoc_events: &'rt mut AssociatedEvents,
     focus_queue: &'rt mut FocusQueue<'static>,
+    timers: &'rt mut Timers,
+    animations: &'rt mut Animations,
+    tasks: &'rt mut Tasks,
     external_state: Option<&'rt ExternalState<'rt>>,
 }
 
 impl<'rt> ComponentContext<'rt> {
     pub fn new(
         state_id: StateId,
+        component_id: WidgetComponentId,
+        widget_id: WidgetId,
         parent: Option<WidgetComponentId>,
         assoc_functions: &'rt [(StringId, StringId)],
         assoc_events: &'rt mut AssociatedEvents,
         focus_queue: &'rt mut FocusQueue<'static>,
+        timers: &'rt mut Timers,
+        animations: &'rt mut Animations,
+        tasks: &'rt mut Tasks,
         external_state: Option<&'rt ExternalState<'rt>>,
     ) -> Self {
         Self {
             parent: parent.map(Into::into),
             state_id,
+            component_id,
+            widget_id,
             assoc_functions,
             assoc_events,
             focus_queue,
+            timers,
+            animations,
+            tasks,
             external_state,
         }
     }
+
+    fn reborrow(&mut self) -> ComponentContext<'_> {
+        ComponentContext {
+            parent: self.parent,
+            state_id: self.state_id,
+            component_id: self.component_id,
+            widget_id: self.widget_id,
+            assoc_functions: self.assoc_functions,
+            assoc_events: self.assoc_events,
+            focus_queue: self.focus_queue,
+            timers: self.timers,
+            animations: self.animations,
+            tasks: self.tasks,
+            external_state: self.external_state,
+        }
+    }
 }
 
 pub struct AssociatedEvent {
@@ -362,50 +521,61 @@ pub trait Component {
     type State: State;
     type Message;
 
+    /// Set this to `true` if the component uses [`Component::tick`].
+    ///
+    /// The runtime only sleeps while there is nothing to do if none of the components are ticking.
+    /// A component that is not ticking only runs in response to events, messages and timers.
+    const TICKS: bool = false;
+
     #[allow(unused_variables, unused_mut)]
-    fn on_blur(
-        &mut self,
-        state: &mut Self::State,
-        mut elements: Elements<'_, '_>,
-        mut context: Context<'_, Self::State>,
-    ) {
-    }
+    fn on_blur(&mut self, state: &mut Self::State, mut elements: Elements<'_, '_>, mut context: Context<'_, Self>) {}
 
     #[allow(unused_variables, unused_mut)]
-    fn on_focus(
+    fn on_focus(&mut self, state: &mut Self::State, mut elements: Elements<'_, '_>, mut context: Context<'_, Self>) {}
+
+    #[allow(unused_variables, unused_mut)]
+    fn on_key(
         &mut self,
+        key: KeyEvent,
         state: &mut Self::State,
         mut elements: Elements<'_, '_>,
-        mut context: Context<'_, Self::State>,
+        mut context: Context<'_, Self>,
     ) {
     }
 
+    /// Mouse events are only sent to the topmost component under the cursor,
+    /// not to every component.
+    /// Drag and release events go to the component that received the press.
     #[allow(unused_variables, unused_mut)]
-    fn on_key(
+    fn on_mouse(
         &mut self,
-        key: KeyEvent,
+        mouse: MouseEvent,
         state: &mut Self::State,
         mut elements: Elements<'_, '_>,
-        mut context: Context<'_, Self::State>,
+        mut context: Context<'_, Self>,
     ) {
     }
 
+    /// Text pasted into the terminal.
+    /// This requires bracketed paste to be enabled on the backend.
     #[allow(unused_variables, unused_mut)]
-    fn on_mouse(
+    fn on_paste(
         &mut self,
-        mouse: MouseEvent,
+        text: &str,
         state: &mut Self::State,
         mut elements: Elements<'_, '_>,
-        mut context: Context<'_, Self::State>,
+        mut context: Context<'_, Self>,
     ) {
     }
 
+    /// Called once per frame, with the time passed since the previous frame.
+    /// This is only called if [`Component::TICKS`] is `true`.
     #[allow(unused_variables, unused_mut)]
     fn tick(
         &mut self,
         state: &mut Self::State,
         mut elements: Elements<'_, '_>,
-        context: Context<'_, Self::State>,
+        context: Context<'_, Self>,
         dt: Duration,
     ) {
     }
@@ -416,33 +586,51 @@ pub trait Component {
         message: Self::Message,
         state: &mut Self::State,
         mut elements: Elements<'_, '_>,
-        mut context: Context<'_, Self::State>,
+        mut context: Context<'_, Self>,
     ) {
     }
 
     #[allow(unused_variables, unused_mut)]
-    fn resize(
+    fn resize(&mut self, state: &mut Self::State, mut elements: Elements<'_, '_>, mut context: Context<'_, Self>) {}
+
+    #[allow(unused_variables, unused_mut)]
+    fn receive(
         &mut self,
+        ident: &str,
+        value: CommonVal<'_>,
         state: &mut Self::State,
         mut elements: Elements<'_, '_>,
-        mut context: Context<'_, Self::State>,
+        mut context: Context<'_, Self>,
     ) {
     }
 
+    /// Called when a value in the external state, set by the parent, changes.
+    /// This is also called once for every value when the component is created.
+    /// The new value is read with [`Context::get_external`].
     #[allow(unused_variables, unused_mut)]
-    fn receive(
+    fn on_external_change(
         &mut self,
-        ident: &str,
-        value: CommonVal<'_>,
+        key: &str,
         state: &mut Self::State,
         mut elements: Elements<'_, '_>,
-        mut context: Context<'_, Self::State>,
+        mut context: Context<'_, Self>,
     ) {
     }
 
     fn accept_focus(&self) -> bool {
         true
     }
+
+    /// Key bindings for the component.
+    ///
+    /// Key events are matched against the keymap before they are passed to
+    /// [`Component::on_key`]. A completed key sequence is sent to
+    /// [`Component::message`], and keys that are part of a key sequence
+    /// are not passed on to [`Component::on_key`], unless the sequence is
+    /// broken by another key or times out.
+    fn keymap(&mut self) -> Option<&mut Keymap<Self::Message>> {
+        None
+    }
 }
 
 impl Component for () {
@@ -475,7 +663,32 @@ pub trait AnyComponent {
 
     fn any_receive(&mut self, ctx: AnyEventCtx<'_, '_, '_>, name: &str, value: CommonVal<'_>);
 
+    fn any_external_change(&mut self, ctx: AnyEventCtx<'_, '_, '_>, key: &str);
+
     fn any_accept_focus(&self) -> bool;
+
+    fn any_ticks(&self) -> bool;
+
+    /// The time at which the partial key sequence of the component keymap times out
+    fn any_keymap_deadline(&mut self) -> Option<Instant>;
+
+    /// Send the keys of a timed out key sequence to [`Component::on_key`]
+    fn any_keymap_expire(&mut self, ctx: AnyEventCtx<'_, '_, '_>, now: Instant);
+}
+
+// Send the keys of an abandoned key sequence to `Component::on_key`
+fn on_abandoned_keys<T: Component + 'static>(
+    component: &mut T,
+    state: &mut T::State,
+    elements: &mut Elements<'_, '_>,
+    context: UntypedContext<'_>,
+    component_ctx: &mut ComponentContext<'_>,
+) {
+    let Some(keys) = component.keymap().map(Keymap::take_abandoned) else { return };
+    for key in keys {
+        let context = Context::<T>::new(context, component_ctx.reborrow());
+        component.on_key(key, state, elements.reborrow(), context);
+    }
 }
 
 impl<T> AnyComponent for T
@@ -488,11 +701,30 @@ where
             .state
             .and_then(|s| s.to_any_mut().downcast_mut::<T::State>())
             .expect("components always have a state");
-        let context = Context::<T::State>::new(ctx.context, ctx.component_ctx);
+        let mut elements = ctx.elements;
+        let mut component_ctx = ctx.component_ctx;
+
+        // The keys of an abandoned key sequence are handled before the key that abandoned them
+        let keymap_result = match event {
+            Event::Key(ev) => self.keymap().map(|keymap| keymap.feed(ev)),
+            _ => None,
+        };
+        on_abandoned_keys(self, state, &mut elements, ctx.context, &mut component_ctx);
+
+        let context = Context::<T>::new(ctx.context, component_ctx);
         match event {
             Event::Blur | Event::Focus => (), // Application focus, not component focus.
-            Event::Key(ev) => self.on_key(ev, state, ctx.elements, context),
-            Event::Mouse(ev) => self.on_mouse(ev, state, ctx.elements, context),
+            Event::Key(ev) => match keymap_result {
+                Some(KeymapResult::Action(message)) => self.message(message, state, elements, context),
+                Some(KeymapResult::Pending) => (),
+                Some(KeymapResult::NoMatch) | None => self.on_key(ev, state, elements, context),
+            },
+            Event::Mouse(ev) => self.on_mouse(ev, state, elements, context),
+            Event::Paste(paste) => {
+                if let Some(text) = paste.text() {
+                    self.on_paste(&text, state, elements, context)
+                }
+            }
             Event::Resize(_, _) | Event::Noop | Event::Stop => (),
         }
         event
@@ -502,13 +734,34 @@ where
         self.accept_focus()
     }
 
+    fn any_ticks(&self) -> bool {
+        T::TICKS
+    }
+
+    fn any_keymap_deadline(&mut self) -> Option<Instant> {
+        self.keymap().and_then(|keymap| keymap.deadline())
+    }
+
+    fn any_keymap_expire(&mut self, ctx: AnyEventCtx<'_, '_, '_>, now: Instant) {
+        let Some(keymap) = self.keymap() else { return };
+        keymap.expire(now);
+
+        let state = ctx
+            .state
+            .and_then(|s| s.to_any_mut().downcast_mut::<T::State>())
+            .expect("components always have a state");
+        let mut elements = ctx.elements;
+        let mut component_ctx = ctx.component_ctx;
+        on_abandoned_keys(self, state, &mut elements, ctx.context, &mut component_ctx);
+    }
+
     fn any_message(&mut self, message: Box<dyn Any>, ctx: AnyEventCtx<'_, '_, '_>) {
         let state = ctx
             .state
             .and_then(|s| s.to_any_mut().downcast_mut::<T::State>())
             .expect("components always have a state");
         let Ok(message) = message.downcast::<T::Message>() else { return };
-        let context = Context::<T::State>::new(ctx.context, ctx.component_ctx);
+        let context = Context::<T>::new(ctx.context, ctx.component_ctx);
         self.message(*message, state, ctx.elements, context);
     }
 
@@ -517,7 +770,7 @@ where
             .state
             .and_then(|s| s.to_any_mut().downcast_mut::<T::State>())
             .expect("components always have a state");
-        let context = Context::<T::State>::new(ctx.context, ctx.component_ctx);
+        let context = Context::<T>::new(ctx.context, ctx.component_ctx);
         self.on_focus(state, ctx.elements, context);
     }
 
@@ -526,7 +779,7 @@ where
             .state
             .and_then(|s| s.to_any_mut().downcast_mut::<T::State>())
             .expect("components always have a state");
-        let context = Context::<T::State>::new(ctx.context, ctx.component_ctx);
+        let context = Context::<T>::new(ctx.context, ctx.component_ctx);
         self.on_blur(state, ctx.elements, context);
     }
 
@@ -535,7 +788,7 @@ where
             .state
             .and_then(|s| s.to_any_mut().downcast_mut::<T::State>())
             .expect("components always have a state");
-        let context = Context::<T::State>::new(ctx.context, ctx.component_ctx);
+        let context = Context::<T>::new(ctx.context, ctx.component_ctx);
         self.tick(state, ctx.elements, context, dt);
     }
 
@@ -544,7 +797,7 @@ where
             .state
             .and_then(|s| s.to_any_mut().downcast_mut::<T::State>())
             .expect("components always have a state");
-        let context = Context::<T::State>::new(ctx.context, ctx.component_ctx);
+        let context = Context::<T>::new(ctx.context, ctx.component_ctx);
         self.resize(state, ctx.elements, context);
     }
 
@@ -554,10 +807,19 @@ where
             .and_then(|s| s.to_any_mut().downcast_mut::<T::State>())
             .expect("components always have a state");
 
-        let context = Context::<T::State>::new(ctx.context, ctx.component_ctx);
+        let context = Context::<T>::new(ctx.context, ctx.component_ctx);
 
         self.receive(name, value, state, ctx.elements, context);
     }
+
+    fn any_external_change(&mut self, ctx: AnyEventCtx<'_, '_, '_>, key: &str) {
+        let state = ctx
+            .state
+            .and_then(|s| s.to_any_mut().downcast_mut::<T::State>())
+            .expect("components always have a state");
+        let context = Context::<T>::new(ctx.context, ctx.component_ctx);
+        self.on_external_change(key, state, ctx.elements, context);
+    }
 }
 
 impl std::fmt::Debug for dyn AnyComponent {
@@ -0,0 +1,181 @@
+use std::any::Any;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Poll, Wake, Waker};
+use std::thread::Thread;
+use std::time::Duration;
+
+use super::{Emitter, Recipient, ViewMessage};
+use crate::WidgetId;
+
+type Payload = Box<dyn Any + Send + Sync>;
+type Job = Box<dyn FnOnce() + Send>;
+
+/// How often a parked future checks if it was cancelled
+const CANCEL_CHECK: Duration = Duration::from_millis(100);
+
+/// Work running on the worker pool on behalf of components.
+///
+/// The worker threads are not started until the first task is spawned.
+/// Tasks belonging to a component are cancelled once the component is removed from the tree,
+/// and the result of a cancelled task is never delivered.
+pub struct Tasks {
+    workers: usize,
+    sender: Option<flume::Sender<Job>>,
+    running: Vec<(WidgetId, Arc<AtomicBool>)>,
+}
+
+impl Tasks {
+    pub fn new() -> Self {
+        let workers = std::thread::available_parallelism().map(usize::from).unwrap_or(4);
+        Self {
+            workers,
+            sender: None,
+            running: vec![],
+        }
+    }
+
+    fn sender(&mut self) -> &flume::Sender<Job> {
+        self.sender.get_or_insert_with(|| {
+            let (tx, rx) = flume::unbounded::<Job>();
+            for i in 0..self.workers {
+                let rx = rx.clone();
+                std::thread::Builder::new()
+                    .name(format!("anathema-worker-{i}"))
+                    .spawn(move || {
+                        // A panicking task should not take the worker down with it
+                        while let Ok(job) = rx.recv() {
+                            let _ = std::panic::catch_unwind(AssertUnwindSafe(job));
+                        }
+                    })
+                    .expect("failed to spawn worker thread");
+            }
+            tx
+        })
+    }
+
+    pub(super) fn spawn<F>(&mut self, emitter: Emitter, widget_id: WidgetId, f: F)
+    where
+        F: FnOnce(&AtomicBool) -> Option<Payload> + Send + 'static,
+    {
+        // Forget about the tasks that are done
+        self.running.retain(|(_, cancelled)| Arc::strong_count(cancelled) > 1);
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.running.push((widget_id, cancelled.clone()));
+
+        let job = Box::new(move || {
+            if cancelled.load(Ordering::Relaxed) {
+                return;
+            }
+
+            let Some(payload) = f(&cancelled) else { return };
+
+            if cancelled.load(Ordering::Relaxed) {
+                return;
+            }
+
+            // This can only fail if the runtime is dropped,
+            // in which case there is no one to receive the message.
+            let _ = emitter.0.send(ViewMessage {
+                payload,
+                recipient: Recipient::Widget(widget_id),
+            });
+        });
+
+        let _ = self.sender().send(job);
+    }
+
+    /// Cancel all tasks spawned by a component
+    pub fn cancel(&mut self, widget_id: WidgetId) {
+        self.running.retain(|(id, cancelled)| {
+            if *id != widget_id {
+                return true;
+            }
+            cancelled.store(true, Ordering::Relaxed);
+            false
+        });
+    }
+
+    /// Cancel all tasks
+    pub fn cancel_all(&mut self) {
+        self.running
+            .drain(..)
+            .for_each(|(_, cancelled)| cancelled.store(true, Ordering::Relaxed));
+    }
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Drive a future to completion on the current thread.
+/// Returns `None` if the task is cancelled before the future completes.
+pub(super) fn block_on<F: Future>(future: F, cancelled: &AtomicBool) -> Option<F::Output> {
+    let mut future = std::pin::pin!(future);
+    let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+    let mut cx = std::task::Context::from_waker(&waker);
+
+    loop {
+        if cancelled.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(value) => return Some(value),
+            Poll::Pending => std::thread::park_timeout(CANCEL_CHECK),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn setup() -> (Tasks, Emitter, flume::Receiver<ViewMessage>) {
+        let (tx, rx) = flume::unbounded();
+        (Tasks::new(), tx.into(), rx)
+    }
+
+    #[test]
+    fn deliver_result() {
+        let (mut tasks, emitter, rx) = setup();
+        tasks.spawn(emitter, WidgetId::ZERO, |_| Some(Box::new(1usize)));
+
+        let msg = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(msg.recipient(), Recipient::Widget(WidgetId::ZERO));
+        assert_eq!(*msg.payload().downcast::<usize>().unwrap(), 1);
+    }
+
+    #[test]
+    fn cancelled_task_is_not_delivered() {
+        let (mut tasks, emitter, rx) = setup();
+        let (release_tx, release_rx) = flume::bounded::<()>(1);
+        tasks.spawn(emitter, WidgetId::ZERO, move |_| {
+            release_rx.recv().unwrap();
+            Some(Box::new(1usize))
+        });
+
+        tasks.cancel(WidgetId::ZERO);
+        release_tx.send(()).unwrap();
+        assert!(rx.recv_timeout(Duration::from_millis(100)).is_err());
+    }
+
+    #[test]
+    fn block_on_future() {
+        let cancelled = AtomicBool::new(false);
+        let (tx, rx) = flume::bounded(1);
+        std::thread::spawn(move || tx.send(5).unwrap());
+        let value = block_on(async move { rx.recv_async().await.unwrap() + 1 }, &cancelled);
+        assert_eq!(value, Some(6));
+
+        cancelled.store(true, Ordering::Relaxed);
+        assert!(block_on(async { 1 }, &cancelled).is_none());
+    }
+}
@@ -0,0 +1,213 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use super::{Emitter, Recipient, ViewMessage};
+use crate::WidgetId;
+
+type Payload = Box<dyn Any + Send + Sync>;
+
+enum TimerMessage {
+    Once(Option<Payload>),
+    Repeat(Duration, Box<dyn Fn() -> Payload>),
+}
+
+struct Timer {
+    due: Instant,
+    recipient: WidgetId,
+    message: TimerMessage,
+    cancelled: Rc<Cell<bool>>,
+}
+
+/// A handle to a timer created by
+/// [`Context::set_timeout`](super::Context::set_timeout) or
+/// [`Context::set_interval`](super::Context::set_interval).
+///
+/// Dropping the handle does not cancel the timer.
+#[derive(Debug, Clone)]
+pub struct TimerHandle(Rc<Cell<bool>>);
+
+impl TimerHandle {
+    /// Cancel the timer.
+    /// No more messages will be sent by the timer after this.
+    pub fn cancel(&self) {
+        self.0.set(true);
+    }
+
+    /// Returns true if the timer was cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.get()
+    }
+}
+
+/// Timers waiting to send a message to a component.
+///
+/// Timers belonging to a component are removed once the component is removed from the tree.
+pub struct Timers {
+    inner: Vec<Timer>,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Self { inner: vec![] }
+    }
+
+    pub(super) fn add_timeout(&mut self, recipient: WidgetId, duration: Duration, payload: Payload) -> TimerHandle {
+        self.push(recipient, duration, TimerMessage::Once(Some(payload)))
+    }
+
+    pub(super) fn add_interval(
+        &mut self,
+        recipient: WidgetId,
+        duration: Duration,
+        f: Box<dyn Fn() -> Payload>,
+    ) -> TimerHandle {
+        self.push(recipient, duration, TimerMessage::Repeat(duration, f))
+    }
+
+    fn push(&mut self, recipient: WidgetId, duration: Duration, message: TimerMessage) -> TimerHandle {
+        let cancelled = Rc::new(Cell::new(false));
+        self.inner.push(Timer {
+            due: Instant::now() + duration,
+            recipient,
+            message,
+            cancelled: cancelled.clone(),
+        });
+        TimerHandle(cancelled)
+    }
+
+    /// The earliest point in time a timer is due
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.inner
+            .iter()
+            .filter(|timer| !timer.cancelled.get())
+            .map(|timer| timer.due)
+            .min()
+    }
+
+    /// Send the messages of all the timers that are due.
+    /// Timeouts and cancelled timers are removed, and intervals are rescheduled.
+    ///
+    /// An interval that has fallen behind (e.g. the runtime was busy)
+    /// sends a single message rather than one for every missed interval.
+    pub fn send_due(&mut self, now: Instant, emitter: &Emitter) {
+        self.inner.retain_mut(|timer| {
+            if timer.cancelled.get() {
+                return false;
+            }
+
+            if timer.due > now {
+                return true;
+            }
+
+            let (payload, keep) = match &mut timer.message {
+                TimerMessage::Once(payload) => (payload.take(), false),
+                TimerMessage::Repeat(interval, f) => {
+                    timer.due += *interval;
+                    if timer.due <= now {
+                        timer.due = now + *interval;
+                    }
+                    (Some(f()), true)
+                }
+            };
+
+            if let Some(payload) = payload {
+                let msg = ViewMessage {
+                    payload,
+                    recipient: Recipient::Widget(timer.recipient),
+                };
+                // This can only fail if the runtime is dropped,
+                // in which case there is no one to receive the message.
+                let _ = emitter.0.send(msg);
+            }
+
+            keep
+        });
+    }
+
+    /// Remove all timers set by a component
+    pub fn remove(&mut self, widget_id: WidgetId) {
+        self.inner.retain(|timer| timer.recipient != widget_id);
+    }
+
+    /// Returns true if there are no timers
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn setup() -> (Timers, Emitter, flume::Receiver<ViewMessage>) {
+        let (tx, rx) = flume::unbounded();
+        (Timers::new(), tx.into(), rx)
+    }
+
+    fn recv(rx: &flume::Receiver<ViewMessage>) -> Vec<usize> {
+        rx.try_iter()
+            .map(|msg| *msg.payload().downcast::<usize>().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn timeout() {
+        let (mut timers, emitter, rx) = setup();
+        let now = Instant::now();
+        timers.add_timeout(WidgetId::ZERO, Duration::from_millis(10), Box::new(1usize));
+
+        timers.send_due(now, &emitter);
+        assert!(recv(&rx).is_empty());
+
+        timers.send_due(now + Duration::from_millis(20), &emitter);
+        assert_eq!(recv(&rx), vec![1]);
+        assert!(timers.is_empty());
+    }
+
+    #[test]
+    fn interval() {
+        let (mut timers, emitter, rx) = setup();
+        let now = Instant::now();
+        timers.add_interval(WidgetId::ZERO, Duration::from_millis(10), Box::new(|| Box::new(2usize)));
+
+        let deadline = timers.next_deadline().unwrap();
+        timers.send_due(deadline, &emitter);
+        timers.send_due(deadline + Duration::from_millis(10), &emitter);
+        assert_eq!(recv(&rx), vec![2, 2]);
+
+        // Falling behind only sends one message
+        timers.send_due(now + Duration::from_secs(1), &emitter);
+        assert_eq!(recv(&rx), vec![2]);
+        assert!(timers.next_deadline().unwrap() > now + Duration::from_secs(1));
+    }
+
+    #[test]
+    fn cancel() {
+        let (mut timers, emitter, rx) = setup();
+        let handle = timers.add_interval(WidgetId::ZERO, Duration::ZERO, Box::new(|| Box::new(3usize)));
+        timers.send_due(Instant::now(), &emitter);
+        assert_eq!(recv(&rx), vec![3]);
+
+        handle.cancel();
+        assert!(timers.next_deadline().is_none());
+        timers.send_due(Instant::now(), &emitter);
+        assert!(recv(&rx).is_empty());
+        assert!(timers.is_empty());
+    }
+
+    #[test]
+    fn remove_component_timers() {
+        let (mut timers, emitter, rx) = setup();
+        timers.add_interval(WidgetId::ZERO, Duration::ZERO, Box::new(|| Box::new(1usize)));
+        timers.add_interval(WidgetId::ONE, Duration::ZERO, Box::new(|| Box::new(2usize)));
+
+        timers.remove(WidgetId::ZERO);
+        timers.send_due(Instant::now(), &emitter);
+        let msg = rx.try_recv().unwrap();
+        assert_eq!(msg.recipient(), Recipient::Widget(WidgetId::ONE));
+        assert_eq!(*msg.payload().downcast::<usize>().unwrap(), 2);
+        assert!(rx.try_recv().is_err());
+    }
+}
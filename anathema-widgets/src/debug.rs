@@ -35,8 +35,36 @@ impl DebugWriter for EvalValueDebug<'_> {
             }
             EvalValue::Negative(_) => todo!(),
             EvalValue::Op(_, _, _) => todo!(),
+            EvalValue::Range(from, to) => {
+                EvalValueDebug(from).write(output)?;
+                write!(output, "..")?;
+                EvalValueDebug(to).write(output)
+            }
             EvalValue::Not(_) => todo!(),
             EvalValue::Equality(_, _, _) => todo!(),
+            EvalValue::Conditional(cond, lhs, rhs) => {
+                EvalValueDebug(cond).write(output)?;
+                write!(output, " ? ")?;
+                EvalValueDebug(lhs).write(output)?;
+                write!(output, " : ")?;
+                EvalValueDebug(rhs).write(output)
+            }
+            EvalValue::TextSegments(segments) => {
+                write!(output, "<text>(")?;
+                segments.iter().try_for_each(|val| {
+                    EvalValueDebug(val).write(output)?;
+                    write!(output, ", ")
+                })?;
+                write!(output, ")")
+            }
+            EvalValue::Call(_, args) => {
+                write!(output, "<fn>(")?;
+                args.iter().try_for_each(|val| {
+                    EvalValueDebug(val).write(output)?;
+                    write!(output, ", ")
+                })?;
+                write!(output, ")")
+            }
         }
     }
 }
@@ -80,6 +108,7 @@ impl DebugWriter for ForDebug<'_> {
                 write!(output, " {} ", usize::from(value_ref.owned_key()))
             }
             crate::values::Collection::Static(_) => write!(output, " <value> "),
+            crate::values::Collection::Range(..) => write!(output, " <range> "),
             crate::values::Collection::Future => write!(output, " <future> "),
             crate::values::Collection::Index(_, _) => todo!(),
         }?;
@@ -119,6 +148,11 @@ impl DebugWriter for WidgetDebug<'_, '_> {
                 Some(cond) => write!(output, "<else cond = {} | show = {}>", cond.load_bool(), widget.show),
                 None => write!(output, "<else show = {}>", widget.show),
             },
+            WidgetKind::Switch(_) => write!(output, "<switch>"),
+            WidgetKind::Case(widget) => match &widget.value {
+                Some(_) => write!(output, "<case show = {}>", widget.show),
+                None => write!(output, "<default show = {}>", widget.show),
+            },
             WidgetKind::Component(_) => write!(output, "<component>"),
         }
     }
@@ -1,23 +1,56 @@
 use std::fmt::Display;
 
+use anathema_templates::error::Location;
+
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
 pub enum Error {
-    InvalidElement(String),
-    TreeTransactionFailed,
-    ComponentConsumed,
+    InvalidElement { ident: String, location: Option<Location> },
+    UnknownFunction { ident: String, location: Option<Location> },
+    Function(String),
+    TreeTransactionFailed { location: Option<Location> },
+    ComponentConsumed { location: Option<Location> },
+}
+
+impl Error {
+    pub(crate) fn tree_transaction_failed(location: Option<&Location>) -> Self {
+        Self::TreeTransactionFailed {
+            location: location.cloned(),
+        }
+    }
+}
+
+fn write_location(f: &mut std::fmt::Formatter<'_>, location: &Option<Location>) -> std::fmt::Result {
+    match location {
+        Some(location) => write!(f, "\n{location}"),
+        None => Ok(()),
+    }
 }
 
 impl Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Error::InvalidElement(el) => write!(f, "element `{el}` does not exist"),
-            Error::TreeTransactionFailed => write!(
-                f,
-                "failed to insert into the widget tree (most likely the parent was removed)"
-            ),
-            Error::ComponentConsumed => write!(f, "this component has already been used"),
+            Error::InvalidElement { ident, location } => {
+                write!(f, "error: element `{ident}` does not exist")?;
+                write_location(f, location)
+            }
+            Error::UnknownFunction { ident, location } => {
+                write!(f, "error: function `{ident}` does not exist")?;
+                write_location(f, location)
+            }
+            Error::Function(msg) => write!(f, "error: {msg}"),
+            Error::TreeTransactionFailed { location } => {
+                write!(
+                    f,
+                    "failed to insert into the widget tree (most likely the parent was removed)"
+                )?;
+                write_location(f, location)
+            }
+            Error::ComponentConsumed { location } => {
+                write!(f, "this component has already been used")?;
+                write_location(f, location)
+            }
         }
     }
 }
@@ -6,6 +6,7 @@ use anathema_state::{register_future, CommonVal, Number, Path, PendingValue, Sha
 use anathema_templates::expressions::{Equality, Op};
 use anathema_templates::{Expression, Globals};
 
+use crate::functions::{Function, FunctionValue, Functions};
 use crate::scope::{Scope, ScopeLookup};
 use crate::values::{Collection, ValueId};
 use crate::Value;
@@ -18,6 +19,8 @@ pub(crate) fn future_value<'a>(id: ValueId) -> EvalValue<'a> {
 pub enum Either<'a> {
     Static(CommonVal<'a>),
     Dyn(SharedState<'a>),
+    /// The result of a function call
+    Owned(FunctionValue),
 }
 
 impl<'a> Either<'a> {
@@ -25,6 +28,7 @@ impl<'a> Either<'a> {
         match self {
             Either::Static(val) => val.to_bool(),
             Either::Dyn(state) => state.to_common().map(|v| v.to_bool()).unwrap_or(false),
+            Either::Owned(val) => val.to_common().to_bool(),
         }
     }
 
@@ -32,6 +36,7 @@ impl<'a> Either<'a> {
         match self {
             Either::Static(val) => val.to_number(),
             Either::Dyn(state) => state.to_common().and_then(|v| v.to_number()),
+            Either::Owned(val) => val.to_common().to_number(),
         }
     }
 
@@ -45,6 +50,11 @@ impl<'a> Either<'a> {
                 CommonVal::Str(s) => Some(Path::Key(s)),
                 _ => None,
             },
+            Either::Owned(val) => match val.to_common() {
+                CommonVal::Int(n) => Some(Path::Index(n as usize)),
+                CommonVal::Str(s) => Some(Path::Key(s)),
+                _ => None,
+            },
         }
     }
 
@@ -52,6 +62,7 @@ impl<'a> Either<'a> {
         match self {
             Either::Static(val) => Some(*val),
             Either::Dyn(state) => state.to_common(),
+            Either::Owned(val) => Some(val.to_common()),
         }
     }
 }
@@ -93,10 +104,26 @@ pub enum EvalValue<'bp> {
     Negative(Box<Self>),
     Op(Box<Self>, Box<Self>, Op),
 
+    /// `from..to`.
+    /// This is only used as a collection in a for-loop.
+    Range(Box<Self>, Box<Self>),
+
     // Equality
     Not(Box<Self>),
     Equality(Box<Self>, Box<Self>, Equality),
 
+    /// `cond ? lhs : rhs`.
+    /// Both branches are resolved so a change to either
+    /// one of them updates the value.
+    Conditional(Box<Self>, Box<Self>, Box<Self>),
+
+    /// An interpolated string.
+    /// Every segment is resolved so a change to any one of them updates the value.
+    TextSegments(Box<[Self]>),
+
+    // Function call
+    Call(Function, Box<[Self]>),
+
     Empty,
 }
 
@@ -122,22 +149,46 @@ impl<'bp> EvalValue<'bp> {
                 rhs.copy_with_sub(value_id).into(),
                 *op,
             ),
+            Self::Range(from, to) => {
+                Self::Range(from.copy_with_sub(value_id).into(), to.copy_with_sub(value_id).into())
+            }
             Self::Not(val) => Self::Not(val.copy_with_sub(value_id).into()),
             Self::Equality(lhs, rhs, eq) => Self::Equality(
                 lhs.copy_with_sub(value_id).into(),
                 rhs.copy_with_sub(value_id).into(),
                 *eq,
             ),
+            Self::Conditional(cond, lhs, rhs) => Self::Conditional(
+                cond.copy_with_sub(value_id).into(),
+                lhs.copy_with_sub(value_id).into(),
+                rhs.copy_with_sub(value_id).into(),
+            ),
+            Self::TextSegments(segments) => {
+                Self::TextSegments(segments.iter().map(|val| val.copy_with_sub(value_id)).collect())
+            }
+            Self::Call(fun, args) => Self::Call(
+                fun.clone(),
+                args.iter().map(|arg| arg.copy_with_sub(value_id)).collect(),
+            ),
             Self::Empty => Self::Empty,
         }
     }
 
+    // The selected branch of a conditional
+    fn branch<'a>(cond: &Self, lhs: &'a Self, rhs: &'a Self) -> &'a Self {
+        match cond.load_bool() {
+            true => lhs,
+            false => rhs,
+        }
+    }
+
     fn get(&self, path: Path<'_>, value_id: ValueId) -> Option<EvalValue<'bp>> {
         match self {
             EvalValue::Dyn(value) => Some(EvalValue::Dyn(
                 value.as_state().and_then(|state| state.state_get(path, value_id))?,
             )),
             EvalValue::Index(value, _) => value.get(path, value_id),
+            EvalValue::Conditional(cond, lhs, rhs) => Self::branch(cond, lhs, rhs).get(path, value_id),
             EvalValue::Pending(_) => {
                 unreachable!("pending values are resolved by the scope and should never exist here")
             }
@@ -152,8 +203,11 @@ impl<'bp> EvalValue<'bp> {
             EvalValue::Static(_)
             | EvalValue::Negative(_)
             | EvalValue::Op(_, _, _)
+            | EvalValue::Range(..)
             | EvalValue::Not(_)
             | EvalValue::Equality(_, _, _)
+            | EvalValue::TextSegments(_)
+            | EvalValue::Call(..)
             | EvalValue::Empty => None,
         }
     }
@@ -178,12 +232,20 @@ impl<'bp> EvalValue<'bp> {
             }
             Self::Negative(val) => Self::Negative(val.inner_downgrade().into()),
             Self::Op(lhs, rhs, op) => Self::Op(lhs.inner_downgrade().into(), rhs.inner_downgrade().into(), *op),
+            Self::Range(from, to) => Self::Range(from.inner_downgrade().into(), to.inner_downgrade().into()),
             Self::Not(val) => Self::Not(val.inner_downgrade().into()),
             Self::Equality(lhs, rhs, eq) => {
                 let lhs = lhs.inner_downgrade().into();
                 let rhs = rhs.inner_downgrade().into();
                 Self::Equality(lhs, rhs, *eq)
             }
+            Self::Conditional(cond, lhs, rhs) => Self::Conditional(
+                cond.inner_downgrade().into(),
+                lhs.inner_downgrade().into(),
+                rhs.inner_downgrade().into(),
+            ),
+            Self::TextSegments(segments) => Self::TextSegments(segments.iter().map(Self::inner_downgrade).collect()),
+            Self::Call(fun, args) => Self::Call(fun.clone(), args.iter().map(Self::inner_downgrade).collect()),
             Self::Empty => Self::Empty,
         }
     }
@@ -214,12 +276,27 @@ impl<'bp> EvalValue<'bp> {
                 rhs.inner_upgrade(value_id).into(),
                 *op,
             ),
+            Self::Range(from, to) => {
+                Self::Range(from.inner_upgrade(value_id).into(), to.inner_upgrade(value_id).into())
+            }
             Self::Not(val) => Self::Not(val.inner_upgrade(value_id).into()),
             Self::Equality(lhs, rhs, eq) => {
                 let lhs = lhs.inner_upgrade(value_id).into();
                 let rhs = rhs.inner_upgrade(value_id).into();
                 Self::Equality(lhs, rhs, *eq)
             }
+            Self::Conditional(cond, lhs, rhs) => Self::Conditional(
+                cond.inner_upgrade(value_id).into(),
+                lhs.inner_upgrade(value_id).into(),
+                rhs.inner_upgrade(value_id).into(),
+            ),
+            Self::TextSegments(segments) => {
+                Self::TextSegments(segments.iter().map(|segment| segment.inner_upgrade(value_id)).collect())
+            }
+            Self::Call(fun, args) => Self::Call(
+                fun.clone(),
+                args.iter().map(|arg| arg.inner_upgrade(value_id)).collect(),
+            ),
             Self::Empty => future_value(value_id),
         }
     }
@@ -237,10 +314,9 @@ impl<'bp> EvalValue<'bp> {
             ControlFlow::Continue(())
         };
 
-        match self.internal_str_iter(&mut wrapped_f) {
-            Some(control_flow) => control_flow,
-            None => ControlFlow::Break(()),
-        };
+        // The wrapped function never breaks, and a value that can't be
+        // turned into a string has nothing to write
+        let _ = self.internal_str_iter(&mut wrapped_f);
     }
 
     pub fn str_iter<F>(&self, mut f: F) -> ControlFlow<()>
@@ -258,9 +334,11 @@ impl<'bp> EvalValue<'bp> {
         F: FnMut(&str) -> ControlFlow<()>,
     {
         let val = match self {
-            EvalValue::ExprList(list) => {
+            EvalValue::ExprList(list) | EvalValue::TextSegments(list) => {
                 for value in list.iter() {
-                    value.internal_str_iter(f)?;
+                    if value.internal_str_iter(f)?.is_break() {
+                        return Some(ControlFlow::Break(()));
+                    }
                 }
                 ControlFlow::Continue(())
             }
@@ -277,6 +355,7 @@ impl<'bp> EvalValue<'bp> {
                 f(s)
             }
             EvalValue::Index(val, _) => val.internal_str_iter(f)?,
+            EvalValue::Conditional(cond, lhs, rhs) => Self::branch(cond, lhs, rhs).internal_str_iter(f)?,
             _ => {
                 let val = self.load_common_val()?;
                 let val = val.to_common()?;
@@ -297,6 +376,7 @@ impl<'bp> EvalValue<'bp> {
             EvalValue::Pending(_) => None,
             EvalValue::ExprMap(_) => None,
             EvalValue::ExprList(_) => None,
+            EvalValue::Range(..) => None,
 
             // Operations
             EvalValue::Negative(expr) => expr.load_number().map(|n| -n).map(Into::into),
@@ -336,24 +416,29 @@ impl<'bp> EvalValue<'bp> {
                 };
                 Some(CommonVal::from(b).into())
             }
+
+            EvalValue::Conditional(cond, lhs, rhs) => Self::branch(cond, lhs, rhs).load_common_val(),
+
+            EvalValue::TextSegments(_) => {
+                let mut string = String::new();
+                self.str_for_each(|s| string.push_str(s));
+                Some(Either::Owned(string.into()))
+            }
+
+            // Function call
+            EvalValue::Call(fun, args) => fun.invoke(args).map(Either::Owned),
             EvalValue::Empty => None,
         }
     }
 
-    pub(crate) fn load_bool(&self) -> bool {
+    pub fn load_bool(&self) -> bool {
         let Some(value) = self.load_common_val() else { return false };
-        match value {
-            Either::Static(val) => val.to_bool(),
-            Either::Dyn(state) => (*state).to_common().map(|v| v.to_bool()).unwrap_or(false),
-        }
+        value.load_bool()
     }
 
-    pub(crate) fn load_number(&self) -> Option<Number> {
+    pub fn load_number(&self) -> Option<Number> {
         let val = self.load_common_val()?;
-        match val {
-            Either::Static(val) => val.to_number(),
-            Either::Dyn(state) => (*state).to_common().and_then(|v| v.to_number()),
-        }
+        val.load_number()
     }
 
     // Load a value from an expression.
@@ -400,7 +485,12 @@ impl<'bp> EvalValue<'bp> {
                 let val = CommonVal::Bool(s.load_bool());
                 T::try_from(val).ok()
             }
-            EvalValue::Empty => None,
+            EvalValue::Conditional(cond, lhs, rhs) => Self::branch(cond, lhs, rhs).load::<T>(),
+            val @ (EvalValue::Call(..) | EvalValue::TextSegments(_)) => {
+                let val = val.load_common_val()?;
+                T::try_from(val.to_common()?).ok()
+            }
+            EvalValue::Range(..) | EvalValue::Empty => None,
             e => panic!("{e:?}"),
         }
     }
@@ -411,6 +501,8 @@ impl<'bp> EvalValue<'bp> {
         match self {
             Self::Index(..) => true,
             Self::ExprList(list) => list.iter().any(Self::contains_index),
+            Self::Call(_, args) | Self::TextSegments(args) => args.iter().any(Self::contains_index),
+            Self::Conditional(cond, lhs, rhs) => [cond, lhs, rhs].into_iter().any(|val| val.contains_index()),
             Self::ExprMap(_) => todo!(),
             _ => false,
         }
@@ -446,17 +538,19 @@ impl<'a> TryFrom<&EvalValue<'a>> for &'a str {
     }
 }
 
-struct ValueResolver<'bp> {
+struct ValueResolver<'a, 'bp> {
     globals: &'bp Globals,
+    functions: &'a Functions,
     scope_offset: Option<usize>,
     value_id: ValueId,
 }
 
-impl<'bp> ValueResolver<'bp> {
-    fn new(globals: &'bp Globals, value_id: ValueId) -> Self {
+impl<'a, 'bp> ValueResolver<'a, 'bp> {
+    fn new(globals: &'bp Globals, functions: &'a Functions, value_id: ValueId) -> Self {
         Self {
             scope_offset: None,
             globals,
+            functions,
             value_id,
         }
     }
@@ -465,6 +559,7 @@ impl<'bp> ValueResolver<'bp> {
         Self {
             scope_offset: None,
             globals: self.globals,
+            functions: self.functions,
             value_id: self.value_id,
         }
     }
@@ -564,6 +659,13 @@ impl<'bp> ValueResolver<'bp> {
             // -----------------------------------------------------------------------------
             E::Primitive(val) => V::Static((*val).into()),
             E::Str(s) => V::Static(CommonVal::Str(s)),
+            E::TextSegments(segments) => {
+                let inner = segments
+                    .iter()
+                    .map(|expr| self.reset_offset().resolve(expr, scope, states))
+                    .collect();
+                V::TextSegments(inner)
+            }
             E::Map(map) => {
                 let inner = map
                     .iter()
@@ -593,6 +695,11 @@ impl<'bp> ValueResolver<'bp> {
                 self.reset_offset().resolve(rhs, scope, states).into(),
                 *eq,
             ),
+            E::Conditional(cond, lhs, rhs) => V::Conditional(
+                self.reset_offset().resolve(cond, scope, states).into(),
+                self.reset_offset().resolve(lhs, scope, states).into(),
+                self.reset_offset().resolve(rhs, scope, states).into(),
+            ),
 
             // -----------------------------------------------------------------------------
             //   - Maths -
@@ -604,11 +711,50 @@ impl<'bp> ValueResolver<'bp> {
                 let rhs = self.reset_offset().resolve(rhs, scope, states);
                 V::Op(lhs.into(), rhs.into(), *op)
             }
+            E::Range(from, to) => {
+                let from = self.reset_offset().resolve(from, scope, states);
+                let to = self.reset_offset().resolve(to, scope, states);
+                V::Range(from.into(), to.into())
+            }
 
             // -----------------------------------------------------------------------------
             //   - Function call -
             // -----------------------------------------------------------------------------
-            E::Call { fun: _, args: _ } => todo!(),
+            E::Call { fun, args } => {
+                // A call on an index is treated as a method call, where
+                // the indexed value is the first argument:
+                // `a.upper()` is the same as `upper(a)`
+                let (ident, receiver) = match fun.as_ref() {
+                    E::Ident(ident) => (ident, None),
+                    E::Index(lhs, rhs) => match rhs.as_ref() {
+                        E::Str(ident) => (ident, Some(lhs.as_ref())),
+                        _ => return V::Empty,
+                    },
+                    _ => return V::Empty,
+                };
+
+                let Some(fun) = self.functions.get(ident) else { return V::Empty };
+
+                let args = receiver
+                    .into_iter()
+                    .chain(args.iter())
+                    .map(|arg| self.reset_offset().resolve(arg, scope, states))
+                    .collect();
+
+                V::Call(fun.clone(), args)
+            }
+            // A filter is a function call where the filtered value is the first argument:
+            // `a | truncate(5)` is the same as `truncate(a, 5)`
+            E::Filter { value, name, args } => {
+                let Some(fun) = self.functions.get(name) else { return V::Empty };
+
+                let args = std::iter::once(value.as_ref())
+                    .chain(args.iter())
+                    .map(|arg| self.reset_offset().resolve(arg, scope, states))
+                    .collect();
+
+                V::Call(fun.clone(), args)
+            }
         }
     }
 }
@@ -616,27 +762,30 @@ impl<'bp> ValueResolver<'bp> {
 pub(crate) fn eval<'bp>(
     expr: &'bp Expression,
     globals: &'bp Globals,
+    functions: &Functions,
     scope: &Scope<'bp>,
     states: &States,
     value_id: impl Into<ValueId>,
 ) -> Value<'bp, EvalValue<'bp>> {
     let value_id = value_id.into();
-    let value = ValueResolver::new(globals, value_id).resolve(expr, scope, states);
+    let value = ValueResolver::new(globals, functions, value_id).resolve(expr, scope, states);
     Value::new(value, Some(expr))
 }
 
 pub(crate) fn eval_collection<'bp>(
     expr: &'bp Expression,
     globals: &'bp Globals,
+    functions: &Functions,
     scope: &Scope<'bp>,
     states: &States,
     value_id: ValueId,
 ) -> Value<'bp, Collection<'bp>> {
-    let value = ValueResolver::new(globals, value_id).resolve(expr, scope, states);
+    let value = ValueResolver::new(globals, functions, value_id).resolve(expr, scope, states);
 
     let collection = match value {
         EvalValue::Dyn(val) => Collection::Dyn(val),
         EvalValue::ExprList(list) => Collection::Static(list),
+        EvalValue::Range(from, to) => Collection::Range(*from, *to),
         EvalValue::Index(list, rhs) => match *list {
             EvalValue::Dyn(val) => Collection::Index(Collection::Dyn(val).into(), rhs),
             EvalValue::ExprList(list) => Collection::Index(Collection::Static(list).into(), rhs),
@@ -651,10 +800,10 @@ pub(crate) fn eval_collection<'bp>(
 #[cfg(test)]
 mod test {
 
-    use anathema_state::{List, Map, Value};
+    use anathema_state::{CommonVal, List, Map, Value};
     use anathema_templates::expressions::{
-        add, and, eq, greater_than, greater_than_equal, ident, index, less_than, less_than_equal, mul, neg, not, num,
-        or, strlit, sub,
+        add, and, conditional, eq, greater_than, greater_than_equal, ident, index, less_than, less_than_equal, mul,
+        neg, not, num, or, strlit, sub, text_segments,
     };
 
     use crate::testing::ScopedTest;
@@ -853,4 +1002,46 @@ mod test {
                 assert!(b);
             });
     }
+
+    #[test]
+    fn conditional_expr() {
+        ScopedTest::new()
+            .with_value("a", 2)
+            .with_expr(conditional(
+                greater_than(ident("a"), num(1)),
+                strlit("big"),
+                strlit("small"),
+            ))
+            .eval(|value| {
+                let mut output = String::new();
+                value.str_for_each(|s| output.push_str(s));
+                assert_eq!(output, "big");
+            });
+    }
+
+    #[test]
+    fn conditional_number() {
+        ScopedTest::new()
+            .with_value("a", false)
+            .with_expr(conditional(ident("a"), num(1), add(num(1), num(2))))
+            .eval(|value| {
+                let val = value.load::<u32>().unwrap();
+                assert_eq!(val, 3);
+            });
+    }
+
+    #[test]
+    fn text_segments_expr() {
+        ScopedTest::new()
+            .with_value("a", 2)
+            .with_expr(text_segments([strlit("a is "), ident("a"), strlit("!")]))
+            .eval(|value| {
+                let mut output = String::new();
+                value.str_for_each(|s| output.push_str(s));
+                assert_eq!(output, "a is 2!");
+
+                let val = value.load_common_val().unwrap();
+                assert_eq!(val.to_common().unwrap(), CommonVal::Str("a is 2!"));
+            });
+    }
 }
@@ -0,0 +1,832 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Write};
+use std::rc::Rc;
+
+use anathema_state::{Color, CommonVal, Hex, Number, Path};
+use anathema_templates::blueprints::Blueprint;
+use anathema_templates::error::Location;
+use anathema_templates::Expression;
+
+use crate::error::{Error, Result};
+use crate::expressions::EvalValue;
+
+/// The result of calling a function that can fail, see [`Functions::register_fallible`].
+/// The error is a message describing what went wrong, e.g. an invalid argument.
+pub type FunctionResult = std::result::Result<Option<FunctionValue>, String>;
+
+type FunctionFn = dyn Fn(&[EvalValue<'_>]) -> FunctionResult;
+
+// Errors returned by the functions of a function table
+type Errors = Rc<RefCell<Vec<String>>>;
+
+/// The value produced by calling a [`Function`].
+///
+/// Unlike [`CommonVal`] this owns any string data,
+/// as the value is created during evaluation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FunctionValue {
+    Common(CommonVal<'static>),
+    String(String),
+}
+
+impl FunctionValue {
+    pub fn to_common(&self) -> CommonVal<'_> {
+        match self {
+            Self::Common(val) => *val,
+            Self::String(s) => CommonVal::Str(s),
+        }
+    }
+}
+
+impl From<String> for FunctionValue {
+    fn from(value: String) -> Self {
+        Self::String(value)
+    }
+}
+
+impl From<&str> for FunctionValue {
+    fn from(value: &str) -> Self {
+        Self::String(value.into())
+    }
+}
+
+impl From<CommonVal<'_>> for FunctionValue {
+    fn from(value: CommonVal<'_>) -> Self {
+        match value {
+            CommonVal::Bool(b) => Self::Common(CommonVal::Bool(b)),
+            CommonVal::Char(c) => Self::Common(CommonVal::Char(c)),
+            CommonVal::Int(i) => Self::Common(CommonVal::Int(i)),
+            CommonVal::Float(f) => Self::Common(CommonVal::Float(f)),
+            CommonVal::Hex(hex) => Self::Common(CommonVal::Hex(hex)),
+            CommonVal::Color(color) => Self::Common(CommonVal::Color(color)),
+            CommonVal::Str(s) => Self::String(s.into()),
+        }
+    }
+}
+
+macro_rules! impl_from_common {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for FunctionValue {
+                fn from(value: $ty) -> Self {
+                    Self::Common(value.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_common!(bool, char, i64, i32, usize, f64, f32, Number, Hex, Color);
+
+/// A function that can be called from a template.
+///
+/// Functions can also be used as filters, in which case the filtered value
+/// is passed as the first argument.
+///
+/// ```text
+/// text upper(name) " has " len(items) " items"
+/// text name | truncate(10) " " size | bytes
+/// ```
+#[derive(Clone)]
+pub struct Function {
+    f: Rc<FunctionFn>,
+    errors: Errors,
+}
+
+impl Function {
+    // Functions are called while the widgets are laid out and painted,
+    // so an error is stored in the function table until the runtime takes it.
+    pub(crate) fn invoke(&self, args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+        match (self.f)(args) {
+            Ok(value) => value,
+            Err(err) => {
+                self.errors.borrow_mut().push(err);
+                None
+            }
+        }
+    }
+}
+
+impl Debug for Function {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<fn>")
+    }
+}
+
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.f, &other.f)
+    }
+}
+
+/// Functions available to templates.
+///
+/// Calling a function that isn't registered is an error, see [`Functions::check`].
+pub struct Functions {
+    functions: HashMap<Box<str>, Function>,
+    errors: Errors,
+}
+
+impl Functions {
+    /// Create an empty function table, without the built-in functions
+    pub fn empty() -> Self {
+        Self {
+            functions: HashMap::new(),
+            errors: Errors::default(),
+        }
+    }
+
+    /// Create a function table containing the built-in functions:
+    /// `len`, `upper`, `lower`, `join`, `min`, `max`, `round` and `format`,
+    /// as well as the functions intended to be used as filters:
+    /// `capitalize`, `truncate`, `pad_left`, `pad_right`, `precision`,
+    /// `thousands`, `duration` and `bytes`.
+    pub fn new() -> Self {
+        let mut functions = Self::empty();
+        functions.register("len", len);
+        functions.register("upper", upper);
+        functions.register("lower", lower);
+        functions.register("join", join);
+        functions.register("min", min);
+        functions.register("max", max);
+        functions.register_fallible("round", round);
+        functions.register("format", format);
+        functions.register("capitalize", capitalize);
+        functions.register("truncate", truncate);
+        functions.register("pad_left", pad_left);
+        functions.register("pad_right", pad_right);
+        functions.register("precision", precision);
+        functions.register("thousands", thousands);
+        functions.register("duration", duration);
+        functions.register("bytes", bytes);
+        functions
+    }
+
+    /// Register a function.
+    /// This will replace any existing function with the same name.
+    pub fn register(&mut self, ident: &str, f: impl Fn(&[EvalValue<'_>]) -> Option<FunctionValue> + 'static) {
+        self.register_fallible(ident, move |args| Ok(f(args)));
+    }
+
+    /// Register a function that can fail.
+    /// This will replace any existing function with the same name.
+    ///
+    /// The error is returned by the runtime once the current frame is done.
+    pub fn register_fallible(&mut self, ident: &str, f: impl Fn(&[EvalValue<'_>]) -> FunctionResult + 'static) {
+        let function = Function {
+            f: Rc::new(f),
+            errors: self.errors.clone(),
+        };
+        self.functions.insert(ident.into(), function);
+    }
+
+    pub fn get(&self, ident: &str) -> Option<&Function> {
+        self.functions.get(ident)
+    }
+
+    /// Take the first error returned by a function since the last call, if any, and clear the rest
+    pub fn take_error(&self) -> Option<Error> {
+        let error = self.errors.borrow_mut().drain(..).next()?;
+        Some(Error::Function(error))
+    }
+
+    /// Check that every function called by the blueprint is registered.
+    pub fn check(&self, blueprint: &Blueprint) -> Result<()> {
+        match blueprint {
+            Blueprint::Single(single) => {
+                let exprs = single.value.iter().chain(single.attributes.iter().map(|(_, e)| e));
+                self.check_exprs(exprs, single.location.as_ref())?;
+                self.check_all(&single.children)
+            }
+            Blueprint::For(for_loop) => {
+                self.check_exprs([&for_loop.data], None)?;
+                self.check_all(&for_loop.body)?;
+                self.check_all(&for_loop.else_body)
+            }
+            Blueprint::ControlFlow(flow) => {
+                self.check_exprs([&flow.if_node.cond], None)?;
+                self.check_all(&flow.if_node.body)?;
+                flow.elses.iter().try_for_each(|e| {
+                    self.check_exprs(e.cond.as_ref(), None)?;
+                    self.check_all(&e.body)
+                })
+            }
+            Blueprint::Switch(switch) => {
+                self.check_exprs([&switch.value], None)?;
+                switch.cases.iter().try_for_each(|case| {
+                    self.check_exprs(case.value.as_ref(), None)?;
+                    self.check_all(&case.body)
+                })
+            }
+            Blueprint::Component(component) => {
+                let state = component.state.iter().flat_map(|state| state.values());
+                self.check_exprs(component.attributes.iter().map(|(_, e)| e).chain(state), None)?;
+                self.check_all(&component.body)
+            }
+        }
+    }
+
+    fn check_all(&self, blueprints: &[Blueprint]) -> Result<()> {
+        blueprints.iter().try_for_each(|blueprint| self.check(blueprint))
+    }
+
+    fn check_exprs<'a>(
+        &self,
+        exprs: impl IntoIterator<Item = &'a Expression>,
+        location: Option<&Location>,
+    ) -> Result<()> {
+        exprs
+            .into_iter()
+            .try_for_each(|expr| self.check_expr(expr))
+            .map_err(|ident| Error::UnknownFunction {
+                ident,
+                location: location.cloned(),
+            })
+    }
+
+    // Returns the name of the first function that isn't registered
+    fn check_expr(&self, expr: &Expression) -> std::result::Result<(), String> {
+        match expr {
+            Expression::Primitive(_) | Expression::Str(_) | Expression::Ident(_) => Ok(()),
+            Expression::List(list) | Expression::TextSegments(list) => list.iter().try_for_each(|e| self.check_expr(e)),
+            Expression::Map(map) => map.values().try_for_each(|e| self.check_expr(e)),
+            Expression::Not(expr) | Expression::Negative(expr) => self.check_expr(expr),
+            Expression::Equality(lhs, rhs, _)
+            | Expression::Index(lhs, rhs)
+            | Expression::Op(lhs, rhs, _)
+            | Expression::Range(lhs, rhs) => {
+                self.check_expr(lhs)?;
+                self.check_expr(rhs)
+            }
+            Expression::Conditional(cond, lhs, rhs) => {
+                self.check_expr(cond)?;
+                self.check_expr(lhs)?;
+                self.check_expr(rhs)
+            }
+            Expression::Call { fun, args } => {
+                // `a.upper()` is a method call where `a` is the first argument
+                let receiver = match fun.as_ref() {
+                    Expression::Ident(ident) if self.get(ident).is_some() => None,
+                    Expression::Index(lhs, rhs) => match rhs.as_ref() {
+                        Expression::Str(ident) if self.get(ident).is_some() => Some(lhs),
+                        Expression::Str(ident) => return Err(ident.to_string()),
+                        _ => return Err(fun.to_string()),
+                    },
+                    fun => return Err(fun.to_string()),
+                };
+                receiver.into_iter().try_for_each(|e| self.check_expr(e))?;
+                args.iter().try_for_each(|e| self.check_expr(e))
+            }
+            Expression::Filter { value, name, args } => {
+                if self.get(name).is_none() {
+                    return Err(name.to_string());
+                }
+                self.check_expr(value)?;
+                args.iter().try_for_each(|e| self.check_expr(e))
+            }
+        }
+    }
+}
+
+impl Default for Functions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// -----------------------------------------------------------------------------
+//   - Helpers -
+// -----------------------------------------------------------------------------
+fn to_string(value: &EvalValue<'_>) -> String {
+    let mut s = String::new();
+    value.str_for_each(|val| s.push_str(val));
+    s
+}
+
+// Produce the items of a collection, if the value is one.
+fn items(value: &EvalValue<'_>) -> Option<Vec<FunctionValue>> {
+    match value {
+        EvalValue::ExprList(list) => Some(
+            list.iter()
+                .filter_map(|val| Some(val.load_common_val()?.to_common()?.into()))
+                .collect(),
+        ),
+        EvalValue::Dyn(value_ref) => {
+            let state = value_ref.as_state()?;
+            if state.to_common().is_some() {
+                return None;
+            }
+            let items = (0..state.count())
+                .filter_map(|i| state.state_lookup(Path::Index(i)))
+                .filter_map(|pending| pending.as_state(|state| Some(state.to_common()?.into())))
+                .collect();
+            Some(items)
+        }
+        EvalValue::Index(value, _) => items(value),
+        _ => None,
+    }
+}
+
+// Numbers from either a single collection or all the arguments
+fn numbers(args: &[EvalValue<'_>]) -> Vec<Number> {
+    if let [arg] = args {
+        if let Some(items) = items(arg) {
+            return items.iter().filter_map(|val| val.to_common().to_number()).collect();
+        }
+    }
+
+    args.iter().filter_map(EvalValue::load_number).collect()
+}
+
+// -----------------------------------------------------------------------------
+//   - Built-in functions -
+// -----------------------------------------------------------------------------
+fn len(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    let [arg] = args else { return None };
+    if let Some(items) = items(arg) {
+        return Some(items.len().into());
+    }
+    Some(to_string(arg).chars().count().into())
+}
+
+fn upper(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    let [arg] = args else { return None };
+    Some(to_string(arg).to_uppercase().into())
+}
+
+fn lower(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    let [arg] = args else { return None };
+    Some(to_string(arg).to_lowercase().into())
+}
+
+fn join(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    let (list, sep) = match args {
+        [list] => (list, String::new()),
+        [list, sep] => (list, to_string(sep)),
+        _ => return None,
+    };
+
+    let items = items(list)?;
+    let mut output = String::new();
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            output.push_str(&sep);
+        }
+        let _ = write!(output, "{}", item.to_common());
+    }
+    Some(output.into())
+}
+
+fn min(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    let min = numbers(args)
+        .into_iter()
+        .reduce(|a, b| match b.as_float() < a.as_float() {
+            true => b,
+            false => a,
+        })?;
+    Some(min.into())
+}
+
+fn max(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    let max = numbers(args)
+        .into_iter()
+        .reduce(|a, b| match b.as_float() > a.as_float() {
+            true => b,
+            false => a,
+        })?;
+    Some(max.into())
+}
+
+// The number of decimals a float can hold
+const MAX_PRECISION: i64 = f64::DIGITS as i64;
+
+fn round(args: &[EvalValue<'_>]) -> FunctionResult {
+    let args = match args {
+        [value] => value.load_number().map(|value| (value, 0)),
+        [value, precision] => value.load_number().zip(precision.load_number().map(|p| p.as_int())),
+        _ => None,
+    };
+    let Some((value, precision)) = args else { return Ok(None) };
+
+    if !value.is_float() {
+        return Ok(Some(value.into()));
+    }
+
+    // Check the range before the precision is used as an exponent
+    if !(0..=MAX_PRECISION).contains(&precision) {
+        return Err(format!(
+            "round: the precision has to be between 0 and {MAX_PRECISION}, got {precision}"
+        ));
+    }
+
+    let value = match precision {
+        0 => (value.as_float().round() as i64).into(),
+        precision => {
+            let factor = 10f64.powi(precision as i32);
+            ((value.as_float() * factor).round() / factor).into()
+        }
+    };
+    Ok(Some(value))
+}
+
+// Replace every `{}` in the first argument with the remaining arguments.
+// `{{` and `}}` produces literal braces.
+fn format(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    let (fmt, args) = args.split_first()?;
+    let fmt = to_string(fmt);
+    let mut args = args.iter();
+    let mut output = String::new();
+    let mut chars = fmt.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('{', Some('{')) | ('}', Some('}')) => {
+                chars.next();
+                output.push(c);
+            }
+            ('{', Some('}')) => {
+                chars.next();
+                if let Some(arg) = args.next() {
+                    arg.str_for_each(|s| output.push_str(s));
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    Some(output.into())
+}
+
+fn capitalize(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    let [arg] = args else { return None };
+    let string = to_string(arg);
+    let mut chars = string.chars();
+    let output = match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    };
+    Some(output.into())
+}
+
+// Truncate a string to at most `len` chars.
+// If the string is truncated it ends with the suffix, which defaults to `…`.
+fn truncate(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    let (value, len, suffix) = match args {
+        [value, len] => (value, len, String::from("…")),
+        [value, len, suffix] => (value, len, to_string(suffix)),
+        _ => return None,
+    };
+
+    let len = len.load_number()?.as_int().max(0) as usize;
+    let string = to_string(value);
+    if string.chars().count() <= len {
+        return Some(string.into());
+    }
+
+    let suffix_len = suffix.chars().count().min(len);
+    let mut output = string.chars().take(len - suffix_len).collect::<String>();
+    output.extend(suffix.chars().take(suffix_len));
+    Some(output.into())
+}
+
+// Pad a string to `width` chars, using the optional fill char or space
+fn pad(args: &[EvalValue<'_>], left: bool) -> Option<FunctionValue> {
+    let (value, width, fill) = match args {
+        [value, width] => (value, width, ' '),
+        [value, width, fill] => (value, width, to_string(fill).chars().next()?),
+        _ => return None,
+    };
+
+    let width = width.load_number()?.as_int().max(0) as usize;
+    let string = to_string(value);
+    let padding = fill.to_string().repeat(width.saturating_sub(string.chars().count()));
+    match left {
+        true => Some(format!("{padding}{string}").into()),
+        false => Some(format!("{string}{padding}").into()),
+    }
+}
+
+fn pad_left(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    pad(args, true)
+}
+
+fn pad_right(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    pad(args, false)
+}
+
+// Format a number with a fixed number of decimals
+fn precision(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    let [value, precision] = args else { return None };
+    let value = value.load_number()?.as_float();
+    let precision = precision.load_number()?.as_int().max(0) as usize;
+    Some(format!("{value:.precision$}").into())
+}
+
+// Separate every three digits of the integer part of a number.
+// The separator defaults to `,`.
+fn thousands(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    let (value, sep) = match args {
+        [value] => (value, String::from(",")),
+        [value, sep] => (value, to_string(sep)),
+        _ => return None,
+    };
+
+    let value = value.load_number()?;
+    let number = match value.is_float() {
+        true => value.as_float().to_string(),
+        false => value.as_int().to_string(),
+    };
+
+    let (sign, number) = match number.strip_prefix('-') {
+        Some(number) => ("-", number),
+        None => ("", &*number),
+    };
+
+    let (int, fract) = match number.split_once('.') {
+        Some((int, fract)) => (int, Some(fract)),
+        None => (number, None),
+    };
+
+    let mut output = String::from(sign);
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 {
+            output.push_str(&sep);
+        }
+        output.push(c);
+    }
+
+    if let Some(fract) = fract {
+        output.push('.');
+        output.push_str(fract);
+    }
+
+    Some(output.into())
+}
+
+// A human readable duration from a number of seconds, e.g `1h 2m 5s`.
+// Durations less than a second are shown in milliseconds.
+fn duration(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    let [value] = args else { return None };
+    let seconds = value.load_number()?.as_float();
+    if seconds < 0.0 {
+        return None;
+    }
+
+    if seconds > 0.0 && seconds < 1.0 {
+        return Some(format!("{}ms", (seconds * 1000.0).round() as u64).into());
+    }
+
+    let mut seconds = seconds as u64;
+    let mut output = String::new();
+    for (unit, size) in [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)] {
+        let count = seconds / size;
+        seconds %= size;
+        if count == 0 {
+            continue;
+        }
+        if !output.is_empty() {
+            output.push(' ');
+        }
+        let _ = write!(output, "{count}{unit}");
+    }
+
+    if output.is_empty() {
+        output.push_str("0s");
+    }
+
+    Some(output.into())
+}
+
+// A human readable size from a number of bytes, e.g `1.5 KiB`
+fn bytes(args: &[EvalValue<'_>]) -> Option<FunctionValue> {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    let [value] = args else { return None };
+    let mut size = value.load_number()?.as_float();
+    let mut unit = 0;
+    while size.abs() >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => Some(format!("{size} B").into()),
+        _ => Some(format!("{size:.1} {}", UNITS[unit]).into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anathema_state::List;
+    use anathema_templates::expressions::{filter, float, ident, list, neg, num, strlit};
+    use anathema_templates::{Document, Expression};
+
+    use super::*;
+    use crate::testing::ScopedTest;
+
+    fn call(fun: &str, args: impl IntoIterator<Item = Box<Expression>>) -> Expression {
+        Expression::Call {
+            fun: ident(fun),
+            args: args.into_iter().map(|arg| *arg).collect(),
+        }
+    }
+
+    fn eval_to_string(test: &mut ScopedTest<impl anathema_state::State, crate::testing::WithExpr>) -> String {
+        let mut output = String::new();
+        test.eval(|value| value.str_for_each(|s| output.push_str(s)));
+        output
+    }
+
+    #[test]
+    fn len_of_state_list() {
+        let list = List::from_iter([1, 2, 3]);
+        ScopedTest::new()
+            .with_value("a", list)
+            .with_expr(call("len", [ident("a")]))
+            .eval(|value| {
+                let len = value.load::<usize>().unwrap();
+                assert_eq!(len, 3);
+            });
+    }
+
+    #[test]
+    fn len_of_string() {
+        ScopedTest::new()
+            .with_value("a", "hello")
+            .with_expr(call("len", [ident("a")]))
+            .eval(|value| {
+                let len = value.load::<usize>().unwrap();
+                assert_eq!(len, 5);
+            });
+    }
+
+    #[test]
+    fn upper_and_lower() {
+        let mut test = ScopedTest::new()
+            .with_value("a", "Hello")
+            .with_expr(call("upper", [ident("a")]));
+        assert_eq!(eval_to_string(&mut test), "HELLO");
+
+        let mut test = ScopedTest::<u32, _>::new().with_expr(call("lower", [strlit("Hello")]));
+        assert_eq!(eval_to_string(&mut test), "hello");
+    }
+
+    #[test]
+    fn join_list() {
+        let mut test = ScopedTest::<u32, _>::new().with_expr(call("join", [list([1, 2, 3]), strlit(", ")]));
+        assert_eq!(eval_to_string(&mut test), "1, 2, 3");
+    }
+
+    #[test]
+    fn min_max() {
+        ScopedTest::new()
+            .with_value("a", 7)
+            .with_expr(call("min", [ident("a"), num(3), num(5)]))
+            .eval(|value| assert_eq!(value.load::<i64>().unwrap(), 3));
+
+        ScopedTest::<u32, _>::new()
+            .with_expr(call("max", [list([1, 9, 3])]))
+            .eval(|value| assert_eq!(value.load::<i64>().unwrap(), 9));
+    }
+
+    #[test]
+    fn round_float() {
+        ScopedTest::new()
+            .with_value("a", 1.5f64)
+            .with_expr(call("round", [ident("a")]))
+            .eval(|value| assert_eq!(value.load::<i64>().unwrap(), 2));
+
+        ScopedTest::new()
+            .with_value("a", 1.256f64)
+            .with_expr(call("round", [ident("a"), num(2)]))
+            .eval(|value| assert_eq!(value.load::<f64>().unwrap(), 1.26));
+    }
+
+    #[test]
+    fn format_args() {
+        let mut test = ScopedTest::new()
+            .with_value("a", 1)
+            .with_expr(call("format", [strlit("{} of {} {{}}"), ident("a"), num(2)]));
+        assert_eq!(eval_to_string(&mut test), "1 of 2 {}");
+    }
+
+    #[test]
+    fn method_call_syntax() {
+        let fun = Expression::Index(ident("a"), strlit("upper"));
+        let expr = Expression::Call {
+            fun: fun.into(),
+            args: Box::new([]),
+        };
+        let mut test = ScopedTest::new().with_value("a", "hi").with_expr(expr);
+        assert_eq!(eval_to_string(&mut test), "HI");
+    }
+
+    #[test]
+    fn unknown_function() {
+        let functions = Functions::new();
+        let check = |template: &str| {
+            let (blueprint, _) = Document::new(template).compile().unwrap();
+            functions.check(&blueprint)
+        };
+
+        assert!(check("text upper('a') a.lower()").is_ok());
+
+        let err = check("vstack\n    text 'a' nope(1)").unwrap_err();
+        assert!(matches!(err, Error::UnknownFunction { ref ident, location: Some(_) } if ident == "nope"));
+
+        let err = check("if a.nope()\n    text").unwrap_err();
+        assert!(matches!(err, Error::UnknownFunction { ref ident, .. } if ident == "nope"));
+
+        let err = check("text a | upper | nope(1)").unwrap_err();
+        assert!(matches!(err, Error::UnknownFunction { ref ident, .. } if ident == "nope"));
+    }
+
+    #[test]
+    fn round_precision_out_of_range() {
+        ScopedTest::new()
+            .with_value("a", 1.5f64)
+            .with_expr(call("round", [ident("a"), neg(num(1))]))
+            .eval(|value| assert!(value.load_common_val().is_none()));
+
+        let functions = Functions::new();
+        let round = functions.get("round").unwrap();
+        let args = |precision: i64| [EvalValue::Static(1.5.into()), EvalValue::Static(precision.into())];
+        assert!(round.invoke(&args(-1)).is_none());
+        assert!(round.invoke(&args(1 << 32)).is_none());
+
+        let err = functions.take_error().unwrap();
+        assert_eq!(
+            err.to_string(),
+            "error: round: the precision has to be between 0 and 15, got -1"
+        );
+        assert!(functions.take_error().is_none());
+    }
+
+    fn filtered(expr: Box<Expression>) -> String {
+        let mut test = ScopedTest::<u32, _>::new().with_expr(expr);
+        eval_to_string(&mut test)
+    }
+
+    #[test]
+    fn filter_as_first_argument() {
+        let mut test = ScopedTest::new().with_value("a", "hello").with_expr(filter(
+            filter(ident("a"), "upper", []),
+            "pad_left",
+            [num(7), strlit("-")],
+        ));
+        assert_eq!(eval_to_string(&mut test), "--HELLO");
+    }
+
+    #[test]
+    fn case_filters() {
+        assert_eq!(filtered(filter(strlit("hello world"), "capitalize", [])), "Hello world");
+        assert_eq!(filtered(filter(strlit(""), "capitalize", [])), "");
+    }
+
+    #[test]
+    fn truncate_filter() {
+        assert_eq!(filtered(filter(strlit("hello world"), "truncate", [num(5)])), "hell…");
+        assert_eq!(
+            filtered(filter(strlit("hello world"), "truncate", [num(8), strlit("...")])),
+            "hello..."
+        );
+        assert_eq!(filtered(filter(strlit("hello"), "truncate", [num(5)])), "hello");
+    }
+
+    #[test]
+    fn pad_filters() {
+        assert_eq!(filtered(filter(num(42), "pad_left", [num(5)])), "   42");
+        assert_eq!(filtered(filter(num(42), "pad_left", [num(5), strlit("0")])), "00042");
+        assert_eq!(
+            filtered(filter(strlit("ab"), "pad_right", [num(4), strlit(".")])),
+            "ab.."
+        );
+        assert_eq!(filtered(filter(strlit("abcdef"), "pad_right", [num(4)])), "abcdef");
+    }
+
+    #[test]
+    fn number_filters() {
+        assert_eq!(filtered(filter(float(1.23456), "precision", [num(2)])), "1.23");
+        assert_eq!(filtered(filter(num(3), "precision", [num(1)])), "3.0");
+        assert_eq!(filtered(filter(num(1234567), "thousands", [])), "1,234,567");
+        assert_eq!(filtered(filter(num(-1234), "thousands", [strlit(" ")])), "-1 234");
+        assert_eq!(filtered(filter(float(1234.5), "thousands", [])), "1,234.5");
+        assert_eq!(filtered(filter(num(123), "thousands", [])), "123");
+    }
+
+    #[test]
+    fn duration_filter() {
+        assert_eq!(filtered(filter(num(3725), "duration", [])), "1h 2m 5s");
+        assert_eq!(filtered(filter(num(90_000), "duration", [])), "1d 1h");
+        assert_eq!(filtered(filter(float(0.25), "duration", [])), "250ms");
+        assert_eq!(filtered(filter(num(0), "duration", [])), "0s");
+    }
+
+    #[test]
+    fn bytes_filter() {
+        assert_eq!(filtered(filter(num(512), "bytes", [])), "512 B");
+        assert_eq!(filtered(filter(num(1536), "bytes", [])), "1.5 KiB");
+        assert_eq!(filtered(filter(num(5 * 1024 * 1024 * 1024), "bytes", [])), "5.0 GiB");
+    }
+}
@@ -84,8 +84,13 @@ impl<'frame, 'bp> TreeFilter for LayoutFilter<'frame, 'bp> {
                 widget.update(children, widgets);
                 ControlFlow::Continue(None)
             }
+            WidgetKind::Switch(widget) => {
+                widget.update(children, widgets);
+                ControlFlow::Continue(None)
+            }
             WidgetKind::If(widget) if !widget.show => ControlFlow::Break(()),
             WidgetKind::Else(widget) if !widget.show => ControlFlow::Break(()),
+            WidgetKind::Case(widget) if !widget.show => ControlFlow::Break(()),
             _ => ControlFlow::Continue(None),
         }
     }
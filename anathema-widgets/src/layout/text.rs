@@ -107,6 +107,19 @@ pub enum Segment<'a> {
     Str(&'a str),
 }
 
+// A char that was left out of the layout,
+// such as a newline or the whitespace that caused a line to wrap
+#[derive(Debug, Copy, Clone)]
+struct Gap {
+    // Byte index of the char in the text
+    source: usize,
+    // Byte index in the layout where the char would have been
+    index: usize,
+    len: usize,
+    // The line ended by the char, if the char broke the line
+    line: Option<usize>,
+}
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub(crate) enum LineEntry {
     Width(u16),
@@ -140,6 +153,11 @@ pub struct Strings {
     wrap: Wrap,
     // Byte index where the current line starts
     line: usize,
+    // Byte index where each line starts
+    line_starts: Vec<usize>,
+    // Byte index in the text of the next char
+    source: usize,
+    gaps: Vec<Gap>,
     current_width: LineWidth,
 }
 
@@ -155,6 +173,9 @@ impl Strings {
             wrap,
             size: Size::new(0, 1),
             line: 0,
+            line_starts: vec![0],
+            source: 0,
+            gaps: vec![],
             current_width: LineWidth::ZERO,
         }
     }
@@ -177,6 +198,7 @@ impl Strings {
                     self.freeze();
                     return res;
                 }
+                self.source += c.len_utf8();
             }
         }
 
@@ -205,6 +227,41 @@ impl Strings {
         })
     }
 
+    /// The row and column (in cells) of a cursor placed before the byte at `index`
+    /// of the text passed to [`Strings::add_str`].
+    /// A cursor placed before a newline, or before the whitespace that wrapped a line,
+    /// is placed at the end of that line.
+    /// ```
+    /// # use anathema_widgets::layout::text::*;
+    /// # use anathema_geometry::Size;
+    /// let mut text = Strings::new(Size::new(5, 10), Wrap::Normal);
+    /// text.add_str("hello world");
+    /// text.finish();
+    ///
+    /// assert_eq!(text.cursor_position(5), (0, 5));
+    /// assert_eq!(text.cursor_position(6), (1, 0));
+    /// ```
+    pub fn cursor_position(&self, index: usize) -> (usize, usize) {
+        let mut removed = 0;
+        for gap in &self.gaps {
+            if gap.source > index {
+                break;
+            }
+
+            if gap.source == index {
+                if let Some(line) = gap.line {
+                    return (line, self.str(self.line_starts[line], gap.index).width());
+                }
+            }
+
+            removed += gap.len;
+        }
+
+        let index = index.saturating_sub(removed).min(self.bytes.len());
+        let line = self.line_starts.partition_point(|start| *start <= index) - 1;
+        (line, self.str(self.line_starts[line], index).width())
+    }
+
     pub fn set_style(&mut self, style: WidgetId) {
         let index = self.bytes.len();
         self.layout.push((index as u32, Entry::Style(style)));
@@ -213,7 +270,7 @@ impl Strings {
     /// Finalize the layout, converting entries to lines
     pub fn finish(&mut self) -> Size {
         self.frozen = true;
-        self.layout.sort_by(|a, b| a.0.cmp(&b.0));
+        self.layout.sort_by_key(|a| a.0);
 
         let last_line = self.line(self.bytes.len());
         let last_line_width = last_line.width();
@@ -284,6 +341,16 @@ impl Strings {
                 word_boundary
             }
         };
+        self.line_starts.push(self.line);
+    }
+
+    fn gap(&mut self, c: char, line: Option<usize>) {
+        self.gaps.push(Gap {
+            source: self.source,
+            index: self.chomper.index(),
+            len: c.len_utf8(),
+            line,
+        });
     }
 
     fn line(&self, index: usize) -> &str {
@@ -308,6 +375,7 @@ impl Strings {
             for _ in 0..c.len_utf8() {
                 self.bytes.pop();
             }
+            self.gap(c, None);
             return ProcessResult::Continue;
         }
 
@@ -320,6 +388,7 @@ impl Strings {
                 return ProcessResult::Break;
             }
 
+            self.gap(c, Some(self.size.height - 1));
             self.chomper.force_word_boundary();
             self.newline();
             return ProcessResult::Continue;
@@ -337,6 +406,7 @@ impl Strings {
                     self.bytes.pop();
                 }
 
+                self.gap(c, Some(self.size.height - 1));
                 self.chomper.force_word_boundary();
                 self.newline();
 
@@ -5,8 +5,9 @@ pub use crate::nodes::eval::EvalContext;
 pub use crate::nodes::{eval_blueprint, try_resolve_future_values, update_tree, Element, Stringify, WidgetKind};
 pub use crate::values::{Value, Values};
 pub use crate::widget::{
-    AnyWidget, AttributeStorage, Attributes, ComponentParents, Components, DirtyWidgets, Elements, Factory,
-    FloatingWidgets, LayoutChildren, PaintChildren, PositionChildren, Widget, WidgetId, WidgetRenderer, WidgetTree,
+    component_at, AnyWidget, AttributeStorage, Attributes, ComponentParents, Components, DirtyWidgets, Elements,
+    Factory, FloatingWidgets, LayoutChildren, PaintChildren, PositionChildren, Widget, WidgetId, WidgetRenderer,
+    WidgetTree, COMMON_ATTRIBUTES,
 };
 
 pub mod components;
@@ -14,6 +15,7 @@ mod container;
 pub mod debug;
 pub mod error;
 pub mod expressions;
+pub mod functions;
 pub mod layout;
 mod nodes;
 pub mod paint;
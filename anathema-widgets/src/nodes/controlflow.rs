@@ -1,3 +1,4 @@
+use anathema_state::CommonVal;
 use anathema_store::tree::{Node, TreeValues};
 use anathema_templates::blueprints::Blueprint;
 
@@ -65,6 +66,50 @@ impl Else<'_> {
     }
 }
 
+#[derive(Debug)]
+pub struct Switch<'bp> {
+    pub value: Value<'bp, EvalValue<'bp>>,
+}
+
+impl Switch<'_> {
+    pub(crate) fn update(&self, children: &[Node], values: &mut TreeValues<WidgetKind<'_>>) {
+        let value = self.value.load_common_val();
+        let value = value.as_ref().and_then(|value| value.to_common());
+
+        // Show the first matching case, and the default case
+        // if none of the cases match
+        let mut was_set = false;
+        for node in children {
+            let Some((_, WidgetKind::Case(case))) = values.get_mut(node.value()) else { continue };
+            case.show = !was_set && case.matches(value);
+            was_set |= case.show;
+        }
+
+        for node in children {
+            let Some((_, WidgetKind::Case(case))) = values.get_mut(node.value()) else { continue };
+            if case.value.is_none() {
+                case.show = !was_set;
+                was_set = true;
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Case<'bp> {
+    /// The value is `None` for the default case
+    pub value: Option<Value<'bp, EvalValue<'bp>>>,
+    pub show: bool,
+}
+
+impl Case<'_> {
+    fn matches(&self, value: Option<CommonVal<'_>>) -> bool {
+        let Some(case) = self.value.as_ref() else { return false };
+        let Some(case) = case.load_common_val() else { return false };
+        case.to_common().is_some_and(|case| Some(case) == value)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use anathema_state::{Map, States};
@@ -136,4 +181,61 @@ mod test {
 
         assert_eq!(expected.trim(), output.trim());
     }
+
+    #[test]
+    fn switch_stmt() {
+        let tpl = "
+        switch a
+            case 1
+                test 1
+            case 2
+                test 2
+            default
+                test
+        ";
+        let mut map = Map::empty();
+        map.insert("a", 2);
+
+        let mut doc = Document::new(tpl);
+        let (blueprint, globals) = doc.compile().unwrap();
+        let mut widget_tree = Tree::<_>::empty();
+        let mut attribute_storage = AttributeStorage::empty();
+        let mut floating_widgets = FloatingWidgets::empty();
+        let factory = setup_test_factory();
+        let mut component_registry = ComponentRegistry::new();
+        let mut components = Components::new();
+        let mut states = States::new();
+        let state_id = states.insert(Box::new(map));
+        let mut scope = Scope::new();
+        scope.insert_state(state_id);
+
+        let mut ctx = EvalContext::new(
+            &globals,
+            &factory,
+            &mut scope,
+            &mut states,
+            &mut component_registry,
+            &mut attribute_storage,
+            &mut floating_widgets,
+            &mut components,
+        );
+
+        eval_blueprint(&blueprint, &mut ctx, &[], &mut widget_tree).unwrap();
+
+        let mut stringify = Stringify::new(&attribute_storage);
+        widget_tree.apply_visitor(&mut stringify);
+        let output = stringify.finish();
+
+        let expected = "
+<switch value = 2>
+    <case value = 1, show = false>
+        test Int(1)
+    <case value = 2, show = true>
+        test Int(2)
+    <default show = false>
+        test
+    ";
+
+        assert_eq!(expected.trim(), output.trim());
+    }
 }
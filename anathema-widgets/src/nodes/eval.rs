@@ -1,11 +1,11 @@
 use anathema_geometry::{Pos, Rect, Size};
-use anathema_state::{AnyState, States, Value};
+use anathema_state::{AnyState, States};
 use anathema_store::smallmap::{SmallIndex, SmallMap};
-use anathema_templates::blueprints::{Component, ControlFlow, Else, For, If, Single};
+use anathema_templates::blueprints::{Case, Component, ControlFlow, Else, For, If, Single, Switch};
+use anathema_templates::error::Location;
 use anathema_templates::{Globals, WidgetComponentId};
 
 use super::element::Element;
-use super::loops::{Iteration, LOOP_INDEX};
 use super::{component, controlflow};
 use crate::components::{AnyComponent, ComponentKind, ComponentRegistry};
 use crate::container::Container;
@@ -93,18 +93,32 @@ impl Evaluator for SingleEval {
 
         if let Some(expr) = single.value.as_ref() {
             let value = attributes.insert_with(ValueKey::Value, |value_index| {
-                eval(expr, ctx.globals, ctx.scope, ctx.states, (widget_id, value_index))
+                eval(
+                    expr,
+                    ctx.globals,
+                    ctx.factory.functions(),
+                    ctx.scope,
+                    ctx.states,
+                    (widget_id, value_index),
+                )
             });
             attributes.value = Some(value);
         }
 
         for (key, expr) in single.attributes.iter() {
             attributes.insert_with(ValueKey::Attribute(key), |value_index| {
-                eval(expr, ctx.globals, ctx.scope, ctx.states, (widget_id, value_index))
+                eval(
+                    expr,
+                    ctx.globals,
+                    ctx.factory.functions(),
+                    ctx.scope,
+                    ctx.states,
+                    (widget_id, value_index),
+                )
             });
         }
 
-        let widget = ctx.factory.make(&single.ident, &attributes)?;
+        let widget = ctx.factory.make(single, &attributes)?;
 
         // Is the widget a floating widget?
         if widget.any_floats() {
@@ -127,7 +141,9 @@ impl Evaluator for SingleEval {
         // Widget
         let widget = WidgetKind::Element(Element::new(&single.ident, container));
 
-        transaction.commit_child(widget).ok_or(Error::TreeTransactionFailed)?;
+        transaction
+            .commit_child(widget)
+            .ok_or_else(|| Error::tree_transaction_failed(single.location.as_ref()))?;
 
         // Children
         let parent = tree.path(widget_id);
@@ -144,35 +160,19 @@ pub(super) struct ForLoopEval;
 impl ForLoopEval {
     pub(super) fn eval_body<'bp>(
         &self,
-        for_loop: &super::loops::For<'bp>,
+        for_loop: &mut super::loops::For<'bp>,
         ctx: &mut EvalContext<'_, '_, 'bp>,
         parent: &[u16],
         tree: &mut WidgetTree<'bp>,
     ) -> Result<()> {
-        for index in 0..for_loop.collection.count() {
-            ctx.scope.push();
-            for_loop.scope_value(ctx.scope, index);
-
-            let iter_id = tree
-                .insert(parent)
-                .commit_child(WidgetKind::Iteration(Iteration {
-                    loop_index: Value::new(index as i64),
-                    binding: for_loop.binding,
-                }))
-                .ok_or(Error::TreeTransactionFailed)?;
-
-            // Scope the iteration value
-            tree.with_value_mut(iter_id, |parent, widget, tree| {
-                let WidgetKind::Iteration(iter) = widget else { unreachable!() };
-                ctx.scope.scope_pending(LOOP_INDEX, iter.loop_index.to_pending());
-
-                for bp in for_loop.body {
-                    eval_blueprint(bp, ctx, parent, tree)?;
-                }
-                Ok(())
-            })?;
+        let count = for_loop.collection.count();
+        if count == 0 {
+            return for_loop.eval_else(ctx, parent, tree);
+        }
 
-            ctx.scope.pop();
+        for_loop.shows_else = false;
+        for index in 0..count {
+            for_loop.eval_iteration(index, ctx, parent, tree)?;
         }
 
         Ok(())
@@ -193,14 +193,28 @@ impl Evaluator for ForLoopEval {
         let value_id = ValueId::from((transaction.node_id(), ValueIndex::ZERO));
 
         let for_loop = super::loops::For {
+            key_binding: for_loop.key.as_deref(),
             binding: &for_loop.binding,
-            collection: eval_collection(&for_loop.data, ctx.globals, ctx.scope, ctx.states, value_id),
+            shows_else: false,
+            else_body: &for_loop.else_body,
+            collection: eval_collection(
+                &for_loop.data,
+                ctx.globals,
+                ctx.factory.functions(),
+                ctx.scope,
+                ctx.states,
+                value_id,
+            ),
             body: &for_loop.body,
+            location: for_loop.location.as_ref(),
         };
 
+        let location = for_loop.location;
         let widget = WidgetKind::For(for_loop);
 
-        let for_loop_id = transaction.commit_child(widget).ok_or(Error::TreeTransactionFailed)?;
+        let for_loop_id = transaction
+            .commit_child(widget)
+            .ok_or_else(|| Error::tree_transaction_failed(location))?;
 
         tree.with_value_mut(for_loop_id, move |parent, widget, tree| {
             let WidgetKind::For(for_loop) = widget else { unreachable!() };
@@ -225,16 +239,19 @@ impl Evaluator for ControlFlowEval {
         tree: &mut WidgetTree<'bp>,
     ) -> Result<()> {
         let transaction = tree.insert(parent);
+        let location = control_flow.location.as_ref();
         let widget = WidgetKind::ControlFlow(controlflow::ControlFlow {});
-        let for_loop_id = transaction.commit_child(widget).ok_or(Error::TreeTransactionFailed)?;
+        let for_loop_id = transaction
+            .commit_child(widget)
+            .ok_or_else(|| Error::tree_transaction_failed(location))?;
         let parent = tree.path(for_loop_id);
 
         tree.with_value_mut(for_loop_id, move |parent, _widget, tree| {
-            IfEval.eval(&control_flow.if_node, ctx, parent, tree)?;
+            IfEval { location }.eval(&control_flow.if_node, ctx, parent, tree)?;
             control_flow
                 .elses
                 .iter()
-                .try_for_each(|e| ElseEval.eval(e, ctx, parent, tree))?;
+                .try_for_each(|e| ElseEval { location }.eval(e, ctx, parent, tree))?;
             Ok(())
         })?;
 
@@ -267,9 +284,12 @@ impl Evaluator for ControlFlowEval {
     }
 }
 
-struct IfEval;
+// Located at the `if` it belongs to
+struct IfEval<'a> {
+    location: Option<&'a Location>,
+}
 
-impl Evaluator for IfEval {
+impl Evaluator for IfEval<'_> {
     type Input<'bp> = &'bp If;
 
     fn eval<'bp>(
@@ -283,13 +303,20 @@ impl Evaluator for IfEval {
         let node_id = transaction.node_id();
 
         let value_id = (node_id, ValueIndex::ZERO);
-        let cond = eval(&input.cond, ctx.globals, ctx.scope, ctx.states, value_id);
+        let cond = eval(
+            &input.cond,
+            ctx.globals,
+            ctx.factory.functions(),
+            ctx.scope,
+            ctx.states,
+            value_id,
+        );
 
         let if_widget = controlflow::If { cond, show: false };
 
         let if_widget_id = transaction
             .commit_child(WidgetKind::If(if_widget))
-            .ok_or(Error::TreeTransactionFailed)?;
+            .ok_or_else(|| Error::tree_transaction_failed(self.location))?;
 
         let parent = tree.path(if_widget_id);
         for bp in &input.body {
@@ -300,9 +327,12 @@ impl Evaluator for IfEval {
     }
 }
 
-struct ElseEval;
+// Located at the `if` it belongs to
+struct ElseEval<'a> {
+    location: Option<&'a Location>,
+}
 
-impl Evaluator for ElseEval {
+impl Evaluator for ElseEval<'_> {
     type Input<'bp> = &'bp Else;
 
     fn eval<'bp>(
@@ -316,10 +346,16 @@ impl Evaluator for ElseEval {
         let widget_id = transaction.node_id();
         let value_id = (widget_id, ValueIndex::ZERO);
 
-        let cond = input
-            .cond
-            .as_ref()
-            .map(|cond| eval(cond, ctx.globals, ctx.scope, ctx.states, value_id));
+        let cond = input.cond.as_ref().map(|cond| {
+            eval(
+                cond,
+                ctx.globals,
+                ctx.factory.functions(),
+                ctx.scope,
+                ctx.states,
+                value_id,
+            )
+        });
 
         let else_widget = controlflow::Else {
             cond,
@@ -329,7 +365,99 @@ impl Evaluator for ElseEval {
 
         let _ = transaction
             .commit_child(WidgetKind::Else(else_widget))
-            .ok_or(Error::TreeTransactionFailed)?;
+            .ok_or_else(|| Error::tree_transaction_failed(self.location))?;
+
+        let parent = tree.path(widget_id);
+        for bp in &input.body {
+            eval_blueprint(bp, ctx, &parent, tree)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub(super) struct SwitchEval;
+
+impl Evaluator for SwitchEval {
+    type Input<'bp> = &'bp Switch;
+
+    fn eval<'bp>(
+        &mut self,
+        input: Self::Input<'bp>,
+        ctx: &mut EvalContext<'_, '_, 'bp>,
+        parent: &[u16],
+        tree: &mut WidgetTree<'bp>,
+    ) -> Result<()> {
+        let transaction = tree.insert(parent);
+        let node_id = transaction.node_id();
+
+        let value_id = (node_id, ValueIndex::ZERO);
+        let value = eval(
+            &input.value,
+            ctx.globals,
+            ctx.factory.functions(),
+            ctx.scope,
+            ctx.states,
+            value_id,
+        );
+
+        let widget = WidgetKind::Switch(controlflow::Switch { value });
+        let location = input.location.as_ref();
+        let switch_id = transaction
+            .commit_child(widget)
+            .ok_or_else(|| Error::tree_transaction_failed(location))?;
+
+        tree.with_value_mut(switch_id, move |parent, _widget, tree| {
+            input
+                .cases
+                .iter()
+                .try_for_each(|case| CaseEval { location }.eval(case, ctx, parent, tree))
+        })?;
+
+        tree.with_nodes_and_values(switch_id, |widget, children, values| {
+            let WidgetKind::Switch(switch) = widget else { unreachable!() };
+            switch.update(children, values);
+        });
+
+        Ok(())
+    }
+}
+
+// Located at the `switch` it belongs to
+struct CaseEval<'a> {
+    location: Option<&'a Location>,
+}
+
+impl Evaluator for CaseEval<'_> {
+    type Input<'bp> = &'bp Case;
+
+    fn eval<'bp>(
+        &mut self,
+        input: Self::Input<'bp>,
+        ctx: &mut EvalContext<'_, '_, 'bp>,
+        parent: &[u16],
+        tree: &mut WidgetTree<'bp>,
+    ) -> Result<()> {
+        let transaction = tree.insert(parent);
+        let widget_id = transaction.node_id();
+        let value_id = (widget_id, ValueIndex::ZERO);
+
+        let value = input.value.as_ref().map(|value| {
+            eval(
+                value,
+                ctx.globals,
+                ctx.factory.functions(),
+                ctx.scope,
+                ctx.states,
+                value_id,
+            )
+        });
+
+        let case_widget = controlflow::Case { value, show: false };
+
+        let _ = transaction
+            .commit_child(WidgetKind::Case(case_widget))
+            .ok_or_else(|| Error::tree_transaction_failed(self.location))?;
 
         let parent = tree.path(widget_id);
         for bp in &input.body {
@@ -359,7 +487,14 @@ impl Evaluator for ComponentEval {
                 let mut state_map = SmallMap::empty();
                 for (i, (k, v)) in map.iter().enumerate() {
                     let idx: SmallIndex = (i as u8).into();
-                    let val = eval(v, ctx.globals, ctx.scope, ctx.states, (transaction.node_id(), idx));
+                    let val = eval(
+                        v,
+                        ctx.globals,
+                        ctx.factory.functions(),
+                        ctx.scope,
+                        ctx.states,
+                        (transaction.node_id(), idx),
+                    );
                     state_map.set(&**k, (idx, val));
                 }
                 Some(state_map)
@@ -368,7 +503,11 @@ impl Evaluator for ComponentEval {
         };
 
         let component_id = usize::from(input.id).into();
-        let (kind, component, state) = ctx.get_component(component_id).ok_or(Error::ComponentConsumed)?;
+        let (kind, component, state) = ctx
+            .get_component(component_id)
+            .ok_or_else(|| Error::ComponentConsumed {
+                location: input.location.clone(),
+            })?;
         let state_id = ctx.states.insert(state);
         let comp_widget = component::Component::new(
             &input.body,
@@ -383,19 +522,29 @@ impl Evaluator for ComponentEval {
 
         let widget_id = transaction
             .commit_child(WidgetKind::Component(comp_widget))
-            .ok_or(Error::TreeTransactionFailed)?;
+            .ok_or_else(|| Error::tree_transaction_failed(input.location.as_ref()))?;
 
         // Attributes
         let mut attributes = Attributes::empty(widget_id);
         for (key, expr) in input.attributes.iter() {
             attributes.insert_with(ValueKey::Attribute(key), |value_index| {
-                eval(expr, ctx.globals, ctx.scope, ctx.states, (widget_id, value_index))
+                eval(
+                    expr,
+                    ctx.globals,
+                    ctx.factory.functions(),
+                    ctx.scope,
+                    ctx.states,
+                    (widget_id, value_index),
+                )
             });
         }
         ctx.attribute_storage.insert(widget_id, attributes);
 
         let path = tree.path(widget_id);
         ctx.components.push(path, widget_id, state_id, component_id);
+        for i in 0..input.state.as_ref().map_or(0, |map| map.len()) {
+            ctx.components.external_changed(widget_id, (i as u8).into());
+        }
 
         tree.with_value_mut(widget_id, move |parent, widget, tree| {
             let WidgetKind::Component(component) = widget else { unreachable!() };
@@ -3,13 +3,12 @@ use anathema_store::tree::PathFinder;
 use anathema_templates::Globals;
 
 use super::element::Element;
-use super::eval::EvalContext;
-use super::loops::LOOP_INDEX;
+use super::eval::{EvalContext, ForLoopEval};
 use super::update::scope_value;
 use crate::components::ComponentRegistry;
-use crate::error::{Error, Result};
+use crate::error::Result;
 use crate::expressions::{eval, eval_collection};
-use crate::values::{Collection, ValueId};
+use crate::values::ValueId;
 use crate::widget::{Components, FloatingWidgets};
 use crate::{AttributeStorage, Factory, Scope, WidgetKind, WidgetTree};
 
@@ -97,7 +96,14 @@ fn try_resolve_value<'bp>(
             };
 
             if let Some(expr) = val.expr {
-                let value = eval(expr, ctx.globals, ctx.scope, ctx.states, value_id);
+                let value = eval(
+                    expr,
+                    ctx.globals,
+                    ctx.factory.functions(),
+                    ctx.scope,
+                    ctx.states,
+                    value_id,
+                );
                 *val = value;
             }
         }
@@ -109,83 +115,64 @@ fn try_resolve_value<'bp>(
             for_loop.collection = eval_collection(
                 for_loop.collection.expr.unwrap(),
                 ctx.globals,
+                ctx.factory.functions(),
                 ctx.scope,
                 ctx.states,
                 value_id,
             );
 
             tree.remove_children(path);
-
-            let collection = &for_loop.collection;
-            let binding = &for_loop.binding;
-            let body = for_loop.body;
-            let parent = path;
-
-            for index in 0..collection.count() {
-                ctx.scope.push();
-
-                match collection.inner() {
-                    Collection::Static(expressions) => {
-                        let downgrade = expressions[index].downgrade();
-                        ctx.scope.scope_downgrade(binding, downgrade)
-                    }
-                    Collection::Dyn(value_ref) => {
-                        let value = value_ref
-                            .as_state()
-                            .and_then(|state| state.state_lookup(index.into()))
-                            .expect("the collection has a value since it has a length");
-                        ctx.scope.scope_pending(binding, value)
-                    }
-                    Collection::Index(collection, _) => match &**collection {
-                        Collection::Static(expressions) => {
-                            let downgrade = expressions[index].downgrade();
-                            ctx.scope.scope_downgrade(binding, downgrade)
-                        }
-                        Collection::Dyn(value_ref) => {
-                            let value = value_ref
-                                .as_state()
-                                .and_then(|state| state.state_lookup(index.into()))
-                                .expect("the collection has a value since it has a length");
-                            ctx.scope.scope_pending(binding, value)
-                        }
-                        Collection::Future => {}
-                        Collection::Index(_, _) => unreachable!("maaybe it's not?"),
-                    },
-                    Collection::Future => {}
-                }
-
-                let iter_id = tree
-                    .insert(parent)
-                    .commit_child(WidgetKind::Iteration(super::loops::Iteration {
-                        loop_index: anathema_state::Value::new(index as i64),
-                        binding,
-                    }))
-                    .ok_or(Error::TreeTransactionFailed)?;
-
-                // Scope the iteration value
-                tree.with_value_mut(iter_id, |parent, widget, tree| {
-                    let WidgetKind::Iteration(iter) = widget else { unreachable!() };
-                    ctx.scope.scope_pending(LOOP_INDEX, iter.loop_index.to_pending());
-
-                    for bp in body {
-                        crate::eval_blueprint(bp, ctx, parent, tree)?;
-                    }
-                    Ok(())
-                })?;
-
-                ctx.scope.pop();
-            }
+            ForLoopEval.eval_body(for_loop, ctx, path, tree)?;
         }
         WidgetKind::If(widget) => {
             if let Some(expr) = widget.cond.expr {
-                let value = eval(expr, ctx.globals, ctx.scope, ctx.states, value_id);
+                let value = eval(
+                    expr,
+                    ctx.globals,
+                    ctx.factory.functions(),
+                    ctx.scope,
+                    ctx.states,
+                    value_id,
+                );
                 widget.cond = value;
             }
         }
         WidgetKind::Else(el) => {
             let Some(val) = &mut el.cond else { return Ok(()) };
             if let Some(expr) = val.expr {
-                *val = eval(expr, ctx.globals, ctx.scope, ctx.states, value_id);
+                *val = eval(
+                    expr,
+                    ctx.globals,
+                    ctx.factory.functions(),
+                    ctx.scope,
+                    ctx.states,
+                    value_id,
+                );
+            }
+        }
+        WidgetKind::Switch(switch) => {
+            if let Some(expr) = switch.value.expr {
+                switch.value = eval(
+                    expr,
+                    ctx.globals,
+                    ctx.factory.functions(),
+                    ctx.scope,
+                    ctx.states,
+                    value_id,
+                );
+            }
+        }
+        WidgetKind::Case(case) => {
+            let Some(val) = &mut case.value else { return Ok(()) };
+            if let Some(expr) = val.expr {
+                *val = eval(
+                    expr,
+                    ctx.globals,
+                    ctx.factory.functions(),
+                    ctx.scope,
+                    ctx.states,
+                    value_id,
+                );
             }
         }
         WidgetKind::ControlFlow(_) => unreachable!(),
@@ -195,7 +182,14 @@ fn try_resolve_value<'bp>(
             for (_, (i, v)) in state.iter_mut() {
                 if *i == value_id.index() {
                     if let Some(expr) = v.expr {
-                        *v = eval(expr, ctx.globals, ctx.scope, ctx.states, value_id);
+                        *v = eval(
+                            expr,
+                            ctx.globals,
+                            ctx.factory.functions(),
+                            ctx.scope,
+                            ctx.states,
+                            value_id,
+                        );
                     }
                 }
             }
@@ -213,6 +207,7 @@ mod test {
     use anathema_templates::Expression;
 
     use super::*;
+    use crate::functions::Functions;
 
     fn future_value(expr: &Expression, value_id: ValueId) {
         let globals = Globals::default();
@@ -223,7 +218,7 @@ mod test {
         drain_futures(&mut futures);
         assert_eq!(futures.len(), 0);
 
-        eval(expr, &globals, &scope, &states, value_id);
+        eval(expr, &globals, &Functions::new(), &scope, &states, value_id);
 
         drain_futures(&mut futures);
         assert_eq!(futures.len(), 1);
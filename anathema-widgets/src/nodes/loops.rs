@@ -1,7 +1,11 @@
+use std::rc::Rc;
+
 use anathema_state::Change;
 use anathema_store::tree::new_node_path;
 use anathema_templates::blueprints::Blueprint;
+use anathema_templates::error::Location;
 
+use super::eval::ForLoopEval;
 use super::WidgetKind;
 use crate::error::{Error, Result};
 use crate::expressions::eval_collection;
@@ -14,9 +18,15 @@ pub(super) const LOOP_INDEX: &str = "loop";
 
 #[derive(Debug)]
 pub struct For<'bp> {
+    pub(super) key_binding: Option<&'bp str>,
     pub(super) binding: &'bp str,
     pub(super) collection: Value<'bp, Collection<'bp>>,
     pub(super) body: &'bp [Blueprint],
+    pub(super) else_body: &'bp [Blueprint],
+    /// True while the else body is shown in place of the iterations
+    pub(super) shows_else: bool,
+    /// Where the loop is declared in the template
+    pub(super) location: Option<&'bp Location>,
 }
 
 impl<'bp> For<'bp> {
@@ -24,6 +34,62 @@ impl<'bp> For<'bp> {
         self.collection.scope(scope, self.binding, index)
     }
 
+    pub(super) fn iteration(&self, index: usize, key: Option<Rc<str>>) -> Iteration<'bp> {
+        Iteration {
+            loop_index: anathema_state::Value::new(index as i64),
+            key: key.map(anathema_state::Value::new),
+            number: self.collection.number(index).map(anathema_state::Value::new),
+            binding: self.binding,
+            key_binding: self.key_binding,
+        }
+    }
+
+    /// Evaluate the body of the iteration at the index, as the last child of the loop.
+    pub(super) fn eval_iteration(
+        &self,
+        index: usize,
+        ctx: &mut EvalContext<'_, '_, 'bp>,
+        parent: &[u16],
+        tree: &mut WidgetTree<'bp>,
+    ) -> Result<()> {
+        ctx.scope.push();
+        self.scope_value(ctx.scope, index);
+
+        let key = self.collection.key(index);
+        let iter_id = tree
+            .insert(parent)
+            .commit_child(WidgetKind::Iteration(self.iteration(index, key)))
+            .ok_or_else(|| Error::tree_transaction_failed(self.location))?;
+
+        // Scope the iteration value
+        tree.with_value_mut(iter_id, |parent, widget, tree| {
+            let WidgetKind::Iteration(iter) = widget else { unreachable!() };
+            iter.scope(ctx.scope);
+
+            for bp in self.body {
+                eval_blueprint(bp, ctx, parent, tree)?;
+            }
+            Ok(())
+        })?;
+
+        ctx.scope.pop();
+        Ok(())
+    }
+
+    /// Evaluate the else body as the children of the loop.
+    pub(super) fn eval_else(
+        &mut self,
+        ctx: &mut EvalContext<'_, '_, 'bp>,
+        parent: &[u16],
+        tree: &mut WidgetTree<'bp>,
+    ) -> Result<()> {
+        self.shows_else = true;
+        for bp in self.else_body {
+            eval_blueprint(bp, ctx, parent, tree)?;
+        }
+        Ok(())
+    }
+
     pub(crate) fn collection(&self) -> &Collection<'_> {
         self.collection.inner()
     }
@@ -45,16 +111,19 @@ impl<'bp> For<'bp> {
                 // 5. Scope new value
                 // 6. Eval body
 
+                if self.shows_else {
+                    tree.remove_children(path);
+                    self.shows_else = false;
+                }
+
                 ctx.scope.push();
                 ctx.scope.scope_pending(self.binding, *value);
 
                 let insert_at = new_node_path(path, *index as u16);
+                let key = self.collection.key_of(*value);
                 let iter_id = tree
                     .insert(&insert_at)
-                    .commit_at(WidgetKind::Iteration(Iteration {
-                        loop_index: anathema_state::Value::new(*index as i64),
-                        binding: self.binding,
-                    }))
+                    .commit_at(WidgetKind::Iteration(self.iteration(*index as usize, key)))
                     .unwrap(); // TODO unwrap
 
                 // Bump the index for every subsequent sibling of the newly inserted node
@@ -88,7 +157,7 @@ impl<'bp> For<'bp> {
                     // change is applied, which would lead to scoping `"c" to `0`
                     // twice.
                     let WidgetKind::Iteration(iter) = iter_widget else { unreachable!() };
-                    ctx.scope.scope_pending(LOOP_INDEX, iter.loop_index.to_pending());
+                    iter.scope(ctx.scope);
 
                     for bp in self.body {
                         eval_blueprint(bp, ctx, parent, tree)?;
@@ -100,8 +169,29 @@ impl<'bp> For<'bp> {
                 ctx.scope.pop();
             }
             Change::Removed(index) => {
+                if self.shows_else {
+                    return Ok(());
+                }
+
                 let child_to_remove = new_node_path(path, *index as u16);
+
+                // Lower the index for every subsequent sibling of the removed node
+                tree.children_after(&child_to_remove, |node, values| {
+                    let iter_widget = values.get_mut(node.value());
+                    let Some((_, WidgetKind::Iteration(iter))) = iter_widget else { unreachable!() };
+                    *iter.loop_index.to_mut() -= 1;
+                });
+
                 tree.remove(&child_to_remove);
+
+                let is_empty = tree
+                    .get_node_by_path(path)
+                    .map(|(node, _)| node.children().is_empty())
+                    .unwrap_or(false);
+
+                if is_empty {
+                    self.eval_else(ctx, path, tree)?;
+                }
             }
             Change::Dropped => {
                 tree.remove_children(path);
@@ -110,37 +200,18 @@ impl<'bp> For<'bp> {
                 self.collection = eval_collection(
                     self.collection.expr.unwrap(),
                     ctx.globals,
+                    ctx.factory.functions(),
                     ctx.scope,
                     ctx.states,
                     value_id,
                 );
 
-                for index in 0..self.collection.count() {
-                    self.scope_value(ctx.scope, index);
-                    ctx.scope.push();
-
-                    let iter_id = tree
-                        .insert(path)
-                        .commit_child(WidgetKind::Iteration(Iteration {
-                            loop_index: anathema_state::Value::new(index as i64),
-                            binding: self.binding,
-                        }))
-                        .ok_or(Error::TreeTransactionFailed)?;
-
-                    // Scope the iteration value
-                    tree.with_value_mut(iter_id, |parent, widget, tree| -> Result<()> {
-                        let WidgetKind::Iteration(iter) = widget else { unreachable!() };
-                        ctx.scope.scope_pending(LOOP_INDEX, iter.loop_index.to_pending());
-
-                        for bp in self.body {
-                            eval_blueprint(bp, ctx, parent, tree)?;
-                        }
-
-                        Ok(())
-                    })?;
-
-                    ctx.scope.pop();
-                }
+                ForLoopEval.eval_body(self, ctx, path, tree)?;
+            }
+            Change::Changed if matches!(self.collection.inner(), Collection::Range(..)) => {
+                // The bounds of a range are read when counting, so there is
+                // no need to evaluate the collection again.
+                self.update_range(ctx, path, tree)?;
             }
             Change::Changed => {
                 // TODO implement this as an optimisation once the runtime is done.
@@ -154,25 +225,99 @@ impl<'bp> For<'bp> {
 
         Ok(())
     }
+
+    // Iterations that are still in the range after a bound changed are kept,
+    // and given their new number if the start of the range moved.
+    fn update_range(
+        &mut self,
+        ctx: &mut EvalContext<'_, '_, 'bp>,
+        path: &[u16],
+        tree: &mut WidgetTree<'bp>,
+    ) -> Result<()> {
+        let count = self.collection.count();
+        if self.shows_else {
+            if count == 0 {
+                return Ok(());
+            }
+            tree.remove_children(path);
+            self.shows_else = false;
+        }
+
+        let current = tree
+            .get_node_by_path(path)
+            .map(|(node, _)| node.children().len())
+            .unwrap_or(0);
+
+        for index in (count..current).rev() {
+            tree.remove(&new_node_path(path, index as u16));
+        }
+
+        let collection = self.collection.inner();
+        let mut index = 0;
+        tree.children_of(path, |node, values| {
+            let Some((_, WidgetKind::Iteration(iter))) = values.get_mut(node.value()) else { unreachable!() };
+            if let (Some(number), Some(new_number)) = (iter.number.as_mut(), collection.number(index)) {
+                if *number.to_ref() != new_number {
+                    *number.to_mut() = new_number;
+                }
+            }
+            index += 1;
+        });
+
+        if count == 0 {
+            return self.eval_else(ctx, path, tree);
+        }
+
+        for index in current..count {
+            self.eval_iteration(index, ctx, path, tree)?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
 pub struct Iteration<'bp> {
     pub loop_index: anathema_state::Value<i64>,
+    /// The key of the entry when iterating over a map
+    pub key: Option<anathema_state::Value<Rc<str>>>,
+    /// The number of the iteration when iterating over a range
+    pub number: Option<anathema_state::Value<i64>>,
     pub binding: &'bp str,
+    pub key_binding: Option<&'bp str>,
+}
+
+impl<'bp> Iteration<'bp> {
+    /// Scope the loop index, and the key binding if there is one.
+    /// The key binding is the key of a map entry, or the index of a list item.
+    pub(super) fn scope(&self, scope: &mut Scope<'bp>) {
+        let loop_index = self.loop_index.to_pending();
+        scope.scope_pending(LOOP_INDEX, loop_index);
+
+        if let Some(number) = &self.number {
+            scope.scope_pending(self.binding, number.to_pending());
+        }
+
+        if let Some(binding) = self.key_binding {
+            let key = self.key.as_ref().map(|key| key.to_pending()).unwrap_or(loop_index);
+            scope.scope_pending(binding, key);
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use anathema_state::{drain_changes, Changes, List, Map, StateId, States};
+    use anathema_state::{drain_changes, Changes, CommonVal, List, Map, StateId, States};
     use anathema_store::tree::root_node;
     use anathema_templates::Document;
 
     use super::*;
     use crate::components::ComponentRegistry;
+    use crate::expressions::EvalValue;
     use crate::nodes::stringify::Stringify;
     use crate::nodes::{eval_blueprint, update_tree};
     use crate::testing::setup_test_factory;
+    use crate::values::MAX_RANGE;
     use crate::{AttributeStorage, Components, FloatingWidgets};
 
     #[test]
@@ -275,19 +420,19 @@ mod test {
 
         let expected = "
 <for>
-    <iter binding = x, index = 2>
+    <iter binding = x, index = 0>
         test Int(9)
-    <iter binding = x, index = 3>
+    <iter binding = x, index = 1>
         test Int(1)
-    <iter binding = x, index = 4>
+    <iter binding = x, index = 2>
         test Int(2)
-    <iter binding = x, index = 5>
+    <iter binding = x, index = 3>
         test Int(3)
-    <iter binding = x, index = 6>
+    <iter binding = x, index = 4>
         test Int(100)
-    <iter binding = x, index = 7>
+    <iter binding = x, index = 5>
         test Int(101)
-    <iter binding = x, index = 8>
+    <iter binding = x, index = 6>
         test Int(102)";
         assert_eq!(expected.trim(), output.trim());
     }
@@ -351,4 +496,479 @@ mod test {
 ";
         assert_eq!(expected.trim(), output.trim());
     }
+
+    #[test]
+    fn eval_for_with_index() {
+        let mut list = List::empty();
+        list.push_back(1u32);
+        list.push_back(2u32);
+        let mut map = Map::<List<_>>::empty();
+        map.insert("a", list);
+
+        let tpl = "
+        for i, x in a
+            test i
+                test x
+        ";
+        let (blueprint, globals) = Document::new(tpl).compile().unwrap();
+        let mut tree = WidgetTree::empty();
+        let mut attribute_storage = AttributeStorage::empty();
+        let mut floating_widgets = FloatingWidgets::empty();
+        let mut components = Components::new();
+        let factory = setup_test_factory();
+        let mut component_reg = ComponentRegistry::new();
+        let mut states = States::new();
+        let state_id = states.insert(Box::new(map));
+        let mut scope = Scope::new();
+        scope.insert_state(state_id);
+        let mut ctx = EvalContext::new(
+            &globals,
+            &factory,
+            &mut scope,
+            &mut states,
+            &mut component_reg,
+            &mut attribute_storage,
+            &mut floating_widgets,
+            &mut components,
+        );
+        eval_blueprint(&blueprint, &mut ctx, root_node(), &mut tree).unwrap();
+
+        let mut stringify = Stringify::new(&attribute_storage);
+        tree.apply_visitor(&mut stringify);
+        let output = stringify.finish();
+
+        let expected = "
+<for>
+    <iter binding = x, index = 0>
+        test Int(0)
+            test Int(1)
+    <iter binding = x, index = 1>
+        test Int(1)
+            test Int(2)
+";
+        assert_eq!(expected.trim(), output.trim());
+    }
+
+    #[test]
+    fn map_insert_and_remove() {
+        let mut inner = Map::<u32>::empty();
+        inner.insert("one", 1u32);
+        inner.insert("two", 2u32);
+        let mut map = Map::<Map<u32>>::empty();
+        map.insert("a", inner);
+
+        let tpl = "
+        for key, value in a
+            test key
+                test value
+        ";
+
+        let (blueprint, globals) = Document::new(tpl).compile().unwrap();
+        let mut widget_tree = WidgetTree::empty();
+        let mut attribute_storage = AttributeStorage::empty();
+        let mut floating_widgets = FloatingWidgets::empty();
+        let factory = setup_test_factory();
+        let mut component_registry = ComponentRegistry::new();
+        let mut components = Components::new();
+        let mut states = States::new();
+        let state_id = states.insert(Box::new(map));
+        let mut scope = Scope::new();
+        scope.insert_state(state_id);
+        let mut ctx = EvalContext::new(
+            &globals,
+            &factory,
+            &mut scope,
+            &mut states,
+            &mut component_registry,
+            &mut attribute_storage,
+            &mut floating_widgets,
+            &mut components,
+        );
+
+        eval_blueprint(&blueprint, &mut ctx, &[], &mut widget_tree).unwrap();
+
+        {
+            let map = states.get_mut(StateId::ZERO).unwrap();
+            let map = map
+                .to_any_mut()
+                .downcast_mut::<anathema_state::Value<Map<Map<u32>>>>()
+                .unwrap();
+            let mut map = map.to_mut();
+            let inner = map.get_mut("a").unwrap();
+            inner.insert("three", 3); // one, two, three
+            inner.remove("one"); // two, three
+            inner.insert("four", 4); // two, three, four
+            inner.remove("four"); // two, three
+        }
+
+        let mut local_changes = Changes::empty();
+        drain_changes(&mut local_changes);
+        local_changes.drain().rev().for_each(|(subs, change)| {
+            subs.with(|sub| {
+                // Widgets of removed entries are gone from the tree
+                let Some(widget_path) = widget_tree.try_path_ref(sub).map(|path| path.to_vec()) else { return };
+                let mut scope = Scope::with_capacity(10);
+                update_tree(
+                    &globals,
+                    &factory,
+                    &mut scope,
+                    &mut states,
+                    &mut component_registry,
+                    &change,
+                    sub,
+                    &widget_path,
+                    &mut widget_tree,
+                    &mut attribute_storage,
+                    &mut floating_widgets,
+                    &mut components,
+                );
+            });
+        });
+
+        let mut stringify = Stringify::new(&attribute_storage);
+        widget_tree.apply_visitor(&mut stringify);
+        let output = stringify.finish();
+
+        let expected = "
+<for>
+    <iter binding = value, index = 0, key = two>
+        test Str(\"two\")
+            test Int(2)
+    <iter binding = value, index = 1, key = three>
+        test Str(\"three\")
+            test Int(3)";
+        assert_eq!(expected.trim(), output.trim());
+    }
+
+    #[test]
+    fn range_update() {
+        let mut map = Map::<u32>::empty();
+        map.insert("n", 2u32);
+
+        let tpl = "
+        for x in 0..n
+            test x
+        ";
+
+        let (blueprint, globals) = Document::new(tpl).compile().unwrap();
+        let mut widget_tree = WidgetTree::empty();
+        let mut attribute_storage = AttributeStorage::empty();
+        let mut floating_widgets = FloatingWidgets::empty();
+        let factory = setup_test_factory();
+        let mut component_registry = ComponentRegistry::new();
+        let mut components = Components::new();
+        let mut states = States::new();
+        let state_id = states.insert(Box::new(map));
+        let mut scope = Scope::new();
+        scope.insert_state(state_id);
+        let mut ctx = EvalContext::new(
+            &globals,
+            &factory,
+            &mut scope,
+            &mut states,
+            &mut component_registry,
+            &mut attribute_storage,
+            &mut floating_widgets,
+            &mut components,
+        );
+
+        eval_blueprint(&blueprint, &mut ctx, &[], &mut widget_tree).unwrap();
+
+        let mut stringify = Stringify::new(&attribute_storage);
+        widget_tree.apply_visitor(&mut stringify);
+        let output = stringify.finish();
+
+        let expected = "
+<for>
+    <iter binding = x, index = 0>
+        test Int(0)
+    <iter binding = x, index = 1>
+        test Int(1)";
+        assert_eq!(expected.trim(), output.trim());
+
+        {
+            let map = states.get_mut(StateId::ZERO).unwrap();
+            let map = map
+                .to_any_mut()
+                .downcast_mut::<anathema_state::Value<Map<u32>>>()
+                .unwrap();
+            let mut map = map.to_mut();
+            *map.get_mut("n").unwrap().to_mut() = 3;
+        }
+
+        let mut local_changes = Changes::empty();
+        drain_changes(&mut local_changes);
+        local_changes.drain().rev().for_each(|(subs, change)| {
+            subs.with(|sub| {
+                let Some(widget_path) = widget_tree.try_path_ref(sub).map(|path| path.to_vec()) else { return };
+                let mut scope = Scope::with_capacity(10);
+                scope.insert_state(state_id);
+                update_tree(
+                    &globals,
+                    &factory,
+                    &mut scope,
+                    &mut states,
+                    &mut component_registry,
+                    &change,
+                    sub,
+                    &widget_path,
+                    &mut widget_tree,
+                    &mut attribute_storage,
+                    &mut floating_widgets,
+                    &mut components,
+                );
+            });
+        });
+
+        let mut stringify = Stringify::new(&attribute_storage);
+        widget_tree.apply_visitor(&mut stringify);
+        let output = stringify.finish();
+
+        let expected = "
+<for>
+    <iter binding = x, index = 0>
+        test Int(0)
+    <iter binding = x, index = 1>
+        test Int(1)
+    <iter binding = x, index = 2>
+        test Int(2)";
+        assert_eq!(expected.trim(), output.trim());
+    }
+
+    #[test]
+    fn range_start_update() {
+        let mut map = Map::<u32>::empty();
+        map.insert("from", 0u32);
+        map.insert("to", 2u32);
+
+        let tpl = "
+        for x in from..to
+            test x
+        ";
+
+        let (blueprint, globals) = Document::new(tpl).compile().unwrap();
+        let mut widget_tree = WidgetTree::empty();
+        let mut attribute_storage = AttributeStorage::empty();
+        let mut floating_widgets = FloatingWidgets::empty();
+        let factory = setup_test_factory();
+        let mut component_registry = ComponentRegistry::new();
+        let mut components = Components::new();
+        let mut states = States::new();
+        let state_id = states.insert(Box::new(map));
+        let mut scope = Scope::new();
+        scope.insert_state(state_id);
+        let mut ctx = EvalContext::new(
+            &globals,
+            &factory,
+            &mut scope,
+            &mut states,
+            &mut component_registry,
+            &mut attribute_storage,
+            &mut floating_widgets,
+            &mut components,
+        );
+
+        eval_blueprint(&blueprint, &mut ctx, &[], &mut widget_tree).unwrap();
+        let first_iteration = widget_tree.id(&[0, 0]).unwrap();
+
+        {
+            let map = states.get_mut(StateId::ZERO).unwrap();
+            let map = map
+                .to_any_mut()
+                .downcast_mut::<anathema_state::Value<Map<u32>>>()
+                .unwrap();
+            let mut map = map.to_mut();
+            *map.get_mut("from").unwrap().to_mut() = 1;
+            *map.get_mut("to").unwrap().to_mut() = 4;
+        }
+
+        // Renumbering the iterations changes the values in their bodies
+        let mut local_changes = Changes::empty();
+        loop {
+            drain_changes(&mut local_changes);
+            if local_changes.is_empty() {
+                break;
+            }
+
+            local_changes.drain().rev().for_each(|(subs, change)| {
+                subs.with(|sub| {
+                    let Some(widget_path) = widget_tree.try_path_ref(sub).map(|path| path.to_vec()) else { return };
+                    let mut scope = Scope::with_capacity(10);
+                    scope.insert_state(state_id);
+                    update_tree(
+                        &globals,
+                        &factory,
+                        &mut scope,
+                        &mut states,
+                        &mut component_registry,
+                        &change,
+                        sub,
+                        &widget_path,
+                        &mut widget_tree,
+                        &mut attribute_storage,
+                        &mut floating_widgets,
+                        &mut components,
+                    );
+                });
+            });
+        }
+
+        let mut stringify = Stringify::new(&attribute_storage);
+        widget_tree.apply_visitor(&mut stringify);
+        let output = stringify.finish();
+
+        let expected = "
+<for>
+    <iter binding = x, index = 0>
+        test Int(1)
+    <iter binding = x, index = 1>
+        test Int(2)
+    <iter binding = x, index = 2>
+        test Int(3)";
+        assert_eq!(expected.trim(), output.trim());
+
+        // The existing iterations are kept
+        assert_eq!(widget_tree.id(&[0, 0]), Some(first_iteration));
+    }
+
+    #[test]
+    fn range_is_capped() {
+        let range = Collection::Range(
+            EvalValue::Static(CommonVal::Int(i64::MIN)),
+            EvalValue::Static(CommonVal::Int(i64::MAX)),
+        );
+        assert_eq!(range.count(), MAX_RANGE);
+    }
+
+    #[test]
+    fn for_else() {
+        let mut map = Map::<List<u32>>::empty();
+        map.insert("a", List::empty());
+
+        let tpl = "
+        for x in a
+            test x
+        else
+            test \"empty\"
+        ";
+
+        let (blueprint, globals) = Document::new(tpl).compile().unwrap();
+        let mut widget_tree = WidgetTree::empty();
+        let mut attribute_storage = AttributeStorage::empty();
+        let mut floating_widgets = FloatingWidgets::empty();
+        let factory = setup_test_factory();
+        let mut component_registry = ComponentRegistry::new();
+        let mut components = Components::new();
+        let mut states = States::new();
+        let state_id = states.insert(Box::new(map));
+        let mut scope = Scope::new();
+        scope.insert_state(state_id);
+        let mut ctx = EvalContext::new(
+            &globals,
+            &factory,
+            &mut scope,
+            &mut states,
+            &mut component_registry,
+            &mut attribute_storage,
+            &mut floating_widgets,
+            &mut components,
+        );
+
+        eval_blueprint(&blueprint, &mut ctx, &[], &mut widget_tree).unwrap();
+
+        let mut stringify = Stringify::new(&attribute_storage);
+        widget_tree.apply_visitor(&mut stringify);
+        let output = stringify.finish();
+
+        let expected_else = "
+<for>
+    test Str(\"empty\")";
+        assert_eq!(expected_else.trim(), output.trim());
+
+        {
+            let map = states.get_mut(StateId::ZERO).unwrap();
+            let map = map
+                .to_any_mut()
+                .downcast_mut::<anathema_state::Value<Map<List<u32>>>>()
+                .unwrap();
+            let mut map = map.to_mut();
+            let list = map.get_mut("a").unwrap();
+            list.push_back(1);
+        }
+
+        let mut local_changes = Changes::empty();
+        drain_changes(&mut local_changes);
+        local_changes.drain().rev().for_each(|(subs, change)| {
+            subs.with(|sub| {
+                let Some(widget_path) = widget_tree.try_path_ref(sub).map(|path| path.to_vec()) else { return };
+                let mut scope = Scope::with_capacity(10);
+                scope.insert_state(state_id);
+                update_tree(
+                    &globals,
+                    &factory,
+                    &mut scope,
+                    &mut states,
+                    &mut component_registry,
+                    &change,
+                    sub,
+                    &widget_path,
+                    &mut widget_tree,
+                    &mut attribute_storage,
+                    &mut floating_widgets,
+                    &mut components,
+                );
+            });
+        });
+
+        let mut stringify = Stringify::new(&attribute_storage);
+        widget_tree.apply_visitor(&mut stringify);
+        let output = stringify.finish();
+
+        let expected = "
+<for>
+    <iter binding = x, index = 0>
+        test Int(1)";
+        assert_eq!(expected.trim(), output.trim());
+
+        {
+            let map = states.get_mut(StateId::ZERO).unwrap();
+            let map = map
+                .to_any_mut()
+                .downcast_mut::<anathema_state::Value<Map<List<u32>>>>()
+                .unwrap();
+            let mut map = map.to_mut();
+            let list = map.get_mut("a").unwrap();
+            list.remove(0);
+        }
+
+        let mut local_changes = Changes::empty();
+        drain_changes(&mut local_changes);
+        local_changes.drain().rev().for_each(|(subs, change)| {
+            subs.with(|sub| {
+                let Some(widget_path) = widget_tree.try_path_ref(sub).map(|path| path.to_vec()) else { return };
+                let mut scope = Scope::with_capacity(10);
+                scope.insert_state(state_id);
+                update_tree(
+                    &globals,
+                    &factory,
+                    &mut scope,
+                    &mut states,
+                    &mut component_registry,
+                    &change,
+                    sub,
+                    &widget_path,
+                    &mut widget_tree,
+                    &mut attribute_storage,
+                    &mut floating_widgets,
+                    &mut components,
+                );
+            });
+        });
+
+        let mut stringify = Stringify::new(&attribute_storage);
+        widget_tree.apply_visitor(&mut stringify);
+        let output = stringify.finish();
+
+        assert_eq!(expected_else.trim(), output.trim());
+    }
 }
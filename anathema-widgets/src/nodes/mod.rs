@@ -2,7 +2,7 @@ use anathema_templates::blueprints::Blueprint;
 
 pub use self::component::ExternalState;
 pub use self::element::Element;
-use self::eval::{ComponentEval, ControlFlowEval, EvalContext, Evaluator, ForLoopEval, SingleEval};
+use self::eval::{ComponentEval, ControlFlowEval, EvalContext, Evaluator, ForLoopEval, SingleEval, SwitchEval};
 pub use self::future::try_resolve_future_values;
 pub use self::stringify::Stringify;
 pub use self::update::update_tree;
@@ -26,6 +26,8 @@ pub enum WidgetKind<'bp> {
     ControlFlow(controlflow::ControlFlow),
     If(controlflow::If<'bp>),
     Else(controlflow::Else<'bp>),
+    Switch(controlflow::Switch<'bp>),
+    Case(controlflow::Case<'bp>),
     Component(component::Component<'bp>),
 }
 
@@ -39,6 +41,7 @@ pub fn eval_blueprint<'bp>(
         Blueprint::Single(single) => SingleEval.eval(single, ctx, parent, tree),
         Blueprint::For(for_loop) => ForLoopEval.eval(for_loop, ctx, parent, tree),
         Blueprint::ControlFlow(flow) => ControlFlowEval.eval(flow, ctx, parent, tree),
+        Blueprint::Switch(switch) => SwitchEval.eval(switch, ctx, parent, tree),
         Blueprint::Component(component) => ComponentEval.eval(component, ctx, parent, tree),
     }
 }
@@ -49,6 +52,7 @@ mod test {
     use anathema_templates::{Expression, Globals};
 
     use crate::expressions::eval_collection;
+    use crate::functions::Functions;
     use crate::scope::ScopeLookup;
     use crate::values::ValueId;
     use crate::Scope;
@@ -61,6 +65,7 @@ mod test {
         let mut states = States::new();
         let mut scope = Scope::new();
         let globals = Globals::new(Default::default());
+        let functions = Functions::new();
 
         // Setup state to contain a list mapped to the key "list"
         let mut state = Map::<List<_>>::empty();
@@ -79,7 +84,7 @@ mod test {
 
         // Here we are associating the `val` path with the collection, which
         // is either a slice of expressions or a `PendingValue`.
-        let collection = eval_collection(&list_expr, &globals, &scope, &states, for_key);
+        let collection = eval_collection(&list_expr, &globals, &functions, &scope, &states, for_key);
 
         // Next up the value would be scoped per iteraton, so `val` is pulled out
         // of the collection by an index, and the resulting value
@@ -112,6 +117,7 @@ mod test {
         let mut states = States::new();
         let mut scope = Scope::new();
         let globals = Globals::new(Default::default());
+        let functions = Functions::new();
 
         // Setup state to contain a list mapped to the key "list"
         let mut state = Map::<List<_>>::empty();
@@ -129,7 +135,7 @@ mod test {
         let list_expr = Expression::Ident("list".into());
         let for_key = Subscriber::ZERO;
 
-        let collection = eval_collection(&lists_expr, &globals, &scope, &states, for_key);
+        let collection = eval_collection(&lists_expr, &globals, &functions, &scope, &states, for_key);
 
         for index in 0..1 {
             scope.push();
@@ -141,7 +147,7 @@ mod test {
 
             // Next up the value would be scoped per iteraton, so `val` is scoped to `(list, index)`
             for index in 0..2 {
-                let collection = eval_collection(&list_expr, &globals, &scope, &states, for_key);
+                let collection = eval_collection(&list_expr, &globals, &functions, &scope, &states, for_key);
                 scope.push();
                 collection.scope(&mut scope, "val", index);
 
@@ -69,10 +69,14 @@ impl<'a, 'bp> NodeVisitor<WidgetKind<'_>> for Stringify<'a, 'bp> {
             WidgetKind::Iteration(iteration) => {
                 let _ = write!(
                     &mut self.output,
-                    "<iter binding = {}, index = {}>",
+                    "<iter binding = {}, index = {}",
                     iteration.binding,
                     iteration.loop_index.copy_value()
                 );
+                if let Some(key) = &iteration.key {
+                    let _ = write!(&mut self.output, ", key = {}", &*key.to_ref());
+                }
+                let _ = write!(&mut self.output, ">");
             }
             WidgetKind::ControlFlow(_) => {
                 let _ = write!(&mut self.output, "<control flow>");
@@ -86,6 +90,23 @@ impl<'a, 'bp> NodeVisitor<WidgetKind<'_>> for Stringify<'a, 'bp> {
                 }
                 None => drop(write!(&mut self.output, "<else>")),
             },
+            WidgetKind::Switch(switch) => {
+                let value = switch.value.load_common_val();
+                match value.as_ref().and_then(|value| value.to_common()) {
+                    Some(value) => drop(write!(&mut self.output, "<switch value = {value}>")),
+                    None => drop(write!(&mut self.output, "<switch>")),
+                }
+            }
+            WidgetKind::Case(case) => {
+                let value = case.value.as_ref().and_then(|value| value.load_common_val());
+                match (&case.value, value.as_ref().and_then(|value| value.to_common())) {
+                    (None, _) => drop(write!(&mut self.output, "<default show = {}>", case.show)),
+                    (Some(_), Some(value)) => {
+                        let _ = write!(&mut self.output, "<case value = {value}, show = {}>", case.show);
+                    }
+                    (Some(_), None) => drop(write!(&mut self.output, "<case show = {}>", case.show)),
+                }
+            }
             WidgetKind::Component(_) => drop(write!(&mut self.output, "<component>")),
         }
 
@@ -4,7 +4,6 @@ use anathema_templates::Globals;
 
 use super::element::Element;
 use super::eval::EvalContext;
-use super::loops::LOOP_INDEX;
 use crate::components::ComponentRegistry;
 use crate::error::Result;
 use crate::values::ValueId;
@@ -104,7 +103,7 @@ fn update_widget<'bp>(
             if let Change::Dropped | Change::Changed = change {
                 let attributes = ctx.attribute_storage.get_mut(value_id.key());
                 if let Some(value) = attributes.get_mut_with_index(value_id.index()) {
-                    value.reload_val(value_id, ctx.globals, ctx.scope, ctx.states);
+                    value.reload_val(value_id, ctx.globals, ctx.factory.functions(), ctx.scope, ctx.states);
                 }
             }
         }
@@ -116,11 +115,12 @@ fn update_widget<'bp>(
         // branches.
         WidgetKind::ControlFlow(_) => unreachable!("update is never called on ControlFlow, only the children"),
         WidgetKind::If(_) | WidgetKind::Else(_) => (), // If / Else are not updated by themselves
-        WidgetKind::Component(_) => {
-            if let Change::Dropped = change {
-                ctx.components.remove(path);
-            }
-        }
+        // The switch shows the matching case during layout
+        WidgetKind::Switch(_) | WidgetKind::Case(_) => (),
+        WidgetKind::Component(_) => match change {
+            Change::Dropped => ctx.components.remove(path),
+            _ => ctx.components.external_changed(value_id.key(), value_id.index()),
+        },
     }
 
     Ok(())
@@ -128,16 +128,15 @@ fn update_widget<'bp>(
 
 pub(super) fn scope_value<'bp>(widget: &WidgetKind<'bp>, scope: &mut Scope<'bp>, children: &[u16]) {
     match widget {
-        WidgetKind::For(for_loop) => match children {
-            [next, ..] => {
+        // The children of a loop showing its else body are not iterations
+        WidgetKind::For(for_loop) if for_loop.shows_else => {}
+        WidgetKind::For(for_loop) => {
+            if let [next, ..] = children {
                 let index = *next as usize;
                 for_loop.collection.scope(scope, for_loop.binding, index);
             }
-            [] => {}
-        },
-        WidgetKind::Iteration(iter) => {
-            scope.scope_pending(LOOP_INDEX, iter.loop_index.to_pending());
         }
+        WidgetKind::Iteration(iter) => iter.scope(scope),
         WidgetKind::Component(component) => {
             if let Some(state) = &component.external_state {
                 for (k, (_, v)) in state.iter() {
@@ -149,6 +148,11 @@ pub(super) fn scope_value<'bp>(widget: &WidgetKind<'bp>, scope: &mut Scope<'bp>,
             let state_id = component.state_id();
             scope.insert_state(state_id);
         }
-        WidgetKind::ControlFlow(_) | WidgetKind::Element(Element { .. }) | WidgetKind::If(_) | WidgetKind::Else(_) => {}
+        WidgetKind::ControlFlow(_)
+        | WidgetKind::Element(Element { .. })
+        | WidgetKind::If(_)
+        | WidgetKind::Else(_)
+        | WidgetKind::Switch(_)
+        | WidgetKind::Case(_) => {}
     }
 }
@@ -62,6 +62,7 @@ impl<'frame, 'bp> TreeFilter for PaintFilter<'frame, 'bp> {
             },
             WidgetKind::If(widget) if !widget.show => ControlFlow::Break(()),
             WidgetKind::Else(widget) if !widget.show => ControlFlow::Break(()),
+            WidgetKind::Case(widget) if !widget.show => ControlFlow::Break(()),
             _ => ControlFlow::Continue(None),
         }
     }
@@ -227,6 +228,23 @@ impl<'screen> PaintCtx<'screen, SizePos> {
         self.surface.set_attributes(attrs, screen_pos);
     }
 
+    /// Place the terminal cursor at a position in local space.
+    /// The cursor is not placed if the position is clipped or outside the screen.
+    pub fn set_cursor(&mut self, pos: LocalPos) {
+        if let Some(clip) = self.clip.as_ref() {
+            if !self.clip(pos, clip) {
+                return;
+            }
+        }
+
+        if !self.pos_inside_local_region(pos, 1) {
+            return;
+        }
+
+        let Some(screen_pos) = self.translate_to_global(pos) else { return };
+        self.surface.set_cursor(screen_pos);
+    }
+
     // Place a char on the screen buffer, return the next cursor position in local space.
     //
     // The `input_pos` is the position, in local space, where the character
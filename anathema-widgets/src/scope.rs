@@ -230,6 +230,7 @@ mod test {
 
     use super::*;
     use crate::expressions::eval_collection;
+    use crate::functions::Functions;
     use crate::testing::ScopedTest;
 
     #[test]
@@ -251,7 +252,7 @@ mod test {
         let scope = Scope::new();
         let expr = Expression::Ident("list".into());
         let globals = Globals::new(Default::default());
-        eval_collection(&expr, &globals, &scope, &states, ValueId::ZERO);
+        eval_collection(&expr, &globals, &Functions::new(), &scope, &states, ValueId::ZERO);
 
         //         let one = [Expression::Primitive(1i64.into())];
 
@@ -6,6 +6,7 @@ use anathema_store::tree::TreeForEach;
 use anathema_templates::{Expression, Globals};
 
 use crate::expressions::{eval, EvalValue};
+use crate::functions::Functions;
 use crate::layout::{Constraints, LayoutCtx, LayoutFilter, PositionCtx};
 use crate::scope::{Scope, ScopeLookup};
 use crate::values::{ValueId, ValueIndex};
@@ -79,7 +80,8 @@ impl<T: 'static + State> ScopedTest<T, WithExpr> {
         let mut scope = Scope::new();
         let globals = Globals::new(Default::default());
         scope.insert_state(StateId::ZERO);
-        let value = eval(&self.test_state.0, &globals, &scope, &self.states, value_id);
+        let functions = Functions::new();
+        let value = eval(&self.test_state.0, &globals, &functions, &scope, &self.states, value_id);
         f(value)
     }
 }
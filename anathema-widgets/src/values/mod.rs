@@ -1,6 +1,7 @@
 use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
 
-use anathema_state::ValueRef;
+use anathema_state::{PendingValue, ValueRef};
 use anathema_store::smallmap::{SmallIndex, SmallMap};
 use anathema_templates::Expression;
 
@@ -12,6 +13,10 @@ pub(crate) type ValueId = anathema_state::Subscriber;
 pub type ValueIndex = SmallIndex;
 pub type Values<'bp> = SmallMap<ValueKey<'bp>, Value<'bp, EvalValue<'bp>>>;
 
+/// The most iterations a range can have, as the children of a node are indexed by `u16`.
+/// Any range larger than this is cut short.
+pub(crate) const MAX_RANGE: usize = u16::MAX as usize;
+
 /// A value that can be re-evaluated in the future.
 ///
 /// A widget may contain a value that doesn't yet exist but may exist
@@ -62,6 +67,7 @@ impl<'bp> Value<'bp, EvalValue<'bp>> {
         &mut self,
         id: ValueId,
         globals: &'bp anathema_templates::Globals,
+        functions: &crate::functions::Functions,
         scope: &Scope<'bp>,
         states: &anathema_state::States,
     ) {
@@ -69,7 +75,7 @@ impl<'bp> Value<'bp, EvalValue<'bp>> {
             return;
         }
         let Some(expr) = self.expr else { return };
-        let Value { inner, .. } = crate::expressions::eval(expr, globals, scope, states, id);
+        let Value { inner, .. } = crate::expressions::eval(expr, globals, functions, scope, states, id);
         self.inner = inner;
     }
 }
@@ -111,6 +117,14 @@ pub(crate) enum Collection<'bp> {
     ///     text x
     /// ```
     Static(Box<[EvalValue<'bp>]>),
+    /// A range of numbers, excluding the end, of at most [`MAX_RANGE`] numbers.
+    /// The bounds can be state values, in which case
+    /// the collection changes along with them.
+    /// ```text
+    /// for x in 0..count
+    ///     text x
+    /// ```
+    Range(EvalValue<'bp>, EvalValue<'bp>),
     /// This will (probably) resolve to a collection from a state.
     Dyn(ValueRef),
     /// Index value.
@@ -125,18 +139,54 @@ impl<'bp> Collection<'bp> {
     pub(crate) fn count(&self) -> usize {
         match self {
             Self::Static(e) => e.len(),
+            Self::Range(from, to) => match (from.load_number(), to.load_number()) {
+                (Some(from), Some(to)) => to.as_int().saturating_sub(from.as_int()).clamp(0, MAX_RANGE as i64) as usize,
+                _ => 0,
+            },
             Self::Dyn(value_ref) => value_ref.as_state().map(|state| state.count()).unwrap_or(0),
             Self::Index(collection, _) => collection.count(),
             Self::Future => 0,
         }
     }
 
+    /// The key of the entry at the index, if the collection is a map
+    pub(crate) fn key(&self, index: usize) -> Option<Rc<str>> {
+        match self {
+            Self::Dyn(value_ref) => value_ref.as_state()?.state_key(index),
+            Self::Index(collection, _) => collection.key(index),
+            Self::Static(_) | Self::Range(..) | Self::Future => None,
+        }
+    }
+
+    /// The number at the index, if the collection is a range
+    pub(crate) fn number(&self, index: usize) -> Option<i64> {
+        match self {
+            Self::Range(from, _) => Some(from.load_number()?.as_int().saturating_add(index as i64)),
+            Self::Index(collection, _) => collection.number(index),
+            Self::Static(_) | Self::Dyn(_) | Self::Future => None,
+        }
+    }
+
+    /// The key of the entry holding the value, if the collection is a map.
+    ///
+    /// The index of an inserted value can not be used to find the key,
+    /// as the collection might have changed again since the value was inserted.
+    pub(crate) fn key_of(&self, value: PendingValue) -> Option<Rc<str>> {
+        match self {
+            Self::Dyn(value_ref) => value_ref.as_state()?.state_key_of(value),
+            Self::Index(collection, _) => collection.key_of(value),
+            Self::Static(_) | Self::Range(..) | Self::Future => None,
+        }
+    }
+
     pub(crate) fn scope(&self, scope: &mut Scope<'bp>, binding: &'bp str, index: usize) {
         match self {
             Collection::Static(expressions) => {
                 let downgrade = expressions[index].downgrade();
                 scope.scope_downgrade(binding, downgrade);
             }
+            // The iteration holds the number, so it can change along with the bounds
+            Collection::Range(..) => {}
             Collection::Dyn(value_ref) => {
                 let value = value_ref
                     .as_state()
@@ -1,26 +1,107 @@
 use std::collections::HashMap;
 
-use super::{AnyWidget, Widget};
+use anathema_templates::blueprints::Single;
+use anathema_templates::diagnostics::{Attribute, WidgetSchema};
+
+use super::{AnyWidget, Widget, COMMON_ATTRIBUTES};
 use crate::error::{Error, Result};
+use crate::expressions::EvalValue;
+use crate::functions::{FunctionResult, FunctionValue, Functions};
 use crate::Attributes;
 
-pub struct Factory(HashMap<Box<str>, Box<dyn Fn(&Attributes<'_>) -> Box<dyn AnyWidget>>>);
+pub struct Factory {
+    widgets: HashMap<Box<str>, Box<dyn Fn(&Attributes<'_>) -> Box<dyn AnyWidget>>>,
+    attributes: HashMap<Box<str>, Vec<Attribute>>,
+    docs: HashMap<Box<str>, &'static str>,
+    functions: Functions,
+}
 
 impl Factory {
     pub fn new() -> Self {
-        Self(HashMap::new())
+        Self {
+            widgets: HashMap::new(),
+            attributes: HashMap::new(),
+            docs: HashMap::new(),
+            functions: Functions::new(),
+        }
     }
 
-    pub(crate) fn make(&self, ident: &str, attribs: &Attributes<'_>) -> Result<Box<dyn AnyWidget>> {
-        let f = self.0.get(ident).ok_or(Error::InvalidElement(ident.to_string()))?;
+    pub(crate) fn make(&self, single: &Single, attribs: &Attributes<'_>) -> Result<Box<dyn AnyWidget>> {
+        let f = self.widgets.get(&*single.ident).ok_or_else(|| Error::InvalidElement {
+            ident: single.ident.to_string(),
+            location: single.location.clone(),
+        })?;
         Ok((f)(attribs))
     }
 
     pub fn register_widget(&mut self, ident: &str, factory: impl Fn(&Attributes<'_>) -> Box<dyn AnyWidget> + 'static) {
-        self.0.insert(ident.into(), Box::new(factory));
+        self.widgets.insert(ident.into(), Box::new(factory));
     }
 
+    /// Register a widget made with `Default`,
+    /// along with its [`attributes`](Widget::attributes).
     pub fn register_default<W: 'static + Widget + Default>(&mut self, ident: &str) {
-        self.0.insert(ident.into(), Box::new(|_| Box::<W>::default()));
+        self.widgets.insert(ident.into(), Box::new(|_| Box::<W>::default()));
+        if let Some(attributes) = W::attributes() {
+            self.register_attributes(ident, attributes);
+        }
+    }
+
+    /// Register the attributes a widget accepts, in addition to the [`COMMON_ATTRIBUTES`].
+    /// Calling this more than once for the same widget adds to the existing attributes.
+    ///
+    /// This is only used to check templates (see [`Document::check`](anathema_templates::Document::check)),
+    /// attributes of widgets without any registered attributes are not checked.
+    pub fn register_attributes(&mut self, ident: &str, attributes: &[Attribute]) {
+        self.attributes
+            .entry(ident.into())
+            .or_insert_with(|| COMMON_ATTRIBUTES.to_vec())
+            .extend_from_slice(attributes);
+    }
+
+    /// Register documentation for a widget, shown by editors when hovering the widget in a template.
+    pub fn register_docs(&mut self, ident: &str, docs: &'static str) {
+        self.docs.insert(ident.into(), docs);
+    }
+
+    /// The documentation of a widget, if any was registered
+    pub fn docs(&self, ident: &str) -> Option<&'static str> {
+        self.docs.get(ident).copied()
+    }
+
+    /// The names of all registered widgets
+    pub fn widgets(&self) -> impl Iterator<Item = &str> {
+        self.widgets.keys().map(|ident| &**ident)
+    }
+
+    /// Register a function that can be called from templates.
+    /// This will replace any existing function with the same name,
+    /// including the built-in functions.
+    pub fn register_function(&mut self, ident: &str, f: impl Fn(&[EvalValue<'_>]) -> Option<FunctionValue> + 'static) {
+        self.functions.register(ident, f);
+    }
+
+    /// Register a function that can fail, see [`Functions::register_fallible`].
+    pub fn register_fallible_function(
+        &mut self,
+        ident: &str,
+        f: impl Fn(&[EvalValue<'_>]) -> FunctionResult + 'static,
+    ) {
+        self.functions.register_fallible(ident, f);
+    }
+
+    /// The functions available to templates
+    pub fn functions(&self) -> &Functions {
+        &self.functions
+    }
+}
+
+impl WidgetSchema for Factory {
+    fn has_widget(&self, ident: &str) -> bool {
+        self.widgets.contains_key(ident)
+    }
+
+    fn attributes(&self, ident: &str) -> Option<&[Attribute]> {
+        self.attributes.get(ident).map(Vec::as_slice)
     }
 }
@@ -0,0 +1,120 @@
+use anathema_geometry::{Pos, Region};
+use anathema_store::tree::{AsNodePath, Node, TreeValues};
+
+use crate::layout::Display;
+use crate::nodes::element::Element;
+use crate::{AttributeStorage, FloatingWidgets, WidgetId, WidgetKind, WidgetTree};
+
+/// Find the topmost component under a given position.
+///
+/// The widgets are tested in reverse paint order: first the floating widgets,
+/// starting with the last one painted, then the rest of the tree from the last
+/// child to the first.
+/// The first element that contains the position is the topmost one,
+/// and the closest component above that element is the one that is returned.
+/// An element that is not inside a component still covers anything below it.
+pub fn component_at(
+    pos: Pos,
+    tree: &mut WidgetTree<'_>,
+    attribute_storage: &AttributeStorage<'_>,
+    floating_widgets: &FloatingWidgets,
+) -> Option<WidgetId> {
+    // Floating widgets are painted in the order they are stored
+    let floating = floating_widgets.iter().copied().collect::<Vec<_>>();
+    for widget_id in floating.into_iter().rev() {
+        let component = parent_component(tree, widget_id);
+        let mut hit = None;
+        // The floating widget itself is only a placement and covers nothing,
+        // only its children are tested
+        tree.with_nodes_and_values(widget_id, |_, children, values| {
+            hit = hit_test(pos, children, values, attribute_storage, component);
+        });
+
+        if let Some(hit) = hit {
+            return hit;
+        }
+    }
+
+    let (nodes, values) = tree.split();
+    hit_test(pos, nodes, values, attribute_storage, None).flatten()
+}
+
+// Returns `Some` if an element contains the position, with the component
+// that element belongs to (if any).
+fn hit_test(
+    pos: Pos,
+    nodes: &[Node],
+    values: &TreeValues<WidgetKind<'_>>,
+    attribute_storage: &AttributeStorage<'_>,
+    component: Option<WidgetId>,
+) -> Option<Option<WidgetId>> {
+    for node in nodes.iter().rev() {
+        let Some((_, widget)) = values.get(node.value()) else { continue };
+
+        match widget {
+            WidgetKind::Element(el) => {
+                // Floating widgets are tested before the rest of the tree
+                // as they are painted on top
+                if el.container.inner.any_floats() {
+                    continue;
+                }
+
+                // Children are painted after their parent
+                let hit = hit_test(pos, node.children(), values, attribute_storage, component);
+                if hit.is_some() {
+                    return hit;
+                }
+
+                if contains(pos, el, attribute_storage) {
+                    return Some(component);
+                }
+                continue;
+            }
+            WidgetKind::If(widget) if !widget.show => continue,
+            WidgetKind::Else(widget) if !widget.show => continue,
+            WidgetKind::Case(widget) if !widget.show => continue,
+            _ => (),
+        }
+
+        let component = match widget {
+            WidgetKind::Component(_) => Some(node.value()),
+            _ => component,
+        };
+
+        let hit = hit_test(pos, node.children(), values, attribute_storage, component);
+        if hit.is_some() {
+            return hit;
+        }
+    }
+
+    None
+}
+
+// Hidden elements and elements that are yet to be laid out
+// can not be under the cursor.
+fn contains(pos: Pos, el: &Element<'_>, attribute_storage: &AttributeStorage<'_>) -> bool {
+    if el.container.needs_layout || el.container.needs_position {
+        return false;
+    }
+
+    let display = attribute_storage
+        .get(el.id())
+        .get::<Display>("display")
+        .unwrap_or_default();
+
+    match display {
+        Display::Show => Region::from((el.container.pos, el.container.size)).contains(pos),
+        Display::Hide | Display::Exclude => false,
+    }
+}
+
+fn parent_component(tree: &WidgetTree<'_>, widget_id: WidgetId) -> Option<WidgetId> {
+    let mut parent = tree.path_ref(widget_id).parent();
+    while let Some(path) = parent {
+        if let Some(WidgetKind::Component(_)) = tree.get_ref_by_path(path) {
+            return tree.id(path);
+        }
+        parent = path.parent();
+    }
+    None
+}
@@ -8,13 +8,15 @@ pub type WidgetId = anathema_store::slab::Key;
 use anathema_geometry::{Pos, Rect, Size};
 use anathema_state::StateId;
 use anathema_store::slab::SecondaryMap;
-use anathema_store::smallmap::SmallMap;
+use anathema_store::smallmap::{SmallIndex, SmallMap};
 use anathema_store::sorted::SortedList;
 use anathema_store::tree::{NodeWalker, Tree, TreeForEach};
+use anathema_templates::diagnostics::{Attribute, AttributeType};
 use anathema_templates::WidgetComponentId;
 
 pub use self::attributes::{AttributeStorage, Attributes};
 pub use self::factory::Factory;
+pub use self::hit_test::component_at;
 pub use self::query::Elements;
 use crate::layout::{Constraints, LayoutCtx, LayoutFilter, PositionCtx};
 use crate::paint::{CellAttributes, PaintCtx, PaintFilter, SizePos};
@@ -22,6 +24,7 @@ use crate::WidgetKind;
 
 mod attributes;
 mod factory;
+mod hit_test;
 mod query;
 
 #[derive(Debug)]
@@ -34,7 +37,7 @@ pub struct CompEntry {
 
 impl PartialOrd for CompEntry {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.path.cmp(&other.path))
+        Some(self.cmp(other))
     }
 }
 
@@ -56,6 +59,8 @@ pub struct Components {
     pub tab_index: usize,
     inner: SortedList<CompEntry>,
     comp_ids: SmallMap<WidgetComponentId, usize>,
+    // External state values that were set when the component was created, or that changed since
+    external_changes: Vec<(WidgetId, SmallIndex)>,
 }
 
 impl Components {
@@ -64,6 +69,7 @@ impl Components {
             tab_index: 0,
             inner: SortedList::empty(),
             comp_ids: SmallMap::empty(),
+            external_changes: vec![],
         }
     }
 
@@ -98,6 +104,10 @@ impl Components {
         self.inner.get(*index)
     }
 
+    pub fn get_by_widget_id(&mut self, id: WidgetId) -> Option<&CompEntry> {
+        self.inner.iter().find(|entry| entry.widget_id == id)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &CompEntry> {
         self.inner.iter()
     }
@@ -106,6 +116,17 @@ impl Components {
         self.inner.len()
     }
 
+    pub(crate) fn external_changed(&mut self, widget_id: WidgetId, index: SmallIndex) {
+        self.external_changes.push((widget_id, index));
+    }
+
+    /// Take the external state values that were set when a component
+    /// was created, or that changed since the last call.
+    /// The index is the index of the value in the external state of the component.
+    pub fn take_external_changes(&mut self) -> Vec<(WidgetId, SmallIndex)> {
+        std::mem::take(&mut self.external_changes)
+    }
+
     pub fn dodgy_remove(&mut self, widget_id: WidgetId) {
         let Some(index) = self.inner.iter().position(|entry| entry.widget_id == widget_id) else { return };
         let entry = self.inner.remove(index);
@@ -363,8 +384,36 @@ pub trait Widget {
     fn needs_reflow(&self) -> bool {
         false
     }
+
+    /// The attributes the widget reads, in addition to the [`COMMON_ATTRIBUTES`].
+    ///
+    /// This is only used to check templates (see [`Document::check`](anathema_templates::Document::check)).
+    /// The attributes of a widget that returns `None` are not checked.
+    fn attributes() -> Option<&'static [Attribute]>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
+/// The attributes of every widget:
+/// the style used when the widget is painted, whether the widget is displayed,
+/// and the `id` used to find the widget (e.g. with `set_focus`).
+pub const COMMON_ATTRIBUTES: &[Attribute] = &[
+    Attribute::new("foreground", AttributeType::Color),
+    Attribute::new("background", AttributeType::Color),
+    Attribute::new("bold", AttributeType::Bool),
+    Attribute::new("dim", AttributeType::Bool),
+    Attribute::new("italic", AttributeType::Bool),
+    Attribute::new("underline", AttributeType::Bool),
+    Attribute::new("crossed-out", AttributeType::Bool),
+    Attribute::new("overline", AttributeType::Bool),
+    Attribute::new("inverse", AttributeType::Bool),
+    Attribute::new("display", AttributeType::OneOf(&["show", "hide", "exclude"])),
+    Attribute::new("id", AttributeType::Any),
+];
+
 impl Debug for dyn Widget {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "<dyn Widget>")
@@ -377,4 +426,8 @@ pub trait WidgetRenderer {
     fn set_attributes(&mut self, attribs: &dyn CellAttributes, local_pos: Pos);
 
     fn size(&self) -> Size;
+
+    /// Set the position of the terminal cursor for the current frame.
+    /// The cursor is hidden unless a widget sets a position.
+    fn set_cursor(&mut self, _pos: Pos) {}
 }
@@ -33,6 +33,15 @@ impl<'tree, 'bp> Elements<'tree, 'bp> {
         }
     }
 
+    pub(crate) fn reborrow(&mut self) -> Elements<'_, 'bp> {
+        Elements {
+            nodes: self.nodes,
+            widgets: self.widgets,
+            attributes: self.attributes,
+            dirty_widgets: self.dirty_widgets,
+        }
+    }
+
     pub fn at_position(&mut self, pos: impl Into<Pos>) -> Query<'_, 'tree, 'bp, Kind<'_>> {
         Query {
             filter: Kind::AtPosition(pos.into()),
@@ -106,7 +115,8 @@ where
             dirty_widgets: self.elements.dirty_widgets,
         };
 
-        apply_visitor(self.elements.nodes, self.elements.widgets, &mut run);
+        // The traversal is stopped after the first match, unless it's continuous
+        let _ = apply_visitor(self.elements.nodes, self.elements.widgets, &mut run);
     }
 
     pub fn each<T>(self, f: T)
@@ -236,7 +246,7 @@ where
                 }
 
                 if !self.continuous {
-                    return ControlFlow::Break(false);
+                    return ControlFlow::Break(true);
                 }
             }
         }
@@ -0,0 +1,39 @@
+use anathema_state::{CommonVal, Path, PendingValue, State, Subscriber, Value, ValueRef};
+use run::TestCase;
+mod run;
+
+struct TestState {
+    name: Value<String>,
+}
+
+impl State for TestState {
+    fn to_common(&self) -> Option<CommonVal<'_>> {
+        None
+    }
+
+    fn state_get(&self, path: Path<'_>, sub: Subscriber) -> Option<ValueRef> {
+        match path {
+            Path::Key("name") => Some(self.name.value_ref(sub)),
+            _ => None,
+        }
+    }
+
+    fn state_lookup(&self, path: Path<'_>) -> Option<PendingValue> {
+        match path {
+            Path::Key("name") => Some(self.name.to_pending()),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn filter_pipeline() {
+    let state = TestState {
+        name: String::from("hello world").into(),
+    };
+    TestCase::setup("test name | upper | truncate(6)")
+        .build(state)
+        .expect_frame("test Str(\"HELLO…\")")
+        .with_state(0, |state| *state.name.to_mut() = String::from("bye"))
+        .expect_frame("test Str(\"BYE\")");
+}
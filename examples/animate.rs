@@ -1,4 +1,3 @@
-use std::f64::consts::PI;
 use std::time::Duration;
 
 use anathema::component::*;
@@ -12,34 +11,45 @@ struct Num {
 }
 
 struct C {
-    val: f64,
+    animation: Option<AnimationHandle>,
 }
 
-fn ease_in_out(val: f64) -> f64 {
-    let x = PI * val;
-    let x = x.cos() - 1.0;
-    -(x / 2.0)
+impl C {
+    // Move back and forth across the screen, where `speed` is the number of sweeps per second
+    fn start(&mut self, state: &mut Num, context: &mut Context<'_, C>) {
+        if let Some(animation) = self.animation.take() {
+            animation.cancel();
+        }
+
+        let width = context.viewport.size().width.saturating_sub(8) as f64;
+        let sweep = Duration::from_secs_f64(1.0 / *state.speed.to_ref());
+        let animation = Animation::new(width, sweep)
+            .easing(Easing::EaseInOut)
+            .then(0.0, sweep)
+            .easing(Easing::EaseInOut)
+            .repeat(Repeat::Forever);
+
+        state.x.set(0.0);
+        self.animation = Some(context.animate(|state: &mut Num| &mut state.x, animation));
+    }
 }
 
 impl Component for C {
     type Message = ();
     type State = Num;
 
-    fn tick(&mut self, state: &mut Self::State, _: Elements<'_, '_>, context: Context<'_, Self::State>, dt: Duration) {
-        let x = dt.as_millis() as f64;
-
-        self.val += x / 1000.0 * *state.speed.to_ref();
-        let x = ease_in_out(self.val) * (context.viewport.size().width - 8) as f64;
-        state.x.set(x);
+    fn on_focus(&mut self, state: &mut Self::State, _: Elements<'_, '_>, mut context: Context<'_, Self>) {
+        self.start(state, &mut context);
     }
 
-    fn on_key(&mut self, key: KeyEvent, state: &mut Self::State, _: Elements<'_, '_>, _: Context<'_, Self::State>) {
+    fn on_key(&mut self, key: KeyEvent, state: &mut Self::State, _: Elements<'_, '_>, mut context: Context<'_, Self>) {
         if matches!(key.state, KeyState::Press) {
             match key.code {
                 KeyCode::Char('k') => *state.speed.to_mut() += 0.1,
-                KeyCode::Char('j') => *state.speed.to_mut() -= 0.1,
-                _ => {}
+                KeyCode::Char('j') if *state.speed.to_ref() > 0.15 => *state.speed.to_mut() -= 0.1,
+                _ => return,
             }
+            self.start(state, &mut context);
         }
     }
 }
@@ -59,14 +69,14 @@ fn main() {
         .register_component(
             "main",
             "examples/templates/animate/animate.aml",
-            C { val: 0.0 },
+            C { animation: None },
             Num {
                 x: 0.0.into(),
-                speed: 0.1.into(),
+                speed: 0.5.into(),
             },
         )
         .unwrap();
 
     let mut runtime = runtime.finish().unwrap();
-    runtime.run();
+    runtime.run().unwrap();
 }
@@ -23,7 +23,7 @@ impl Component for App {
         _value: CommonVal<'_>,
         state: &mut Self::State,
         _elements: Elements<'_, '_>,
-        _context: Context<'_, Self::State>,
+        _context: Context<'_, Self>,
     ) {
         if ident == "increment" {
             *state.number.to_mut() += 1;
@@ -49,11 +49,11 @@ impl Component for Button {
     type Message = ();
     type State = ButtonState;
 
-    fn on_blur(&mut self, state: &mut Self::State, _elements: Elements<'_, '_>, _context: Context<'_, Self::State>) {
+    fn on_blur(&mut self, state: &mut Self::State, _elements: Elements<'_, '_>, _context: Context<'_, Self>) {
         state.in_focus.set(false);
     }
 
-    fn on_focus(&mut self, state: &mut Self::State, _elements: Elements<'_, '_>, _context: Context<'_, Self::State>) {
+    fn on_focus(&mut self, state: &mut Self::State, _elements: Elements<'_, '_>, _context: Context<'_, Self>) {
         state.in_focus.set(true);
     }
 
@@ -62,7 +62,7 @@ impl Component for Button {
         key: KeyEvent,
         _state: &mut Self::State,
         _elements: Elements<'_, '_>,
-        mut context: Context<'_, Self::State>,
+        mut context: Context<'_, Self>,
     ) {
         if matches!(key.state, KeyState::Press) {
             if let KeyCode::Enter = key.code {
@@ -106,5 +106,5 @@ fn main() {
         .unwrap();
 
     let mut runtime = runtime.finish().unwrap();
-    runtime.run();
+    runtime.run().unwrap();
 }
@@ -25,7 +25,7 @@ impl Component for Index {
         mouse: MouseEvent,
         _state: &mut Self::State,
         mut elements: Elements<'_, '_>,
-        context: Context<'_, Self::State>,
+        context: Context<'_, Self>,
     ) {
         if mouse.lsb_down() {
             elements
@@ -54,13 +54,7 @@ impl Component for Messages {
     type Message = String;
     type State = MessagesState;
 
-    fn message(
-        &mut self,
-        message: Self::Message,
-        state: &mut Self::State,
-        _: Elements<'_, '_>,
-        _: Context<'_, Self::State>,
-    ) {
+    fn message(&mut self, message: Self::Message, state: &mut Self::State, _: Elements<'_, '_>, _: Context<'_, Self>) {
         if state.messages.len() > 20 {
             state.messages.pop_front();
         }
@@ -95,5 +89,5 @@ fn main() {
         )
         .expect("failed to register index component");
 
-    runtime.finish().unwrap().run();
+    runtime.finish().unwrap().run().unwrap();
 }
@@ -11,15 +11,21 @@ pub use {
     anathema_widgets as widgets,                 // wigets
 };
 
+pub use anathema_templates_macro::template;
+
 pub mod prelude {
     pub use crate::backend::tui::TuiBackend;
-    pub use crate::runtime::{GlobalContext, GlobalEvents, Runtime};
+    pub use crate::runtime::{GlobalAction, GlobalContext, GlobalEvents, Runtime};
     pub use crate::templates::{Document, SourceKind, ToSourceKind, WidgetComponentId};
     pub use crate::widgets::components::Context;
 }
 pub mod component {
     pub use crate::state::{Color, CommonVal, List, Map, State, Value};
-    pub use crate::widgets::components::events::{Event, KeyCode, KeyEvent, MouseButton, MouseEvent, MouseState};
+    pub use crate::widgets::components::animation::{Animation, AnimationHandle, Easing, Repeat};
+    pub use crate::widgets::components::events::{
+        Event, KeyCode, KeyEvent, Modifiers, MouseButton, MouseEvent, MouseState,
+    };
+    pub use crate::widgets::components::keymap::Keymap;
     pub use crate::widgets::components::{Component, ComponentId, Context, Emitter};
     pub use crate::widgets::Elements;
 }